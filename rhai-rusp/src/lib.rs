@@ -4,11 +4,21 @@
 use rhai::def_package;
 use rhai::{
     plugin::{combine_with_exported_module, export_module, mem, Dynamic, EvalAltResult},
-    Array, Blob, Map, Variant,
+    Array, Blob, Map, NativeCallContext, Variant,
 };
 use rusp_lib::usp::{Body, Msg};
 use rusp_lib::usp_builder;
 use rusp_lib::usp_record::{self, Record};
+use rusp_lib::usp_stream::{StreamItem, StreamReader};
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::BufReader;
+use std::rc::Rc;
+
+mod properties;
+mod system;
+
+pub use system::SystemAccess;
 
 /// Evaluate a Rhai script in the context of the `rusp` package and return a supported type, like
 /// [`Record`], [`Msg`] or [`String`]
@@ -2759,6 +2769,28 @@ pub mod rhai_rusp_operateresp {
     }
 }
 
+/// A stream of USP Records or Msgs read lazily from a file, as returned by `rusp::stream_records`
+/// and `rusp::stream_msgs`. Iterating the stream in Rhai decodes one item at a time
+#[derive(Clone)]
+pub struct UspStream<T>(Rc<RefCell<StreamReader<BufReader<File>, T>>>);
+
+/// A [`UspStream`] of [`Record`]s
+pub type RecordStream = UspStream<Record>;
+
+/// A [`UspStream`] of [`Msg`]s
+pub type MsgStream = UspStream<Msg>;
+
+impl<T: StreamItem> Iterator for UspStream<T> {
+    type Item = Result<T, Box<EvalAltResult>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .borrow_mut()
+            .next()
+            .map(|item| Ok(item.map_err(|e| format!("{e:#}"))?))
+    }
+}
+
 /// Supply Rusp de-/serialization functionality
 /// ```
 /// // Rhai script
@@ -2779,7 +2811,14 @@ pub mod rhai_rusp_operateresp {
 pub mod rhai_rusp {
     use std::io::{Read, Write as _};
 
-    use rusp_lib::usp_decoder::{try_decode_msg, try_decode_record};
+    use rusp_lib::usp_c_header::{c_identifier, CHeaderBuilder};
+    use rusp_lib::usp_c_parser;
+    use rusp_lib::usp_capture;
+    use rusp_lib::usp_decoder::{decode_base64, decode_hex, try_decode_msg, try_decode_record};
+    use rusp_lib::usp_diff;
+    use rusp_lib::usp_encoder::SourceLanguage;
+    use rusp_lib::usp_hexdump;
+    use rusp_lib::usp_stream::{Framing, StreamWriter};
     use usp_builder::{MsgBuilder, RecordBuilder};
     use usp_record::mod_Record::OneOfrecord_type;
 
@@ -3248,6 +3287,109 @@ pub mod rhai_rusp {
         rhai::serde::to_dynamic(record)
     }
 
+    /// Converts a Rhai Map in the format produced by `to_map()` back into a USP Body, the
+    /// reverse of [`body_to_map`]
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_max_depth(1).with_params(["Device."]).build().to_map();
+    /// body.Request.Get.max_depth = 2;
+    /// rusp::body_from_map(body)
+    ///     .as_msg_builder()
+    ///     .with_msg_id("get")
+    ///     .build()
+    ///     .to_string()
+    /// # "#;
+    /// # let msg = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert!(msg.contains("\"max_depth\": 2"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the map does not represent a USP Body, e.g. because it contains unknown fields or values of
+    /// the wrong type.
+    #[rhai_fn(global, return_raw)]
+    pub fn body_from_map(map: Map) -> Result<Body, Box<EvalAltResult>> {
+        let value = map_helpers::map_to_json(map)?;
+        Ok(Body::from_json_value(&value).map_err(|e| format!("{e:#}"))?)
+    }
+
+    /// Converts a Rhai Map in the format produced by `to_map()` back into a [`Msg`], the reverse
+    /// of [`msg_to_map`]
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let msg = rusp::get_builder()
+    ///     .with_max_depth(1)
+    ///     .with_params(["Device."])
+    ///     .build()
+    ///     .as_msg_builder()
+    ///     .with_msg_id("get")
+    ///     .build()
+    ///     .to_map();
+    /// msg.Header.msg_id = "get-2";
+    /// msg.Body.Request.Get.param_paths.push("Device.LocalAgent.");
+    /// let msg = rusp::msg_from_map(msg);
+    /// [msg.msg_id, msg.to_map().Body.Request.Get.param_paths.len()]
+    /// # "#;
+    /// # let result = rhai_rusp::eval_rusp::<rhai::Array>(script).unwrap();
+    /// # assert_eq!(result[0].clone().into_string().unwrap(), "get-2");
+    /// # assert_eq!(result[1].as_int().unwrap(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the map does not represent a USP Msg, e.g. because it contains unknown fields or values of
+    /// the wrong type.
+    #[rhai_fn(global, return_raw)]
+    pub fn msg_from_map(map: Map) -> Result<Msg, Box<EvalAltResult>> {
+        let value = map_helpers::map_to_json(map)?;
+        Ok(Msg::from_json_value(&value).map_err(|e| format!("{e:#}"))?)
+    }
+
+    /// Converts a Rhai Map in the format produced by `to_map()` back into a [`Record`], the
+    /// reverse of [`record_to_map`]
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let record = rusp::record_builder()
+    ///   .with_version("1.3")
+    ///   .with_to_id("proto::to")
+    ///   .with_from_id("proto::from")
+    ///   .as_websocket_connect_record()
+    ///   .build()
+    ///   .to_map();
+    /// record.to_id = "proto::other";
+    /// rusp::record_from_map(record)
+    /// # "#;
+    /// # let record = rhai_rusp::eval_rusp::<rusp_lib::usp_record::Record>(script).unwrap();
+    /// # assert_eq!(record.to_id, "proto::other");
+    /// # assert!(record.check_validity().is_ok());
+    /// ```
+    ///
+    /// Errors point to the offending field:
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// rusp::msg_from_map(#{ Header: #{ msg_id: "a", msg_typ: "GET" } })
+    /// # "#;
+    /// # let err = rhai_rusp::eval_rusp::<rusp_lib::usp::Msg>(script).unwrap_err();
+    /// # assert!(err.to_string().contains("in `Header`: unknown field `msg_typ` in usp.Header"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the map does not represent a USP Record, e.g. because it contains unknown fields or values
+    /// of the wrong type.
+    #[rhai_fn(global, return_raw)]
+    pub fn record_from_map(map: Map) -> Result<Record, Box<EvalAltResult>> {
+        let value = map_helpers::map_to_json(map)?;
+        Ok(Record::from_json_value(&value).map_err(|e| format!("{e:#}"))?)
+    }
+
     /// Render a [`Msg`] into C string format
     ///
     /// # Errors
@@ -3415,133 +3557,1048 @@ pub mod rhai_rusp {
         Ok(record.to_c_array_custom(name).map_err(|e| e.to_string())?)
     }
 
-    /// Render a [`Msg`] into C array format and save it to the specified file
+    /// Render a [`Msg`] into a byte literal of the given programming language (`python`, `go`,
+    /// `rust`, `rust-bytes` or `javascript`) named `pb`, preceded by a comment containing the JSON
+    /// representation, this function is polymorphic in Rhai and available as `to_source()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::getsupportedprotocol_builder("1.3,1.4")
+    ///     .build();
+    /// rusp::msg_builder()
+    ///     .with_msg_id("Foo")
+    ///     .with_body(body)
+    ///     .build()
+    ///     .to_source("javascript")
+    /// # "#;
+    /// # let msg = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert!(msg.ends_with("const pb = new Uint8Array([\n  0x0a, 0x07, 0x0a, 0x03, 0x46, 0x6f, 0x6f, 0x10, /* ____Foo_ */\n  0x11, 0x12, 0x0d, 0x0a, 0x0b, 0x4a, 0x09, 0x0a, /* _____J__ */\n  0x07, 0x31, 0x2e, 0x33, 0x2c, 0x31, 0x2e, 0x34, /* _1.3,1.4 */\n]);\n"));
+    /// ```
     ///
     /// # Errors
     ///
-    /// This function will return `Err` containing a textual description of the encountered error
-    /// if the serialization of the structure into a C array or the creation of the specified file
-    /// fails.
-    #[rhai_fn(global, name = "save_c_array", return_raw)]
-    pub fn msg_save_c_array(msg: &mut Msg, filename: &str) -> Result<(), Box<EvalAltResult>> {
-        let data = msg.to_c_array().map_err(|e| e.to_string())?;
-        std::fs::write(filename, data).map_err(|e| e.to_string())?;
-        Ok(())
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the language is not supported or the serialization of the structure fails.
+    #[rhai_fn(global, name = "to_source", return_raw)]
+    pub fn msg_to_source(msg: &mut Msg, language: &str) -> Result<String, Box<EvalAltResult>> {
+        msg_to_source_custom(msg, language, "pb")
     }
 
-    /// Render a [`Record`] into C array format and save it to the specified file
+    /// Render a [`Msg`] into a byte literal of the given programming language with a custom
+    /// variable name, this function is polymorphic in Rhai and available as `to_source()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::getsupportedprotocol_builder("1.3,1.4")
+    ///     .build();
+    /// rusp::msg_builder()
+    ///     .with_msg_id("Foo")
+    ///     .with_body(body)
+    ///     .build()
+    ///     .to_source("python", "get_supported_protocol")
+    /// # "#;
+    /// # let msg = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert!(msg.ends_with("GET_SUPPORTED_PROTOCOL = (\n    b\"\\x0a\\x07\\x0a\\x03Foo\\x10\\x11\\x12\\x0d\\x0a\\x0bJ\\x09\\x0a\"\n    b\"\\x071.3,1.4\"\n)\n"));
+    /// ```
     ///
     /// # Errors
     ///
-    /// This function will return `Err` containing a textual description of the encountered error
-    /// if the serialization of the structure into a C array or the creation of the specified file
-    /// fails.
-    #[rhai_fn(global, name = "save_c_array", return_raw)]
-    pub fn record_save_c_array(
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the language is not supported or the serialization of the structure fails.
+    #[rhai_fn(global, name = "to_source", return_raw)]
+    pub fn msg_to_source_custom(
+        msg: &mut Msg,
+        language: &str,
+        name: &str,
+    ) -> Result<String, Box<EvalAltResult>> {
+        let language = language
+            .parse::<SourceLanguage>()
+            .map_err(|e| e.to_string())?;
+        Ok(msg.to_source(language, name).map_err(|e| e.to_string())?)
+    }
+
+    /// Render a [`Record`] into a byte literal of the given programming language (`python`,
+    /// `go`, `rust`, `rust-bytes` or `javascript`) named `pb`, preceded by a comment containing the JSON
+    /// representation, this function is polymorphic in Rhai and available as `to_source()`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the language is not supported or the serialization of the structure fails.
+    #[rhai_fn(global, name = "to_source", return_raw)]
+    pub fn record_to_source(
         record: &mut Record,
-        filename: &str,
-    ) -> Result<(), Box<EvalAltResult>> {
-        let data = record.to_c_array().map_err(|e| e.to_string())?;
-        std::fs::write(filename, data).map_err(|e| e.to_string())?;
-        Ok(())
+        language: &str,
+    ) -> Result<String, Box<EvalAltResult>> {
+        record_to_source_custom(record, language, "pb")
     }
 
-    /// Render a [`Msg`] into Protobuf and print it to stdout
+    /// Render a [`Record`] into a byte literal of the given programming language with a custom
+    /// variable name, this function is polymorphic in Rhai and available as `to_source()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// rusp::record_builder()
+    ///   .with_to_id("proto::to")
+    ///   .with_from_id("proto::from")
+    ///   .as_disconnect_record("Bye", 0)
+    ///   .build()
+    ///   .to_source("go", "disconnect")
+    /// # "#;
+    /// # let record = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert!(record.starts_with("// {\n//   \"version\": \"1.4\",\n"));
+    /// # assert!(record.contains("\nvar disconnect = []byte{\n\t0x0a, 0x03, 0x31, 0x2e, 0x34,"));
+    /// ```
     ///
     /// # Errors
     ///
-    /// This function will return `Err` containing a textual description of the encountered error
-    /// if the serialization of the structure into Protobuf format fails.
-    #[rhai_fn(global, name = "print_protobuf", return_raw)]
-    pub fn msg_print_protobuf(msg: &mut Msg) -> Result<(), Box<EvalAltResult>> {
-        std::io::stdout()
-            .write_all(&msg.to_vec().map_err(|e| e.to_string())?)
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the language is not supported or the serialization of the structure fails.
+    #[rhai_fn(global, name = "to_source", return_raw)]
+    pub fn record_to_source_custom(
+        record: &mut Record,
+        language: &str,
+        name: &str,
+    ) -> Result<String, Box<EvalAltResult>> {
+        let language = language
+            .parse::<SourceLanguage>()
             .map_err(|e| e.to_string())?;
-        Ok(())
+        Ok(record
+            .to_source(language, name)
+            .map_err(|e| e.to_string())?)
     }
 
-    /// Render a [`Record`] into Protobuf and print it to stdout
+    /// Render a [`Msg`] into Protobuf text format, this function is polymorphic in Rhai and available as `to_prototxt()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::getsupportedprotocol_builder("1.3,1.4")
+    ///     .build();
+    /// rusp::msg_builder()
+    ///     .with_msg_id("Foo")
+    ///     .with_body(body)
+    ///     .build()
+    ///     .to_prototxt()
+    /// # "#;
+    /// # let msg = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(msg, "header {\n  msg_id: \"Foo\"\n  msg_type: GET_SUPPORTED_PROTO\n}\nbody {\n  request {\n    get_supported_protocol {\n      controller_supported_protocol_versions: \"1.3,1.4\"\n    }\n  }\n}\n");
+    /// ```
+    ///
+    /// This example will return a string like:
+    /// ```text
+    /// header {
+    ///   msg_id: "Foo"
+    ///   msg_type: GET_SUPPORTED_PROTO
+    /// }
+    /// body {
+    ///   request {
+    ///     get_supported_protocol {
+    ///       controller_supported_protocol_versions: "1.3,1.4"
+    ///     }
+    ///   }
+    /// }
+    /// ```
     ///
     /// # Errors
     ///
-    /// This function will return `Err` containing a textual description of the encountered error
-    /// if the serialization of the structure into Protobuf format fails.
-    #[rhai_fn(global, name = "print_protobuf", return_raw)]
-    pub fn record_print_protobuf(record: &mut Record) -> Result<(), Box<EvalAltResult>> {
-        std::io::stdout()
-            .write_all(&record.to_vec().map_err(|e| e.to_string())?)
-            .map_err(|e| e.to_string())?;
-        Ok(())
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf text format fails.
+    #[rhai_fn(global, name = "to_prototxt", return_raw)]
+    pub fn msg_to_prototxt(msg: &mut Msg) -> Result<String, Box<EvalAltResult>> {
+        Ok(msg.to_prototxt().map_err(|e| e.to_string())?)
     }
 
-    /// Render a [`Msg`] into Protobuf and save it to the specified file
+    /// Render a [`Record`] into Protobuf text format, this function is polymorphic in Rhai and available as `to_prototxt()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// rusp::record_builder()
+    ///   .with_version("1.3")
+    ///   .with_to_id("proto::to")
+    ///   .with_from_id("proto::from")
+    ///   .as_websocket_connect_record()
+    ///   .build()
+    ///   .to_prototxt()
+    /// # "#;
+    /// # let record = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(record, "version: \"1.3\"\nto_id: \"proto::to\"\nfrom_id: \"proto::from\"\nwebsocket_connect {\n}\n");
+    /// ```
+    ///
+    /// This example will return a string like:
+    /// ```text
+    /// version: "1.3"
+    /// to_id: "proto::to"
+    /// from_id: "proto::from"
+    /// websocket_connect {
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf text format fails.
+    #[rhai_fn(global, name = "to_prototxt", return_raw)]
+    pub fn record_to_prototxt(record: &mut Record) -> Result<String, Box<EvalAltResult>> {
+        Ok(record.to_prototxt().map_err(|e| e.to_string())?)
+    }
+
+    /// Render a [`Msg`] into Protobuf text format and save it to the specified file
     ///
     /// # Errors
     ///
     /// This function will return `Err` containing a textual description of the encountered error
-    /// if the serialization of the structure into Protobuf format or the creation of the specified file
-    /// fails.
-    #[rhai_fn(global, name = "save_protobuf", return_raw)]
-    pub fn msg_save_protobuf(msg: &mut Msg, filename: &str) -> Result<(), Box<EvalAltResult>> {
-        let data = msg.to_vec().map_err(|e| e.to_string())?;
-        std::fs::File::create(filename)
-            .and_then(|mut f| f.write_all(&data))
-            .map_err(|e| e.to_string())?;
+    /// if the serialization of the structure into Protobuf text format or the creation of the
+    /// specified file fails.
+    #[rhai_fn(global, name = "save_prototxt", return_raw)]
+    pub fn msg_save_prototxt(
+        ctx: NativeCallContext,
+        msg: &mut Msg,
+        filename: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = msg.to_prototxt().map_err(|e| e.to_string())?;
+        std::fs::write(filename, data).map_err(|e| e.to_string())?;
         Ok(())
     }
 
-    /// Render a [`Record`] into Protobuf and save it to the specified file
+    /// Render a [`Record`] into Protobuf text format and save it to the specified file
     ///
     /// # Errors
     ///
     /// This function will return `Err` containing a textual description of the encountered error
-    /// if the serialization of the structure into Protobuf format or the creation of the specified file
-    /// fails.
-    #[rhai_fn(global, name = "save_protobuf", return_raw)]
-    pub fn record_save_protobuf(
+    /// if the serialization of the structure into Protobuf text format or the creation of the
+    /// specified file fails.
+    #[rhai_fn(global, name = "save_prototxt", return_raw)]
+    pub fn record_save_prototxt(
+        ctx: NativeCallContext,
         record: &mut Record,
         filename: &str,
     ) -> Result<(), Box<EvalAltResult>> {
-        let data = record.to_vec().map_err(|e| e.to_string())?;
-        std::fs::File::create(filename)
-            .and_then(|mut f| f.write_all(&data))
-            .map_err(|e| e.to_string())?;
+        crate::system::check_fs_access(&ctx)?;
+        let data = record.to_prototxt().map_err(|e| e.to_string())?;
+        std::fs::write(filename, data).map_err(|e| e.to_string())?;
         Ok(())
     }
 
-    /// Extracts the [`Record`]'s payload. Returning [`Dynamic::UNIT`] for Records without payloads
+    /// Render a [`Msg`] into a base64 encoded string of its Protobuf encoding, this function is polymorphic in Rhai and available as `to_base64()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_params(["Device."]).build();
+    /// rusp::msg_builder()
+    ///   .with_msg_id("get")
+    ///   .with_body(body)
+    ///   .build()
+    ///   .to_base64()
+    /// # "#;
+    /// # let base64 = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(base64, "CgcKA2dldBABEg0KCwoJCgdEZXZpY2Uu");
+    /// ```
     ///
-    /// To avoid clones, this function leaves the used [`Record`] with an empty payload
-    #[rhai_fn(global, name = "extract_msg")]
-    pub fn record_extract_msg(record: &mut Record) -> Dynamic {
-        match &mut record.record_type {
-            OneOfrecord_type::session_context(ctx) => {
-                let payload = ctx.payload_flatten();
-                Dynamic::from_blob(mem::take(payload))
-            }
-            OneOfrecord_type::no_session_context(ctx) => {
-                Dynamic::from_blob(mem::take(&mut ctx.payload))
-            }
-            _ => Dynamic::UNIT,
-        }
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "to_base64", return_raw)]
+    pub fn msg_to_base64(msg: &mut Msg) -> Result<String, Box<EvalAltResult>> {
+        Ok(msg.to_base64().map_err(|e| e.to_string())?)
     }
 
-    /// Parses a [`Msg`] from the provided Protobuf bytes
+    /// Render a [`Msg`] into a hex encoded string of its Protobuf encoding, this function is polymorphic in Rhai and available as `to_hex()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_params(["Device."]).build();
+    /// rusp::msg_builder()
+    ///   .with_msg_id("get")
+    ///   .with_body(body)
+    ///   .build()
+    ///   .to_hex()
+    /// # "#;
+    /// # let hex = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(hex, "0a070a036765741001120d0a0b0a090a074465766963652e");
+    /// ```
     ///
     /// # Errors
     ///
-    /// This function will return `Err` when the deserialization of the structure from the Protobuf
-    /// format fails
-    #[rhai_fn(global, name = "parse_msg", return_raw)]
-    pub fn parse_msg_protobuf(protobuf: &mut Blob) -> Result<Msg, Box<EvalAltResult>> {
-        try_decode_msg(protobuf).map_err(|e| e.to_string().into())
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "to_hex", return_raw)]
+    pub fn msg_to_hex(msg: &mut Msg) -> Result<String, Box<EvalAltResult>> {
+        Ok(msg.to_hex().map_err(|e| e.to_string())?)
     }
 
-    /// Read a [`Msg`] in Protobuf format from stdin. In Rhai this function is called `read_msg`.
+    /// Render a [`Record`] into a base64 encoded string of its Protobuf encoding, this function is polymorphic in Rhai and available as `to_base64()`
     ///
     /// # Errors
     ///
-    /// This function will return `Err` containing a textual description of the encountered error
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "to_base64", return_raw)]
+    pub fn record_to_base64(record: &mut Record) -> Result<String, Box<EvalAltResult>> {
+        Ok(record.to_base64().map_err(|e| e.to_string())?)
+    }
+
+    /// Render a [`Record`] into a hex encoded string of its Protobuf encoding, this function is polymorphic in Rhai and available as `to_hex()`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "to_hex", return_raw)]
+    pub fn record_to_hex(record: &mut Record) -> Result<String, Box<EvalAltResult>> {
+        Ok(record.to_hex().map_err(|e| e.to_string())?)
+    }
+
+    /// Render an annotated hex dump of a [`Msg`], this function is polymorphic in Rhai and available as `to_hexdump()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_params(["Device."]).build();
+    /// rusp::msg_builder()
+    ///   .with_msg_id("get")
+    ///   .with_body(body)
+    ///   .build()
+    ///   .to_hexdump()
+    /// # "#;
+    /// # let dump = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert!(dump.starts_with("usp.Msg, 24 bytes\n000000  0a 07"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "to_hexdump", return_raw)]
+    pub fn msg_to_hexdump(msg: &mut Msg) -> Result<String, Box<EvalAltResult>> {
+        let data = msg.to_vec().map_err(|e| e.to_string())?;
+        Ok(usp_hexdump::hexdump_msg(&data))
+    }
+
+    /// Render an annotated hex dump of a [`Record`], this function is polymorphic in Rhai and available as `to_hexdump()`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "to_hexdump", return_raw)]
+    pub fn record_to_hexdump(record: &mut Record) -> Result<String, Box<EvalAltResult>> {
+        let data = record.to_vec().map_err(|e| e.to_string())?;
+        Ok(usp_hexdump::hexdump_record(&data))
+    }
+
+    /// Render an annotated hex dump of Protobuf bytes supposed to contain a [`Msg`]. Since no
+    /// decoding into a [`Msg`] takes place, this also works for malformed data
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let data = blob();
+    /// data.push(0x0a);
+    /// data.push(0x05);
+    /// data.push(0x0a);
+    /// rusp::hexdump_msg(data)
+    /// # "#;
+    /// # let dump = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(dump, "usp.Msg, 3 bytes\n000000  0a 05 0a                                         !! LEN field (1: header): unexpected end of data, 3 byte(s) not decoded\n");
+    /// ```
+    #[rhai_fn(global, name = "hexdump_msg")]
+    pub fn hexdump_msg(protobuf: &mut Blob) -> String {
+        usp_hexdump::hexdump_msg(protobuf)
+    }
+
+    /// Render an annotated hex dump of a Protobuf file supposed to contain a [`Msg`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the file pointed to by the filename doesn't exist or is not readable.
+    #[rhai_fn(global, name = "hexdump_msg", return_raw)]
+    pub fn hexdump_msg_file(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<String, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = std::fs::read(filename).map_err(|e| e.to_string())?;
+        Ok(usp_hexdump::hexdump_msg(&data))
+    }
+
+    /// Render an annotated hex dump of Protobuf bytes supposed to contain a [`Record`]. Since no
+    /// decoding into a [`Record`] takes place, this also works for malformed data
+    #[rhai_fn(global, name = "hexdump_record")]
+    pub fn hexdump_record(protobuf: &mut Blob) -> String {
+        usp_hexdump::hexdump_record(protobuf)
+    }
+
+    /// Render an annotated hex dump of a Protobuf file supposed to contain a [`Record`]
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// print(rusp::hexdump_record("test.pb"));
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the file pointed to by the filename doesn't exist or is not readable.
+    #[rhai_fn(global, name = "hexdump_record", return_raw)]
+    pub fn hexdump_record_file(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<String, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = std::fs::read(filename).map_err(|e| e.to_string())?;
+        Ok(usp_hexdump::hexdump_record(&data))
+    }
+
+    /// Converts the differences found by [`usp_diff`] into an array of Rhai maps
+    fn differences_to_array(
+        differences: Vec<usp_diff::Difference>,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        differences.iter().map(rhai::serde::to_dynamic).collect()
+    }
+
+    /// Compare two [`Msg`]s structurally, this function is polymorphic in Rhai and available as
+    /// `diff()`. Map fields like `result_params` and repeated fields are compared as sets. The
+    /// differences are returned as an array of maps with a `kind` (`"added"`, `"removed"` or
+    /// `"changed"`), the `path` of the element and its `value` or its value `before` and `after`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let before = rusp::get_builder().with_params(["Device.WiFi.", "Device.IP."]).build();
+    /// let after = rusp::get_builder().with_params(["Device.IP.", "Device.DNS."]).build();
+    /// let before = rusp::msg_builder().with_msg_id("1").with_body(before).build();
+    /// let after = rusp::msg_builder().with_msg_id("2").with_body(after).build();
+    /// let diff = before.diff(after, true);
+    /// diff.map(|d| `${d.kind} ${d.path}: ${d.value}`)
+    /// # "#;
+    /// # let diff = rhai_rusp::eval_rusp::<rhai::Array>(script).unwrap();
+    /// # let diff: Vec<String> = diff.into_iter().map(|d| d.into_string().unwrap()).collect();
+    /// # assert_eq!(diff, ["removed Body.Request.Get.param_paths[0]: Device.WiFi.", "added Body.Request.Get.param_paths[1]: Device.DNS."]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if one of the [`Msg`]s cannot be serialized.
+    #[rhai_fn(global, name = "diff", return_raw)]
+    pub fn msg_diff_ignore_msg_id(
+        old: &mut Msg,
+        new: Msg,
+        ignore_msg_id: bool,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let options = usp_diff::DiffOptions::new().with_ignore_msg_id(ignore_msg_id);
+        let differences = usp_diff::diff_msg(old, &new, &options).map_err(|e| e.to_string())?;
+        differences_to_array(differences)
+    }
+
+    /// Compare two [`Msg`]s structurally including their `msg_id`, this function is polymorphic
+    /// in Rhai and available as `diff()`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if one of the [`Msg`]s cannot be serialized.
+    #[rhai_fn(global, name = "diff", return_raw)]
+    pub fn msg_diff(old: &mut Msg, new: Msg) -> Result<Array, Box<EvalAltResult>> {
+        msg_diff_ignore_msg_id(old, new, false)
+    }
+
+    /// Compare two [`Record`]s structurally, including the [`Msg`]s carried in their payload,
+    /// this function is polymorphic in Rhai and available as `diff()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_params(["Device."]).build();
+    /// let msg = rusp::msg_builder().with_msg_id("get").with_body(body).build();
+    /// let before = rusp::record_builder().with_to_id("proto::to").with_from_id("proto::from").with_no_session_context_payload(msg).build();
+    /// let after = rusp::record_builder().with_to_id("proto::other").with_from_id("proto::from").with_no_session_context_payload(msg).build();
+    /// let diff = before.diff(after, false);
+    /// `${diff.len()} ${diff[0].kind} ${diff[0].path}: ${diff[0].before} -> ${diff[0].after}`
+    /// # "#;
+    /// # let diff = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(diff, "1 changed to_id: proto::to -> proto::other");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if one of the [`Record`]s cannot be serialized.
+    #[rhai_fn(global, name = "diff", return_raw)]
+    pub fn record_diff_ignore_msg_id(
+        old: &mut Record,
+        new: Record,
+        ignore_msg_id: bool,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        let options = usp_diff::DiffOptions::new().with_ignore_msg_id(ignore_msg_id);
+        let differences = usp_diff::diff_record(old, &new, &options).map_err(|e| e.to_string())?;
+        differences_to_array(differences)
+    }
+
+    /// Compare two [`Record`]s structurally including the `msg_id` of the carried [`Msg`]s, this
+    /// function is polymorphic in Rhai and available as `diff()`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if one of the [`Record`]s cannot be serialized.
+    #[rhai_fn(global, name = "diff", return_raw)]
+    pub fn record_diff(old: &mut Record, new: Record) -> Result<Array, Box<EvalAltResult>> {
+        record_diff_ignore_msg_id(old, new, false)
+    }
+
+    /// Render a [`Msg`] into C array format and save it to the specified file
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into a C array or the creation of the specified file
+    /// fails.
+    #[rhai_fn(global, name = "save_c_array", return_raw)]
+    pub fn msg_save_c_array(
+        ctx: NativeCallContext,
+        msg: &mut Msg,
+        filename: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = msg.to_c_array().map_err(|e| e.to_string())?;
+        std::fs::write(filename, data).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Render a [`Record`] into C array format and save it to the specified file
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into a C array or the creation of the specified file
+    /// fails.
+    #[rhai_fn(global, name = "save_c_array", return_raw)]
+    pub fn record_save_c_array(
+        ctx: NativeCallContext,
+        record: &mut Record,
+        filename: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = record.to_c_array().map_err(|e| e.to_string())?;
+        std::fs::write(filename, data).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Derives the name of a C header from its file name
+    fn c_header_name(filename: &str) -> String {
+        let stem = std::path::Path::new(filename)
+            .file_stem()
+            .map_or_else(|| filename.into(), |s| s.to_string_lossy());
+        c_identifier(&stem)
+    }
+
+    /// Render a [`Msg`] into a complete C header containing an array with the given name and save
+    /// it to the specified file, the include guard is derived from the file name
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_params(["Device."]).build();
+    /// rusp::msg_builder()
+    ///   .with_msg_id("get")
+    ///   .with_body(body)
+    ///   .build()
+    ///   .save_c_array("get_msg.h", "get_msg");
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into a C header or the creation of the specified file
+    /// fails.
+    #[rhai_fn(global, name = "save_c_array", return_raw)]
+    pub fn msg_save_c_header(
+        ctx: NativeCallContext,
+        msg: &mut Msg,
+        filename: &str,
+        name: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = CHeaderBuilder::new(&c_header_name(filename))
+            .with_msg(name, msg.clone())
+            .build()
+            .map_err(|e| e.to_string())?;
+        std::fs::write(filename, data).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Render a [`Record`] into a complete C header containing an array with the given name and
+    /// save it to the specified file, the include guard is derived from the file name
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into a C header or the creation of the specified file
+    /// fails.
+    #[rhai_fn(global, name = "save_c_array", return_raw)]
+    pub fn record_save_c_header(
+        ctx: NativeCallContext,
+        record: &mut Record,
+        filename: &str,
+        name: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = CHeaderBuilder::new(&c_header_name(filename))
+            .with_record(name, record.clone())
+            .build()
+            .map_err(|e| e.to_string())?;
+        std::fs::write(filename, data).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Render several [`Msg`]s and/or [`Record`]s, given as a map of array names to values, into
+    /// a complete C header and save it to the specified file, the include guard is derived from
+    /// the file name
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the map contains something other than [`Msg`]s and [`Record`]s, the serialization into
+    /// a C header or the creation of the specified file fails.
+    #[rhai_fn(global, name = "save_c_array", return_raw)]
+    pub fn save_c_header(
+        ctx: NativeCallContext,
+        filename: &str,
+        items: Map,
+    ) -> Result<(), Box<EvalAltResult>> {
+        save_c_header_with_table(ctx, filename, items, false)
+    }
+
+    /// Render several [`Msg`]s and/or [`Record`]s, given as a map of array names to values, into
+    /// a complete C header and save it to the specified file, optionally adding a table of all
+    /// arrays named after the file
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_params(["Device."]).build();
+    /// let msg = rusp::msg_builder()
+    ///   .with_msg_id("get")
+    ///   .with_body(body)
+    ///   .build();
+    /// let record = rusp::record_builder()
+    ///   .with_to_id("proto::to")
+    ///   .with_from_id("proto::from")
+    ///   .with_no_session_context_payload(msg)
+    ///   .build();
+    /// rusp::save_c_array("test_vectors.h", #{ get_msg: msg, get_record: record }, true);
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the map contains something other than [`Msg`]s and [`Record`]s, the serialization into
+    /// a C header or the creation of the specified file fails.
+    #[rhai_fn(global, name = "save_c_array", return_raw)]
+    pub fn save_c_header_with_table(
+        ctx: NativeCallContext,
+        filename: &str,
+        items: Map,
+        table: bool,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let mut builder = CHeaderBuilder::new(&c_header_name(filename)).with_table(table);
+        for (name, item) in items {
+            builder = if item.is::<Msg>() {
+                builder.with_msg(&name, item.cast::<Msg>())
+            } else if item.is::<Record>() {
+                builder.with_record(&name, item.cast::<Record>())
+            } else {
+                return Err(format!(
+                    "Expected a Msg or Record for {name}, got a {}",
+                    item.type_name()
+                )
+                .into());
+            };
+        }
+
+        let data = builder.build().map_err(|e| e.to_string())?;
+        std::fs::write(filename, data).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Render a [`Msg`] into Protobuf and print it to stdout
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "print_protobuf", return_raw)]
+    pub fn msg_print_protobuf(msg: &mut Msg) -> Result<(), Box<EvalAltResult>> {
+        std::io::stdout()
+            .write_all(&msg.to_vec().map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Render a [`Record`] into Protobuf and print it to stdout
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into Protobuf format fails.
+    #[rhai_fn(global, name = "print_protobuf", return_raw)]
+    pub fn record_print_protobuf(record: &mut Record) -> Result<(), Box<EvalAltResult>> {
+        std::io::stdout()
+            .write_all(&record.to_vec().map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Render a [`Msg`] into Protobuf and save it to the specified file
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into Protobuf format or the creation of the specified file
+    /// fails.
+    #[rhai_fn(global, name = "save_protobuf", return_raw)]
+    pub fn msg_save_protobuf(
+        ctx: NativeCallContext,
+        msg: &mut Msg,
+        filename: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = msg.to_vec().map_err(|e| e.to_string())?;
+        std::fs::File::create(filename)
+            .and_then(|mut f| f.write_all(&data))
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Render a [`Record`] into Protobuf and save it to the specified file
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the serialization of the structure into Protobuf format or the creation of the specified file
+    /// fails.
+    #[rhai_fn(global, name = "save_protobuf", return_raw)]
+    pub fn record_save_protobuf(
+        ctx: NativeCallContext,
+        record: &mut Record,
+        filename: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let data = record.to_vec().map_err(|e| e.to_string())?;
+        std::fs::File::create(filename)
+            .and_then(|mut f| f.write_all(&data))
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Extracts the [`Record`]'s payload. Returning [`Dynamic::UNIT`] for Records without payloads
+    ///
+    /// To avoid clones, this function leaves the used [`Record`] with an empty payload
+    #[rhai_fn(global, name = "extract_msg")]
+    pub fn record_extract_msg(record: &mut Record) -> Dynamic {
+        match &mut record.record_type {
+            OneOfrecord_type::session_context(ctx) => {
+                let payload = ctx.payload_flatten();
+                Dynamic::from_blob(mem::take(payload))
+            }
+            OneOfrecord_type::no_session_context(ctx) => {
+                Dynamic::from_blob(mem::take(&mut ctx.payload))
+            }
+            _ => Dynamic::UNIT,
+        }
+    }
+
+    /// Parses a [`Msg`] from the provided Protobuf bytes
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` when the deserialization of the structure from the Protobuf
+    /// format fails
+    #[rhai_fn(global, name = "parse_msg", return_raw)]
+    pub fn parse_msg_protobuf(protobuf: &mut Blob) -> Result<Msg, Box<EvalAltResult>> {
+        try_decode_msg(protobuf).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Parses a [`Msg`] from a base64 encoded string of its Protobuf encoding. In Rhai this
+    /// function is called `parse_msg_base64`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let msg = rusp::parse_msg_base64("CggKBHRlc3QQAxIoCiZCJAoFbm90aWYQAUIZCgYwMDQ0RkYSA0ZvbxoFMDEyMzQiAzEuMw==");
+    /// print(msg);
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` when the string is not valid base64 or the
+    /// deserialization of the structure from the Protobuf format fails
+    #[rhai_fn(global, name = "parse_msg_base64", return_raw)]
+    pub fn parse_msg_base64(text: &str) -> Result<Msg, Box<EvalAltResult>> {
+        let protobuf = decode_base64(text).map_err(|e| format!("{e:#}"))?;
+        try_decode_msg(&protobuf).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Parses a [`Msg`] from a hex encoded string of its Protobuf encoding. In Rhai this
+    /// function is called `parse_msg_hex`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` when the string is not valid hex or the
+    /// deserialization of the structure from the Protobuf format fails
+    #[rhai_fn(global, name = "parse_msg_hex", return_raw)]
+    pub fn parse_msg_hex(text: &str) -> Result<Msg, Box<EvalAltResult>> {
+        let protobuf = decode_hex(text).map_err(|e| format!("{e:#}"))?;
+        try_decode_msg(&protobuf).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Parses a [`Record`] from a base64 encoded string of its Protobuf encoding. In Rhai this
+    /// function is called `parse_record_base64`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` when the string is not valid base64 or the
+    /// deserialization of the structure from the Protobuf format fails
+    #[rhai_fn(global, name = "parse_record_base64", return_raw)]
+    pub fn parse_record_base64(text: &str) -> Result<Record, Box<EvalAltResult>> {
+        let protobuf = decode_base64(text).map_err(|e| format!("{e:#}"))?;
+        try_decode_record(&protobuf).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Parses a [`Record`] from a hex encoded string of its Protobuf encoding. In Rhai this
+    /// function is called `parse_record_hex`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let record = rusp::parse_record_hex("0a03312e331207646f633a3a746f1a09646f633a3a66726f6d520908011205746f706963");
+    /// print(record.to_base64());
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` when the string is not valid hex or the
+    /// deserialization of the structure from the Protobuf format fails
+    #[rhai_fn(global, name = "parse_record_hex", return_raw)]
+    pub fn parse_record_hex(text: &str) -> Result<Record, Box<EvalAltResult>> {
+        let protobuf = decode_hex(text).map_err(|e| format!("{e:#}"))?;
+        try_decode_record(&protobuf).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Parses a [`Msg`] from the provided Protobuf text format string
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let msg = rusp::parse_msg_prototxt(`
+    ///     header { msg_id: "get" msg_type: GET }
+    ///     body { request { get { param_paths: "Device." } } }
+    /// `);
+    /// msg.to_string()
+    /// # "#;
+    /// # let msg = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(msg, "{\n  \"Header\": {\n    \"msg_id\": \"get\",\n    \"msg_type\": \"GET\"\n  },\n  \"Body\": {\n    \"Request\": {\n      \"Get\": {\n        \"param_paths\": [\n          \"Device.\"\n        ],\n        \"max_depth\": 0\n      }\n    }\n  }\n}");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` when the deserialization of the structure from the Protobuf
+    /// text format fails
+    #[rhai_fn(global, name = "parse_msg_prototxt", return_raw)]
+    pub fn parse_msg_prototxt(prototxt: &str) -> Result<Msg, Box<EvalAltResult>> {
+        Msg::from_prototxt(prototxt).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Parses a [`Record`] from the provided Protobuf text format string
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let record = rusp::parse_record_prototxt(`
+    ///     version: "1.3"
+    ///     to_id: "proto::to"
+    ///     from_id: "proto::from"
+    ///     websocket_connect {}
+    /// `);
+    /// record.to_string()
+    /// # "#;
+    /// # let record = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(record, "{\n  \"version\": \"1.3\",\n  \"to_id\": \"proto::to\",\n  \"from_id\": \"proto::from\",\n  \"originator_id\": \"\",\n  \"destination_id\": \"\",\n  \"payload_security\": \"PLAINTEXT\",\n  \"mac_signature\": [],\n  \"sender_cert\": [],\n  \"websocket_connect\": null\n}");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` when the deserialization of the structure from the Protobuf
+    /// text format fails
+    #[rhai_fn(global, name = "parse_record_prototxt", return_raw)]
+    pub fn parse_record_prototxt(prototxt: &str) -> Result<Record, Box<EvalAltResult>> {
+        let record = Record::from_prototxt(prototxt).map_err(|e| format!("{e:#}"))?;
+        if record.record_type == OneOfrecord_type::None {
+            Err("Protobuf text doesn't contain a valid USP Record")?;
+        }
+        Ok(record)
+    }
+
+    /// Load a [`Msg`] from a Protobuf text format file. In Rhai this function is called
+    /// `load_msg_prototxt`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the file pointed to by the filename doesn't exist, is not readable or the
+    /// deserialization of the structure from Protobuf text format fails.
+    #[rhai_fn(global, name = "load_msg_prototxt", return_raw)]
+    pub fn load_msg_prototxt(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<Msg, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let contents = std::fs::read_to_string(filename).map_err(|e| e.to_string())?;
+        parse_msg_prototxt(&contents)
+    }
+
+    /// Load a [`Record`] from a Protobuf text format file. In Rhai this function is called
+    /// `load_record_prototxt`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the file pointed to by the filename doesn't exist, is not readable or the
+    /// deserialization of the structure from Protobuf text format fails.
+    #[rhai_fn(global, name = "load_record_prototxt", return_raw)]
+    pub fn load_record_prototxt(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<Record, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let contents = std::fs::read_to_string(filename).map_err(|e| e.to_string())?;
+        parse_record_prototxt(&contents)
+    }
+
+    /// Parses a [`Msg`] from C source code containing a single byte array initialiser or string
+    /// literal, e.g. as produced by `to_c_array()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let msg = rusp::parse_msg_c(`
+    ///     unsigned int pb_len = 24;
+    ///     const char pb[] = {
+    ///       0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10, /* ____get_ */
+    ///       0x01, 0x12, 0x0d, 0x0a, 0x0b, 0x0a, 0x09, 0x0a, /* ________ */
+    ///       0x07, 0x44, 0x65, 0x76, 0x69, 0x63, 0x65, 0x2e, /* _Device. */
+    ///     };
+    /// `);
+    /// msg.to_prototxt()
+    /// # "#;
+    /// # let msg = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert_eq!(msg, "header {\n  msg_id: \"get\"\n  msg_type: GET\n}\nbody {\n  request {\n    get {\n      param_paths: \"Device.\"\n    }\n  }\n}\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the source doesn't contain exactly one byte array or
+    /// string literal or the deserialization of the structure from Protobuf format fails
+    #[rhai_fn(global, name = "parse_msg_c", return_raw)]
+    pub fn parse_msg_c(source: &str) -> Result<Msg, Box<EvalAltResult>> {
+        usp_c_parser::try_decode_msg_c(source).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Parses a [`Record`] from C source code containing a single byte array initialiser or
+    /// string literal, e.g. as produced by `to_c_string()`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let record = rusp::parse_record_c(
+    ///     "\"\\x0a\\x031.3\\x12\\x07doc\\x3a\\x3ato\\x1a\\x09doc\\x3a\\x3afromR\\x09\\x08\\x01\\x12\\x05topic\""
+    /// );
+    /// record.to_string()
+    /// # "#;
+    /// # let record = rhai_rusp::eval_rusp::<String>(script).unwrap();
+    /// # assert!(record.contains("\"to_id\": \"doc::to\""));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the source doesn't contain exactly one byte array or
+    /// string literal or the deserialization of the structure from Protobuf format fails
+    #[rhai_fn(global, name = "parse_record_c", return_raw)]
+    pub fn parse_record_c(source: &str) -> Result<Record, Box<EvalAltResult>> {
+        usp_c_parser::try_decode_record_c(source).map_err(|e| format!("{e:#}").into())
+    }
+
+    /// Load a [`Msg`] from a C source file containing a single byte array initialiser or string
+    /// literal. In Rhai this function is called `load_msg_c`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the file pointed to by the filename doesn't exist, is not readable, doesn't contain
+    /// exactly one byte array or string literal or the deserialization of the structure fails.
+    #[rhai_fn(global, name = "load_msg_c", return_raw)]
+    pub fn load_msg_c(ctx: NativeCallContext, filename: &str) -> Result<Msg, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let contents = std::fs::read_to_string(filename).map_err(|e| e.to_string())?;
+        parse_msg_c(&contents)
+    }
+
+    /// Load a [`Record`] from a C source file containing a single byte array initialiser or
+    /// string literal. In Rhai this function is called `load_record_c`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the file pointed to by the filename doesn't exist, is not readable, doesn't contain
+    /// exactly one byte array or string literal or the deserialization of the structure fails.
+    #[rhai_fn(global, name = "load_record_c", return_raw)]
+    pub fn load_record_c(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<Record, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let contents = std::fs::read_to_string(filename).map_err(|e| e.to_string())?;
+        parse_record_c(&contents)
+    }
+
+    /// Extracts all byte array initialisers and string literals assigned to variables from C
+    /// source code into a map of variable names to blobs, which can be decoded with `parse_msg()`
+    /// or `parse_record()`. A literal without a variable name is called `pb`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let literals = rusp::parse_c_literals(`
+    ///     const uint8_t first[] = { 0x0a, 0x00 };
+    ///     const size_t first_len = sizeof(first);
+    ///     const char *second = "\\x0a\\x00";
+    /// `);
+    /// literals.keys()
+    /// # "#;
+    /// # let keys = rhai_rusp::eval_rusp::<rhai::Array>(script).unwrap();
+    /// # assert_eq!(keys.len(), 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the source contains malformed literals or comments.
+    #[rhai_fn(global, name = "parse_c_literals", return_raw)]
+    pub fn parse_c_literals(source: &str) -> Result<Map, Box<EvalAltResult>> {
+        let literals = usp_c_parser::parse_c_literals(source).map_err(|e| format!("{e:#}"))?;
+        Ok(literals
+            .into_iter()
+            .map(|literal| {
+                (
+                    literal.name.unwrap_or_else(|| "pb".into()).into(),
+                    Dynamic::from_blob(literal.data),
+                )
+            })
+            .collect())
+    }
+
+    /// Read a [`Msg`] in Protobuf format from stdin. In Rhai this function is called `read_msg`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
     /// if the deserialization of the structure from Protobuf format fails.
     #[rhai_fn(global, name = "read_msg", return_raw)]
     pub fn read_msg_protobuf() -> Result<Msg, Box<EvalAltResult>> {
@@ -3549,7 +4606,7 @@ pub mod rhai_rusp {
         std::io::stdin()
             .read_to_end(&mut contents)
             .map_err(|e| e.to_string())?;
-        Ok(try_decode_msg(&contents).map_err(|e| e.to_string())?)
+        Ok(try_decode_msg(&contents).map_err(|e| format!("{e:#}"))?)
     }
 
     /// Load a [`Msg`] from a Protobuf file. In Rhai this function is called `load_msg`
@@ -3560,12 +4617,16 @@ pub mod rhai_rusp {
     /// if the file pointed to by the filename doesn't exist, is not readable or the
     /// deserialization of the structure from Protobuf format fails.
     #[rhai_fn(global, name = "load_msg", return_raw)]
-    pub fn load_msg_protobuf(filename: &str) -> Result<Msg, Box<EvalAltResult>> {
+    pub fn load_msg_protobuf(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<Msg, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
         let mut contents = Vec::new();
         let _ = std::fs::File::open(filename)
             .map(|mut f| f.read_to_end(&mut contents))
             .map_err(|e| e.to_string())?;
-        Ok(try_decode_msg(&contents).map_err(|e| e.to_string())?)
+        Ok(try_decode_msg(&contents).map_err(|e| format!("{e:#}"))?)
     }
 
     /// Read a [`Record`] in Protobuf format from stdin. In Rhai this function is called `read_record`.
@@ -3587,7 +4648,7 @@ pub mod rhai_rusp {
         std::io::stdin()
             .read_to_end(&mut contents)
             .map_err(|e| e.to_string())?;
-        let record = try_decode_record(&contents).map_err(|e| e.to_string())?;
+        let record = try_decode_record(&contents).map_err(|e| format!("{e:#}"))?;
         if record.record_type == OneOfrecord_type::None {
             Err("Protobuf file doesn't contain a valid USP Record")?;
         }
@@ -3609,17 +4670,466 @@ pub mod rhai_rusp {
     /// if the file pointed to by the filename doesn't exist, is not readable or the
     /// deserialization of the structure from Protobuf format fails.
     #[rhai_fn(global, name = "load_record", return_raw)]
-    pub fn load_record_protobuf(filename: &str) -> Result<Record, Box<EvalAltResult>> {
+    pub fn load_record_protobuf(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<Record, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
         let mut contents = Vec::new();
         let _ = std::fs::File::open(filename)
             .map(|mut f| f.read_to_end(&mut contents))
             .map_err(|e| e.to_string())?;
-        let record = try_decode_record(&contents).map_err(|e| e.to_string())?;
+        let record = try_decode_record(&contents).map_err(|e| format!("{e:#}"))?;
         if record.record_type == OneOfrecord_type::None {
             Err("Protobuf file doesn't contain a valid USP Record")?;
         }
         Ok(record)
     }
+
+    /// Extract all [`Record`]s from the contents of a network capture in pcap or pcapng format,
+    /// see [`load_capture`] for details
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the data is neither in pcap nor in pcapng format.
+    #[rhai_fn(global, name = "parse_capture", return_raw)]
+    pub fn parse_capture(capture: &mut Blob) -> Result<Array, Box<EvalAltResult>> {
+        let records = usp_capture::extract_records(capture).map_err(|e| format!("{e:#}"))?;
+        Ok(records
+            .into_iter()
+            .map(|captured| {
+                let mut map = Map::new();
+                map.insert("timestamp".into(), captured.timestamp.as_secs_f64().into());
+                map.insert("source".into(), captured.source.to_string().into());
+                map.insert(
+                    "destination".into(),
+                    captured.destination.to_string().into(),
+                );
+                map.insert("transport".into(), captured.transport.name().into());
+                map.insert("record".into(), Dynamic::from(captured.record));
+                map.into()
+            })
+            .collect())
+    }
+
+    /// Extract all [`Record`]s carried via WebSocket, MQTT or STOMP from a network capture file in
+    /// pcap or pcapng format, e.g. recorded by tcpdump. The Records are returned in chronological
+    /// order as maps containing the `timestamp` in seconds since the UNIX epoch, the `source` and
+    /// `destination` endpoints, the `transport` (`"websocket"`, `"mqtt"` or `"stomp"`) and the
+    /// decoded `record`. Encrypted connections are not supported
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// for captured in rusp::load_capture("gateway.pcapng") {
+    ///     print(`${captured.timestamp} ${captured.source} -> ${captured.destination}`);
+    ///     print(captured.record);
+    /// }
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the file pointed to by the filename doesn't exist, is not readable or is neither in pcap
+    /// nor in pcapng format.
+    #[rhai_fn(global, name = "load_capture", return_raw)]
+    pub fn load_capture(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<Array, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let mut capture = std::fs::read(filename).map_err(|e| e.to_string())?;
+        parse_capture(&mut capture)
+    }
+
+    fn open_stream<T: StreamItem>(
+        ctx: NativeCallContext,
+        filename: &str,
+        framing: &str,
+    ) -> Result<UspStream<T>, Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let framing = framing.parse::<Framing>().map_err(|e| e.to_string())?;
+        let file = File::open(filename).map_err(|e| e.to_string())?;
+        let reader = StreamReader::new(BufReader::new(file), framing);
+        Ok(UspStream(Rc::new(RefCell::new(reader))))
+    }
+
+    /// Open a file containing a stream of [`Record`]s, either length-delimited Protobuf
+    /// (`"length-delimited"`) or one JSON Record per line (`"json-lines"`). The Records are decoded
+    /// one by one while iterating over the stream, so large traffic logs can be processed without
+    /// loading them into memory. In Rhai this function is called `stream_records`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// for record in rusp::stream_records("traffic.bin", "length-delimited") {
+    ///     print(record.to_string());
+    /// }
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the framing is unknown or the file pointed to by the filename doesn't exist or is not
+    /// readable. Iterating the stream fails on the first Record which cannot be decoded.
+    #[rhai_fn(global, name = "stream_records", return_raw)]
+    pub fn stream_records(
+        ctx: NativeCallContext,
+        filename: &str,
+        framing: &str,
+    ) -> Result<RecordStream, Box<EvalAltResult>> {
+        open_stream(ctx, filename, framing)
+    }
+
+    /// Open a file containing a stream of [`Msg`]s, either length-delimited Protobuf
+    /// (`"length-delimited"`) or one JSON Msg per line (`"json-lines"`), see [`stream_records`]
+    /// for details. In Rhai this function is called `stream_msgs`
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the framing is unknown or the file pointed to by the filename doesn't exist or is not
+    /// readable. Iterating the stream fails on the first Msg which cannot be decoded.
+    #[rhai_fn(global, name = "stream_msgs", return_raw)]
+    pub fn stream_msgs(
+        ctx: NativeCallContext,
+        filename: &str,
+        framing: &str,
+    ) -> Result<MsgStream, Box<EvalAltResult>> {
+        open_stream(ctx, filename, framing)
+    }
+
+    /// Save an array of [`Record`]s and [`Msg`]s as stream with the given framing, either
+    /// length-delimited Protobuf (`"length-delimited"`) or one JSON item per line (`"json-lines"`).
+    /// In Rhai this function is called `save_stream`
+    /// ```
+    /// # let file = std::env::temp_dir().join("rusp-doctest-stream.jsonl");
+    /// # let script = r#"
+    /// // Rhai script
+    /// let record = rusp::record_builder()
+    ///   .with_to_id("proto::to")
+    ///   .with_from_id("proto::from")
+    ///   .as_disconnect_record("Bye", 0)
+    ///   .build();
+    /// rusp::save_stream([record, record], "traffic.jsonl", "json-lines");
+    /// let count = 0;
+    /// for record in rusp::stream_records("traffic.jsonl", "json-lines") {
+    ///     count += 1;
+    /// }
+    /// count
+    /// # "#.replace("traffic.jsonl", &file.display().to_string());
+    /// # let count = rhai_rusp::eval_rusp::<i64>(&script).unwrap();
+    /// # assert_eq!(count, 2);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the framing is unknown, the array contains other types than Records and Msgs or the
+    /// file cannot be written.
+    #[rhai_fn(global, name = "save_stream", return_raw)]
+    pub fn save_stream(
+        ctx: NativeCallContext,
+        items: Array,
+        filename: &str,
+        framing: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        crate::system::check_fs_access(&ctx)?;
+        let framing = framing.parse::<Framing>().map_err(|e| e.to_string())?;
+        let file = File::create(filename).map_err(|e| e.to_string())?;
+        let mut writer = StreamWriter::new(std::io::BufWriter::new(file), framing);
+        for item in items {
+            let written = if item.is::<Record>() {
+                writer.write(&item.cast::<Record>())
+            } else if item.is::<Msg>() {
+                writer.write(&item.cast::<Msg>())
+            } else {
+                Err(format!(
+                    "Cannot write a {} to a USP stream",
+                    item.type_name()
+                ))?
+            };
+            written.map_err(|e| format!("{e:#}"))?;
+        }
+        Ok(writer.flush().map_err(|e| e.to_string())?)
+    }
+}
+
+/// Helpers for the conversion of Rhai Maps into USP structures
+mod map_helpers {
+    use rhai::{Blob, Dynamic, EvalAltResult, Map};
+    use serde_json::Value;
+
+    /// Converts a Rhai value into JSON, representing [`Blob`]s as arrays of bytes like the
+    /// serialisation of `bytes` fields
+    pub fn to_json(value: Dynamic) -> Result<Value, Box<EvalAltResult>> {
+        if value.is_blob() {
+            let blob = value.cast::<Blob>();
+            return Ok(Value::Array(blob.into_iter().map(Value::from).collect()));
+        }
+        if value.is_map() {
+            return value
+                .cast::<Map>()
+                .into_iter()
+                .map(|(key, value)| Ok((key.to_string(), to_json(value)?)))
+                .collect::<Result<_, _>>()
+                .map(Value::Object);
+        }
+        if value.is_array() {
+            return value
+                .into_array()?
+                .into_iter()
+                .map(to_json)
+                .collect::<Result<_, _>>()
+                .map(Value::Array);
+        }
+        rhai::serde::from_dynamic(&value)
+    }
+
+    /// Converts a Rhai Map into its JSON representation
+    pub fn map_to_json(map: Map) -> Result<Value, Box<EvalAltResult>> {
+        to_json(map.into())
+    }
+}
+
+mod assert_helpers {
+    use rhai::{Dynamic, EvalAltResult, NativeCallContext};
+    use rusp_lib::usp::{Body, Msg};
+    use rusp_lib::usp_decoder::try_decode_msg;
+    use rusp_lib::usp_diff::{self, DiffOptions, Difference};
+    use rusp_lib::usp_record::Record;
+
+    pub fn failed(message: &str, details: &str) -> Box<EvalAltResult> {
+        format!("assertion failed: {message}\n{details}").into()
+    }
+
+    pub fn format_differences(differences: &[Difference]) -> String {
+        differences
+            .iter()
+            .map(|difference| format!("  {difference}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the [`Msg`] given directly or carried in a [`Record`]
+    pub fn msg_of(usp: &Dynamic) -> Result<Msg, Box<EvalAltResult>> {
+        if let Some(msg) = usp.read_lock::<Msg>() {
+            return Ok(msg.clone());
+        }
+        let Some(mut record) = usp.clone().try_cast::<Record>() else {
+            Err(format!("Expected a Msg or Record, got {}", usp.type_name()))?
+        };
+        let payload = record
+            .payload_flatten()
+            .ok_or("The Record doesn't carry a Msg")?;
+        Ok(try_decode_msg(payload).map_err(|e| format!("{e:#}"))?)
+    }
+
+    /// Compares two values, [`Msg`]s and [`Record`]s are compared structurally
+    pub fn check_eq(
+        ctx: &NativeCallContext,
+        left: &Dynamic,
+        right: &Dynamic,
+        message: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let options = DiffOptions::new();
+        let differences = if let (Some(left), Some(right)) =
+            (left.read_lock::<Msg>(), right.read_lock::<Msg>())
+        {
+            usp_diff::diff_msg(&left, &right, &options).map_err(|e| e.to_string())?
+        } else if let (Some(left), Some(right)) =
+            (left.read_lock::<Record>(), right.read_lock::<Record>())
+        {
+            usp_diff::diff_record(&left, &right, &options).map_err(|e| e.to_string())?
+        } else if let (Some(left), Some(right)) =
+            (left.read_lock::<Body>(), right.read_lock::<Body>())
+        {
+            if *left == *right {
+                return Ok(());
+            }
+            let json = |body: &Body| serde_json::to_string(body).unwrap_or_default();
+            return Err(failed(
+                message,
+                &format!("  left: {}\n right: {}", json(&left), json(&right)),
+            ));
+        } else {
+            if ctx.call_native_fn::<bool>("==", (left.clone(), right.clone()))? {
+                return Ok(());
+            }
+            return Err(failed(
+                message,
+                &format!("  left: {left:?}\n right: {right:?}"),
+            ));
+        };
+
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(failed(message, &format_differences(&differences)))
+        }
+    }
+}
+
+/// Assertions for tests written in Rhai, e.g. run by `rusp-run test`. Failing assertions raise an
+/// error whose message starts with `assertion failed`, which test runners can tell apart from
+/// other errors
+/// ```
+/// // Rhai script
+/// # let script = r#"
+/// let body = rusp::get_builder().with_params(["Device."]).build();
+/// let expected = rusp::msg_builder().with_msg_id("1").with_body(body).build();
+/// let actual = rusp::msg_builder().with_msg_id("2").with_body(body).build();
+/// rusp::assert_eq(1 + 1, 2);
+/// rusp::assert_msg_eq(actual, expected);
+/// rusp::assert_eq(actual, expected, "msg_ids differ");
+/// # "#;
+/// # let err = rhai_rusp::eval_rusp::<()>(script).unwrap_err();
+/// # assert_eq!(err.to_string(), "Runtime error: assertion failed: msg_ids differ\n  ~ Header.msg_id: \"2\" -> \"1\"");
+/// ```
+#[export_module]
+pub mod rhai_rusp_assert {
+    use super::assert_helpers::{check_eq, failed, format_differences, msg_of};
+    use rhai::NativeCallContext;
+    use rusp_lib::usp::mod_Body::OneOfmsg_body;
+    use rusp_lib::usp_diff::{self, DiffOptions};
+
+    /// Assert that two values are equal. [`Msg`]s, [`Record`]s and [`Body`]s are compared
+    /// structurally and the differences are listed if the assertion fails
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the values are not equal.
+    #[rhai_fn(global, name = "assert_eq", return_raw)]
+    pub fn assert_eq(
+        ctx: NativeCallContext,
+        left: Dynamic,
+        right: Dynamic,
+    ) -> Result<(), Box<EvalAltResult>> {
+        check_eq(&ctx, &left, &right, "left == right")
+    }
+
+    /// Assert that two values are equal, failing with the given message. [`Msg`]s, [`Record`]s
+    /// and [`Body`]s are compared structurally and the differences are listed if the assertion
+    /// fails
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the values are not equal.
+    #[rhai_fn(global, name = "assert_eq", return_raw)]
+    pub fn assert_eq_message(
+        ctx: NativeCallContext,
+        left: Dynamic,
+        right: Dynamic,
+        message: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        check_eq(&ctx, &left, &right, message)
+    }
+
+    /// Assert that two [`Msg`]s are structurally equal, ignoring their `msg_id`, the order of
+    /// map entries and of repeated fields. [`Record`]s can be given instead of [`Msg`]s, in which
+    /// case the [`Msg`]s carried in their payload are compared
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let body = rusp::get_builder().with_params(["Device."]).build();
+    /// let msg = rusp::msg_builder().with_msg_id("1").with_body(body).build();
+    /// let record = rusp::record_builder().with_to_id("proto::to").with_from_id("proto::from").with_no_session_context_payload(msg).build();
+    /// let expected = rusp::msg_builder().with_msg_id("2").with_body(body).build();
+    /// rusp::assert_msg_eq(record, expected);
+    /// # "#;
+    /// # rhai_rusp::eval_rusp::<()>(script).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the [`Msg`]s differ or a [`Record`] doesn't carry a
+    /// [`Msg`].
+    #[rhai_fn(global, name = "assert_msg_eq", return_raw)]
+    pub fn assert_msg_eq(left: Dynamic, right: Dynamic) -> Result<(), Box<EvalAltResult>> {
+        assert_msg_eq_message(left, right, "Msgs are equal")
+    }
+
+    /// Assert that two [`Msg`]s are structurally equal, failing with the given message, see
+    /// [`assert_msg_eq`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the [`Msg`]s differ or a [`Record`] doesn't carry a
+    /// [`Msg`].
+    #[rhai_fn(global, name = "assert_msg_eq", return_raw)]
+    pub fn assert_msg_eq_message(
+        left: Dynamic,
+        right: Dynamic,
+        message: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let options = DiffOptions::new().with_ignore_msg_id(true);
+        let differences = usp_diff::diff_msg(&msg_of(&left)?, &msg_of(&right)?, &options)
+            .map_err(|e| e.to_string())?;
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(failed(message, &format_differences(&differences)))
+        }
+    }
+
+    /// Assert that a [`Msg`], the [`Msg`] carried in a [`Record`] or a [`Body`] is an Error with
+    /// the given error code
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let msg = rusp::error_builder()
+    ///     .set_err(7004, "Invalid arguments")
+    ///     .build()
+    ///     .as_msg_builder()
+    ///     .with_msg_id("1")
+    ///     .build();
+    /// rusp::assert_error_code(msg, 7004);
+    /// rusp::assert_error_code(msg, 7002);
+    /// # "#;
+    /// # let err = rhai_rusp::eval_rusp::<()>(script).unwrap_err();
+    /// # assert_eq!(err.to_string(), "Runtime error: assertion failed: error code is 7002\n  actual: 7004 (Invalid arguments)");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the message is not an Error or carries a different
+    /// error code.
+    #[rhai_fn(global, name = "assert_error_code", return_raw)]
+    pub fn assert_error_code(usp: Dynamic, err_code: i64) -> Result<(), Box<EvalAltResult>> {
+        assert_error_code_message(usp, err_code, &format!("error code is {err_code}"))
+    }
+
+    /// Assert that a [`Msg`], the [`Msg`] carried in a [`Record`] or a [`Body`] is an Error with
+    /// the given error code, failing with the given message, see [`assert_error_code`]
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the message is not an Error or carries a different
+    /// error code.
+    #[rhai_fn(global, name = "assert_error_code", return_raw)]
+    pub fn assert_error_code_message(
+        usp: Dynamic,
+        err_code: i64,
+        message: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let error = match usp.clone().try_cast::<Body>() {
+            Some(body) => match body.msg_body {
+                OneOfmsg_body::error(error) => Some(error),
+                _ => None,
+            },
+            None => msg_of(&usp)?.get_error(),
+        };
+
+        match error {
+            Some(error) if i64::from(error.err_code) == err_code => Ok(()),
+            Some(error) => Err(failed(
+                message,
+                &format!("  actual: {} ({})", error.err_code, error.err_msg),
+            )),
+            None => Err(failed(message, "  actual: not an Error")),
+        }
+    }
 }
 
 def_package! {
@@ -3650,5 +5160,11 @@ def_package! {
         combine_with_exported_module!(module, "rusp", rhai_rusp_registerresp);
         combine_with_exported_module!(module, "rusp", rhai_rusp_set);
         combine_with_exported_module!(module, "rusp", rhai_rusp_setresp);
+        combine_with_exported_module!(module, "rusp", rhai_rusp_assert);
+        properties::register_properties(module);
+        module.set_sub_module("fs", rhai::exported_module!(system::rhai_rusp_fs));
+        module.set_sub_module("env", rhai::exported_module!(system::rhai_rusp_env));
+        module.set_iterable_result::<RecordStream, Record>();
+        module.set_iterable_result::<MsgStream, Msg>();
     }
 }
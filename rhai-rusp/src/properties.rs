@@ -0,0 +1,414 @@
+//! Property getters and setters giving Rhai scripts direct access to the fields of USP Records,
+//! Msgs and all nested types, e.g. `msg.msg_id` or `resp.req_path_results`
+//!
+//! The properties are named after the fields of the USP Protobuf schema. Messages are returned
+//! as their Rust types, repeated fields as arrays, maps as object maps, bytes as blobs and enums as
+//! the names of their values. Every variant of a `oneof` is available as property returning `()`
+//! unless the variant is set, the `oneof` itself returns the name of the set variant.
+//!
+//! Properties are returned as copies, but Rhai writes modified copies back when setting a nested
+//! property like `msg.body.request.get.max_depth = 2`. As shortcut, the `msg_id` and `msg_type`
+//! of the header are also available directly on the Msg.
+//!
+//! ```
+//! // Rhai script
+//! # let script = r#"
+//! let msg = rusp::get_builder()
+//!     .with_params(["Device.DeviceInfo."])
+//!     .build()
+//!     .as_msg_builder()
+//!     .with_msg_id("get")
+//!     .build();
+//! let get = msg.body.request.get;
+//! msg.msg_id = "patched";
+//! msg.body.request.get.max_depth = 2;
+//! msg.body.request.get.param_paths += "Device.Time.";
+//! [msg.msg_id, msg.msg_type, msg.body.msg_body, msg.body.request.req_type, msg.body.response,
+//!  get.max_depth, msg.body.request.get.max_depth, msg.body.request.get.param_paths.len()]
+//! # "#;
+//! # let values = rhai_rusp::eval_rusp::<rhai::Array>(script).unwrap();
+//! # let values: Vec<String> = values.into_iter().map(|v| v.to_string()).collect();
+//! # assert_eq!(values, ["patched", "GET", "request", "get", "", "0", "2", "2"]);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use rhai::{Array, Blob, Dynamic, EvalAltResult, Map, Module, Variant, INT};
+use rusp_lib::{usp, usp_record};
+
+/// A message type of the USP schema
+trait Message: Variant + Clone {}
+
+/// A field type convertible from and into a Rhai value
+trait Property: Sized {
+    fn to_dynamic(&self) -> Dynamic;
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>>;
+}
+
+fn type_error(expected: &str, value: &Dynamic) -> Box<EvalAltResult> {
+    format!("Expected {expected}, got {}", value.type_name()).into()
+}
+
+impl Property for String {
+    fn to_dynamic(&self) -> Dynamic {
+        self.clone().into()
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        value
+            .clone()
+            .into_string()
+            .map_err(|_| type_error("a string", &value))
+    }
+}
+
+impl Property for bool {
+    fn to_dynamic(&self) -> Dynamic {
+        (*self).into()
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        value.as_bool().map_err(|_| type_error("a bool", &value))
+    }
+}
+
+impl Property for u32 {
+    fn to_dynamic(&self) -> Dynamic {
+        INT::from(*self).into()
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        let int = value
+            .as_int()
+            .map_err(|_| type_error("an integer", &value))?;
+        Ok(Self::try_from(int).map_err(|_| format!("{int} is out of range for a u32"))?)
+    }
+}
+
+impl Property for u64 {
+    fn to_dynamic(&self) -> Dynamic {
+        // Values beyond the range of a Rhai integer are shown as negative numbers
+        (*self as INT).into()
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        let int = value
+            .as_int()
+            .map_err(|_| type_error("an integer", &value))?;
+        Ok(int as Self)
+    }
+}
+
+impl Property for Vec<u8> {
+    fn to_dynamic(&self) -> Dynamic {
+        Dynamic::from_blob(self.clone())
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        value
+            .clone()
+            .try_cast::<Blob>()
+            .ok_or_else(|| type_error("a blob", &value))
+    }
+}
+
+impl Property for HashMap<String, String> {
+    fn to_dynamic(&self) -> Dynamic {
+        self.iter()
+            .map(|(key, value)| (key.into(), value.to_dynamic()))
+            .collect::<Map>()
+            .into()
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        let map = value
+            .clone()
+            .try_cast::<Map>()
+            .ok_or_else(|| type_error("an object map", &value))?;
+        map.into_iter()
+            .map(|(key, value)| Ok((key.into(), String::from_dynamic(value)?)))
+            .collect()
+    }
+}
+
+/// Repeated strings, bytes and messages
+trait Element: Property {}
+
+impl Element for String {}
+impl Element for Vec<u8> {}
+impl<T: Message> Element for T {}
+
+impl<T: Element> Property for Vec<T> {
+    fn to_dynamic(&self) -> Dynamic {
+        self.iter()
+            .map(Property::to_dynamic)
+            .collect::<Array>()
+            .into()
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        let array = value
+            .clone()
+            .try_cast::<Array>()
+            .ok_or_else(|| type_error("an array", &value))?;
+        array.into_iter().map(T::from_dynamic).collect()
+    }
+}
+
+impl<T: Message> Property for T {
+    fn to_dynamic(&self) -> Dynamic {
+        Dynamic::from(self.clone())
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        let type_name = std::any::type_name::<T>();
+        let type_name = type_name.rsplit("::").next().unwrap_or(type_name);
+        value
+            .clone()
+            .try_cast::<T>()
+            .ok_or_else(|| type_error(type_name, &value))
+    }
+}
+
+impl<T: Message> Property for Option<T> {
+    fn to_dynamic(&self) -> Dynamic {
+        self.as_ref().map_or(Dynamic::UNIT, Property::to_dynamic)
+    }
+
+    fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+        if value.is_unit() {
+            return Ok(None);
+        }
+        T::from_dynamic(value).map(Some)
+    }
+}
+
+fn enum_to_dynamic<T: Debug>(value: &T) -> Dynamic {
+    format!("{value:?}").into()
+}
+
+fn enum_from_dynamic<T>(value: Dynamic) -> Result<T, Box<EvalAltResult>>
+where
+    T: Debug + for<'a> From<&'a str>,
+{
+    let name = String::from_dynamic(value)?;
+    // Unknown names are converted into the default value by the generated code
+    let parsed = T::from(name.as_str());
+    if format!("{parsed:?}") != name {
+        let type_name = std::any::type_name::<T>();
+        let type_name = type_name.rsplit("::").next().unwrap_or(type_name);
+        Err(format!("Unknown value {name} of enum {type_name}"))?;
+    }
+    Ok(parsed)
+}
+
+/// Registers getters and setters for the `msg_id` and `msg_type` of a Msg, adding a header if
+/// missing
+fn register_shortcuts(module: &mut Module) {
+    module.set_getter_fn("msg_id", |msg: &mut usp::Msg| {
+        Ok(msg
+            .header
+            .as_ref()
+            .map_or(Dynamic::UNIT, |header| header.msg_id.to_dynamic()))
+    });
+    module.set_setter_fn("msg_id", |msg: &mut usp::Msg, value: Dynamic| {
+        msg.header.get_or_insert_with(Default::default).msg_id = String::from_dynamic(value)?;
+        Ok(())
+    });
+    module.set_getter_fn("msg_type", |msg: &mut usp::Msg| {
+        Ok(msg
+            .header
+            .as_ref()
+            .map_or(Dynamic::UNIT, |header| header.msg_type.to_dynamic()))
+    });
+    module.set_setter_fn("msg_type", |msg: &mut usp::Msg, value: Dynamic| {
+        msg.header.get_or_insert_with(Default::default).msg_type = Property::from_dynamic(value)?;
+        Ok(())
+    });
+}
+
+/// Implements [`Property`] for enums and [`Message`] for message types and registers getters and
+/// setters for all fields of the message types
+macro_rules! usp_properties {
+    (
+        enums { $($enum:ty),* $(,)? }
+        messages { $($message:ty { $($field:ident),* $(,)? }),* $(,)? }
+        oneofs { $($parent:ty => $oneof:ident: $oneof_type:ty {
+            $($variant:ident: $variant_type:ty),* $(,)?
+        }),* $(,)? }
+    ) => {
+        $(
+            impl Property for $enum {
+                fn to_dynamic(&self) -> Dynamic {
+                    enum_to_dynamic(self)
+                }
+
+                fn from_dynamic(value: Dynamic) -> Result<Self, Box<EvalAltResult>> {
+                    enum_from_dynamic(value)
+                }
+            }
+        )*
+
+        $(impl Message for $message {})*
+
+        /// Registers the getters and setters of all fields in the module
+        pub(crate) fn register_properties(module: &mut Module) {
+            register_shortcuts(module);
+
+            $($(
+                module.set_getter_fn(stringify!($field), |message: &mut $message| {
+                    Ok(message.$field.to_dynamic())
+                });
+                module.set_setter_fn(stringify!($field), |message: &mut $message, value: Dynamic| {
+                    message.$field = Property::from_dynamic(value)?;
+                    Ok(())
+                });
+            )*)*
+
+            $({
+                type OneOf = $oneof_type;
+                module.set_getter_fn(stringify!($oneof), |message: &mut $parent| {
+                    Ok(match &message.$oneof {
+                        $(OneOf::$variant(_) => stringify!($variant).into(),)*
+                        OneOf::None => Dynamic::UNIT,
+                    })
+                });
+                $(
+                    module.set_getter_fn(stringify!($variant), |message: &mut $parent| {
+                        Ok(match &message.$oneof {
+                            OneOf::$variant(value) => value.to_dynamic(),
+                            _ => Dynamic::UNIT,
+                        })
+                    });
+                    module.set_setter_fn(
+                        stringify!($variant),
+                        |message: &mut $parent, value: Dynamic| {
+                            message.$oneof = OneOf::$variant(<$variant_type>::from_dynamic(value)?);
+                            Ok(())
+                        },
+                    );
+                )*
+            })*
+        }
+    };
+}
+
+usp_properties! {
+    enums {
+        usp::mod_Header::MsgType,
+        usp::mod_GetSupportedDMResp::ParamAccessType,
+        usp::mod_GetSupportedDMResp::ObjAccessType,
+        usp::mod_GetSupportedDMResp::ParamValueType,
+        usp::mod_GetSupportedDMResp::ValueChangeType,
+        usp::mod_GetSupportedDMResp::CmdType,
+        usp_record::mod_Record::PayloadSecurity,
+        usp_record::mod_SessionContextRecord::PayloadSARState,
+        usp_record::mod_MQTTConnectRecord::MQTTVersion,
+        usp_record::mod_STOMPConnectRecord::STOMPVersion,
+    }
+    messages {
+        usp::Msg { header, body },
+        usp::Header { msg_id, msg_type },
+        usp::Body {},
+        usp::Request {},
+        usp::Response {},
+        usp::Error { err_code, err_msg, param_errs },
+        usp::mod_Error::ParamError { param_path, err_code, err_msg },
+        usp::Get { param_paths, max_depth },
+        usp::GetResp { req_path_results },
+        usp::mod_GetResp::RequestedPathResult { requested_path, err_code, err_msg, resolved_path_results },
+        usp::mod_GetResp::ResolvedPathResult { resolved_path, result_params },
+        usp::GetSupportedDM { obj_paths, first_level_only, return_commands, return_events, return_params, return_unique_key_sets },
+        usp::GetSupportedDMResp { req_obj_results },
+        usp::mod_GetSupportedDMResp::RequestedObjectResult { req_obj_path, err_code, err_msg, data_model_inst_uri, supported_objs },
+        usp::mod_GetSupportedDMResp::SupportedObjectResult { supported_obj_path, access, is_multi_instance, supported_commands, supported_events, supported_params, divergent_paths, unique_key_sets },
+        usp::mod_GetSupportedDMResp::SupportedParamResult { param_name, access, value_type, value_change },
+        usp::mod_GetSupportedDMResp::SupportedCommandResult { command_name, input_arg_names, output_arg_names, command_type },
+        usp::mod_GetSupportedDMResp::SupportedEventResult { event_name, arg_names },
+        usp::mod_GetSupportedDMResp::SupportedUniqueKeySet { key_names },
+        usp::GetInstances { obj_paths, first_level_only },
+        usp::GetInstancesResp { req_path_results },
+        usp::mod_GetInstancesResp::RequestedPathResult { requested_path, err_code, err_msg, curr_insts },
+        usp::mod_GetInstancesResp::CurrInstance { instantiated_obj_path, unique_keys },
+        usp::GetSupportedProtocol { controller_supported_protocol_versions },
+        usp::GetSupportedProtocolResp { agent_supported_protocol_versions },
+        usp::Add { allow_partial, create_objs },
+        usp::mod_Add::CreateObject { obj_path, param_settings },
+        usp::mod_Add::CreateParamSetting { param, value, required },
+        usp::AddResp { created_obj_results },
+        usp::mod_AddResp::CreatedObjectResult { requested_path, oper_status },
+        usp::mod_AddResp::mod_CreatedObjectResult::OperationStatus {},
+        usp::mod_AddResp::mod_CreatedObjectResult::mod_OperationStatus::OperationFailure { err_code, err_msg },
+        usp::mod_AddResp::mod_CreatedObjectResult::mod_OperationStatus::OperationSuccess { instantiated_path, param_errs, unique_keys },
+        usp::mod_AddResp::ParameterError { param, err_code, err_msg },
+        usp::Delete { allow_partial, obj_paths },
+        usp::DeleteResp { deleted_obj_results },
+        usp::mod_DeleteResp::DeletedObjectResult { requested_path, oper_status },
+        usp::mod_DeleteResp::mod_DeletedObjectResult::OperationStatus {},
+        usp::mod_DeleteResp::mod_DeletedObjectResult::mod_OperationStatus::OperationFailure { err_code, err_msg },
+        usp::mod_DeleteResp::mod_DeletedObjectResult::mod_OperationStatus::OperationSuccess { affected_paths, unaffected_path_errs },
+        usp::mod_DeleteResp::UnaffectedPathError { unaffected_path, err_code, err_msg },
+        usp::Set { allow_partial, update_objs },
+        usp::mod_Set::UpdateObject { obj_path, param_settings },
+        usp::mod_Set::UpdateParamSetting { param, value, required },
+        usp::SetResp { updated_obj_results },
+        usp::mod_SetResp::UpdatedObjectResult { requested_path, oper_status },
+        usp::mod_SetResp::mod_UpdatedObjectResult::OperationStatus {},
+        usp::mod_SetResp::mod_UpdatedObjectResult::mod_OperationStatus::OperationFailure { err_code, err_msg, updated_inst_failures },
+        usp::mod_SetResp::mod_UpdatedObjectResult::mod_OperationStatus::OperationSuccess { updated_inst_results },
+        usp::mod_SetResp::UpdatedInstanceFailure { affected_path, param_errs },
+        usp::mod_SetResp::UpdatedInstanceResult { affected_path, param_errs, updated_params },
+        usp::mod_SetResp::ParameterError { param, err_code, err_msg },
+        usp::Operate { command, command_key, send_resp, input_args },
+        usp::OperateResp { operation_results },
+        usp::mod_OperateResp::OperationResult { executed_command },
+        usp::mod_OperateResp::mod_OperationResult::OutputArgs { output_args },
+        usp::mod_OperateResp::mod_OperationResult::CommandFailure { err_code, err_msg },
+        usp::Notify { subscription_id, send_resp },
+        usp::mod_Notify::Event { obj_path, event_name, params },
+        usp::mod_Notify::ValueChange { param_path, param_value },
+        usp::mod_Notify::ObjectCreation { obj_path, unique_keys },
+        usp::mod_Notify::ObjectDeletion { obj_path },
+        usp::mod_Notify::OperationComplete { obj_path, command_name, command_key },
+        usp::mod_Notify::mod_OperationComplete::OutputArgs { output_args },
+        usp::mod_Notify::mod_OperationComplete::CommandFailure { err_code, err_msg },
+        usp::mod_Notify::OnBoardRequest { oui, product_class, serial_number, agent_supported_protocol_versions },
+        usp::NotifyResp { subscription_id },
+        usp::Register { allow_partial, reg_paths },
+        usp::mod_Register::RegistrationPath { path },
+        usp::RegisterResp { registered_path_results },
+        usp::mod_RegisterResp::RegisteredPathResult { requested_path, oper_status },
+        usp::mod_RegisterResp::mod_RegisteredPathResult::OperationStatus {},
+        usp::mod_RegisterResp::mod_RegisteredPathResult::mod_OperationStatus::OperationFailure { err_code, err_msg },
+        usp::mod_RegisterResp::mod_RegisteredPathResult::mod_OperationStatus::OperationSuccess { registered_path },
+        usp::Deregister { paths },
+        usp::DeregisterResp { deregistered_path_results },
+        usp::mod_DeregisterResp::DeregisteredPathResult { requested_path, oper_status },
+        usp::mod_DeregisterResp::mod_DeregisteredPathResult::OperationStatus {},
+        usp::mod_DeregisterResp::mod_DeregisteredPathResult::mod_OperationStatus::OperationFailure { err_code, err_msg },
+        usp::mod_DeregisterResp::mod_DeregisteredPathResult::mod_OperationStatus::OperationSuccess { deregistered_path },
+        usp_record::Record { version, to_id, from_id, originator_id, destination_id, payload_security, mac_signature, sender_cert },
+        usp_record::NoSessionContextRecord { payload },
+        usp_record::SessionContextRecord { session_id, sequence_id, expected_id, retransmit_id, payload_sar_state, payloadrec_sar_state, payload },
+        usp_record::WebSocketConnectRecord {},
+        usp_record::MQTTConnectRecord { version, subscribed_topic },
+        usp_record::STOMPConnectRecord { version, subscribed_destination },
+        usp_record::UDSConnectRecord {},
+        usp_record::DisconnectRecord { reason, reason_code },
+    }
+    oneofs {
+        usp::Body => msg_body: usp::mod_Body::OneOfmsg_body { request: usp::Request, response: usp::Response, error: usp::Error },
+        usp::Request => req_type: usp::mod_Request::OneOfreq_type { get: usp::Get, get_supported_dm: usp::GetSupportedDM, get_instances: usp::GetInstances, set: usp::Set, add: usp::Add, delete: usp::Delete, operate: usp::Operate, notify: usp::Notify, get_supported_protocol: usp::GetSupportedProtocol, register: usp::Register, deregister: usp::Deregister },
+        usp::Response => resp_type: usp::mod_Response::OneOfresp_type { get_resp: usp::GetResp, get_supported_dm_resp: usp::GetSupportedDMResp, get_instances_resp: usp::GetInstancesResp, set_resp: usp::SetResp, add_resp: usp::AddResp, delete_resp: usp::DeleteResp, operate_resp: usp::OperateResp, notify_resp: usp::NotifyResp, get_supported_protocol_resp: usp::GetSupportedProtocolResp, register_resp: usp::RegisterResp, deregister_resp: usp::DeregisterResp },
+        usp::mod_AddResp::mod_CreatedObjectResult::OperationStatus => oper_status: usp::mod_AddResp::mod_CreatedObjectResult::mod_OperationStatus::OneOfoper_status { oper_failure: usp::mod_AddResp::mod_CreatedObjectResult::mod_OperationStatus::OperationFailure, oper_success: usp::mod_AddResp::mod_CreatedObjectResult::mod_OperationStatus::OperationSuccess },
+        usp::mod_DeleteResp::mod_DeletedObjectResult::OperationStatus => oper_status: usp::mod_DeleteResp::mod_DeletedObjectResult::mod_OperationStatus::OneOfoper_status { oper_failure: usp::mod_DeleteResp::mod_DeletedObjectResult::mod_OperationStatus::OperationFailure, oper_success: usp::mod_DeleteResp::mod_DeletedObjectResult::mod_OperationStatus::OperationSuccess },
+        usp::mod_SetResp::mod_UpdatedObjectResult::OperationStatus => oper_status: usp::mod_SetResp::mod_UpdatedObjectResult::mod_OperationStatus::OneOfoper_status { oper_failure: usp::mod_SetResp::mod_UpdatedObjectResult::mod_OperationStatus::OperationFailure, oper_success: usp::mod_SetResp::mod_UpdatedObjectResult::mod_OperationStatus::OperationSuccess },
+        usp::mod_OperateResp::OperationResult => operation_resp: usp::mod_OperateResp::mod_OperationResult::OneOfoperation_resp { req_obj_path: String, req_output_args: usp::mod_OperateResp::mod_OperationResult::OutputArgs, cmd_failure: usp::mod_OperateResp::mod_OperationResult::CommandFailure },
+        usp::Notify => notification: usp::mod_Notify::OneOfnotification { event: usp::mod_Notify::Event, value_change: usp::mod_Notify::ValueChange, obj_creation: usp::mod_Notify::ObjectCreation, obj_deletion: usp::mod_Notify::ObjectDeletion, oper_complete: usp::mod_Notify::OperationComplete, on_board_req: usp::mod_Notify::OnBoardRequest },
+        usp::mod_Notify::OperationComplete => operation_resp: usp::mod_Notify::mod_OperationComplete::OneOfoperation_resp { req_output_args: usp::mod_Notify::mod_OperationComplete::OutputArgs, cmd_failure: usp::mod_Notify::mod_OperationComplete::CommandFailure },
+        usp::mod_RegisterResp::mod_RegisteredPathResult::OperationStatus => oper_status: usp::mod_RegisterResp::mod_RegisteredPathResult::mod_OperationStatus::OneOfoper_status { oper_failure: usp::mod_RegisterResp::mod_RegisteredPathResult::mod_OperationStatus::OperationFailure, oper_success: usp::mod_RegisterResp::mod_RegisteredPathResult::mod_OperationStatus::OperationSuccess },
+        usp::mod_DeregisterResp::mod_DeregisteredPathResult::OperationStatus => oper_status: usp::mod_DeregisterResp::mod_DeregisteredPathResult::mod_OperationStatus::OneOfoper_status { oper_failure: usp::mod_DeregisterResp::mod_DeregisteredPathResult::mod_OperationStatus::OperationFailure, oper_success: usp::mod_DeregisterResp::mod_DeregisteredPathResult::mod_OperationStatus::OperationSuccess },
+        usp_record::Record => record_type: usp_record::mod_Record::OneOfrecord_type { no_session_context: usp_record::NoSessionContextRecord, session_context: usp_record::SessionContextRecord, websocket_connect: usp_record::WebSocketConnectRecord, mqtt_connect: usp_record::MQTTConnectRecord, stomp_connect: usp_record::STOMPConnectRecord, disconnect: usp_record::DisconnectRecord, uds_connect: usp_record::UDSConnectRecord },
+    }
+}
@@ -0,0 +1,467 @@
+//! Access to the file system and the environment for Rhai scripts, available as `rusp::fs` and
+//! `rusp::env`
+//!
+//! All functions touching the file system, including the loading and saving functions of the
+//! `rusp` module, and the environment variables can be disabled per engine via [`SystemAccess`]
+//! when running untrusted scripts.
+//!
+//! ```
+//! // Rhai script
+//! # let script = r#"
+//! let dir = rusp::env::get("TMPDIR") ?? "/tmp";
+//! let file = `${dir}/rhai-rusp-doc-${rusp::env::unique_msg_id()}.json`;
+//! rusp::fs::write_json(file, #{ msg_id: "get", paths: ["Device."] });
+//! let fixture = rusp::fs::read_json(file);
+//! rusp::fs::remove_file(file);
+//! fixture.paths
+//! # "#;
+//! # let paths = rhai_rusp::eval_rusp::<rhai::Array>(script).unwrap();
+//! # assert_eq!(paths[0].clone().into_string().unwrap(), "Device.");
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rhai::plugin::{export_module, Dynamic, EvalAltResult};
+use rhai::{Array, Engine, Map, NativeCallContext};
+
+/// Access rights of Rhai scripts to the file system and the environment, granting all access by
+/// default
+///
+/// The rights are kept per [`Engine`] in its default tag, so they must be applied via
+/// [`SystemAccess::apply`] after any other use of [`Engine::set_default_tag`]. While file system
+/// access is denied, all functions reading or writing files, like `rusp::load_msg`,
+/// `rusp::save_protobuf` or `rusp::fs::read_text`, fail with an error. While environment access
+/// is denied, `rusp::env::get` and `rusp::env::vars` fail with an error.
+///
+/// ```
+/// use rhai::{packages::Package, Engine};
+/// use rhai_rusp::{RuspPackage, SystemAccess};
+///
+/// let mut engine = Engine::new();
+/// engine.register_static_module("rusp", RuspPackage::new().as_shared_module());
+/// SystemAccess::default()
+///     .with_fs(false)
+///     .with_env(false)
+///     .apply(&mut engine);
+///
+/// let err = engine.eval::<rusp_lib::usp::Msg>(r#"rusp::load_msg("get.pb")"#).unwrap_err();
+/// assert!(err.to_string().contains("File system access is disabled"));
+/// let err = engine.eval::<rhai::Dynamic>(r#"rusp::env::get("HOME")"#).unwrap_err();
+/// assert!(err.to_string().contains("Environment access is disabled"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SystemAccess {
+    fs: bool,
+    env: bool,
+}
+
+impl Default for SystemAccess {
+    fn default() -> Self {
+        Self {
+            fs: true,
+            env: true,
+        }
+    }
+}
+
+impl SystemAccess {
+    /// Allows or denies access to the file system
+    #[must_use]
+    pub const fn with_fs(mut self, allowed: bool) -> Self {
+        self.fs = allowed;
+        self
+    }
+
+    /// Allows or denies access to the environment variables
+    #[must_use]
+    pub const fn with_env(mut self, allowed: bool) -> Self {
+        self.env = allowed;
+        self
+    }
+
+    /// Returns whether access to the file system is allowed
+    #[must_use]
+    pub const fn fs(&self) -> bool {
+        self.fs
+    }
+
+    /// Returns whether access to the environment variables is allowed
+    #[must_use]
+    pub const fn env(&self) -> bool {
+        self.env
+    }
+
+    /// Applies the access rights to all scripts evaluated by an engine
+    pub fn apply(self, engine: &mut Engine) {
+        engine.set_default_tag(Dynamic::from(self));
+    }
+
+    /// Returns the access rights applied to an engine, all access is granted unless
+    /// [`SystemAccess::apply`] has been called
+    #[must_use]
+    pub fn of(engine: &Engine) -> Self {
+        Self::from_tag(Some(engine.default_tag()))
+    }
+
+    fn from_tag(tag: Option<&Dynamic>) -> Self {
+        tag.and_then(|tag| tag.read_lock::<Self>().map(|access| *access))
+            .unwrap_or_default()
+    }
+}
+
+/// Fails unless the calling script may access the file system
+pub(crate) fn check_fs_access(ctx: &NativeCallContext) -> Result<(), Box<EvalAltResult>> {
+    if SystemAccess::from_tag(ctx.tag()).fs {
+        Ok(())
+    } else {
+        Err("File system access is disabled".into())
+    }
+}
+
+/// Fails unless the calling script may access the environment variables
+fn check_env_access(ctx: &NativeCallContext) -> Result<(), Box<EvalAltResult>> {
+    if SystemAccess::from_tag(ctx.tag()).env {
+        Ok(())
+    } else {
+        Err("Environment access is disabled".into())
+    }
+}
+
+/// Matches a file name against a pattern with the wildcards `*` and `?`
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| wildcard_match(rest, &name[skip..])),
+        Some(('?', rest)) => name
+            .split_first()
+            .is_some_and(|(_, name)| wildcard_match(rest, name)),
+        Some((c, rest)) => name
+            .split_first()
+            .is_some_and(|(n, name)| n == c && wildcard_match(rest, name)),
+    }
+}
+
+/// Returns the entries of a directory, the empty path denoting the current directory
+fn dir_entries(dir: &Path) -> Vec<PathBuf> {
+    let read = if dir.as_os_str().is_empty() {
+        std::fs::read_dir(".")
+    } else {
+        std::fs::read_dir(dir)
+    };
+    read.into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| dir.join(entry.file_name()))
+        .collect()
+}
+
+/// Adds all directories below `dir` to `dirs`
+fn sub_dirs(dir: &Path, dirs: &mut Vec<PathBuf>) {
+    for entry in dir_entries(dir) {
+        if entry.is_dir() {
+            dirs.push(entry.clone());
+            sub_dirs(&entry, dirs);
+        }
+    }
+}
+
+/// Returns the sorted paths matching a pattern, in which `*` and `?` match within a single path
+/// component and `**` matches any number of directories
+///
+/// Like in shells, wildcards don't match names starting with a dot.
+fn glob(pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![if pattern.starts_with('/') {
+        PathBuf::from("/")
+    } else {
+        PathBuf::new()
+    }];
+
+    for component in pattern.split('/').filter(|c| !c.is_empty()) {
+        if component == "**" {
+            let mut dirs = vec![];
+            for path in paths
+                .iter()
+                .filter(|p| p.as_os_str().is_empty() || p.is_dir())
+            {
+                dirs.push(path.clone());
+                sub_dirs(path, &mut dirs);
+            }
+            paths = dirs;
+        } else if component.contains(['*', '?']) {
+            let pattern: Vec<char> = component.chars().collect();
+            paths = paths
+                .iter()
+                .flat_map(|path| dir_entries(path))
+                .filter(|path| {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    let name: Vec<char> = name.chars().collect();
+                    !name.starts_with(&['.']) && wildcard_match(&pattern, &name)
+                })
+                .collect();
+        } else {
+            paths = paths
+                .iter()
+                .map(|path| path.join(component))
+                .filter(|path| path.exists())
+                .collect();
+        }
+    }
+
+    paths.retain(|path| !path.as_os_str().is_empty());
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Formats a number of seconds since the Unix epoch as RFC 3339 timestamp in UTC
+fn format_timestamp(secs: i64) -> String {
+    // Conversion of days to the civil calendar, see http://howardhinnant.github.io/date_algorithms.html
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Returns the time elapsed since the Unix epoch
+fn since_epoch() -> std::time::Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// Reading and writing of files, available as `rusp::fs`
+#[export_module]
+pub mod rhai_rusp_fs {
+    /// Read the contents of a text file
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if file system access is disabled or the file cannot be read.
+    #[rhai_fn(return_raw)]
+    pub fn read_text(ctx: NativeCallContext, filename: &str) -> Result<String, Box<EvalAltResult>> {
+        super::check_fs_access(&ctx)?;
+        Ok(std::fs::read_to_string(filename).map_err(|e| format!("{filename}: {e}"))?)
+    }
+
+    /// Write a string into a file, replacing its contents
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if file system access is disabled or the file cannot be written.
+    #[rhai_fn(return_raw)]
+    pub fn write_text(
+        ctx: NativeCallContext,
+        filename: &str,
+        text: &str,
+    ) -> Result<(), Box<EvalAltResult>> {
+        super::check_fs_access(&ctx)?;
+        Ok(std::fs::write(filename, text).map_err(|e| format!("{filename}: {e}"))?)
+    }
+
+    /// Read a JSON file into a Rhai value, objects are turned into maps
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let devices = rusp::fs::read_json("devices.json");
+    /// for device in devices {
+    ///     print(device.endpoint_id);
+    /// }
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if file system access is disabled, the file cannot be read or does not contain valid JSON.
+    #[rhai_fn(return_raw)]
+    pub fn read_json(
+        ctx: NativeCallContext,
+        filename: &str,
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let text = read_text(ctx, filename)?;
+        let value: serde_json::Value =
+            serde_json::from_str(&text).map_err(|e| format!("{filename}: {e}"))?;
+        rhai::serde::to_dynamic(value)
+    }
+
+    /// Write a Rhai value, e.g. a map or the result of `to_map()`, into a file as pretty printed
+    /// JSON
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if file system access is disabled, the file cannot be written or the value cannot be
+    /// represented in JSON.
+    #[rhai_fn(return_raw)]
+    pub fn write_json(
+        ctx: NativeCallContext,
+        filename: &str,
+        value: Dynamic,
+    ) -> Result<(), Box<EvalAltResult>> {
+        let value = crate::map_helpers::to_json(value)?;
+        let text = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
+        write_text(ctx, filename, &text)
+    }
+
+    /// Return the sorted paths matching a pattern, in which `*` and `?` match within a single
+    /// path component and `**` matches any number of directories
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// for file in rusp::fs::glob("fixtures/**/*.pb") {
+    ///     print(rusp::load_msg(file).msg_id);
+    /// }
+    /// # "#;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if file system access is disabled.
+    #[rhai_fn(return_raw)]
+    pub fn glob(ctx: NativeCallContext, pattern: &str) -> Result<Array, Box<EvalAltResult>> {
+        super::check_fs_access(&ctx)?;
+        Ok(super::glob(pattern)
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned().into())
+            .collect())
+    }
+
+    /// Check whether a file or directory exists
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if file system access is disabled.
+    #[rhai_fn(return_raw)]
+    pub fn exists(ctx: NativeCallContext, path: &str) -> Result<bool, Box<EvalAltResult>> {
+        super::check_fs_access(&ctx)?;
+        Ok(std::path::Path::new(path).exists())
+    }
+
+    /// Create a directory including all missing parent directories
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if file system access is disabled or the directory cannot be created.
+    #[rhai_fn(return_raw)]
+    pub fn create_dir(ctx: NativeCallContext, path: &str) -> Result<(), Box<EvalAltResult>> {
+        super::check_fs_access(&ctx)?;
+        Ok(std::fs::create_dir_all(path).map_err(|e| format!("{path}: {e}"))?)
+    }
+
+    /// Remove a file
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if file system access is disabled or the file cannot be removed.
+    #[rhai_fn(return_raw)]
+    pub fn remove_file(ctx: NativeCallContext, filename: &str) -> Result<(), Box<EvalAltResult>> {
+        super::check_fs_access(&ctx)?;
+        Ok(std::fs::remove_file(filename).map_err(|e| format!("{filename}: {e}"))?)
+    }
+}
+
+/// Environment variables, time and unique identifiers, available as `rusp::env`
+#[export_module]
+pub mod rhai_rusp_env {
+    /// Return the value of an environment variable or `()` if it is not set
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// let agent = rusp::env::get("AGENT_ID") ?? "proto::agent";
+    /// # agent
+    /// # "#;
+    /// # assert!(!rhai_rusp::eval_rusp::<String>(script).unwrap().is_empty());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if environment access is disabled.
+    #[rhai_fn(return_raw)]
+    pub fn get(ctx: NativeCallContext, name: &str) -> Result<Dynamic, Box<EvalAltResult>> {
+        super::check_env_access(&ctx)?;
+        Ok(std::env::var(name).map_or(Dynamic::UNIT, Into::into))
+    }
+
+    /// Return all environment variables as map
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if environment access is disabled.
+    #[rhai_fn(return_raw)]
+    pub fn vars(ctx: NativeCallContext) -> Result<Map, Box<EvalAltResult>> {
+        super::check_env_access(&ctx)?;
+        Ok(std::env::vars()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect())
+    }
+
+    /// Return the current time as RFC 3339 timestamp in UTC, e.g. `2024-01-01T00:00:00Z` as used
+    /// for `dateTime` parameters
+    pub fn now() -> String {
+        let secs = i64::try_from(super::since_epoch().as_secs()).unwrap_or(i64::MAX);
+        super::format_timestamp(secs)
+    }
+
+    /// Return the number of seconds since the Unix epoch
+    pub fn timestamp() -> f64 {
+        super::since_epoch().as_secs_f64()
+    }
+
+    /// Format a number of seconds since the Unix epoch as RFC 3339 timestamp in UTC
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// rusp::env::format_timestamp(1704067200)
+    /// # "#;
+    /// # assert_eq!(rhai_rusp::eval_rusp::<String>(script).unwrap(), "2024-01-01T00:00:00Z");
+    /// ```
+    pub fn format_timestamp(secs: i64) -> String {
+        super::format_timestamp(secs)
+    }
+
+    /// Return a msg_id which is unique across calls and processes, e.g. `rusp-1704067200123-4711-0`
+    /// ```
+    /// // Rhai script
+    /// # let script = r#"
+    /// [rusp::env::unique_msg_id(), rusp::env::unique_msg_id("get")]
+    /// # "#;
+    /// # let ids = rhai_rusp::eval_rusp::<rhai::Array>(script).unwrap();
+    /// # assert_ne!(ids[0].to_string(), ids[1].to_string());
+    /// # assert!(ids[1].to_string().starts_with("get-"));
+    /// ```
+    #[rhai_fn(name = "unique_msg_id")]
+    pub fn unique_msg_id() -> String {
+        unique_msg_id_with_prefix("rusp")
+    }
+
+    /// Return a msg_id with the given prefix which is unique across calls and processes
+    #[rhai_fn(name = "unique_msg_id")]
+    pub fn unique_msg_id_with_prefix(prefix: &str) -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        format!(
+            "{prefix}-{}-{}-{}",
+            super::since_epoch().as_millis(),
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+}
@@ -0,0 +1,69 @@
+use argh::FromArgs;
+use rusp_lib::usp_c_header::CHeaderBuilder;
+
+use anyhow::{Context, Result};
+
+use std::path::PathBuf;
+
+use crate::input::{input_files, read_protobuf, InputFormat, Protobuf, ProtobufType};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "c-header")]
+/// generate a C header containing USP Records and Msgs from Protobuf files
+pub(crate) struct CHeaderArgs {
+    #[argh(option, long = "output", short = 'o')]
+    /// filename of the C header to write, the header is written to stdout if omitted
+    output: Option<PathBuf>,
+
+    #[argh(option, long = "name", short = 'n')]
+    /// name of the header used for the include guard and the table, defaults to the name of the
+    /// output file
+    name: Option<String>,
+
+    #[argh(switch, long = "table", short = 't')]
+    /// add a table of all contained arrays
+    table: bool,
+
+    #[argh(
+        option,
+        long = "input-format",
+        short = 'f',
+        default = "InputFormat::Protobuf"
+    )]
+    /// format of the input files: protobuf (default), base64, hex, prototxt or json
+    input_format: InputFormat,
+
+    #[argh(positional)]
+    /// files containing USP Records or Msgs, the arrays are named after the files, stdin is read
+    /// if omitted
+    files: Vec<PathBuf>,
+}
+
+pub(crate) fn c_header(args: CHeaderArgs) -> Result<()> {
+    let name = args
+        .name
+        .or_else(|| {
+            args.output
+                .as_ref()
+                .and_then(|output| output.file_stem())
+                .map(|stem| stem.to_string_lossy().into())
+        })
+        .unwrap_or_else(|| "usp_test_vectors".into());
+
+    let mut builder = CHeaderBuilder::new(&name).with_table(args.table);
+    for file in input_files(&args.files) {
+        builder = match read_protobuf(file, args.input_format, ProtobufType::Auto)? {
+            (name, Protobuf::Msg(msg)) => builder.with_msg(&name, msg),
+            (name, Protobuf::Record(record)) => builder.with_record(&name, record),
+        };
+    }
+
+    let header = builder.build()?;
+    match args.output {
+        Some(output) => std::fs::write(&output, header)
+            .with_context(|| format!("Couldn't write C header {}", output.display()))?,
+        None => print!("{header}"),
+    }
+
+    Ok(())
+}
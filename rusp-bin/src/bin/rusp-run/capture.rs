@@ -0,0 +1,47 @@
+use argh::FromArgs;
+use rusp_lib::usp_capture::extract_records;
+
+use anyhow::{Context, Result};
+
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::input::{input_files, read_input, InputFormat};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "capture")]
+/// extract USP Records carried via WebSocket, MQTT or STOMP from pcap or pcapng captures
+pub(crate) struct CaptureArgs {
+    #[argh(option, long = "output", short = 'o')]
+    /// filename to write the output to, the output is written to stdout if omitted
+    output: Option<PathBuf>,
+
+    #[argh(positional)]
+    /// pcap or pcapng files, e.g. recorded by tcpdump, stdin is read if omitted
+    files: Vec<PathBuf>,
+}
+
+pub(crate) fn capture(args: &CaptureArgs) -> Result<()> {
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(output) => Box::new(
+            File::create(output)
+                .with_context(|| format!("Couldn't create output file {}", output.display()))?,
+        ),
+        None => Box::new(std::io::stdout()),
+    };
+
+    let mut records = vec![];
+    for file in input_files(&args.files) {
+        let data = read_input(file, InputFormat::Protobuf)?;
+        records.extend(extract_records(&data).with_context(|| file.display().to_string())?);
+    }
+    // The Records of several captures are merged chronologically
+    records.sort_by_key(|record| record.timestamp);
+
+    for record in &records {
+        writeln!(out, "{}", serde_json::to_string(record)?)?;
+    }
+
+    Ok(())
+}
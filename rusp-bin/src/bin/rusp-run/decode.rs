@@ -0,0 +1,211 @@
+use argh::FromArgs;
+use rusp_lib::usp_decoder::try_decode_msg;
+use rusp_lib::usp_hexdump::{hexdump_msg, hexdump_record};
+
+use anyhow::{anyhow, Context, Result};
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::input::{
+    decode_protobuf, display_name, input_files, read_input, read_protobuf, InputFormat, Protobuf,
+    ProtobufType,
+};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "decode")]
+/// decode USP Records and Msgs, unwrapping the Msgs carried in Records
+pub(crate) struct DecodeArgs {
+    #[argh(
+        option,
+        long = "input-format",
+        short = 'f',
+        default = "InputFormat::Protobuf"
+    )]
+    /// format of the input files: protobuf (default), base64, hex, prototxt or json
+    input_format: InputFormat,
+
+    #[argh(
+        option,
+        long = "output-format",
+        short = 'F',
+        default = "OutputFormat::Json"
+    )]
+    /// format of the output: json (default), c-array, c-string, protobuf, prototxt or hexdump;
+    /// hexdump also dumps data which cannot be decoded
+    output_format: OutputFormat,
+
+    #[argh(option, long = "type", default = "ProtobufType::Auto")]
+    /// type of the input: auto (default) to detect whether it is a Record or Msg, record or msg
+    kind: ProtobufType,
+
+    #[argh(switch, long = "keep-record", short = 'k')]
+    /// output Records as they are, instead of the Msg carried in their payload
+    keep_record: bool,
+
+    #[argh(option, long = "output", short = 'o')]
+    /// filename to write the output to, the output is written to stdout if omitted
+    output: Option<PathBuf>,
+
+    #[argh(positional)]
+    /// files containing USP Records or Msgs, stdin is read if omitted
+    files: Vec<PathBuf>,
+}
+
+/// The supported output formats
+#[derive(Clone, Copy)]
+enum OutputFormat {
+    /// Valid JSON format
+    Json,
+    /// Protobuf output as C array with preview comments for inclusion in source code
+    CArray,
+    /// Protobuf output as C string where non-ASCII characters are replaced with backslashed
+    /// escaped hex codes
+    CStr,
+    /// Native Protobuf binary output
+    Protobuf,
+    /// Protobuf text format output, as produced by `protoc --decode`
+    Prototxt,
+    /// Annotated hex dump of the Protobuf encoding
+    Hexdump,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "c-array" | "carray" => Ok(Self::CArray),
+            "c-string" | "cstr" => Ok(Self::CStr),
+            "protobuf" | "pb" => Ok(Self::Protobuf),
+            "prototxt" => Ok(Self::Prototxt),
+            "hexdump" => Ok(Self::Hexdump),
+            _ => Err(format!(
+                "Unsupported output format {s}, expected one of json, c-array, c-string, protobuf, prototxt or hexdump"
+            )),
+        }
+    }
+}
+
+/// Writes a USP Record or Msg to the output stream in the specified format
+fn write_protobuf(out: &mut dyn Write, protobuf: &Protobuf, format: OutputFormat) -> Result<()> {
+    let data = match (protobuf, format) {
+        (Protobuf::Msg(msg), OutputFormat::Json) => serde_json::to_string_pretty(msg)? + "\n",
+        (Protobuf::Msg(msg), OutputFormat::CArray) => msg.to_c_array()?,
+        (Protobuf::Msg(msg), OutputFormat::CStr) => msg.to_c_str()?,
+        (Protobuf::Msg(msg), OutputFormat::Protobuf) => return Ok(out.write_all(&msg.to_vec()?)?),
+        (Protobuf::Msg(msg), OutputFormat::Prototxt) => msg.to_prototxt()?,
+        (Protobuf::Msg(msg), OutputFormat::Hexdump) => hexdump_msg(&msg.to_vec()?),
+        (Protobuf::Record(record), OutputFormat::Json) => {
+            serde_json::to_string_pretty(record)? + "\n"
+        }
+        (Protobuf::Record(record), OutputFormat::CArray) => record.to_c_array()?,
+        (Protobuf::Record(record), OutputFormat::CStr) => record.to_c_str()?,
+        (Protobuf::Record(record), OutputFormat::Protobuf) => {
+            return Ok(out.write_all(&record.to_vec()?)?)
+        }
+        (Protobuf::Record(record), OutputFormat::Prototxt) => record.to_prototxt()?,
+        (Protobuf::Record(record), OutputFormat::Hexdump) => hexdump_record(&record.to_vec()?),
+    };
+
+    Ok(out.write_all(data.as_bytes())?)
+}
+
+/// Reads a file and renders an annotated hex dump of its raw contents, unwrapping the Msg carried
+/// in a Record unless told otherwise. Data which cannot be decoded is dumped as well, as Record
+/// unless a Msg is expected, and reported via the returned flag.
+fn hexdump_input(file: &Path, args: &DecodeArgs) -> Result<(String, bool)> {
+    let mut data = read_input(file, args.input_format).context(display_name(file))?;
+    let dump = match decode_protobuf(&data, args.kind) {
+        Ok(Protobuf::Record(mut record)) if !args.keep_record => {
+            // Records without payload, e.g. connect Records, are dumped as they are
+            match record.payload_flatten() {
+                Some(payload) => {
+                    data = std::mem::take(payload);
+                    hexdump_msg(&data)
+                }
+                None => hexdump_record(&data),
+            }
+        }
+        Ok(Protobuf::Record(_)) => hexdump_record(&data),
+        Ok(Protobuf::Msg(_)) => hexdump_msg(&data),
+        Err(err) => {
+            eprintln!("{}: {err:#}", display_name(file));
+            let dump = match args.kind {
+                ProtobufType::Msg => hexdump_msg(&data),
+                ProtobufType::Auto | ProtobufType::Record => hexdump_record(&data),
+            };
+            return Ok((dump, false));
+        }
+    };
+
+    Ok((dump, true))
+}
+
+pub(crate) fn decode(args: &DecodeArgs) -> Result<()> {
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(output) => Box::new(
+            File::create(output)
+                .with_context(|| format!("Couldn't create output file {}", output.display()))?,
+        ),
+        None => Box::new(std::io::stdout()),
+    };
+
+    let mut failed = false;
+    for file in input_files(&args.files) {
+        if matches!(args.output_format, OutputFormat::Hexdump) {
+            match hexdump_input(file, args) {
+                Ok((dump, decoded)) => {
+                    out.write_all(dump.as_bytes())?;
+                    failed |= !decoded;
+                }
+                Err(err) => {
+                    eprintln!("{err:#}");
+                    failed = true;
+                }
+            }
+            continue;
+        }
+
+        let protobuf = match read_protobuf(file, args.input_format, args.kind) {
+            Ok((_, protobuf)) => protobuf,
+            Err(err) => {
+                eprintln!("{err:#}");
+                failed = true;
+                continue;
+            }
+        };
+
+        let protobuf = match protobuf {
+            Protobuf::Record(mut record) if !args.keep_record => {
+                // Records without payload, e.g. connect Records, are output as they are
+                match record
+                    .payload_flatten()
+                    .map(|payload| try_decode_msg(payload))
+                {
+                    Some(Ok(msg)) => Protobuf::Msg(msg),
+                    Some(Err(err)) => {
+                        eprintln!(
+                            "{}: Couldn't decode the Record payload, keeping the Record: {err:#}",
+                            file.display()
+                        );
+                        Protobuf::Record(record)
+                    }
+                    None => Protobuf::Record(record),
+                }
+            }
+            protobuf => protobuf,
+        };
+
+        write_protobuf(&mut out, &protobuf, args.output_format)?;
+    }
+
+    if failed {
+        return Err(anyhow!("Not all inputs could be decoded"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use argh::FromArgs;
+use rusp_lib::usp_decoder::try_decode_msg;
+use rusp_lib::usp_diff::{diff_msg, diff_record, DiffOptions};
+
+use anyhow::{anyhow, Context, Result};
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::input::{read_protobuf, InputFormat, Protobuf, ProtobufType};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "diff")]
+/// compare two USP Records or Msgs structurally
+pub(crate) struct DiffArgs {
+    #[argh(
+        option,
+        long = "input-format",
+        short = 'f',
+        default = "InputFormat::Protobuf"
+    )]
+    /// format of the input files: protobuf (default), base64, hex, prototxt or json
+    input_format: InputFormat,
+
+    #[argh(
+        option,
+        long = "output-format",
+        short = 'F',
+        default = "DiffFormat::Human"
+    )]
+    /// format of the output: human (default) or json
+    output_format: DiffFormat,
+
+    #[argh(option, long = "type", default = "ProtobufType::Auto")]
+    /// type of the inputs: auto (default) to detect whether they are Records or Msgs, record or
+    /// msg
+    kind: ProtobufType,
+
+    #[argh(switch, long = "ignore-msg-id", short = 'i')]
+    /// ignore differing msg_ids
+    ignore_msg_id: bool,
+
+    #[argh(positional)]
+    /// file containing the old USP Record or Msg
+    old: PathBuf,
+
+    #[argh(positional)]
+    /// file containing the new USP Record or Msg
+    new: PathBuf,
+}
+
+/// The supported output formats of the diff subcommand
+#[derive(Clone, Copy)]
+enum DiffFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unsupported diff format {s}, expected one of human or json"
+            )),
+        }
+    }
+}
+
+pub(crate) fn diff(args: &DiffArgs) -> Result<()> {
+    let (_, old) = read_protobuf(&args.old, args.input_format, args.kind)?;
+    let (_, new) = read_protobuf(&args.new, args.input_format, args.kind)?;
+
+    // A Record is compared to a Msg by means of the Msg carried in its payload
+    let unwrap = |protobuf: Protobuf, file: &Path| match protobuf {
+        Protobuf::Record(mut record) => record
+            .payload_flatten()
+            .map(|payload| try_decode_msg(payload))
+            .transpose()
+            .with_context(|| format!("{}: Couldn't decode the Record payload", file.display()))?
+            .map(Protobuf::Msg)
+            .ok_or_else(|| anyhow!("{}: The Record doesn't carry a Msg", file.display())),
+        protobuf => Ok(protobuf),
+    };
+    let (old, new) = match (old, new) {
+        (old @ Protobuf::Record(_), new @ Protobuf::Msg(_)) => (unwrap(old, &args.old)?, new),
+        (old @ Protobuf::Msg(_), new @ Protobuf::Record(_)) => (old, unwrap(new, &args.new)?),
+        inputs => inputs,
+    };
+
+    let options = DiffOptions::new().with_ignore_msg_id(args.ignore_msg_id);
+    let differences = match (&old, &new) {
+        (Protobuf::Msg(old), Protobuf::Msg(new)) => diff_msg(old, new, &options)?,
+        (Protobuf::Record(old), Protobuf::Record(new)) => diff_record(old, new, &options)?,
+        _ => unreachable!("Records are unwrapped when compared to Msgs"),
+    };
+
+    match args.output_format {
+        DiffFormat::Human => {
+            for difference in &differences {
+                println!("{difference}");
+            }
+        }
+        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&differences)?),
+    }
+
+    Ok(())
+}
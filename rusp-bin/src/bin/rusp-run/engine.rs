@@ -0,0 +1,188 @@
+use rhai::packages::Package;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Module, ModuleResolver, Position, Scope};
+use rhai_rand::RandomPackage;
+use rhai_rusp::{RuspPackage, SystemAccess};
+
+use anyhow::Result;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::str::FromStr;
+
+pub(crate) fn eprint_error(input: &str, mut err: EvalAltResult) {
+    fn eprint_line(lines: &[&str], pos: Position, err_msg: &str) {
+        let line = pos.line().unwrap();
+        let line_no = format!("{line}: ");
+
+        eprintln!("{line_no}{}", lines[line - 1]);
+
+        for (i, err_line) in err_msg.to_string().lines().enumerate() {
+            // Display position marker
+            println!(
+                "{0:>1$}{err_line}",
+                if i > 0 { "| " } else { "^ " },
+                line_no.len() + pos.position().unwrap() + 1,
+            );
+        }
+        eprintln!();
+    }
+
+    let lines: Vec<_> = input.lines().collect();
+
+    // Print error
+    let pos = err.take_position();
+
+    if pos.is_none() {
+        // No position
+        eprintln!("{err}");
+    } else {
+        // Specific position
+        eprint_line(&lines, pos, &err.to_string());
+    }
+}
+
+/// A named argument of a script, given as `key=value`
+pub(crate) struct ScriptArg {
+    key: String,
+    value: String,
+}
+
+impl FromStr for ScriptArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid argument {s}, expected key=value"))?;
+        let valid = key
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!(
+                "Invalid argument name {key}, expected a valid Rhai identifier"
+            ));
+        }
+        Ok(Self {
+            key: key.into(),
+            value: value.into(),
+        })
+    }
+}
+
+/// Settings of the scripting engines given on the command line
+pub(crate) struct EngineConfig {
+    /// Directories to look up imported modules in
+    pub(crate) include: Vec<PathBuf>,
+    /// Access rights of the scripts to the file system and the environment
+    pub(crate) access: SystemAccess,
+}
+
+/// Creates a scripting engine with the rusp and rand modules registered
+pub(crate) fn new_engine(config: &EngineConfig) -> Engine {
+    let mut engine = Engine::new();
+    config.access.apply(&mut engine);
+
+    // Create rusp package and add the package into the engine
+    engine.register_static_module("rusp", RuspPackage::new().as_shared_module());
+    engine.register_static_module("rand", RandomPackage::new().as_shared_module());
+    engine.set_optimization_level(rhai::OptimizationLevel::Simple);
+
+    let rusp_path = std::env::var_os("RUSP_PATH").unwrap_or_default();
+    let include = config
+        .include
+        .iter()
+        .cloned()
+        .chain(std::env::split_paths(&rusp_path))
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .collect();
+    engine.set_module_resolver(ScriptModuleResolver {
+        include,
+        cache: RefCell::default(),
+    });
+
+    engine
+}
+
+/// Resolves the modules imported by Rhai scripts
+///
+/// Relative paths are looked up in the directory of the importing script or module first and
+/// then in the include directories. Unlike Rhai's `FileModuleResolver` the modules keep their
+/// full path as source, so the imports of nested modules are resolved relative to their file.
+struct ScriptModuleResolver {
+    include: Vec<PathBuf>,
+    /// Modules are only evaluated once, even if they are imported by several scripts
+    cache: RefCell<HashMap<PathBuf, Rc<Module>>>,
+}
+
+impl ModuleResolver for ScriptModuleResolver {
+    fn resolve(
+        &self,
+        engine: &Engine,
+        source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Result<Rc<Module>, Box<EvalAltResult>> {
+        if !SystemAccess::of(engine).fs() {
+            return Err(EvalAltResult::ErrorInModule(
+                path.into(),
+                "File system access is disabled".into(),
+                pos,
+            )
+            .into());
+        }
+
+        let mut file = PathBuf::from(path);
+        file.set_extension("rhai");
+
+        let dir = source
+            .and_then(|source| Path::new(source).parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let file = std::iter::once(&dir)
+            .chain(&self.include)
+            .map(|dir| dir.join(&file))
+            .find(|file| file.is_file())
+            .ok_or_else(|| EvalAltResult::ErrorModuleNotFound(path.into(), pos))?;
+
+        if let Some(module) = self.cache.borrow().get(&file) {
+            return Ok(module.clone());
+        }
+
+        let mut ast = engine
+            .compile_file(file.clone())
+            .map_err(|err| EvalAltResult::ErrorInModule(path.into(), err, pos))?;
+        ast.set_source(file.to_string_lossy().to_string());
+        let module: Rc<Module> = Module::eval_ast_as_new(Scope::new(), &ast, engine)
+            .map_err(|err| EvalAltResult::ErrorInModule(path.into(), err, pos))?
+            .into();
+
+        self.cache.borrow_mut().insert(file, module.clone());
+        Ok(module)
+    }
+}
+
+/// Makes the arguments of a script run from the command line available as global variables, the
+/// positional arguments in `ARGS` and the named arguments as strings named after their key
+///
+/// Like constants of the script they can be read within functions and modules, but are shadowed
+/// by variables of the same name.
+pub(crate) fn set_script_args(engine: &mut Engine, args: Vec<String>, named: Vec<ScriptArg>) {
+    let args: Array = args.into_iter().map(Dynamic::from).collect();
+    let variables: HashMap<String, Dynamic> = named
+        .into_iter()
+        .map(|ScriptArg { key, value }| (key, value.into()))
+        .chain([("ARGS".into(), args.into())])
+        .collect();
+    // The variable resolver is marked as volatile API by Rhai, but has been stable for years
+    #[allow(deprecated)]
+    engine.on_var(move |name, _, context| {
+        if context.scope().contains(name) {
+            return Ok(None);
+        }
+        Ok(variables.get(name).cloned())
+    });
+}
@@ -0,0 +1,60 @@
+use argh::FromArgs;
+use rusp_lib::usp_c_parser::parse_c_literals;
+
+use anyhow::{Context, Result};
+
+use std::path::PathBuf;
+
+use crate::input::{decode_protobuf, Protobuf, ProtobufType};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "from-c")]
+/// decode USP Records and Msgs from C byte arrays and strings, e.g. legacy test vectors
+pub(crate) struct FromCArgs {
+    #[argh(option, long = "output-dir", short = 'o')]
+    /// directory to save the contained Protobuf data to, one file per array named after it
+    output_dir: Option<PathBuf>,
+
+    #[argh(positional)]
+    /// c source files containing byte arrays or strings
+    files: Vec<PathBuf>,
+}
+
+pub(crate) fn from_c(args: &FromCArgs) -> Result<()> {
+    for file in &args.files {
+        let source = std::fs::read_to_string(file)
+            .with_context(|| format!("Couldn't read C source file {}", file.display()))?;
+        let literals = parse_c_literals(&source)
+            .with_context(|| format!("Couldn't parse C source file {}", file.display()))?;
+
+        for literal in literals {
+            let name = literal.name.unwrap_or_else(|| "pb".into());
+            match decode_protobuf(&literal.data, ProtobufType::Auto) {
+                Ok(Protobuf::Msg(msg)) => println!(
+                    "// {}: {name} (Msg)\n{}",
+                    file.display(),
+                    serde_json::to_string_pretty(&msg)?
+                ),
+                Ok(Protobuf::Record(record)) => {
+                    println!(
+                        "// {}: {name} (Record)\n{}",
+                        file.display(),
+                        serde_json::to_string_pretty(&record)?
+                    );
+                }
+                Err(err) => {
+                    eprintln!("// {}: {name}: {err:#}", file.display());
+                    continue;
+                }
+            }
+
+            if let Some(dir) = &args.output_dir {
+                let output = dir.join(format!("{name}.pb"));
+                std::fs::write(&output, &literal.data)
+                    .with_context(|| format!("Couldn't write {}", output.display()))?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use rusp_lib::usp::Msg;
+use rusp_lib::usp_decoder::{decode_base64, decode_hex, try_decode_msg, try_decode_record};
+use rusp_lib::usp_record::Record;
+
+use anyhow::{anyhow, Context, Result};
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The supported encodings of input files
+#[derive(Clone, Copy)]
+pub(crate) enum InputFormat {
+    /// Native Protobuf binary data
+    Protobuf,
+    /// Base64 encoded Protobuf data
+    Base64,
+    /// Hex encoded Protobuf data
+    Hex,
+    /// Protobuf text format, as accepted by `protoc --encode`
+    Prototxt,
+    /// JSON, as produced by the `json` output format
+    Json,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "protobuf" | "pb" => Ok(Self::Protobuf),
+            "base64" => Ok(Self::Base64),
+            "hex" => Ok(Self::Hex),
+            "prototxt" => Ok(Self::Prototxt),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unsupported input format {s}, expected one of protobuf, base64, hex, prototxt or json"
+            )),
+        }
+    }
+}
+
+/// The types of Protobuf data to decode
+#[derive(Clone, Copy)]
+pub(crate) enum ProtobufType {
+    /// Detect whether the data contains a USP Record or Msg
+    Auto,
+    /// A USP Msg
+    Msg,
+    /// A USP Record
+    Record,
+}
+
+impl FromStr for ProtobufType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "msg" => Ok(Self::Msg),
+            "record" => Ok(Self::Record),
+            _ => Err(format!(
+                "Unsupported type {s}, expected one of auto, msg or record"
+            )),
+        }
+    }
+}
+
+/// A USP Record or Msg read from a Protobuf file
+pub(crate) enum Protobuf {
+    Msg(Msg),
+    Record(Record),
+}
+
+/// Decodes Protobuf data into a USP Record or Msg
+pub(crate) fn decode_protobuf(data: &[u8], kind: ProtobufType) -> Result<Protobuf> {
+    match kind {
+        ProtobufType::Msg => Ok(Protobuf::Msg(try_decode_msg(data)?)),
+        ProtobufType::Record => Ok(Protobuf::Record(try_decode_record(data)?)),
+        ProtobufType::Auto => {
+            // A Msg never carries the Record type, so a valid Record is unambiguous
+            let record_err = match Record::from_bytes(data) {
+                Ok(record) => return Ok(Protobuf::Record(record)),
+                Err(err) => err,
+            };
+
+            match try_decode_msg(data) {
+                Ok(msg) => Ok(Protobuf::Msg(msg)),
+                Err(msg_err) => Err(anyhow!(
+                    "Data contains neither a valid USP Record ({record_err:#}) nor Msg ({msg_err:#})"
+                )),
+            }
+        }
+    }
+}
+
+/// Returns the given input files, or `-` denoting stdin if there are none
+pub(crate) fn input_files(files: &[PathBuf]) -> Vec<&Path> {
+    if files.is_empty() {
+        vec![Path::new("-")]
+    } else {
+        files.iter().map(PathBuf::as_path).collect()
+    }
+}
+
+/// Reads a file, or stdin if the filename is `-`, and decodes its contents into Protobuf data
+pub(crate) fn read_input(file: &Path, format: InputFormat) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    if file == Path::new("-") {
+        std::io::stdin()
+            .read_to_end(&mut data)
+            .context("Couldn't read from stdin")?;
+    } else {
+        data = std::fs::read(file)
+            .with_context(|| format!("Couldn't read input file {}", file.display()))?;
+    }
+
+    decode_input(data, format)
+}
+
+/// Decodes the contents of an input file into Protobuf data
+pub(crate) fn decode_input(data: Vec<u8>, format: InputFormat) -> Result<Vec<u8>> {
+    match format {
+        InputFormat::Protobuf => Ok(data),
+        InputFormat::Base64 => Ok(decode_base64(&String::from_utf8_lossy(&data))?),
+        InputFormat::Hex => Ok(decode_hex(&String::from_utf8_lossy(&data))?),
+        InputFormat::Prototxt => {
+            let text =
+                String::from_utf8(data).context("Protobuf text format input is not valid UTF-8")?;
+            // Records and Msgs have no field names in common, so at most one of them parses
+            let record_err = match Record::from_prototxt(&text) {
+                Ok(record) => return Ok(record.to_vec()?),
+                Err(err) => err,
+            };
+
+            match Msg::from_prototxt(&text) {
+                Ok(msg) => Ok(msg.to_vec()?),
+                Err(msg_err) => Err(anyhow!(
+                    "Input contains neither a valid USP Record ({record_err:#}) nor Msg ({msg_err:#}) in Protobuf text format"
+                )),
+            }
+        }
+        InputFormat::Json => {
+            let text = String::from_utf8(data).context("JSON input is not valid UTF-8")?;
+            // Records and Msgs have no member names in common, so at most one of them parses
+            let record_err = match Record::from_json(&text) {
+                Ok(record) => return Ok(record.to_vec()?),
+                Err(err) => err,
+            };
+
+            match Msg::from_json(&text) {
+                Ok(msg) => Ok(msg.to_vec()?),
+                Err(msg_err) => Err(anyhow!(
+                    "Input contains neither a valid USP Record ({record_err:#}) nor Msg ({msg_err:#}) in JSON"
+                )),
+            }
+        }
+    }
+}
+
+/// Returns the name of an input file for use in messages
+pub(crate) fn display_name(file: &Path) -> String {
+    if file == Path::new("-") {
+        "<stdin>".into()
+    } else {
+        file.display().to_string()
+    }
+}
+
+/// Reads a Protobuf file, returning its contents as a USP Record or Msg along with a name derived
+/// from the filename
+pub(crate) fn read_protobuf(
+    file: &Path,
+    format: InputFormat,
+    kind: ProtobufType,
+) -> Result<(String, Protobuf)> {
+    let name = if file == Path::new("-") {
+        "pb".into()
+    } else {
+        file.file_stem()
+            .map_or_else(|| file.to_string_lossy(), |stem| stem.to_string_lossy())
+            .into()
+    };
+    let display = display_name(file);
+
+    let data = read_input(file, format).context(display.clone())?;
+    let protobuf = decode_protobuf(&data, kind).context(display)?;
+    Ok((name, protobuf))
+}
@@ -1,44 +1,37 @@
-use argh::FromArgs;
-use rhai::packages::Package;
-use rhai::{Engine, EvalAltResult, Position};
-use rhai_rand::RandomPackage;
-use rhai_rusp::RuspPackage;
-
-use std::convert::Into;
-use std::path::PathBuf;
-use std::{fs::File, io::Read, path::Path, process::exit};
-
-fn eprint_error(input: &str, mut err: EvalAltResult) {
-    fn eprint_line(lines: &[&str], pos: Position, err_msg: &str) {
-        let line = pos.line().unwrap();
-        let line_no = format!("{line}: ");
-
-        eprintln!("{line_no}{}", lines[line - 1]);
-
-        for (i, err_line) in err_msg.to_string().lines().enumerate() {
-            // Display position marker
-            println!(
-                "{0:>1$}{err_line}",
-                if i > 0 { "| " } else { "^ " },
-                line_no.len() + pos.position().unwrap() + 1,
-            );
-        }
-        eprintln!();
-    }
+mod c_header;
+mod capture;
+mod decode;
+mod diff;
+mod engine;
+mod from_c;
+mod input;
+mod repl;
+mod source;
+mod stream;
+mod test_runner;
+mod timeline;
+mod watch;
 
-    let lines: Vec<_> = input.lines().collect();
+use argh::FromArgs;
+use rhai_rusp::SystemAccess;
 
-    // Print error
-    let pos = err.take_position();
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::exit;
 
-    if pos.is_none() {
-        // No position
-        eprintln!("{err}");
-    } else {
-        // Specific position
-        eprint_line(&lines, pos, &err.to_string());
-    }
-}
+use crate::c_header::{c_header, CHeaderArgs};
+use crate::capture::{capture, CaptureArgs};
+use crate::decode::{decode, DecodeArgs};
+use crate::diff::{diff, DiffArgs};
+use crate::engine::{eprint_error, new_engine, set_script_args, EngineConfig, ScriptArg};
+use crate::from_c::{from_c, FromCArgs};
+use crate::repl::{repl, ReplArgs};
+use crate::source::{source, SourceArgs};
+use crate::stream::{stream, StreamArgs};
+use crate::test_runner::{test, TestArgs};
+use crate::timeline::{timeline, TimelineArgs};
+use crate::watch::{watch, WatchArgs};
 
 #[derive(FromArgs)]
 /// the Rust USP toolkit, rhai runner
@@ -52,27 +45,94 @@ struct Rusp {
     /// generated by the Rhai script
     comment: bool,
 
+    #[argh(option, long = "include", short = 'I')]
+    /// directory to resolve modules imported by Rhai scripts from, may be given multiple times;
+    /// modules are looked up next to the importing script first, then in these directories and
+    /// finally in the directories listed in RUSP_PATH
+    include: Vec<PathBuf>,
+
+    #[argh(option, long = "arg")]
+    /// a key=value pair made available to the script as string constant named key, may be given
+    /// multiple times
+    arg: Vec<ScriptArg>,
+
+    #[argh(switch, long = "no-fs")]
+    /// deny scripts any file system access, including imports of modules, for running untrusted
+    /// scripts
+    no_fs: bool,
+
+    #[argh(switch, long = "no-env")]
+    /// deny scripts access to the environment variables, for running untrusted scripts
+    no_env: bool,
+
     #[argh(positional)]
-    /// a filename for a Rhai script to parse
-    filename: Option<PathBuf>,
+    /// a filename for a Rhai script to parse, followed by arguments passed to the script as
+    /// strings in the global ARGS array; all arguments are passed to inline scripts
+    filename: Vec<String>,
+
+    #[argh(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    CHeader(CHeaderArgs),
+    Source(SourceArgs),
+    FromC(FromCArgs),
+    Decode(DecodeArgs),
+    Diff(DiffArgs),
+    Capture(CaptureArgs),
+    Timeline(TimelineArgs),
+    Stream(StreamArgs),
+    Watch(WatchArgs),
+    Test(TestArgs),
+    Repl(ReplArgs),
 }
 
 fn main() {
     let args: Rusp = argh::from_env();
+    let config = EngineConfig {
+        include: args.include,
+        access: SystemAccess::default()
+            .with_fs(!args.no_fs)
+            .with_env(!args.no_env),
+    };
+
+    if let Some(command) = args.command {
+        let result = match command {
+            Command::CHeader(args) => c_header(args),
+            Command::Source(args) => source(&args),
+            Command::FromC(args) => from_c(&args),
+            Command::Decode(args) => decode(&args),
+            Command::Diff(args) => diff(&args),
+            Command::Capture(args) => capture(&args),
+            Command::Timeline(args) => timeline(&args),
+            Command::Stream(args) => stream(&args),
+            Command::Watch(watch_args) => watch(&watch_args, &config),
+            Command::Test(test_args) => test(&test_args, &config),
+            Command::Repl(repl_args) => repl(&repl_args, &config),
+        };
+        if let Err(err) = result {
+            eprintln!("{err:#}");
+            exit(1);
+        }
+        return;
+    }
 
     // Initialize scripting engine
-    let mut engine = Engine::new();
+    let mut engine = new_engine(&config);
 
-    // Create rusp package and add the package into the engine
-    engine.register_static_module("rusp", RuspPackage::new().as_shared_module());
-    engine.register_static_module("rand", RandomPackage::new().as_shared_module());
-    engine.set_optimization_level(rhai::OptimizationLevel::Simple);
+    // Without a script file, all positional arguments are passed to the script
+    let mut script_args = args.filename;
+    let filename = if args.script.is_some() || args.comment || script_args.is_empty() {
+        None
+    } else {
+        Some(PathBuf::from(script_args.remove(0)))
+    };
+    set_script_args(&mut engine, script_args, args.arg);
 
-    if let Some(filename) = args.filename {
-        if args.script.is_some() {
-            eprintln!("Inline scripting and the use of a file are mutual exclusive");
-            exit(1);
-        }
+    if let Some(filename) = filename {
         let mut contents = String::new();
         let filename = match Path::new(&filename).canonicalize() {
             Err(err) => {
@@ -190,6 +250,8 @@ fn main() {
             eprintln!("Couldn't find /** in \"{input}\"");
         }
     } else {
-        eprintln!("You will either have to supply a filename, or you can use the --script option");
+        eprintln!(
+            "You will either have to supply a filename, or you can use the --script option or the repl subcommand"
+        );
     }
 }
@@ -0,0 +1,356 @@
+use argh::FromArgs;
+use rhai::{Dynamic, Engine, FnNamespace, ParseErrorType, Scope, AST};
+use rhai_rusp::SystemAccess;
+use rusp_lib::usp::{Body, Msg};
+use rusp_lib::usp_c_header::c_identifier;
+use rusp_lib::usp_record::Record;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{CompletionType, Config, Editor, Helper};
+
+use anyhow::{anyhow, Context, Result};
+
+use std::path::{Path, PathBuf};
+
+use crate::engine::{eprint_error, new_engine, EngineConfig};
+use crate::input::{read_protobuf, InputFormat, Protobuf, ProtobufType};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "repl")]
+/// start an interactive Rhai shell with the rusp and rand modules loaded
+pub(crate) struct ReplArgs {
+    #[argh(option, long = "history")]
+    /// file to load the command history from and save it to, defaults to ~/.rusp_history
+    history: Option<PathBuf>,
+
+    #[argh(switch, long = "no-history")]
+    /// neither load nor save the command history
+    no_history: bool,
+
+    #[argh(
+        option,
+        long = "input-format",
+        short = 'f',
+        default = "InputFormat::Protobuf"
+    )]
+    /// format of the files loaded via :load: protobuf (default), base64, hex, prototxt or json
+    input_format: InputFormat,
+}
+
+/// Keywords offered for completion in the REPL
+const REPL_KEYWORDS: &[&str] = &[
+    "let", "const", "fn", "if", "else", "switch", "for", "in", "while", "loop", "do", "until",
+    "break", "continue", "return", "throw", "try", "catch", "true", "false", "print", "debug",
+];
+
+const REPL_HELP: &str = "\
+Enter Rhai code to evaluate it, variables and functions are kept between inputs and incomplete
+input is continued on the next line. The following commands are available:
+
+  :load <file> [<name>]  load a USP Record or Msg in the format given by --input-format into a
+                         variable, named after the file unless a name is given
+  :save <name> <file>    save the USP Record or Msg stored in a variable as Protobuf
+  :help                  show this help
+  :quit                  leave the REPL, as does Ctrl-D";
+
+/// Line editor helper of the REPL, providing tab completion and multi-line input
+struct ReplHelper {
+    /// Engine used to check whether the input is complete
+    parser: Engine,
+    /// Functions of the registered modules as pairs of namespace and name
+    functions: Vec<(String, String)>,
+    /// Functions which can be called as methods, e.g. the builder methods
+    methods: Vec<String>,
+    /// Names of the variables currently in scope
+    variables: Vec<String>,
+}
+
+impl ReplHelper {
+    fn new(engine: &Engine) -> Self {
+        let mut functions = engine.collect_fn_metadata(
+            None,
+            |info| {
+                let name = info.metadata.name.as_str();
+                // Skip the global namespace, operators and property accessors
+                (!info.namespace.is_empty()
+                    && name.chars().all(|c| c.is_alphanumeric() || c == '_'))
+                .then(|| {
+                    (
+                        info.namespace.to_string(),
+                        name.to_string(),
+                        info.metadata.namespace == FnNamespace::Global,
+                    )
+                })
+            },
+            false,
+        );
+        functions.sort();
+        functions.dedup();
+
+        let mut methods: Vec<String> = functions
+            .iter()
+            .filter(|(_, _, global)| *global)
+            .map(|(_, name, _)| name.clone())
+            .collect();
+        methods.sort();
+        methods.dedup();
+
+        Self {
+            parser: Engine::new_raw(),
+            functions: functions
+                .into_iter()
+                .map(|(namespace, name, _)| (namespace, name))
+                .collect(),
+            methods,
+            variables: vec![],
+        }
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line
+            .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == ':')
+            .len();
+        let word = &line[start..];
+
+        let (start, prefix, candidates): (_, _, Vec<&str>) =
+            if let Some((namespace, prefix)) = word.rsplit_once("::") {
+                let candidates = self
+                    .functions
+                    .iter()
+                    .filter(|(ns, _)| ns == namespace)
+                    .map(|(_, name)| name.as_str())
+                    .collect();
+                (start + namespace.len() + 2, prefix, candidates)
+            } else if line[..start].ends_with('.') {
+                (
+                    start,
+                    word,
+                    self.methods.iter().map(String::as_str).collect(),
+                )
+            } else {
+                let candidates = self
+                    .variables
+                    .iter()
+                    .map(String::as_str)
+                    .chain(["rusp::", "rand::"])
+                    .chain(REPL_KEYWORDS.iter().copied())
+                    .collect();
+                (start, word, candidates)
+            };
+
+        let mut candidates: Vec<String> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .map(Into::into)
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+
+        Ok((start, candidates))
+    }
+}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        if input.trim_start().starts_with(':') {
+            return Ok(ValidationResult::Valid(None));
+        }
+
+        // Keep reading lines as long as the input ends prematurely, e.g. within a block
+        let Err(err) = self.parser.compile(input) else {
+            return Ok(ValidationResult::Valid(None));
+        };
+        let lines: Vec<_> = input.split('\n').collect();
+        let at_end = err.1.line() == Some(lines.len())
+            && err.1.position() > Some(lines[lines.len() - 1].chars().count());
+
+        match *err.0 {
+            ParseErrorType::UnexpectedEOF => Ok(ValidationResult::Incomplete),
+            ParseErrorType::MissingToken(..) if at_end => Ok(ValidationResult::Incomplete),
+            _ => Ok(ValidationResult::Valid(None)),
+        }
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// Prints the result of an evaluation in the REPL, USP data is printed as JSON
+fn print_result(result: Dynamic) -> Result<()> {
+    if result.is_unit() {
+        return Ok(());
+    }
+
+    let output = if result.is::<Msg>() {
+        serde_json::to_string_pretty(&result.cast::<Msg>())?
+    } else if result.is::<Record>() {
+        serde_json::to_string_pretty(&result.cast::<Record>())?
+    } else if result.is::<Body>() {
+        serde_json::to_string_pretty(&result.cast::<Body>())?
+    } else {
+        format!("{result:?}")
+    };
+    println!("{output}");
+
+    Ok(())
+}
+
+/// Executes a `:` command of the REPL
+fn repl_command(
+    command: &str,
+    format: InputFormat,
+    engine: &Engine,
+    scope: &mut Scope,
+) -> Result<()> {
+    let words = command.split_whitespace().collect::<Vec<_>>();
+    if matches!(words.first(), Some(&("load" | "save"))) && !SystemAccess::of(engine).fs() {
+        return Err(anyhow!("File system access is disabled"));
+    }
+
+    match words.as_slice() {
+        ["load", file, name @ ..] if name.len() <= 1 => {
+            let (stem, protobuf) = read_protobuf(Path::new(file), format, ProtobufType::Auto)?;
+            let name = name
+                .first()
+                .map_or_else(|| c_identifier(&stem), |name| (*name).to_string());
+            match protobuf {
+                Protobuf::Msg(msg) => {
+                    println!("Loaded USP Msg into {name}");
+                    scope.set_value(name, msg);
+                }
+                Protobuf::Record(record) => {
+                    println!("Loaded USP Record into {name}");
+                    scope.set_value(name, record);
+                }
+            }
+            Ok(())
+        }
+        ["save", name, file] => {
+            let value = scope
+                .get_value::<Dynamic>(name)
+                .ok_or_else(|| anyhow!("Variable {name} not found"))?;
+            let data = if value.is::<Msg>() {
+                value.cast::<Msg>().to_vec()?
+            } else if value.is::<Record>() {
+                value.cast::<Record>().to_vec()?
+            } else {
+                return Err(anyhow!(
+                    "Variable {name} contains neither a USP Record nor Msg but {}",
+                    value.type_name()
+                ));
+            };
+            std::fs::write(file, data).with_context(|| format!("Couldn't write file {file}"))?;
+            println!("Saved {name} to {file}");
+            Ok(())
+        }
+        ["help"] => {
+            println!("{REPL_HELP}");
+            Ok(())
+        }
+        _ => Err(anyhow!(
+            "Invalid command :{command}, type :help for a list of commands"
+        )),
+    }
+}
+
+pub(crate) fn repl(args: &ReplArgs, config: &EngineConfig) -> Result<()> {
+    let engine = new_engine(config);
+    let mut scope = Scope::new();
+    // Only the functions defined so far are kept, the statements are evaluated once
+    let mut ast = AST::empty();
+
+    let history = if args.no_history {
+        None
+    } else {
+        args.history
+            .clone()
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".rusp_history")))
+    };
+
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .auto_add_history(false)
+        .build();
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::with_config(config)?;
+    editor.set_helper(Some(ReplHelper::new(&engine)));
+    if let Some(history) = &history {
+        // The history file doesn't exist on the first run
+        _ = editor.load_history(history);
+    }
+
+    println!(
+        "rusp-run {} interactive shell, type :help for help",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    loop {
+        let input = match editor.readline("rusp> ") {
+            Ok(input) => input,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+
+        let input = input.trim();
+        if input.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(input)?;
+
+        if let Some(command) = input.strip_prefix(':') {
+            if matches!(command.trim(), "quit" | "exit" | "q") {
+                break;
+            }
+            if let Err(err) = repl_command(command, args.input_format, &engine, &mut scope) {
+                eprintln!("{err:#}");
+            }
+        } else {
+            let result = engine
+                .compile_with_scope(&scope, input)
+                .map_err(Into::into)
+                .and_then(|new_ast| {
+                    ast += new_ast;
+                    let result = engine.eval_ast_with_scope::<Dynamic>(&mut scope, &ast);
+                    ast.clear_statements();
+                    result
+                });
+
+            match result {
+                Ok(result) => print_result(result)?,
+                Err(err) => eprint_error(input, *err),
+            }
+        }
+
+        if let Some(helper) = editor.helper_mut() {
+            helper.variables = scope.iter().map(|(name, _, _)| name.into()).collect();
+        }
+    }
+
+    if let Some(history) = &history {
+        editor
+            .save_history(history)
+            .with_context(|| format!("Couldn't save history to {}", history.display()))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use argh::FromArgs;
+use rusp_lib::usp_encoder::SourceLanguage;
+
+use anyhow::Result;
+
+use std::path::PathBuf;
+
+use crate::input::{input_files, read_protobuf, InputFormat, Protobuf, ProtobufType};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "source")]
+/// render USP Records and Msgs from Protobuf files into byte literals of a programming language
+pub(crate) struct SourceArgs {
+    #[argh(option, long = "lang", short = 'l')]
+    /// language of the byte literals: python, go, rust, rust-bytes or javascript
+    language: SourceLanguage,
+
+    #[argh(
+        option,
+        long = "input-format",
+        short = 'f',
+        default = "InputFormat::Protobuf"
+    )]
+    /// format of the input files: protobuf (default), base64, hex, prototxt or json
+    input_format: InputFormat,
+
+    #[argh(positional)]
+    /// files containing USP Records or Msgs, the literals are named after the files, stdin is
+    /// read if omitted
+    files: Vec<PathBuf>,
+}
+
+pub(crate) fn source(args: &SourceArgs) -> Result<()> {
+    for (i, file) in input_files(&args.files).into_iter().enumerate() {
+        let source = match read_protobuf(file, args.input_format, ProtobufType::Auto)? {
+            (name, Protobuf::Msg(msg)) => msg.to_source(args.language, &name)?,
+            (name, Protobuf::Record(record)) => record.to_source(args.language, &name)?,
+        };
+        if i > 0 {
+            println!();
+        }
+        print!("{source}");
+    }
+
+    Ok(())
+}
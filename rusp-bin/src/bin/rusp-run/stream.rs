@@ -0,0 +1,147 @@
+use argh::FromArgs;
+use rusp_lib::usp::Msg;
+use rusp_lib::usp_record::Record;
+use rusp_lib::usp_stream::{Framing, StreamItem, StreamReader, StreamWriter};
+
+use anyhow::{anyhow, Context, Result};
+
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::input::input_files;
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "stream")]
+/// decode and convert streams of length-delimited or JSON lines encoded USP Records or Msgs
+pub(crate) struct StreamArgs {
+    #[argh(
+        option,
+        long = "framing",
+        short = 'f',
+        default = "Framing::LengthDelimited"
+    )]
+    /// framing of the input streams: length-delimited (default) or json-lines
+    framing: Framing,
+
+    #[argh(
+        option,
+        long = "output-format",
+        short = 'F',
+        default = "StreamFormat::Json"
+    )]
+    /// format of the output: json (default), json-lines, length-delimited or prototxt
+    output_format: StreamFormat,
+
+    #[argh(switch, long = "msg", short = 'm')]
+    /// the streams contain Msgs instead of Records
+    msg: bool,
+
+    #[argh(option, long = "output", short = 'o')]
+    /// filename to write the output to, the output is written to stdout if omitted
+    output: Option<PathBuf>,
+
+    #[argh(positional)]
+    /// files containing streams of USP Records or Msgs, stdin is read if omitted
+    files: Vec<PathBuf>,
+}
+
+/// The supported output formats of the stream subcommand
+#[derive(Clone, Copy)]
+enum StreamFormat {
+    /// Pretty printed JSON, one document per Record or Msg
+    Json,
+    /// Re-framed as stream with the given framing
+    Stream(Framing),
+    /// Protobuf text format output, as produced by `protoc --decode`
+    Prototxt,
+}
+
+impl FromStr for StreamFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "json-lines" | "jsonl" => Ok(Self::Stream(Framing::JsonLines)),
+            "length-delimited" | "delimited" => Ok(Self::Stream(Framing::LengthDelimited)),
+            "prototxt" => Ok(Self::Prototxt),
+            _ => Err(format!(
+                "Unsupported output format {s}, expected one of json, json-lines, length-delimited or prototxt"
+            )),
+        }
+    }
+}
+
+/// Converts a stream of USP Records or Msgs, item by item
+fn convert_stream<T: StreamItem>(
+    args: &StreamArgs,
+    out: Box<dyn Write>,
+    to_prototxt: fn(&T) -> rusp_lib::Result<String>,
+) -> Result<()> {
+    let framing = match args.output_format {
+        StreamFormat::Stream(framing) => framing,
+        // The writer is only used for its framing if the output is a stream
+        StreamFormat::Json | StreamFormat::Prototxt => Framing::JsonLines,
+    };
+    let mut writer = StreamWriter::new(out, framing);
+
+    let mut failed = false;
+    for file in input_files(&args.files) {
+        let reader: Box<dyn Read> = if file == Path::new("-") {
+            Box::new(std::io::stdin())
+        } else {
+            Box::new(
+                File::open(file)
+                    .with_context(|| format!("Couldn't open input file {}", file.display()))?,
+            )
+        };
+
+        for item in StreamReader::<_, T>::new(BufReader::new(reader), args.framing) {
+            let item = match item {
+                Ok(item) => item,
+                Err(err) => {
+                    eprintln!("{}: {err:#}", file.display());
+                    failed = true;
+                    continue;
+                }
+            };
+
+            match args.output_format {
+                StreamFormat::Stream(_) => writer.write(&item)?,
+                StreamFormat::Json => {
+                    let out = writer.get_mut();
+                    serde_json::to_writer_pretty(&mut *out, &item)?;
+                    writeln!(out)?;
+                }
+                StreamFormat::Prototxt => {
+                    writer.get_mut().write_all(to_prototxt(&item)?.as_bytes())?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+
+    if failed {
+        return Err(anyhow!("Not all Records or Msgs could be decoded"));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn stream(args: &StreamArgs) -> Result<()> {
+    let out: Box<dyn Write> = match &args.output {
+        Some(output) => Box::new(
+            File::create(output)
+                .with_context(|| format!("Couldn't create output file {}", output.display()))?,
+        ),
+        None => Box::new(std::io::stdout()),
+    };
+
+    if args.msg {
+        convert_stream(args, out, Msg::to_prototxt)
+    } else {
+        convert_stream(args, out, Record::to_prototxt)
+    }
+}
@@ -0,0 +1,315 @@
+use argh::FromArgs;
+use rhai::{Dynamic, EvalAltResult, Scope};
+
+use anyhow::{anyhow, Context, Result};
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::engine::{new_engine, EngineConfig};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "test")]
+/// run the test_* functions defined in Rhai scripts, each in an isolated scope
+pub(crate) struct TestArgs {
+    #[argh(option, long = "junit")]
+    /// filename to write a JUnit XML report to
+    junit: Option<PathBuf>,
+
+    #[argh(option, long = "filter")]
+    /// only run tests whose name contains the given string
+    filter: Option<String>,
+
+    #[argh(positional)]
+    /// rhai scripts or directories searched recursively for .rhai files, defaults to the current
+    /// directory
+    paths: Vec<PathBuf>,
+}
+
+/// The outcome of a single Rhai test
+enum TestOutcome {
+    Passed,
+    /// An assertion failed
+    Failed(String),
+    /// The test raised any other error
+    Error(String),
+}
+
+/// The result of a single Rhai test, including the output printed while running it
+struct TestResult {
+    name: String,
+    outcome: TestOutcome,
+    output: String,
+    time: Duration,
+}
+
+/// The results of the tests defined in a single Rhai script
+struct TestSuite {
+    file: PathBuf,
+    results: Vec<TestResult>,
+}
+
+impl TestSuite {
+    fn count(&self, predicate: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|result| predicate(&result.outcome))
+            .count()
+    }
+
+    fn failures(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Failed(_)))
+    }
+
+    fn errors(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Error(_)))
+    }
+
+    fn time(&self) -> Duration {
+        self.results.iter().map(|result| result.time).sum()
+    }
+}
+
+/// Collects the Rhai scripts in the given paths, searching directories recursively
+fn test_scripts(paths: &[PathBuf], scripts: &mut Vec<PathBuf>) -> Result<()> {
+    for path in paths {
+        if path.is_dir() {
+            let mut entries = std::fs::read_dir(path)
+                .with_context(|| format!("Couldn't read directory {}", path.display()))?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            entries.retain(|entry| {
+                entry.is_dir() || entry.extension().is_some_and(|ext| ext == "rhai")
+            });
+            entries.sort();
+            test_scripts(&entries, scripts)?;
+        } else {
+            scripts.push(path.clone());
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs all tests defined in a Rhai script
+fn run_test_script(file: &Path, filter: Option<&str>, config: &EngineConfig) -> TestSuite {
+    let output = Rc::new(RefCell::new(String::new()));
+    let mut engine = new_engine(config);
+    let print_output = output.clone();
+    engine.on_print(move |text| {
+        let mut output = print_output.borrow_mut();
+        output.push_str(text);
+        output.push('\n');
+    });
+    let debug_output = output.clone();
+    engine.on_debug(move |text, _, pos| {
+        let mut output = debug_output.borrow_mut();
+        output.push_str(&format!("{pos:?} | {text}\n"));
+    });
+
+    let ast = match engine.compile_file(file.into()) {
+        Ok(ast) => ast,
+        Err(err) => {
+            // A script which doesn't compile is reported as a single erroneous test
+            let result = TestResult {
+                name: "compile".into(),
+                outcome: TestOutcome::Error(err.to_string()),
+                output: String::new(),
+                time: Duration::ZERO,
+            };
+            return TestSuite {
+                file: file.into(),
+                results: vec![result],
+            };
+        }
+    };
+
+    let mut names: Vec<_> = ast
+        .iter_functions()
+        .filter(|function| function.name.starts_with("test_") && function.params.is_empty())
+        .filter(|function| filter.is_none_or(|filter| function.name.contains(filter)))
+        .map(|function| function.name.to_string())
+        .collect();
+    names.sort();
+
+    let results = names
+        .into_iter()
+        .map(|name| {
+            output.borrow_mut().clear();
+            let start = Instant::now();
+            // Every test gets a fresh scope in which the top-level statements of the script are
+            // run again before calling the test function
+            let result = engine.call_fn::<Dynamic>(&mut Scope::new(), &ast, &name, ());
+            let time = start.elapsed();
+
+            let outcome = match result {
+                Ok(_) => TestOutcome::Passed,
+                Err(err) => match err.unwrap_inner() {
+                    // Assertions of the rusp module fail with messages starting like this
+                    EvalAltResult::ErrorRuntime(value, pos)
+                        if value.to_string().starts_with("assertion failed") =>
+                    {
+                        TestOutcome::Failed(format!("{value}\n  at {pos}"))
+                    }
+                    _ => TestOutcome::Error(err.to_string()),
+                },
+            };
+
+            TestResult {
+                name,
+                outcome,
+                output: output.borrow().clone(),
+                time,
+            }
+        })
+        .collect();
+
+    TestSuite {
+        file: file.into(),
+        results,
+    }
+}
+
+/// Escapes text for use in XML attributes and elements
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\t' => escaped.push(c),
+            // Other control characters are not allowed in XML 1.0
+            c if c.is_control() => escaped.push_str(&format!("&#x{:x};", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders the test results as JUnit XML report
+fn junit_report(suites: &[TestSuite]) -> String {
+    let tests: usize = suites.iter().map(|suite| suite.results.len()).sum();
+    let failures: usize = suites.iter().map(TestSuite::failures).sum();
+    let errors: usize = suites.iter().map(TestSuite::errors).sum();
+    let time: Duration = suites.iter().map(TestSuite::time).sum();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml += &format!(
+        "<testsuites name=\"rusp-run\" tests=\"{tests}\" failures=\"{failures}\" errors=\"{errors}\" time=\"{:.3}\">\n",
+        time.as_secs_f64()
+    );
+    for suite in suites {
+        let name = xml_escape(&suite.file.display().to_string());
+        xml += &format!(
+            "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+            suite.results.len(),
+            suite.failures(),
+            suite.errors(),
+            suite.time().as_secs_f64()
+        );
+        for result in &suite.results {
+            xml += &format!(
+                "    <testcase name=\"{}\" classname=\"{name}\" time=\"{:.3}\">\n",
+                xml_escape(&result.name),
+                result.time.as_secs_f64()
+            );
+            let (element, message) = match &result.outcome {
+                TestOutcome::Passed => (None, ""),
+                TestOutcome::Failed(message) => (Some("failure"), message.as_str()),
+                TestOutcome::Error(message) => (Some("error"), message.as_str()),
+            };
+            if let Some(element) = element {
+                xml += &format!(
+                    "      <{element} message=\"{}\">{}</{element}>\n",
+                    xml_escape(message.lines().next().unwrap_or_default()),
+                    xml_escape(message)
+                );
+            }
+            if !result.output.is_empty() {
+                xml += &format!(
+                    "      <system-out>{}</system-out>\n",
+                    xml_escape(&result.output)
+                );
+            }
+            xml += "    </testcase>\n";
+        }
+        xml += "  </testsuite>\n";
+    }
+    xml += "</testsuites>\n";
+    xml
+}
+
+pub(crate) fn test(args: &TestArgs, config: &EngineConfig) -> Result<()> {
+    let mut scripts = vec![];
+    if args.paths.is_empty() {
+        test_scripts(&[PathBuf::from(".")], &mut scripts)?;
+    } else {
+        test_scripts(&args.paths, &mut scripts)?;
+    }
+
+    let mut suites = vec![];
+    for script in &scripts {
+        let suite = run_test_script(script, args.filter.as_deref(), config);
+        for result in &suite.results {
+            let status = match result.outcome {
+                TestOutcome::Passed => "ok",
+                TestOutcome::Failed(_) => "FAILED",
+                TestOutcome::Error(_) => "ERROR",
+            };
+            println!("test {}::{} ... {status}", script.display(), result.name);
+        }
+        suites.push(suite);
+    }
+
+    let unsuccessful: Vec<_> = suites
+        .iter()
+        .flat_map(|suite| {
+            suite
+                .results
+                .iter()
+                .map(move |result| (&suite.file, result))
+        })
+        .filter(|(_, result)| !matches!(result.outcome, TestOutcome::Passed))
+        .collect();
+    if !unsuccessful.is_empty() {
+        println!("\nfailures:");
+        for (file, result) in &unsuccessful {
+            println!("\n---- {}::{} ----", file.display(), result.name);
+            print!("{}", result.output);
+            if let TestOutcome::Failed(message) | TestOutcome::Error(message) = &result.outcome {
+                println!("{message}");
+            }
+        }
+    }
+
+    let tests: usize = suites.iter().map(|suite| suite.results.len()).sum();
+    let failures: usize = suites.iter().map(TestSuite::failures).sum();
+    let errors: usize = suites.iter().map(TestSuite::errors).sum();
+    let time: Duration = suites.iter().map(TestSuite::time).sum();
+    println!(
+        "\ntest result: {}. {} passed; {failures} failed; {errors} errors; finished in {:.2}s",
+        if unsuccessful.is_empty() {
+            "ok"
+        } else {
+            "FAILED"
+        },
+        tests - failures - errors,
+        time.as_secs_f64()
+    );
+
+    if let Some(junit) = &args.junit {
+        std::fs::write(junit, junit_report(&suites))
+            .with_context(|| format!("Couldn't write JUnit report {}", junit.display()))?;
+    }
+
+    if !unsuccessful.is_empty() {
+        return Err(anyhow!("{} of {tests} tests failed", unsuccessful.len()));
+    }
+
+    Ok(())
+}
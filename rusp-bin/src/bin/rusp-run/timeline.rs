@@ -0,0 +1,104 @@
+use argh::FromArgs;
+use rusp_lib::usp_capture::{extract_records, is_capture};
+use rusp_lib::usp_conversation::{reconstruct_conversations, LoggedRecord};
+use rusp_lib::usp_decoder::try_decode_record;
+
+use anyhow::{Context, Result};
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::input::{decode_input, input_files, read_input, InputFormat};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "timeline")]
+/// reconstruct the conversations contained in a log of USP Records, pairing requests with
+/// responses and flagging unanswered requests, duplicate msg_ids and errors
+pub(crate) struct TimelineArgs {
+    #[argh(
+        option,
+        long = "input-format",
+        short = 'f',
+        default = "InputFormat::Protobuf"
+    )]
+    /// format of the Record files: protobuf (default), base64, hex, prototxt or json
+    input_format: InputFormat,
+
+    #[argh(
+        option,
+        long = "output-format",
+        short = 'F',
+        default = "TimelineFormat::Table"
+    )]
+    /// format of the output: table (default) or json
+    output_format: TimelineFormat,
+
+    #[argh(positional)]
+    /// files containing a USP Record, directories of such files or pcap/pcapng captures, in
+    /// chronological order; stdin is read if omitted
+    paths: Vec<PathBuf>,
+}
+
+/// The supported output formats of the timeline subcommand
+#[derive(Clone, Copy)]
+enum TimelineFormat {
+    Table,
+    Json,
+}
+
+impl FromStr for TimelineFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unsupported timeline format {s}, expected one of table or json"
+            )),
+        }
+    }
+}
+
+pub(crate) fn timeline(args: &TimelineArgs) -> Result<()> {
+    let mut files = vec![];
+    for path in input_files(&args.paths) {
+        if path.is_dir() {
+            let mut entries = std::fs::read_dir(path)
+                .with_context(|| format!("Couldn't read directory {}", path.display()))?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            entries.retain(|entry| entry.is_file());
+            // Logged Records are commonly named in chronological order
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+
+    let mut records = vec![];
+    for file in &files {
+        let data = read_input(file, InputFormat::Protobuf)?;
+        if is_capture(&data) {
+            let captured = extract_records(&data).with_context(|| file.display().to_string())?;
+            records.extend(captured.into_iter().map(LoggedRecord::from));
+        } else {
+            let record = decode_input(data, args.input_format)
+                .and_then(|data| Ok(try_decode_record(&data)?))
+                .with_context(|| file.display().to_string())?;
+            records.push(LoggedRecord::new(record));
+        }
+    }
+
+    let conversations = reconstruct_conversations(&records);
+    match args.output_format {
+        TimelineFormat::Table => {
+            let conversations: Vec<_> = conversations.iter().map(ToString::to_string).collect();
+            print!("{}", conversations.join("\n"));
+        }
+        TimelineFormat::Json => println!("{}", serde_json::to_string_pretty(&conversations)?),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,327 @@
+use argh::FromArgs;
+use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
+use rusp_lib::usp::Msg;
+use rusp_lib::usp_decoder::try_decode_msg;
+use rusp_lib::usp_record::Record;
+
+use anyhow::{anyhow, Context, Result};
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::engine::{new_engine, EngineConfig};
+use crate::input::{read_protobuf, InputFormat, Protobuf, ProtobufType};
+
+#[derive(FromArgs)]
+#[argh(subcommand, name = "watch")]
+/// watch a directory and decode USP Records and Msgs as their files appear, unwrapping the Msgs
+/// carried in Records
+pub(crate) struct WatchArgs {
+    #[argh(
+        option,
+        long = "output-format",
+        short = 'F',
+        default = "WatchFormat::Json"
+    )]
+    /// format of the output: json (default) or json-lines
+    output_format: WatchFormat,
+
+    #[argh(option, long = "type", default = "ProtobufType::Auto")]
+    /// type of the files: auto (default) to detect whether they contain a Record or Msg, record
+    /// or msg
+    kind: ProtobufType,
+
+    #[argh(switch, long = "keep-record", short = 'k')]
+    /// output Records as they are, instead of the Msg carried in their payload
+    keep_record: bool,
+
+    #[argh(
+        option,
+        long = "extension",
+        short = 'e',
+        default = "String::from(\"pb\")"
+    )]
+    /// extension of the files to decode, defaults to pb
+    extension: String,
+
+    #[argh(switch, long = "existing")]
+    /// decode the files already contained in the directory before watching it
+    existing: bool,
+
+    #[argh(option, long = "callback", short = 'c')]
+    /// rhai script defining a function `on_message(usp, file)` which is called for every decoded
+    /// Record or Msg; it may return true to output it, a Record or Msg to output instead, a
+    /// string to print or false or () to skip it
+    callback: Option<PathBuf>,
+
+    #[argh(positional)]
+    /// the directory to watch
+    dir: PathBuf,
+}
+
+/// The supported output formats of the watch subcommand
+#[derive(Clone, Copy)]
+enum WatchFormat {
+    Json,
+    JsonLines,
+}
+
+impl FromStr for WatchFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "json-lines" | "jsonl" => Ok(Self::JsonLines),
+            _ => Err(format!(
+                "Unsupported output format {s}, expected one of json or json-lines"
+            )),
+        }
+    }
+}
+
+/// Calls `handle` for every file with the given extension which is written to or moved into the
+/// directory, until `handle` fails
+#[cfg(target_os = "linux")]
+fn watch_dir(
+    dir: &Path,
+    extension: &str,
+    mut handle: impl FnMut(&Path) -> Result<()>,
+) -> Result<()> {
+    use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+
+    let inotify = Inotify::init(InitFlags::IN_CLOEXEC)?;
+    // Files are only complete once they are closed or moved into the directory
+    inotify
+        .add_watch(
+            dir,
+            AddWatchFlags::IN_CLOSE_WRITE | AddWatchFlags::IN_MOVED_TO,
+        )
+        .with_context(|| format!("Couldn't watch directory {}", dir.display()))?;
+
+    loop {
+        for event in inotify.read_events()? {
+            let Some(name) = event.name else {
+                continue;
+            };
+            let file = dir.join(name);
+            if file.extension().is_some_and(|ext| ext == extension) {
+                handle(&file)?;
+            }
+        }
+    }
+}
+
+/// Calls `handle` for every file with the given extension which appears in the directory, until
+/// `handle` fails
+#[cfg(not(target_os = "linux"))]
+fn watch_dir(
+    dir: &Path,
+    extension: &str,
+    mut handle: impl FnMut(&Path) -> Result<()>,
+) -> Result<()> {
+    let list = || -> Result<std::collections::BTreeSet<PathBuf>> {
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("Couldn't read directory {}", dir.display()))?;
+        let mut files = std::collections::BTreeSet::new();
+        for entry in entries {
+            let file = entry?.path();
+            if file.extension().is_some_and(|ext| ext == extension) {
+                files.insert(file);
+            }
+        }
+        Ok(files)
+    };
+
+    // Without inotify the directory is polled, files present at the start are already known
+    let mut known = list()?;
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let files = list()?;
+        for file in files.difference(&known) {
+            handle(file)?;
+        }
+        known = files;
+    }
+}
+
+/// The Rhai callback of the watch subcommand
+struct WatchCallback {
+    engine: Engine,
+    ast: AST,
+    /// The scope is kept between calls, so the script can keep state like counters
+    scope: Scope<'static>,
+}
+
+impl WatchCallback {
+    fn new(script: &Path, config: &EngineConfig) -> Result<Self> {
+        let engine = new_engine(config);
+        let ast = engine
+            .compile_file(script.into())
+            .map_err(|e| anyhow!("{e}"))
+            .with_context(|| format!("Couldn't compile callback script {}", script.display()))?;
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| anyhow!("{e}"))
+            .with_context(|| format!("Couldn't run callback script {}", script.display()))?;
+
+        Ok(Self { engine, ast, scope })
+    }
+
+    fn call(&mut self, protobuf: Protobuf, file: &Path) -> Result<Option<WatchOutput>> {
+        let usp = match protobuf {
+            Protobuf::Msg(msg) => Dynamic::from(msg),
+            Protobuf::Record(record) => Dynamic::from(record),
+        };
+        let options = CallFnOptions::new().eval_ast(false);
+        let result: Dynamic = self
+            .engine
+            .call_fn_with_options(
+                options,
+                &mut self.scope,
+                &self.ast,
+                "on_message",
+                (usp.clone(), file.display().to_string()),
+            )
+            .map_err(|e| anyhow!("{e}"))
+            .context("Callback on_message failed")?;
+
+        Ok(if result.is_unit() {
+            None
+        } else if let Ok(keep) = result.as_bool() {
+            keep.then(|| WatchOutput::from_dynamic(usp))
+        } else if result.is_string() {
+            Some(WatchOutput::Text(result.into_string().unwrap_or_default()))
+        } else if result.is::<Msg>() || result.is::<Record>() {
+            Some(WatchOutput::from_dynamic(result))
+        } else {
+            Err(anyhow!(
+                "Callback on_message returned unsupported type {}",
+                result.type_name()
+            ))?
+        })
+    }
+}
+
+/// The output of the watch subcommand for a decoded file
+enum WatchOutput {
+    Protobuf(Box<Protobuf>),
+    Text(String),
+}
+
+impl WatchOutput {
+    fn from_dynamic(usp: Dynamic) -> Self {
+        if usp.is::<Msg>() {
+            Self::Protobuf(Box::new(Protobuf::Msg(usp.cast())))
+        } else {
+            Self::Protobuf(Box::new(Protobuf::Record(usp.cast())))
+        }
+    }
+}
+
+fn watch_file(
+    args: &WatchArgs,
+    callback: Option<&mut WatchCallback>,
+    file: &Path,
+) -> Result<Option<WatchOutput>> {
+    let (_, protobuf) = read_protobuf(file, InputFormat::Protobuf, args.kind)?;
+    let protobuf = match protobuf {
+        Protobuf::Record(mut record) if !args.keep_record => {
+            match record
+                .payload_flatten()
+                .map(|payload| try_decode_msg(payload))
+            {
+                Some(msg) => Protobuf::Msg(msg.with_context(|| {
+                    format!("{}: Couldn't decode the Record payload", file.display())
+                })?),
+                None => Protobuf::Record(record),
+            }
+        }
+        protobuf => protobuf,
+    };
+
+    match callback {
+        Some(callback) => callback
+            .call(protobuf, file)
+            .with_context(|| file.display().to_string()),
+        None => Ok(Some(WatchOutput::Protobuf(Box::new(protobuf)))),
+    }
+}
+
+/// Renders a filename as JSON string
+fn json_file(file: &Path) -> String {
+    serde_json::Value::from(file.display().to_string()).to_string()
+}
+
+pub(crate) fn watch(args: &WatchArgs, config: &EngineConfig) -> Result<()> {
+    let mut callback = args
+        .callback
+        .as_deref()
+        .map(|script| WatchCallback::new(script, config))
+        .transpose()?;
+
+    let mut out = std::io::stdout();
+    // Decoding errors are reported and skipped, failing to write the output ends the watch
+    let mut handle = |file: &Path| -> Result<()> {
+        let output = match watch_file(args, callback.as_mut(), file) {
+            Ok(Some(output)) => output,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                eprintln!("{err:#}");
+                return Ok(());
+            }
+        };
+
+        let printed = match output {
+            WatchOutput::Text(text) => text,
+            WatchOutput::Protobuf(protobuf) => match (*protobuf, args.output_format) {
+                (Protobuf::Msg(msg), WatchFormat::Json) => {
+                    format!(
+                        "// {} (Msg)\n{}",
+                        file.display(),
+                        serde_json::to_string_pretty(&msg)?
+                    )
+                }
+                (Protobuf::Record(record), WatchFormat::Json) => {
+                    format!(
+                        "// {} (Record)\n{}",
+                        file.display(),
+                        serde_json::to_string_pretty(&record)?
+                    )
+                }
+                // The lines can be read back by `rusp-run stream -f json-lines`
+                (Protobuf::Msg(msg), WatchFormat::JsonLines) => format!(
+                    "{{\"file\":{},\"msg\":{}}}",
+                    json_file(file),
+                    serde_json::to_string(&msg)?
+                ),
+                (Protobuf::Record(record), WatchFormat::JsonLines) => format!(
+                    "{{\"file\":{},\"record\":{}}}",
+                    json_file(file),
+                    serde_json::to_string(&record)?
+                ),
+            },
+        };
+        writeln!(out, "{printed}")?;
+        Ok(out.flush()?)
+    };
+
+    if args.existing {
+        let mut files = std::fs::read_dir(&args.dir)
+            .with_context(|| format!("Couldn't read directory {}", args.dir.display()))?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        files.retain(|file| {
+            file.extension()
+                .is_some_and(|ext| ext == args.extension.as_str())
+        });
+        files.sort();
+        for file in &files {
+            handle(file)?;
+        }
+    }
+
+    watch_dir(&args.dir, &args.extension, handle)
+}
@@ -0,0 +1,114 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use rusp_lib::usp::Msg;
+use rusp_lib::usp_builder::{
+    GSDMCommandResult, GSDMEventResult, GSDMParamResult, GSDMReqObjectResultBuilder,
+    GSDMSupportedObjectResultBuilder, GetReqPathResultBuilder, GetRespBuilder,
+    GetSupportedDMRespBuilder, MsgBuilder, ResolvedPathResultBuilder,
+};
+use rusp_lib::usp_decoder::{try_decode_msg, try_decode_msg_ref};
+
+/// Builds a GetSupportedDMResp describing `objects` multi-instance objects with 25 parameters,
+/// two commands and an event each, similar to a response for the whole TR-181 data model
+fn gsdm_resp(objects: usize) -> Msg {
+    let supported_objs = (0..objects)
+        .map(|obj| {
+            let params = (0..25)
+                .map(|param| {
+                    GSDMParamResult::new(format!("Parameter{param}"))
+                        .set_access_read_write()
+                        .set_type_string()
+                        .set_value_change_allowed()
+                })
+                .collect();
+            let commands = vec![
+                GSDMCommandResult::new("Reset()".into()).set_sync(),
+                GSDMCommandResult::new("Diagnostic()".into())
+                    .with_input_arg_names(vec!["Interface".into(), "Timeout".into()])
+                    .with_output_arg_names(vec!["Status".into(), "Result.{i}.".into()])
+                    .set_async(),
+            ];
+            let events = vec![GSDMEventResult::new("Changed!".into())
+                .with_arg_names(vec!["Reason".into(), "Timestamp".into()])];
+
+            GSDMSupportedObjectResultBuilder::new(format!("Device.Service.Object{obj}.{{i}}."))
+                .set_access_add_delete()
+                .with_is_multi_instance(true)
+                .with_supported_params(params)
+                .with_supported_commands(commands)
+                .with_supported_events(events)
+                .with_unique_key_sets(vec![vec!["Alias".into()], vec!["Name".into()]])
+        })
+        .collect();
+    let req_obj = GSDMReqObjectResultBuilder::new("Device.".into())
+        .with_data_model_inst_uri("urn:broadband-forum-org:tr-181-2-16-0".into())
+        .with_supported_objs(supported_objs);
+
+    MsgBuilder::new()
+        .with_msg_id("gsdm-rsp".into())
+        .with_body(
+            GetSupportedDMRespBuilder::new()
+                .with_req_obj_results(vec![req_obj])
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap()
+}
+
+/// Builds a GetResp with `instances` resolved instances of 25 parameters each
+fn get_resp(instances: usize) -> Msg {
+    let resolved = (1..=instances)
+        .map(|instance| {
+            ResolvedPathResultBuilder::new(format!("Device.Hosts.Host.{instance}."))
+                .with_result_params(
+                    (0..25)
+                        .map(|param| (format!("Parameter{param}"), format!("Value {param}")))
+                        .collect(),
+                )
+        })
+        .collect();
+    let path_result =
+        GetReqPathResultBuilder::new("Device.Hosts.Host.*.".into()).with_res_path_results(resolved);
+
+    MsgBuilder::new()
+        .with_msg_id("get-rsp".into())
+        .with_body(
+            GetRespBuilder::new()
+                .with_req_path_results(vec![path_result])
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap()
+}
+
+fn decode(c: &mut Criterion) {
+    let msgs = [
+        ("GetSupportedDMResp", gsdm_resp(2000)),
+        ("GetResp", get_resp(2000)),
+    ];
+
+    let mut group = c.benchmark_group("decode");
+    for (name, msg) in msgs {
+        let bytes = msg.to_vec().unwrap();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::new("owned", name), &bytes, |b, bytes| {
+            b.iter(|| try_decode_msg(black_box(bytes)).unwrap());
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", name), &bytes, |b, bytes| {
+            b.iter(|| try_decode_msg_ref(black_box(bytes)).unwrap());
+        });
+        group.bench_with_input(
+            BenchmarkId::new("borrowed_into_owned", name),
+            &bytes,
+            |b, bytes| {
+                b.iter(|| try_decode_msg_ref(black_box(bytes)).unwrap().into_owned());
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
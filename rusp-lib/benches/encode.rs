@@ -0,0 +1,90 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use rusp_lib::usp::Msg;
+use rusp_lib::usp_builder::{MsgBuilder, NotifyBuilder, RecordBuilder};
+
+const NOTIFICATIONS: usize = 1000;
+
+/// Builds `NOTIFICATIONS` ValueChange notifications like an Agent sends them for frequently
+/// changing parameters
+fn value_changes() -> Vec<Msg> {
+    (0..NOTIFICATIONS)
+        .map(|id| {
+            MsgBuilder::new()
+                .with_msg_id(format!("notify-{id}"))
+                .with_body(
+                    NotifyBuilder::new("value-change-subscription".into())
+                        .with_send_resp(false)
+                        .with_value_change(
+                            format!("Device.IP.Interface.1.Stats.BytesSent.{}", id % 8),
+                            format!("{}", id * 1500),
+                        )
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap()
+        })
+        .collect()
+}
+
+fn envelope() -> RecordBuilder {
+    RecordBuilder::new()
+        .with_version("1.3".into())
+        .with_to_id("proto::controller".into())
+        .with_from_id("proto::agent-0011223344556677".into())
+}
+
+fn encode(c: &mut Criterion) {
+    let msgs = value_changes();
+
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Elements(NOTIFICATIONS as u64));
+
+    group.bench_function("msg/to_vec", |b| {
+        b.iter(|| {
+            for msg in &msgs {
+                black_box(msg.to_vec().unwrap());
+            }
+        });
+    });
+    group.bench_function("msg/encode_into", |b| {
+        let mut buf = Vec::new();
+        b.iter(|| {
+            for msg in &msgs {
+                msg.encode_into(&mut buf).unwrap();
+                black_box(&buf);
+            }
+        });
+    });
+
+    group.bench_function("record/builder_to_vec", |b| {
+        b.iter(|| {
+            for msg in &msgs {
+                let record = envelope()
+                    .with_no_session_context_payload(msg)
+                    .build()
+                    .unwrap();
+                black_box(record.to_vec().unwrap());
+            }
+        });
+    });
+    group.bench_function("record/write_with_msg_to", |b| {
+        let record = envelope()
+            .with_no_session_context_payload_bytes(Vec::new())
+            .build()
+            .unwrap();
+        let mut buf = Vec::new();
+        b.iter(|| {
+            for msg in &msgs {
+                buf.clear();
+                record.write_with_msg_to(msg, &mut buf).unwrap();
+                black_box(&buf);
+            }
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
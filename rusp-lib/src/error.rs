@@ -0,0 +1,262 @@
+use std::fmt;
+
+use crate::usp::mod_Header::MsgType;
+use crate::usp_decoder::DecodeError;
+use crate::usp_errors::UspErrorCode;
+
+/// A specialised [`Result`](std::result::Result) type for operations of this library
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The type of USP data an [`Error`] refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// A USP Record
+    Record,
+    /// A USP Msg
+    Msg,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Record => write!(f, "USP Record"),
+            Self::Msg => write!(f, "USP Message"),
+        }
+    }
+}
+
+/// The errors returned by the decoding, encoding, validation and building functions of this
+/// library
+///
+/// Every error can be mapped to the USP error code a responder should send back to the originator
+/// of the offending data via [`Error::err_code`]. Applications can report their own failures to
+/// process a request via [`Error::UnsupportedMsg`] and [`Error::Rejected`], in order to turn them
+/// into a response via [`Msg::error_response_for`](crate::usp::Msg::error_response_for) and
+/// [`Record::error_response_for`](crate::usp_record::Record::error_response_for) as well.
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::try_decode_record;
+/// use rusp_lib::usp_errors::UspErrorCode;
+/// use rusp_lib::{Error, Target};
+///
+/// let err = try_decode_record(&[0x0a, 0x05, 0x31]).unwrap_err();
+/// assert!(matches!(err, Error::Decode { target: Target::Record, .. }));
+/// assert_eq!(err.err_code(), UspErrorCode::RecordNotParsed);
+/// assert_eq!(err.decode_details().unwrap().path, "Record.version");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Protobuf encoded data could not be decoded into a USP Record or Msg
+    Decode {
+        /// The type of the data being decoded
+        target: Target,
+        /// The location and cause of the failure, if it could be determined
+        details: Option<DecodeError>,
+        /// A textual description of the failure
+        reason: String,
+    },
+    /// A textual representation of binary data, like base64 or hex, could not be decoded
+    InvalidEncoding {
+        /// The name of the encoding, e.g. `base64`
+        encoding: &'static str,
+        /// A textual description of the failure
+        reason: String,
+    },
+    /// A USP Record or Msg could not be encoded
+    Encode {
+        /// The type of the data being encoded
+        target: Target,
+        /// A textual description of the failure
+        reason: String,
+    },
+    /// A builder was missing a field required to produce its output
+    MissingField {
+        /// A description of the item being built, e.g. `USP Msg`
+        item: &'static str,
+        /// The name of the missing field, e.g. `msg_id`
+        field: &'static str,
+    },
+    /// A USP Record or Msg violates a constraint of the USP specification
+    Validation {
+        /// The type of the offending data
+        target: Target,
+        /// The path of the offending field, relative to the Record or Msg
+        field: &'static str,
+        /// A textual description of the violation
+        reason: String,
+    },
+    /// A number outside of the range of USP error codes was used as one
+    InvalidErrCode(u32),
+    /// A USP error code was used in a Msg or Record it is not allowed in
+    ErrCodeNotAllowed {
+        /// The offending error code
+        code: UspErrorCode,
+        /// The type of the Msg the code was used in, or `None` for a Record
+        msg_type: Option<MsgType>,
+    },
+    /// A received Msg is of a type which is not supported by the responder
+    UnsupportedMsg(MsgType),
+    /// The responder refused or failed to process a received request
+    Rejected {
+        /// The error code to report
+        code: UspErrorCode,
+        /// A textual description of the failure, the message of the code is used if `None`
+        reason: Option<String>,
+        /// The path, error code and description of every parameter which caused the failure
+        param_errs: Vec<(String, UspErrorCode, String)>,
+    },
+    /// C source code, like a C header produced by this library, could not be parsed
+    Parse {
+        /// A textual description of the failure, including its location in the source
+        reason: String,
+    },
+    /// The Protobuf text format representation of a USP Record or Msg could not be parsed or
+    /// converted
+    Prototxt {
+        /// The type of the data being parsed
+        target: Target,
+        /// A textual description of the failure, including its location in the text
+        reason: String,
+    },
+    /// The JSON representation of a USP Record, Msg or Msg body could not be parsed or converted
+    Json {
+        /// A textual description of the failure, including the path of the offending member
+        reason: String,
+    },
+    /// A packet capture could not be read or did not contain valid USP data
+    Capture {
+        /// A textual description of the failure
+        reason: String,
+    },
+    /// A stream of USP Records or Msgs could not be read or written
+    Stream {
+        /// The position of the offending item in the stream, if known
+        location: Option<String>,
+        /// A textual description of the failure
+        reason: String,
+    },
+    /// An argument, like the name of an option, was not understood
+    InvalidArgument(String),
+}
+
+impl Error {
+    /// Returns the USP error code a responder should send back to the originator of the data
+    /// which caused this error
+    ///
+    /// Failures to decode or validate a Record are reported with the respective Record error codes,
+    /// e.g. [`UspErrorCode::RecordNotParsed`] for a Record which cannot be decoded, while problems
+    /// with a received Msg are reported as [`UspErrorCode::MessageFailed`]. Unsupported Msgs are
+    /// reported as [`UspErrorCode::MessageNotSupported`] and rejected requests with the supplied
+    /// code. All other errors are caused by the responder itself and mapped to
+    /// [`UspErrorCode::InternalError`].
+    #[must_use]
+    pub const fn err_code(&self) -> UspErrorCode {
+        match self {
+            Self::Decode {
+                target: Target::Record,
+                ..
+            } => UspErrorCode::RecordNotParsed,
+            Self::Validation {
+                target: Target::Record,
+                ..
+            } => UspErrorCode::InvalidRecordValue,
+            Self::Decode {
+                target: Target::Msg,
+                ..
+            }
+            | Self::Validation {
+                target: Target::Msg,
+                ..
+            }
+            | Self::InvalidEncoding { .. }
+            | Self::Parse { .. }
+            | Self::Prototxt { .. }
+            | Self::Json { .. }
+            | Self::Capture { .. }
+            | Self::Stream { .. } => UspErrorCode::MessageFailed,
+            Self::Encode { .. }
+            | Self::MissingField { .. }
+            | Self::InvalidArgument(_)
+            | Self::InvalidErrCode(_)
+            | Self::ErrCodeNotAllowed { .. } => UspErrorCode::InternalError,
+            Self::UnsupportedMsg(_) => UspErrorCode::MessageNotSupported,
+            Self::Rejected { code, .. } => *code,
+        }
+    }
+
+    /// Returns the detailed location and cause of a failure to decode Protobuf data, if available
+    #[must_use]
+    pub const fn decode_details(&self) -> Option<&DecodeError> {
+        match self {
+            Self::Decode {
+                details: Some(details),
+                ..
+            } => Some(details),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode { target, reason, .. } => {
+                write!(f, "while parsing protobuf as {target}: {reason}")
+            }
+            Self::InvalidEncoding { encoding, reason } => {
+                write!(f, "while decoding {encoding}: {reason}")
+            }
+            Self::Encode { target, reason } => {
+                write!(f, "Failed serializing {target} to Protobuf: {reason}")
+            }
+            Self::MissingField { item, field } => {
+                write!(f, "Cannot produce {item} without {field}")
+            }
+            Self::Validation { reason, .. } => write!(f, "{reason}"),
+            Self::InvalidErrCode(code) => write!(
+                f,
+                "Invalid USP error code {code}, expected a value between 7000 and 7999"
+            ),
+            Self::ErrCodeNotAllowed {
+                code,
+                msg_type: Some(msg_type),
+            } => write!(
+                f,
+                "Error code {code} is not allowed in a {msg_type:?} message"
+            ),
+            Self::ErrCodeNotAllowed {
+                code,
+                msg_type: None,
+            } => write!(f, "Error code {code} is not allowed in a Record"),
+            Self::UnsupportedMsg(msg_type) => write!(f, "Unsupported Msg type {msg_type:?}"),
+            Self::Rejected {
+                reason: Some(reason),
+                ..
+            } if !reason.is_empty() => write!(f, "{reason}"),
+            Self::Rejected { code, .. } => write!(f, "{}", code.message()),
+            Self::Parse { reason } => write!(f, "while parsing C source: {reason}"),
+            Self::Prototxt { target, reason } => {
+                write!(
+                    f,
+                    "while parsing Protobuf text format as {target}: {reason}"
+                )
+            }
+            Self::Json { reason } | Self::Capture { reason } | Self::InvalidArgument(reason) => {
+                write!(f, "{reason}")
+            }
+            Self::Stream {
+                location: Some(location),
+                reason,
+            } => write!(f, "{location}: {reason}"),
+            Self::Stream {
+                location: None,
+                reason,
+            } => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -18,9 +18,23 @@
 //!   * [USP Messages][`rusp::usp`]
 //! * A library providing:
 //!   * Higher level access to [deserialisation][`rusp::usp_decoder`] functionality
+//!   * [Structured errors][`rusp::Error`] which map to the USP error code to respond with
+//!   * Generation of Error responses for failed incoming **USP** Records and Messages
+//!   * Zero-copy decoding of **USP** Records and Messages into borrowed types
+//!   * Streaming encoding of **USP** Records and Messages into any [`std::io::Write`]
 //!   * Convenience functions to [work with the native Msg types][`rusp::usp_decoder`]
 //!   * Pretty printing of **USP** Records and Messages
 //!   * Serde de-/serialisation of **USP** Records and Messages
+//!   * Conversion of **USP** Records and Messages from and to base64 and hex encoded strings
+//!   * Conversion of **USP** Records and Messages from and to the Protobuf text format
+//!   * [Annotated hex dumps][`rusp::usp_hexdump`] of (possibly malformed) **USP** Records and Messages
+//!   * Generation of [C headers][`rusp::usp_c_header`] containing **USP** Records and Messages
+//!   * [Parsing][`rusp::usp_c_parser`] of **USP** Records and Messages from C arrays and strings
+//!   * Lenient decoding [retaining unknown fields][`rusp::usp_unknown`] of **USP** Records and Messages
+//!   * Structural [comparison][`rusp::usp_diff`] of **USP** Records and Messages
+//!   * [Extraction][`rusp::usp_capture`] of **USP** Records from pcap and pcapng network captures
+//!   * [Reconstruction][`rusp::usp_conversation`] of conversations from logs of **USP** Records
+//!   * [Streaming][`rusp::usp_stream`] of length-delimited and JSON lines encoded **USP** Records and Messages
 //!   * Unittests and documentation (including doctests/examples)
 //! * A **rusp** binary granting access to library functionality via command line. Included functionality at the moment are:
 //!   * Decoding of **USP** Msg Protobuf byte streams from standard input
@@ -37,12 +51,33 @@
 //! [`rusp::usp`]: crate::usp
 //! [`rusp::usp_record`]: crate::usp_record
 //! [`rusp::usp_decoder`]: crate::usp_decoder
+//! [`rusp::Error`]: crate::Error
+//! [`rusp::usp_hexdump`]: crate::usp_hexdump
+//! [`rusp::usp_unknown`]: crate::usp_unknown
+//! [`rusp::usp_c_header`]: crate::usp_c_header
+//! [`rusp::usp_c_parser`]: crate::usp_c_parser
+//! [`rusp::usp_diff`]: crate::usp_diff
+//! [`rusp::usp_capture`]: crate::usp_capture
+//! [`rusp::usp_conversation`]: crate::usp_conversation
+//! [`rusp::usp_stream`]: crate::usp_stream
+
+/// Structured errors returned by this library
+pub mod error;
+pub use error::{Error, Result, Target};
 
 /// Automatically generated bindings for USP Msgs from the [`USP Messages Protobuf schema`]
 ///
 /// [`USP Messages Protobuf schema`]: <https://usp.technology/specification/usp-msg-1-3.proto>
 pub mod usp;
 
+/// Automatically generated borrowed bindings for USP Msgs, referencing strings and bytes in the
+/// decoded data instead of copying them
+///
+/// Use [`try_decode_msg_ref`](crate::usp_decoder::try_decode_msg_ref) for decoding and
+/// [`MsgRef::into_owned`](crate::usp_decoder::MsgRef::into_owned) or `From` for conversion into
+/// the owned types in [`usp`].
+pub mod usp_ref;
+
 /// Builder style functions to generate USP Messages
 pub mod usp_builder;
 
@@ -57,7 +92,44 @@ pub mod usp_encoder;
 /// [`USP Records Protobuf schema`]: <https://usp.technology/specification/usp-record-1-3.proto>
 pub mod usp_record;
 
-/// Helper functions for checking and conversion of USP error codes and messages
+/// Automatically generated borrowed bindings for USP Records, referencing strings and bytes in the
+/// decoded data instead of copying them
+///
+/// Use [`try_decode_record_ref`](crate::usp_decoder::try_decode_record_ref) for decoding and
+/// [`RecordRef::into_owned`](crate::usp_decoder::RecordRef::into_owned) or `From` for conversion
+/// into the owned types in [`usp_record`].
+pub mod usp_record_ref;
+
+/// Typed USP error codes and helper functions for checking and conversion of USP error codes and
+/// messages
 pub mod usp_errors;
 
+/// Generation of C headers containing Protobuf encoded USP Records and Messages
+pub mod usp_c_header;
+
+/// Parsing of Protobuf encoded USP Records and Messages from C arrays and strings
+pub mod usp_c_parser;
+
+/// Annotated hex dumps of Protobuf encoded USP Records and Messages
+pub mod usp_hexdump;
+
+/// Retention of unknown fields for lossless pass-through of USP Records and Messages
+pub mod usp_unknown;
+
+/// Semantic comparison of USP Records and Messages
+pub mod usp_diff;
+
+/// Extraction of USP Records from network captures in pcap and pcapng format
+pub mod usp_capture;
+
+/// Reconstruction of request/response conversations from logs of USP Records
+pub mod usp_conversation;
+
+/// Streaming readers and writers for length-delimited and JSON lines encoded USP Records and Msgs
+pub mod usp_stream;
+
 mod usp_json;
+
+mod usp_prototxt;
+
+mod usp_schema;
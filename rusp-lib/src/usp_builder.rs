@@ -13,6 +13,7 @@ mod notify;
 mod operate;
 mod record;
 mod register;
+mod response;
 mod set;
 
 pub use add::{
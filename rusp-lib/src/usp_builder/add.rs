@@ -3,9 +3,9 @@ use std::collections::HashMap;
 use crate::usp::mod_Add::{CreateObject, CreateParamSetting};
 use crate::usp::mod_AddResp::{mod_CreatedObjectResult::OperationStatus, CreatedObjectResult};
 use crate::usp::{Add, AddResp, Body, Request, Response};
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
-use anyhow::Result;
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct CreateObjectBuilder {
@@ -132,7 +132,8 @@ impl AddOperationStatus {
     }
 
     #[must_use]
-    pub fn set_failure(self, err_code: u32, err_msg: Option<String>) -> Self {
+    pub fn set_failure(self, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        let err_code = err_code.into_err_code();
         Self::Failure(AddOperationFailureBuilder {
             err_code,
             err_msg: err_msg.unwrap_or_else(|| usp_errors::get_err_msg(err_code).to_string()),
@@ -185,7 +186,10 @@ impl AddOperationStatus {
                     unique_keys: s.unique_keys.into_iter().collect(),
                 }),
             }),
-            Self::None => Err(anyhow::anyhow!("")),
+            Self::None => Err(Error::MissingField {
+                item: "AddResp OperationStatus",
+                field: "oper_success or oper_failure",
+            }),
         }
     }
 }
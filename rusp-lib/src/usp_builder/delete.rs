@@ -8,9 +8,9 @@ use crate::usp::mod_DeleteResp::{mod_DeletedObjectResult::OperationStatus, Delet
 use crate::usp::mod_Request::OneOfreq_type::delete;
 use crate::usp::mod_Response::OneOfresp_type::delete_resp;
 use crate::usp::{Body, Delete, DeleteResp, Request, Response};
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
-use anyhow::Result;
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct DeleteBuilder {
@@ -91,7 +91,8 @@ impl DeletedObjectResultsBuilder {
     }
 
     #[must_use]
-    pub fn set_failure(mut self, err_code: u32, err_msg: Option<String>) -> Self {
+    pub fn set_failure(mut self, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        let err_code = err_code.into_err_code();
         self.oper_status = DeleteRespOperationStatus::Failure {
             err_code,
             err_msg: err_msg.unwrap_or_else(|| usp_errors::get_err_msg(err_code).to_string()),
@@ -137,7 +138,10 @@ impl DeletedObjectResultsBuilder {
                 DeleteRespOperationStatus::Failure { err_code, err_msg } => Some(OperationStatus {
                     oper_status: oper_failure(OperationFailure { err_code, err_msg }),
                 }),
-                DeleteRespOperationStatus::None => Err(anyhow::anyhow!(""))?,
+                DeleteRespOperationStatus::None => Err(Error::MissingField {
+                    item: "DeletedObjectResult",
+                    field: "oper_success or oper_failure",
+                })?,
             },
         })
     }
@@ -8,9 +8,9 @@ use crate::usp::mod_Request::OneOfreq_type::deregister;
 use crate::usp::mod_Response::OneOfresp_type::deregister_resp;
 use crate::usp::{Body, Deregister, DeregisterResp, Request, Response};
 
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
-use anyhow::Result;
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct DeregisterBuilder {
@@ -63,7 +63,8 @@ impl DeregisteredPathResultBuilder {
     }
 
     #[must_use]
-    pub fn set_failure(mut self, err_code: u32, err_msg: Option<String>) -> Self {
+    pub fn set_failure(mut self, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        let err_code = err_code.into_err_code();
         self.oper_status = DeregisterOperationStatus::Failure {
             err_code,
             err_msg: err_msg.unwrap_or_else(|| usp_errors::get_err_msg(err_code).to_string()),
@@ -92,7 +93,10 @@ impl DeregisteredPathResultBuilder {
                         deregistered_path: s,
                     }),
                 }),
-                DeregisterOperationStatus::None => Err(anyhow::anyhow!("")),
+                DeregisterOperationStatus::None => Err(Error::MissingField {
+                    item: "DeregisteredPathResult",
+                    field: "oper_success or oper_failure",
+                }),
             }?),
         })
     }
@@ -1,11 +1,11 @@
 use crate::usp::{Body, Error};
 
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
 use crate::usp::mod_Body::OneOfmsg_body::error;
 use crate::usp::mod_Error::ParamError;
 
-use anyhow::Result;
+use crate::error::Result;
 
 #[derive(Clone)]
 pub struct ErrorBuilder {
@@ -25,8 +25,8 @@ impl ErrorBuilder {
     }
 
     #[must_use]
-    pub fn set_err(mut self, code: u32, message: Option<String>) -> Self {
-        self.code = code;
+    pub fn set_err(mut self, code: impl IntoErrCode, message: Option<String>) -> Self {
+        self.code = code.into_err_code();
         self.message = message;
         self
     }
@@ -4,9 +4,9 @@ use crate::usp::mod_Request::OneOfreq_type::get_instances;
 use crate::usp::mod_Response::OneOfresp_type::get_instances_resp;
 use crate::usp::{Body, GetInstances, GetInstancesResp, Request, Response};
 
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
-use anyhow::Result;
+use crate::error::Result;
 
 #[derive(Clone)]
 pub struct GetInstancesBuilder {
@@ -102,8 +102,8 @@ impl ReqPathResultBuilder {
     }
 
     #[must_use]
-    pub fn set_err(mut self, err_code: u32, err_msg: Option<String>) -> Self {
-        self.err_code = err_code;
+    pub fn set_err(mut self, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        self.err_code = err_code.into_err_code();
         self.err_msg = err_msg;
         self
     }
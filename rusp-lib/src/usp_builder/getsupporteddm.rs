@@ -9,9 +9,9 @@ use crate::usp::mod_GetSupportedDMResp::{
 use crate::usp::mod_Request::OneOfreq_type::get_supported_dm;
 use crate::usp::mod_Response::OneOfresp_type::get_supported_dm_resp;
 use crate::usp::{Body, GetSupportedDM, GetSupportedDMResp, Request, Response};
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
-use anyhow::Result;
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct GetSupportedDMBuilder {
@@ -137,9 +137,10 @@ impl GSDMCommandResult {
 
     pub fn build(self) -> Result<SupportedCommandResult> {
         if matches!(self.command_type, CmdType::CMD_UNKNOWN) {
-            anyhow::bail!(
-                "Cannot build a Supported Command Result without a specified command type"
-            );
+            return Err(Error::MissingField {
+                item: "SupportedCommandResult",
+                field: "command_type",
+            });
         }
         Ok(SupportedCommandResult {
             command_name: self.command_name,
@@ -290,7 +291,10 @@ impl GSDMParamResult {
 
     pub fn build(self) -> Result<SupportedParamResult> {
         if matches!(self.value_type, ParamValueType::PARAM_UNKNOWN) {
-            anyhow::bail!("Cannot build a Supported Param Result without a specified value type");
+            return Err(Error::MissingField {
+                item: "SupportedParamResult",
+                field: "value_type",
+            });
         }
         Ok(SupportedParamResult {
             param_name: self.param_name,
@@ -448,7 +452,8 @@ impl GSDMReqObjectResultBuilder {
     }
 
     #[must_use]
-    pub fn set_err(mut self, err_code: u32, err_msg: Option<String>) -> Self {
+    pub fn set_err(mut self, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        let err_code = err_code.into_err_code();
         self.err_code = err_code;
         self.err_msg =
             Some(err_msg.unwrap_or_else(|| usp_errors::get_err_msg(err_code).to_string()));
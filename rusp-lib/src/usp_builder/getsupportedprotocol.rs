@@ -3,7 +3,7 @@ use crate::usp::mod_Request::OneOfreq_type::get_supported_protocol;
 use crate::usp::mod_Response::OneOfresp_type::get_supported_protocol_resp;
 use crate::usp::{Body, GetSupportedProtocol, GetSupportedProtocolResp, Request, Response};
 
-use anyhow::Result;
+use crate::error::Result;
 
 #[derive(Clone)]
 pub struct GetSupportedProtocolBuilder {
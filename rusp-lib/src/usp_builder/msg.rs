@@ -5,8 +5,7 @@ use crate::usp::Body;
 use crate::usp::Header;
 use crate::usp::Msg;
 
-use anyhow::anyhow;
-use anyhow::{Context, Result};
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct MsgBuilder {
@@ -53,12 +52,14 @@ impl MsgBuilder {
             register_resp, set_resp,
         };
 
-        let msg_id = self
-            .msg_id
-            .with_context(|| "Cannot produce USP Msg without msg_id")?;
-        let body = self
-            .body
-            .with_context(|| "Cannot produce USP Msg without msg_body")?;
+        let msg_id = self.msg_id.ok_or(Error::MissingField {
+            item: "USP Msg",
+            field: "msg_id",
+        })?;
+        let body = self.body.ok_or(Error::MissingField {
+            item: "USP Msg",
+            field: "msg_body",
+        })?;
 
         let msg_type = match &body.msg_body {
             request(ref req) => match &req.req_type {
@@ -73,7 +74,10 @@ impl MsgBuilder {
                 get_supported_protocol(_) => GET_SUPPORTED_PROTO,
                 register(_) => REGISTER,
                 deregister(_) => DEREGISTER,
-                OneOfreq_type::None => anyhow::bail!("Request type can't be None"),
+                OneOfreq_type::None => Err(Error::MissingField {
+                    item: "USP Msg",
+                    field: "request type",
+                })?,
             },
             response(ref resp) => match &resp.resp_type {
                 get_resp(_) => GET_RESP,
@@ -87,10 +91,16 @@ impl MsgBuilder {
                 get_supported_protocol_resp(_) => GET_SUPPORTED_PROTO_RESP,
                 register_resp(_) => REGISTER_RESP,
                 deregister_resp(_) => DEREGISTER_RESP,
-                OneOfresp_type::None => Err(anyhow!("Response type can't be None"))?,
+                OneOfresp_type::None => Err(Error::MissingField {
+                    item: "USP Msg",
+                    field: "response type",
+                })?,
             },
             OneOfmsg_body::error(_) => ERROR,
-            OneOfmsg_body::None => Err(anyhow!("Body type can't be None"))?,
+            OneOfmsg_body::None => Err(Error::MissingField {
+                item: "USP Msg",
+                field: "body type",
+            })?,
         };
 
         Ok(Msg {
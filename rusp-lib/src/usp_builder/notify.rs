@@ -12,8 +12,9 @@ use crate::usp::mod_Request::OneOfreq_type::notify;
 use crate::usp::mod_Response::OneOfresp_type::notify_resp;
 use crate::usp::{Body, Notify, Request};
 use crate::usp::{NotifyResp, Response};
+use crate::usp_errors::IntoErrCode;
 
-use anyhow::{Context, Result};
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub enum OperationCompleteType {
@@ -185,22 +186,26 @@ impl NotifyBuilder {
         obj_path: String,
         command_name: String,
         command_key: String,
-        err_code: u32,
+        err_code: impl IntoErrCode,
         err_msg: String,
     ) -> Self {
         self.notify_type = Some(NotifyType::OperationComplete {
             obj_path,
             command_name,
             command_key,
-            operation_resp: OperationCompleteType::CommandFailure(err_code, err_msg),
+            operation_resp: OperationCompleteType::CommandFailure(
+                err_code.into_err_code(),
+                err_msg,
+            ),
         });
         self
     }
 
     pub fn build(self) -> Result<Body> {
-        let notify_type = self
-            .notify_type
-            .context("Must specify a notification type")?;
+        let notify_type = self.notify_type.ok_or(Error::MissingField {
+            item: "Notify",
+            field: "notification type",
+        })?;
 
         let notify_type = match notify_type {
             NotifyType::OnBoardRequest {
@@ -1,4 +1,4 @@
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 use std::collections::HashMap;
 
 use crate::usp::mod_Body::OneOfmsg_body::{request, response};
@@ -11,7 +11,7 @@ use crate::usp::mod_Request::OneOfreq_type::operate;
 use crate::usp::mod_Response::OneOfresp_type::operate_resp;
 use crate::usp::{Body, Operate, OperateResp, Request, Response};
 
-use anyhow::Result;
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct OperateBuilder {
@@ -99,7 +99,8 @@ impl OperateRespResultBuilder {
     }
 
     #[must_use]
-    pub fn set_failure(mut self, err_code: u32, err_msg: Option<String>) -> Self {
+    pub fn set_failure(mut self, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        let err_code = err_code.into_err_code();
         self.operation_result = OperateRespOperationResult::Failure {
             err_code,
             err_msg: err_msg.unwrap_or_else(|| usp_errors::get_err_msg(err_code).to_string()),
@@ -136,9 +137,10 @@ impl OperateRespResultBuilder {
                 }),
                 executed_command: self.executed_command,
             }),
-            OperateRespOperationResult::None => Err(anyhow::anyhow!(
-                "Need to have either OutputArgs or Path or Failure"
-            )),
+            OperateRespOperationResult::None => Err(Error::MissingField {
+                item: "OperationResult",
+                field: "req_output_args, req_obj_path or cmd_failure",
+            }),
             OperateRespOperationResult::Path { req_obj_path } => Ok(OperationResult {
                 operation_resp: OneOfoperation_resp::req_obj_path(req_obj_path),
                 executed_command: self.executed_command,
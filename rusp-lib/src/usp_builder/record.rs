@@ -10,8 +10,9 @@ use crate::usp_record::{
     WebSocketConnectRecord,
 };
 
-use anyhow::anyhow;
-use anyhow::{Context, Result};
+use crate::usp_errors::IntoErrCode;
+
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 enum RecordType {
@@ -89,15 +90,18 @@ impl SessionContextBuilder {
 
     pub fn build(self) -> Result<SessionContextRecord> {
         let scr = SessionContextRecord {
-            session_id: self
-                .session_id
-                .ok_or_else(|| anyhow!("Need to supply a session ID for a session context"))?,
-            sequence_id: self
-                .sequence_id
-                .ok_or_else(|| anyhow!("Need to supply a sequence ID for a session context"))?,
-            expected_id: self
-                .expected_id
-                .ok_or_else(|| anyhow!("Need to supply a expected ID for a session context"))?,
+            session_id: self.session_id.ok_or(Error::MissingField {
+                item: "session context",
+                field: "session_id",
+            })?,
+            sequence_id: self.sequence_id.ok_or(Error::MissingField {
+                item: "session context",
+                field: "sequence_id",
+            })?,
+            expected_id: self.expected_id.ok_or(Error::MissingField {
+                item: "session context",
+                field: "expected_id",
+            })?,
             retransmit_id: self.retransmit_id,
             // FIXME
             payload_sar_state: PayloadSARState::NONE,
@@ -120,7 +124,7 @@ pub struct RecordBuilder {
     sender_cert: Vec<u8>,
     mac_signature: Vec<u8>,
     payload_security: PayloadSecurity,
-    payload: Option<Vec<u8>>,
+    payload: Option<Result<Vec<u8>>>,
     typ: RecordType,
 }
 
@@ -199,17 +203,21 @@ impl RecordBuilder {
         self
     }
 
+    /// Sets the payload of a NoSessionContext Record to the Protobuf encoding of `msg`
+    ///
+    /// An error encoding the `msg` is returned by [`RecordBuilder::build`]. To avoid encoding
+    /// the `msg` into the `Record` first, use
+    /// [`Record::write_with_msg_to`](crate::usp_record::Record::write_with_msg_to) instead.
     #[must_use]
-    pub fn with_no_session_context_payload(self, msg: &Msg) -> Self {
-        let mut buf = Vec::new();
-        let mut writer = quick_protobuf::Writer::new(&mut buf);
-        let _ = quick_protobuf::MessageWrite::write_message(msg, &mut writer);
-        self.with_no_session_context_payload_bytes(buf)
+    pub fn with_no_session_context_payload(mut self, msg: &Msg) -> Self {
+        self.payload = Some(msg.to_vec());
+        self.typ = RecordType::NoSessionContext;
+        self
     }
 
     #[must_use]
     pub fn with_no_session_context_payload_bytes(mut self, buf: Vec<u8>) -> Self {
-        self.payload = Some(buf);
+        self.payload = Some(Ok(buf));
         self.typ = RecordType::NoSessionContext;
         self
     }
@@ -247,10 +255,10 @@ impl RecordBuilder {
     }
 
     #[must_use]
-    pub fn as_disconnect_record(mut self, reason: String, reason_code: u32) -> Self {
+    pub fn as_disconnect_record(mut self, reason: String, reason_code: impl IntoErrCode) -> Self {
         self.typ = RecordType::Disconnect {
             reason,
-            reason_code,
+            reason_code: reason_code.into_err_code(),
         };
         self
     }
@@ -262,12 +270,14 @@ impl RecordBuilder {
     }
 
     pub fn build(self) -> Result<Record> {
-        let to_id = self
-            .to_id
-            .with_context(|| "Cannot produce USP Record without to_id")?;
-        let from_id = self
-            .from_id
-            .with_context(|| "Cannot produce USP Record without from_id")?;
+        let to_id = self.to_id.ok_or(Error::MissingField {
+            item: "USP Record",
+            field: "to_id",
+        })?;
+        let from_id = self.from_id.ok_or(Error::MissingField {
+            item: "USP Record",
+            field: "from_id",
+        })?;
 
         let mut record = Record {
             version: if self.version.is_empty() {
@@ -286,11 +296,15 @@ impl RecordBuilder {
         };
 
         match self.typ {
-            RecordType::None => Err(anyhow!("Cannot produce a USP Record without type"))?,
+            RecordType::None => Err(Error::MissingField {
+                item: "USP Record",
+                field: "type",
+            })?,
             RecordType::NoSessionContext => {
-                let payload = self
-                    .payload
-                    .with_context(|| "Cannot produce USP Record without payload")?;
+                let payload = self.payload.ok_or(Error::MissingField {
+                    item: "USP Record",
+                    field: "payload",
+                })??;
 
                 record.record_type =
                     OneOfrecord_type::no_session_context(NoSessionContextRecord { payload });
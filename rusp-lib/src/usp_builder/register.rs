@@ -9,9 +9,9 @@ use crate::usp::mod_Request::OneOfreq_type::register;
 use crate::usp::mod_Response::OneOfresp_type::register_resp;
 use crate::usp::{Body, Register, RegisterResp, Request, Response};
 
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
-use anyhow::Result;
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct RegisterBuilder {
@@ -83,7 +83,8 @@ impl RegisteredPathResultBuilder {
     }
 
     #[must_use]
-    pub fn set_failure(mut self, err_code: u32, err_msg: Option<String>) -> Self {
+    pub fn set_failure(mut self, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        let err_code = err_code.into_err_code();
         self.oper_status = RegisterOperationStatus::Failure {
             err_code,
             err_msg: err_msg.unwrap_or_else(|| usp_errors::get_err_msg(err_code).to_string()),
@@ -112,7 +113,10 @@ impl RegisteredPathResultBuilder {
                         registered_path: s,
                     }),
                 }),
-                RegisterOperationStatus::None => Err(anyhow::anyhow!("")),
+                RegisterOperationStatus::None => Err(Error::MissingField {
+                    item: "RegisteredPathResult",
+                    field: "oper_success or oper_failure",
+                }),
             }?),
         })
     }
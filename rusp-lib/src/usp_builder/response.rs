@@ -0,0 +1,229 @@
+use crate::usp::mod_Body::OneOfmsg_body;
+use crate::usp::mod_Request::OneOfreq_type;
+use crate::usp::Msg;
+use crate::usp_decoder::try_decode_msg;
+use crate::usp_errors::{ErrorCategory, UspErrorCode};
+use crate::usp_record::mod_Record::OneOfrecord_type;
+use crate::usp_record::Record;
+use crate::usp_schema::{WireReader, WireValue};
+
+use super::{ErrorBuilder, MsgBuilder, RecordBuilder, SessionContextBuilder};
+
+use crate::error::{Error, Result};
+
+/// Builds an Error Msg with the given `msg_id` reporting `err`
+fn error_msg(msg_id: String, err: &Error) -> Result<Msg> {
+    let builder = match err {
+        Error::Rejected {
+            code,
+            reason,
+            param_errs,
+        } => ErrorBuilder::new()
+            .set_err(*code, reason.clone())
+            .with_param_errs(
+                param_errs
+                    .iter()
+                    .map(|(path, code, msg)| (path.clone(), code.code(), msg.clone()))
+                    .collect(),
+            ),
+        _ => ErrorBuilder::new().set_err(err.err_code(), Some(err.to_string())),
+    };
+
+    let msg = MsgBuilder::new()
+        .with_msg_id(msg_id)
+        .with_body(builder.build()?)
+        .build()?;
+    msg.check_err_codes()?;
+    Ok(msg)
+}
+
+/// Extracts the msg_id from the header of a Protobuf encoded Msg, even if the rest of the Msg
+/// cannot be decoded
+fn recover_msg_id(bytes: &[u8]) -> Option<String> {
+    fn find_field(bytes: &[u8], number: u64) -> Option<&[u8]> {
+        let mut reader = WireReader::new(bytes);
+        while let Ok(field) = reader.read_field() {
+            if let (true, WireValue::LengthDelimited(value)) = (field.number == number, field.value)
+            {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    let header = find_field(bytes, 1)?;
+    let msg_id = find_field(header, 1)?;
+    String::from_utf8(msg_id.to_vec()).ok()
+}
+
+/// Returns whether an Error Msg may be sent back in response to `msg`, i.e. whether it is a
+/// request other than a Notify not asking for a response
+fn expects_response(msg: &Msg) -> bool {
+    match msg.body.as_ref().map(|body| &body.msg_body) {
+        Some(OneOfmsg_body::request(request)) => match &request.req_type {
+            OneOfreq_type::notify(notify) => notify.send_resp,
+            _ => true,
+        },
+        Some(OneOfmsg_body::response(_) | OneOfmsg_body::error(_)) => false,
+        // Without a body, the type of the Msg is unknown, so it is treated as a broken request
+        Some(OneOfmsg_body::None) | None => true,
+    }
+}
+
+impl Msg {
+    /// Builds the Error Msg to send back in response to this Msg, after processing it failed
+    ///
+    /// The response carries the msg_id of this Msg and the USP error code `err` maps to, see
+    /// [`Error::err_code`]. An [`Error::Rejected`] is reported with its code, description and
+    /// per parameter errors, all other errors with their textual description.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The error encountered while validating or processing this Msg
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_builder::{GetBuilder, MsgBuilder};
+    /// use rusp_lib::usp_errors::UspErrorCode;
+    /// use rusp_lib::Error;
+    ///
+    /// let msg = MsgBuilder::new()
+    ///     .with_msg_id("get-1".into())
+    ///     .with_body(GetBuilder::new().with_params(vec!["Device.Foo.".into()]).build().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let err = Error::Rejected {
+    ///     code: UspErrorCode::InvalidPath,
+    ///     reason: None,
+    ///     param_errs: vec![("Device.Foo.".into(), UspErrorCode::InvalidPath, "No such object".into())],
+    /// };
+    /// let resp = msg.error_response_for(&err).unwrap();
+    /// assert_eq!(resp.msg_id(), "get-1");
+    /// let error = resp.get_error().unwrap();
+    /// assert_eq!(error.err_code, 7026);
+    /// assert_eq!(error.err_msg, "Invalid path");
+    /// assert_eq!(error.param_errs[0].param_path, "Device.Foo.");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if this Msg must not be answered with an Error Msg, i.e.
+    /// if it is a response, an Error or a Notify not requesting a response via `send_resp`, or if
+    /// the error code is not allowed in an Error Msg, e.g. for errors concerning Records, which
+    /// need to be reported via [`Record::error_response_for`]
+    pub fn error_response_for(&self, err: &Error) -> Result<Self> {
+        if !expects_response(self) {
+            return Err(Error::InvalidArgument(format!(
+                "USP Msg with msg_id {} must not be answered with an Error",
+                self.msg_id()
+            )));
+        }
+        error_msg(self.msg_id().to_string(), err)
+    }
+}
+
+impl Record {
+    /// Builds the Record to send back in response to this Record, after processing it or its
+    /// payload failed
+    ///
+    /// Errors concerning the Record itself, i.e. those mapping to an error code of the
+    /// [`ErrorCategory::Record`], are reported via a DisconnectRecord. All other errors are
+    /// reported via an Error Msg, see [`Msg::error_response_for`], in a Record of the same type as
+    /// this one. If the payload cannot be decoded, the msg_id is recovered from its header if
+    /// possible. Records without payload are answered with a DisconnectRecord in any case, using
+    /// [`UspErrorCode::RecordNotParsed`] as reason code for errors of the
+    /// [`ErrorCategory::Message`] and unknown errors.
+    ///
+    /// The to and from IDs as well as the originator and destination IDs of this Record are
+    /// swapped in the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The error encountered while decoding, validating or processing this Record
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_builder::RecordBuilder;
+    /// use rusp_lib::usp_decoder::try_decode_msg;
+    /// use rusp_lib::usp_record::mod_Record::OneOfrecord_type;
+    /// use rusp_lib::{Error, Target};
+    ///
+    /// let record = RecordBuilder::new()
+    ///     .with_version("1.3".into())
+    ///     .with_to_id("proto::agent".into())
+    ///     .with_from_id("proto::controller".into())
+    ///     .with_no_session_context_payload_bytes(vec![0x0a, 0x05])
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // The payload is truncated, so the Msg cannot be decoded
+    /// let err = try_decode_msg(&[0x0a, 0x05]).unwrap_err();
+    /// let resp = record.error_response_for(&err).unwrap();
+    /// assert_eq!(resp.to_id, "proto::controller");
+    /// assert_eq!(resp.from_id, "proto::agent");
+    /// assert!(matches!(resp.record_type, OneOfrecord_type::no_session_context(_)));
+    ///
+    /// let err = Error::Validation {
+    ///     target: Target::Record,
+    ///     field: "version",
+    ///     reason: "Unsupported USP version".into(),
+    /// };
+    /// let resp = record.error_response_for(&err).unwrap();
+    /// let OneOfrecord_type::disconnect(disconnect) = resp.record_type else { panic!() };
+    /// assert_eq!(disconnect.reason_code, 7104);
+    /// assert_eq!(disconnect.reason, "Unsupported USP version");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the error code is not allowed in the response or if the
+    /// Msg carried by this Record must not be answered, see [`Msg::error_response_for`]
+    pub fn error_response_for(&self, err: &Error) -> Result<Self> {
+        let builder = RecordBuilder::new()
+            .with_version(self.version.clone())
+            .with_to_id(self.from_id.clone())
+            .with_from_id(self.to_id.clone())
+            .with_originator_id(self.destination_id.clone())
+            .with_destination_id(self.originator_id.clone());
+
+        let code = err.err_code();
+        let payload = match &self.record_type {
+            _ if code.category() == Some(ErrorCategory::Record) => None,
+            OneOfrecord_type::no_session_context(record) => Some(record.payload.clone()),
+            OneOfrecord_type::session_context(record) => Some(record.payload.concat()),
+            _ => None,
+        };
+        let Some(payload) = payload else {
+            let code = match code.category() {
+                Some(ErrorCategory::Record | ErrorCategory::Vendor) => code,
+                _ => UspErrorCode::RecordNotParsed,
+            };
+            let record = builder
+                .as_disconnect_record(err.to_string(), code)
+                .build()?;
+            record.check_err_codes()?;
+            return Ok(record);
+        };
+
+        let msg = match try_decode_msg(&payload) {
+            Ok(msg) => msg.error_response_for(err)?,
+            Err(_) => error_msg(recover_msg_id(&payload).unwrap_or_default(), err)?,
+        };
+
+        match &self.record_type {
+            OneOfrecord_type::session_context(record) => builder
+                .with_session_context_builder(
+                    SessionContextBuilder::new()
+                        .with_session_id(record.session_id)
+                        .with_sequence_id(record.expected_id)
+                        .with_expected_id(record.sequence_id.saturating_add(1))
+                        .with_payload(msg.to_vec()?),
+                )
+                .build(),
+            _ => builder.with_no_session_context_payload(&msg).build(),
+        }
+    }
+}
@@ -9,9 +9,9 @@ use crate::usp::mod_SetResp::ParameterError;
 use crate::usp::mod_SetResp::{mod_UpdatedObjectResult::OperationStatus, UpdatedObjectResult};
 use crate::usp::mod_SetResp::{UpdatedInstanceFailure, UpdatedInstanceResult};
 use crate::usp::{Body, Request, Response, Set, SetResp};
-use crate::usp_errors;
+use crate::usp_errors::{self, IntoErrCode};
 
-use anyhow::Result;
+use crate::error::{Error, Result};
 
 #[derive(Clone)]
 pub struct UpdateObjectBuilder {
@@ -113,7 +113,8 @@ pub struct SetRespParameterError {
 
 impl SetRespParameterError {
     #[must_use]
-    pub fn new(param: String, err_code: u32, err_msg: Option<String>) -> Self {
+    pub fn new(param: String, err_code: impl IntoErrCode, err_msg: Option<String>) -> Self {
+        let err_code = err_code.into_err_code();
         Self {
             param,
             err_code,
@@ -197,10 +198,11 @@ impl SetOperationStatus {
     #[must_use]
     pub fn set_failure(
         self,
-        err_code: u32,
+        err_code: impl IntoErrCode,
         err_msg: Option<String>,
         updated_inst_failures: Vec<UpdatedInstanceFailureBuilder>,
     ) -> Self {
+        let err_code = err_code.into_err_code();
         Self::Failure(SetOperationFailureBuilder {
             err_code,
             err_msg: err_msg.unwrap_or_else(|| usp_errors::get_err_msg(err_code).to_string()),
@@ -265,7 +267,10 @@ impl SetOperationStatus {
                         .collect(),
                 }),
             }),
-            Self::None => Err(anyhow::anyhow!("")),
+            Self::None => Err(Error::MissingField {
+                item: "SetResp OperationStatus",
+                field: "oper_success or oper_failure",
+            }),
         }
     }
 }
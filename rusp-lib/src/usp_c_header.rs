@@ -0,0 +1,224 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use crate::error::{Error, Result, Target};
+use crate::usp::Msg;
+use crate::usp_encoder::write_array_lines;
+use crate::usp_record::Record;
+
+/// Turns an arbitrary string, e.g. a file name, into a valid C identifier by replacing all
+/// invalid characters with an underscore
+///
+/// # Arguments
+///
+/// * `name` - The string to turn into a C identifier
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_c_header::c_identifier;
+/// assert_eq!(c_identifier("get-msg.pb"), "get_msg_pb");
+/// assert_eq!(c_identifier("1st"), "_1st");
+/// ```
+#[must_use]
+pub fn c_identifier(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+#[derive(Debug, Clone)]
+enum CHeaderItem {
+    Msg(Msg),
+    Record(Record),
+}
+
+/// A builder for complete C header files containing Protobuf encoded USP Records and Messages,
+/// e.g. to be used as test vectors in the unit tests of an agent or controller
+///
+/// Every Record or Msg is rendered into a `const uint8_t` array accompanied by a `size_t` length
+/// and a doc comment containing the JSON representation of the encoded data. Optionally a table
+/// of all contained arrays can be added to allow iterating over them.
+#[derive(Debug, Clone)]
+pub struct CHeaderBuilder {
+    name: String,
+    items: Vec<(String, CHeaderItem)>,
+    table: bool,
+}
+
+impl CHeaderBuilder {
+    /// Creates a new builder for a C header with the given name, which is used for the include
+    /// guard and the name of the optional table
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header, will be turned into a C identifier if necessary
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: c_identifier(name),
+            items: vec![],
+            table: false,
+        }
+    }
+
+    /// Adds a USP Msg to be rendered as a C array with the given name
+    #[must_use]
+    pub fn with_msg(mut self, name: &str, msg: Msg) -> Self {
+        self.items.push((c_identifier(name), CHeaderItem::Msg(msg)));
+        self
+    }
+
+    /// Adds a USP Record to be rendered as a C array with the given name
+    #[must_use]
+    pub fn with_record(mut self, name: &str, record: Record) -> Self {
+        self.items
+            .push((c_identifier(name), CHeaderItem::Record(record)));
+        self
+    }
+
+    /// Sets whether a table of all contained arrays should be rendered after the arrays
+    #[must_use]
+    pub const fn with_table(mut self, table: bool) -> Self {
+        self.table = table;
+        self
+    }
+
+    /// Renders the C header
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_builder::{GetBuilder, MsgBuilder};
+    /// use rusp_lib::usp_c_header::CHeaderBuilder;
+    ///
+    /// let body = GetBuilder::new()
+    ///     .with_params(vec!["Device.".into()])
+    ///     .build()
+    ///     .unwrap();
+    /// let msg = MsgBuilder::new()
+    ///     .with_msg_id("get".into())
+    ///     .with_body(body)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let header = CHeaderBuilder::new("test_vectors")
+    ///     .with_msg("get", msg)
+    ///     .with_table(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(header.starts_with("#ifndef TEST_VECTORS_H\n#define TEST_VECTORS_H\n"));
+    /// assert!(header.contains("static const uint8_t get[] = {\n  0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10, /* ____get_ */\n"));
+    /// assert!(header.contains("static const size_t get_len = 24;\n"));
+    /// assert!(header.contains("  { \"get\", get, 24 },\n"));
+    /// assert!(header.ends_with("#endif /* TEST_VECTORS_H */\n"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if no Record or Msg has been added, one of them cannot be serialized or an identifier would
+    /// be defined more than once, e.g. because an item is called like the length of another
+    /// one (`x` and `x_len`)
+    pub fn build(self) -> Result<String> {
+        if self.items.is_empty() {
+            return Err(Error::MissingField {
+                item: "C header",
+                field: "a Record or Msg",
+            });
+        }
+
+        // Every item defines an array and its length, the table defines a type, an array and its
+        // length as well, all of which need to be distinct identifiers
+        let mut identifiers = HashSet::new();
+        let mut declare = |identifier: String, origin: &str| {
+            if identifiers.insert(identifier.clone()) {
+                Ok(())
+            } else {
+                Err(Error::InvalidArgument(format!(
+                    "The identifier {identifier} generated for {origin} has been used more than once in the C header"
+                )))
+            }
+        };
+        for (name, _) in &self.items {
+            declare(name.clone(), name)?;
+            declare(format!("{name}_len"), name)?;
+        }
+        if self.table {
+            let name = &self.name;
+            declare(name.clone(), "the table")?;
+            declare(format!("{name}_len"), "the table")?;
+            declare(format!("{name}_entry_t"), "the table")?;
+        }
+
+        let guard = format!("{}_H", self.name.to_uppercase());
+        let mut out = format!("#ifndef {guard}\n#define {guard}\n\n");
+        out += "#include <stddef.h>\n";
+        out += "#include <stdint.h>\n";
+
+        let mut lengths = Vec::with_capacity(self.items.len());
+        for (name, item) in &self.items {
+            let (target, data, json) = match item {
+                CHeaderItem::Msg(msg) => (
+                    Target::Msg,
+                    msg.to_vec()?,
+                    serde_json::to_string_pretty(msg),
+                ),
+                CHeaderItem::Record(record) => (
+                    Target::Record,
+                    record.to_vec()?,
+                    serde_json::to_string_pretty(record),
+                ),
+            };
+            let encode_error = |e: &dyn std::fmt::Display| Error::Encode {
+                target,
+                reason: e.to_string(),
+            };
+            let json = json.map_err(|e| encode_error(&e))?;
+            render_item(&mut out, name, &data, &json).map_err(|e| encode_error(&e))?;
+
+            lengths.push(data.len());
+        }
+
+        if self.table {
+            let name = &self.name;
+            out += "\ntypedef struct {\n";
+            out += "  const char *name;\n";
+            out += "  const uint8_t *data;\n";
+            out += "  size_t len;\n";
+            out += &format!("}} {name}_entry_t;\n\n");
+            out += &format!("static const {name}_entry_t {name}[] = {{\n");
+            for ((item, _), len) in self.items.iter().zip(lengths) {
+                out += &format!("  {{ \"{item}\", {item}, {len} }},\n");
+            }
+            out += "};\n";
+            out += &format!("static const size_t {name}_len = {};\n", self.items.len());
+        }
+
+        out += &format!("\n#endif /* {guard} */\n");
+
+        Ok(out)
+    }
+}
+
+/// Renders a single Record or Msg as a C array, its length and a doc comment containing its JSON
+/// representation
+fn render_item(out: &mut String, name: &str, data: &[u8], json: &str) -> std::fmt::Result {
+    writeln!(out)?;
+    writeln!(out, "/**")?;
+    writeln!(out, " * {name}")?;
+    writeln!(out, " *")?;
+    for line in json.lines() {
+        writeln!(out, " * {}", line.replace("*/", "*\\/"))?;
+    }
+    writeln!(out, " */")?;
+    writeln!(out, "static const uint8_t {name}[] = {{")?;
+    write_array_lines(out, "  ", data)?;
+    writeln!(out, "}};")?;
+    writeln!(out, "static const size_t {name}_len = {};", data.len())
+}
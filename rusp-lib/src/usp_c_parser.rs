@@ -0,0 +1,423 @@
+use crate::error::{Error, Result};
+use crate::usp::Msg;
+use crate::usp_decoder::{try_decode_msg, try_decode_record};
+use crate::usp_record::Record;
+
+/// A byte array initialiser or string literal found in C source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CLiteral {
+    /// The name of the variable the literal is assigned to, if any
+    pub name: Option<String>,
+    /// The bytes contained in the literal
+    pub data: Vec<u8>,
+}
+
+/// Creates an [`Error::Parse`] describing a failure to parse C source code
+fn parse_error(reason: impl Into<String>) -> Error {
+    Error::Parse {
+        reason: reason.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Number(u64),
+    Char(u8),
+    Str(Vec<u8>),
+    Punct(char),
+}
+
+/// Parses the escape sequence following a backslash in a string or character literal
+///
+/// Hexadecimal escapes are limited to two digits, since [`Msg::to_c_str`] and
+/// [`Record::to_c_str`] don't terminate them before hex digit characters
+fn parse_escape(chars: &mut std::iter::Peekable<std::str::CharIndices>) -> Result<u8> {
+    let (_, c) = chars
+        .next()
+        .ok_or_else(|| parse_error("Unterminated escape sequence"))?;
+    Ok(match c {
+        'n' => b'\n',
+        't' => b'\t',
+        'r' => b'\r',
+        'a' => 0x07,
+        'b' => 0x08,
+        'f' => 0x0c,
+        'v' => 0x0b,
+        '\\' | '\'' | '"' | '?' => c as u8,
+        'x' => {
+            let mut value = 0;
+            for i in 0..2 {
+                match chars.peek().and_then(|(_, c)| c.to_digit(16)) {
+                    Some(digit) => {
+                        value = value * 16 + digit;
+                        chars.next();
+                    }
+                    None if i == 0 => {
+                        return Err(parse_error("Invalid hexadecimal escape sequence"))
+                    }
+                    None => break,
+                }
+            }
+            // Can't overflow, at most two digits are consumed
+            value as u8
+        }
+        '0'..='7' => {
+            let mut value = c.to_digit(8).unwrap_or_default();
+            for _ in 0..2 {
+                match chars.peek().and_then(|(_, c)| c.to_digit(8)) {
+                    Some(digit) => {
+                        value = value * 8 + digit;
+                        chars.next();
+                    }
+                    None => break,
+                }
+            }
+            u8::try_from(value).map_err(|_| parse_error("Octal escape sequence out of range"))?
+        }
+        _ => return Err(parse_error(format!("Unsupported escape sequence \\{c}"))),
+    })
+}
+
+fn parse_number(literal: &str) -> Result<u64> {
+    let digits = literal.trim_end_matches(['u', 'U', 'l', 'L']);
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        u64::from_str_radix(hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        u64::from_str_radix(&digits[1..], 8)
+    } else {
+        digits.parse()
+    };
+    value.map_err(|_| parse_error(format!("Invalid number {literal}")))
+}
+
+/// Splits C source code into tokens, skipping comments and preprocessor directives
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+    let mut line_start = true;
+
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '\n' => {
+                line_start = true;
+                continue;
+            }
+            _ if c.is_whitespace() => continue,
+            '#' if line_start => {
+                // Skip preprocessor directives, including continuation lines
+                let mut prev = c;
+                while let Some((_, c)) = chars.next_if(|&(_, c)| c != '\n' || prev == '\\') {
+                    prev = c;
+                }
+            }
+            '/' if chars.next_if(|&(_, c)| c == '/').is_some() => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '/' if chars.next_if(|&(_, c)| c == '*').is_some() => {
+                let mut prev = ' ';
+                loop {
+                    let (_, c) = chars
+                        .next()
+                        .ok_or_else(|| parse_error("Unterminated comment"))?;
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' => {
+                let mut data = vec![];
+                loop {
+                    match chars
+                        .next()
+                        .ok_or_else(|| parse_error("Unterminated string literal"))?
+                    {
+                        (_, '"') => break,
+                        (_, '\\') => data.push(parse_escape(&mut chars)?),
+                        (_, c) => {
+                            let mut buf = [0; 4];
+                            data.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                        }
+                    }
+                }
+                tokens.push(Token::Str(data));
+            }
+            '\'' => {
+                let value = match chars
+                    .next()
+                    .ok_or_else(|| parse_error("Unterminated character literal"))?
+                {
+                    (_, '\\') => parse_escape(&mut chars)?,
+                    (_, c) => u8::try_from(c)
+                        .map_err(|_| parse_error(format!("Unsupported character literal '{c}'")))?,
+                };
+                if chars.next_if(|&(_, c)| c == '\'').is_none() {
+                    return Err(parse_error("Unterminated character literal"));
+                }
+                tokens.push(Token::Char(value));
+            }
+            _ if c.is_ascii_digit() => {
+                let mut end = pos + 1;
+                while let Some((i, _)) = chars.next_if(|&(_, c)| c.is_ascii_alphanumeric()) {
+                    end = i + 1;
+                }
+                tokens.push(Token::Number(parse_number(&source[pos..end])?));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut end = pos + c.len_utf8();
+                while let Some((i, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_') {
+                    end = i + c.len_utf8();
+                }
+                tokens.push(Token::Ident(source[pos..end].into()));
+            }
+            _ => tokens.push(Token::Punct(c)),
+        }
+        line_start = false;
+    }
+
+    Ok(tokens)
+}
+
+/// Returns the bytes of the brace enclosed initialiser starting at `tokens[0]` and the number of
+/// tokens it spans, the bytes are [`None`] if the initialiser is not a byte array
+fn parse_initialiser(tokens: &[Token]) -> Result<(Option<Vec<u8>>, usize)> {
+    let mut data = Some(vec![]);
+    let mut depth = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Punct('{') => {
+                depth += 1;
+                if depth > 1 {
+                    data = None;
+                }
+            }
+            Token::Punct('}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((data, i + 1));
+                }
+            }
+            Token::Punct(',') => (),
+            Token::Number(n) => match (data.as_mut(), u8::try_from(*n)) {
+                (Some(data), Ok(n)) => data.push(n),
+                _ => data = None,
+            },
+            Token::Char(c) => {
+                if let Some(data) = data.as_mut() {
+                    data.push(*c);
+                }
+            }
+            _ => data = None,
+        }
+    }
+
+    Err(parse_error("Unterminated array initialiser"))
+}
+
+/// Extracts all byte array initialisers and string literals assigned to variables from C source
+/// code, e.g. as produced by [`Msg::to_c_array`] or [`Record::to_c_str`]
+///
+/// Comments, preprocessor directives and other statements, like `sizeof` or length assignments,
+/// are skipped, as are initialisers of anything other than byte arrays. Adjacent string literals
+/// are concatenated. If the source contains no assignments at all, the whole source is treated as
+/// a single unnamed literal, which allows parsing of bare initialisers and strings.
+///
+/// Note that hexadecimal escape sequences in strings are limited to two digits, like in Rust,
+/// since this is how [`Msg::to_c_str`] renders them.
+///
+/// # Arguments
+///
+/// * `source` - The C source code to extract the literals from
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_c_parser::parse_c_literals;
+/// let literals = parse_c_literals(r#"
+///     /* Test vectors */
+///     #include <stdint.h>
+///     const uint8_t first[] = { 0x0a, 0x03, /* ___ */ 49, '.', 0x33 };
+///     size_t first_len = sizeof(first);
+///     const char *second = "\x0a\x031" ".3";
+/// "#).unwrap();
+///
+/// assert_eq!(literals.len(), 2);
+/// assert_eq!(literals[0].name.as_deref(), Some("first"));
+/// assert_eq!(literals[0].data, b"\n\x031.3");
+/// assert_eq!(literals[1].name.as_deref(), Some("second"));
+/// assert_eq!(literals[1].data, b"\n\x031.3");
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// the source contains malformed literals or comments
+pub fn parse_c_literals(source: &str) -> Result<Vec<CLiteral>> {
+    let tokens = tokenize(source)?;
+    let mut literals = vec![];
+    let mut name = None;
+    let mut assignments = 0;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Ident(ident) => {
+                name = Some(ident.clone());
+                i += 1;
+            }
+            Token::Punct('=') => {
+                assignments += 1;
+                i += 1;
+                match tokens.get(i) {
+                    Some(Token::Punct('{')) => {
+                        let (data, len) = parse_initialiser(&tokens[i..])?;
+                        if let Some(data) = data {
+                            literals.push(CLiteral {
+                                name: name.clone(),
+                                data,
+                            });
+                        }
+                        i += len;
+                    }
+                    Some(Token::Str(_)) => {
+                        let mut data = vec![];
+                        while let Some(Token::Str(s)) = tokens.get(i) {
+                            data.extend_from_slice(s);
+                            i += 1;
+                        }
+                        literals.push(CLiteral {
+                            name: name.clone(),
+                            data,
+                        });
+                    }
+                    _ => (),
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    if assignments == 0 {
+        let data = if tokens.iter().any(|t| matches!(t, Token::Str(_))) {
+            tokens
+                .iter()
+                .filter_map(|t| match t {
+                    Token::Str(s) => Some(s.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        } else {
+            tokens
+                .iter()
+                .filter_map(|t| match t {
+                    Token::Number(n) => Some(u8::try_from(*n).map_err(|_| {
+                        parse_error(format!("Array element {n} exceeds the byte range"))
+                    })),
+                    Token::Char(c) => Some(Ok(*c)),
+                    _ => None,
+                })
+                .collect::<Result<_>>()?
+        };
+        if !tokens.is_empty() {
+            literals.push(CLiteral { name: None, data });
+        }
+    }
+
+    Ok(literals)
+}
+
+/// Extracts the bytes of the single byte array initialiser or string literal contained in C
+/// source code, see [`parse_c_literals`] for details
+///
+/// # Arguments
+///
+/// * `source` - The C source code to extract the bytes from
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_c_parser::parse_c_bytes;
+/// let bytes = parse_c_bytes("unsigned int pb_len = 2;\nconst char pb[] = {\n  0x0a, 0x00, /* __ */\n};\n").unwrap();
+/// assert_eq!(bytes, [0x0a, 0x00]);
+/// assert_eq!(parse_c_bytes("\"\\x0a\\x00\"").unwrap(), [0x0a, 0x00]);
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// the source contains malformed literals, or not exactly one literal
+pub fn parse_c_bytes(source: &str) -> Result<Vec<u8>> {
+    let mut literals = parse_c_literals(source)?;
+    match literals.len() {
+        0 => Err(parse_error("No byte array or string literal found")),
+        1 => Ok(literals.remove(0).data),
+        n => Err(parse_error(format!(
+            "Expected a single byte array or string literal, found {n}"
+        ))),
+    }
+}
+
+/// Decodes the Protobuf encoded USP Msg contained in a C byte array initialiser or string
+/// literal, e.g. as produced by [`Msg::to_c_array`]
+///
+/// # Arguments
+///
+/// * `source` - The C source code containing a single byte array or string literal
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_c_parser::try_decode_msg_c;
+/// let msg = try_decode_msg_c("unsigned int pb_len = 52;
+/// const char pb[] = {
+///   0x0a, 0x08, 0x0a, 0x04, 0x74, 0x65, 0x73, 0x74, /* ____test */
+///   0x10, 0x03, 0x12, 0x28, 0x0a, 0x26, 0x42, 0x24, /* ___(__B_ */
+///   0x0a, 0x05, 0x6e, 0x6f, 0x74, 0x69, 0x66, 0x10, /* __notif_ */
+///   0x01, 0x42, 0x19, 0x0a, 0x06, 0x30, 0x30, 0x34, /* _B___004 */
+///   0x34, 0x46, 0x46, 0x12, 0x03, 0x46, 0x6f, 0x6f, /* 4FF__Foo */
+///   0x1a, 0x05, 0x30, 0x31, 0x32, 0x33, 0x34, 0x22, /* __01234\" */
+///   0x03, 0x31, 0x2e, 0x33,                         /* _1.3 */
+/// };").unwrap();
+/// assert_eq!(msg.msg_id(), "test");
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// the source doesn't contain exactly one literal or its contents cannot be decoded as a USP Msg
+pub fn try_decode_msg_c(source: &str) -> Result<Msg> {
+    try_decode_msg(&parse_c_bytes(source)?)
+}
+
+/// Decodes the Protobuf encoded USP Record contained in a C byte array initialiser or string
+/// literal, e.g. as produced by [`Record::to_c_str`]
+///
+/// # Arguments
+///
+/// * `source` - The C source code containing a single byte array or string literal
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_c_parser::try_decode_record_c;
+/// let record = try_decode_record_c(
+///     "\"\\x0a\\x031.3\\x12\\x07doc\\x3a\\x3ato\\x1a\\x09doc\\x3a\\x3afromR\\x09\\x08\\x01\\x12\\x05topic\"\n",
+/// ).unwrap();
+/// assert_eq!(record.to_id, "doc::to");
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// the source doesn't contain exactly one literal or its contents cannot be decoded as a USP
+/// Record
+pub fn try_decode_record_c(source: &str) -> Result<Record> {
+    try_decode_record(&parse_c_bytes(source)?)
+}
@@ -0,0 +1,973 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::error::{Error, Result};
+use crate::usp_decoder::try_decode_record;
+use crate::usp_record::mod_Record::OneOfrecord_type;
+use crate::usp_record::Record;
+
+/// The message transfer protocols USP Records can be extracted from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// Binary WebSocket messages, possibly fragmented and masked
+    WebSocket,
+    /// MQTT PUBLISH packets of MQTT 3.1, 3.1.1 and 5.0, also if carried via WebSocket
+    Mqtt,
+    /// STOMP SEND and MESSAGE frames, also if carried via WebSocket
+    Stomp,
+}
+
+impl Transport {
+    /// Returns the lowercase name of the transport
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::WebSocket => "websocket",
+            Self::Mqtt => "mqtt",
+            Self::Stomp => "stomp",
+        }
+    }
+}
+
+/// A USP Record extracted from a network capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedRecord {
+    /// The capture time of the packet completing the Record, relative to the UNIX epoch
+    pub timestamp: Duration,
+    /// The address and port of the sender of the Record
+    pub source: SocketAddr,
+    /// The address and port of the receiver of the Record
+    pub destination: SocketAddr,
+    /// The message transfer protocol carrying the Record
+    pub transport: Transport,
+    /// The decoded Record
+    pub record: Record,
+}
+
+impl Serialize for CapturedRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("CapturedRecord", 5)?;
+        state.serialize_field("timestamp", &self.timestamp.as_secs_f64())?;
+        state.serialize_field("source", &self.source.to_string())?;
+        state.serialize_field("destination", &self.destination.to_string())?;
+        state.serialize_field("transport", self.transport.name())?;
+        state.serialize_field("record", &self.record)?;
+        state.end()
+    }
+}
+
+/// Extracts all USP Records from a network capture in pcap or pcapng format
+///
+/// The TCP streams contained in the capture are reassembled and searched for WebSocket
+/// connections, including masked and fragmented messages, MQTT PUBLISH packets and STOMP SEND and
+/// MESSAGE frames. Every carried message which can be decoded as USP Record via
+/// [`try_decode_record`] is returned along with the time of the packet completing it and the
+/// endpoints of the connection, sorted chronologically. Encrypted connections, e.g. via TLS, are
+/// not supported.
+///
+/// Streams are recognised by their opening handshake, i.e. the capture needs to contain the start
+/// of the connections of interest.
+///
+/// # Arguments
+///
+/// * `capture` - The contents of a pcap or pcapng file
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// the data is neither in pcap nor in pcapng format or is truncated
+pub fn extract_records(capture: &[u8]) -> Result<Vec<CapturedRecord>> {
+    let mut streams = Streams::default();
+    for packet in read_packets(capture).map_err(|reason| Error::Capture { reason })? {
+        if let Some(segment) = parse_link(packet.link_type, packet.data) {
+            streams.add(&segment, packet.timestamp);
+        }
+    }
+
+    let mut records = vec![];
+    for (index, stream) in streams.streams.iter().enumerate() {
+        let peer = streams.peer(index);
+        for (transport, timestamp, payload) in extract_payloads(stream, peer) {
+            if payload.is_empty() {
+                continue;
+            }
+            // Arbitrary data may well decode as Record, but not carry a Record type
+            match try_decode_record(&payload) {
+                Ok(record) if !matches!(record.record_type, OneOfrecord_type::None) => {
+                    records.push(CapturedRecord {
+                        timestamp,
+                        source: stream.source,
+                        destination: stream.destination,
+                        transport,
+                        record,
+                    });
+                }
+                _ => (),
+            }
+        }
+    }
+
+    records.sort_by_key(|record| record.timestamp);
+    Ok(records)
+}
+
+/// Checks whether the given data starts like a capture in pcap or pcapng format
+///
+/// # Arguments
+///
+/// * `data` - The data to check, e.g. the contents of a file
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_capture::is_capture;
+/// assert!(is_capture(&[0xd4, 0xc3, 0xb2, 0xa1, 0x02, 0x00, 0x04, 0x00]));
+/// assert!(!is_capture(&[0x0a, 0x03, 0x31, 0x2e, 0x33]));
+/// ```
+#[must_use]
+pub fn is_capture(data: &[u8]) -> bool {
+    data.get(..4)
+        .and_then(|magic| magic.try_into().ok())
+        .map(u32::from_le_bytes)
+        .is_some_and(|magic| {
+            magic == PCAPNG_SECTION_HEADER
+                || [PCAP_MAGIC_MICROS, PCAP_MAGIC_NANOS].contains(&magic)
+                || [PCAP_MAGIC_MICROS, PCAP_MAGIC_NANOS].contains(&magic.swap_bytes())
+        })
+}
+
+/// A captured packet, starting at the link layer
+struct Packet<'a> {
+    timestamp: Duration,
+    link_type: u32,
+    data: &'a [u8],
+}
+
+/// A cursor over binary data with a configurable byte order
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    const fn new(data: &'a [u8], big_endian: bool) -> Self {
+        Self {
+            data,
+            pos: 0,
+            big_endian,
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> CaptureResult<&'a [u8]> {
+        let Some(bytes) = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+        else {
+            return Err(format!(
+                "Unexpected end of capture at offset {}, {len} bytes needed",
+                self.pos
+            ));
+        };
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> CaptureResult<u16> {
+        let bytes = self.bytes(2)?.try_into().map_err(|e| format!("{e}"))?;
+        Ok(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&mut self) -> CaptureResult<u32> {
+        let bytes = self.bytes(4)?.try_into().map_err(|e| format!("{e}"))?;
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    const fn is_eof(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+/// The result of reading a capture, failures are described by a message including their context
+type CaptureResult<T> = std::result::Result<T, String>;
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b2_3c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+
+/// Reads all packets from a capture in pcap or pcapng format
+fn read_packets(capture: &[u8]) -> CaptureResult<Vec<Packet<'_>>> {
+    let Some(magic) = capture.get(..4) else {
+        return Err("The capture is too short to be in pcap or pcapng format".into());
+    };
+    let magic = u32::from_le_bytes(magic.try_into().map_err(|e| format!("{e}"))?);
+
+    if magic == PCAPNG_SECTION_HEADER {
+        read_pcapng(capture).map_err(|e| format!("Reading pcapng capture: {e}"))
+    } else if [PCAP_MAGIC_MICROS, PCAP_MAGIC_NANOS].contains(&magic)
+        || [PCAP_MAGIC_MICROS, PCAP_MAGIC_NANOS].contains(&magic.swap_bytes())
+    {
+        read_pcap(capture).map_err(|e| format!("Reading pcap capture: {e}"))
+    } else {
+        Err("The capture is neither in pcap nor in pcapng format".into())
+    }
+}
+
+fn read_pcap(capture: &[u8]) -> CaptureResult<Vec<Packet<'_>>> {
+    let magic = u32::from_le_bytes(capture[..4].try_into().map_err(|e| format!("{e}"))?);
+    let big_endian = ![PCAP_MAGIC_MICROS, PCAP_MAGIC_NANOS].contains(&magic);
+    let mut reader = Reader::new(capture, big_endian);
+
+    let nanos = reader.u32()? == PCAP_MAGIC_NANOS;
+    // Version, time zone, accuracy and snapshot length
+    reader.bytes(16)?;
+    let link_type = reader.u32()? & 0x0fff_ffff;
+
+    let mut packets = vec![];
+    while !reader.is_eof() {
+        let secs = reader.u32()?;
+        let fraction = reader.u32()?;
+        let captured_len = reader.u32()? as usize;
+        let _original_len = reader.u32()?;
+        let data = reader.bytes(captured_len)?;
+
+        let timestamp = if nanos {
+            Duration::new(u64::from(secs), 0) + Duration::from_nanos(u64::from(fraction))
+        } else {
+            Duration::new(u64::from(secs), 0) + Duration::from_micros(u64::from(fraction))
+        };
+        packets.push(Packet {
+            timestamp,
+            link_type,
+            data,
+        });
+    }
+
+    Ok(packets)
+}
+
+/// An interface of a pcapng capture
+struct Interface {
+    link_type: u32,
+    /// Timestamp units per second
+    resolution: u64,
+}
+
+impl Interface {
+    fn timestamp(&self, high: u32, low: u32) -> Duration {
+        let units = (u64::from(high) << 32) | u64::from(low);
+        let secs = units / self.resolution;
+        let fraction =
+            u128::from(units % self.resolution) * 1_000_000_000 / u128::from(self.resolution);
+        Duration::new(secs, u32::try_from(fraction).unwrap_or(0))
+    }
+}
+
+fn read_pcapng(capture: &[u8]) -> CaptureResult<Vec<Packet<'_>>> {
+    let mut packets = vec![];
+    let mut interfaces: Vec<Interface> = vec![];
+    let mut big_endian = false;
+    let mut last_timestamp = Duration::ZERO;
+    let mut pos = 0;
+
+    while pos < capture.len() {
+        let mut reader = Reader::new(&capture[pos..], big_endian);
+        let block_type = reader.u32()?;
+
+        if block_type == PCAPNG_SECTION_HEADER {
+            // The byte order of a section is only known after reading its magic
+            let magic = capture
+                .get(pos + 8..pos + 12)
+                .ok_or("Truncated section header block")?;
+            big_endian = match u32::from_le_bytes(magic.try_into().map_err(|e| format!("{e}"))?) {
+                PCAPNG_BYTE_ORDER_MAGIC => false,
+                magic if magic.swap_bytes() == PCAPNG_BYTE_ORDER_MAGIC => true,
+                _ => return Err("Invalid byte order magic in section header block".into()),
+            };
+            reader.big_endian = big_endian;
+            interfaces.clear();
+        }
+
+        let block_len = reader.u32()? as usize;
+        if block_len < 12 || !block_len.is_multiple_of(4) {
+            return Err(format!(
+                "Invalid length {block_len} of block at offset {pos}"
+            ));
+        }
+        let body = capture
+            .get(pos + 8..pos + block_len - 4)
+            .ok_or_else(|| format!("Truncated block at offset {pos}"))?;
+        let mut reader = Reader::new(body, big_endian);
+
+        match block_type {
+            // Interface description block
+            1 => {
+                let link_type = u32::from(reader.u16()?);
+                reader.bytes(6)?;
+                let mut resolution = 1_000_000;
+                while let (Ok(code), Ok(len)) = (reader.u16(), reader.u16()) {
+                    let value = reader.bytes(usize::from(len).next_multiple_of(4))?;
+                    match code {
+                        0 => break,
+                        // if_tsresol, either a power of 10 or of 2
+                        9 if len == 1 => {
+                            let exponent = u32::from(value[0] & 0x7f);
+                            resolution = if value[0] & 0x80 == 0 {
+                                10u64.checked_pow(exponent)
+                            } else {
+                                2u64.checked_pow(exponent)
+                            }
+                            .ok_or("Unsupported timestamp resolution")?;
+                        }
+                        _ => (),
+                    }
+                }
+                interfaces.push(Interface {
+                    link_type,
+                    resolution,
+                });
+            }
+            // Enhanced packet block and the obsolete packet block
+            2 | 6 => {
+                let interface = if block_type == 6 {
+                    reader.u32()?
+                } else {
+                    let interface = u32::from(reader.u16()?);
+                    reader.u16()?;
+                    interface
+                };
+                let interface = interfaces
+                    .get(interface as usize)
+                    .ok_or_else(|| format!("Packet of unknown interface {interface}"))?;
+                let high = reader.u32()?;
+                let low = reader.u32()?;
+                let captured_len = reader.u32()? as usize;
+                let _original_len = reader.u32()?;
+                let data = reader.bytes(captured_len)?;
+
+                last_timestamp = interface.timestamp(high, low);
+                packets.push(Packet {
+                    timestamp: last_timestamp,
+                    link_type: interface.link_type,
+                    data,
+                });
+            }
+            // Simple packet block, which lacks a timestamp
+            3 => {
+                let interface = interfaces.first().ok_or("Packet of unknown interface 0")?;
+                let original_len = reader.u32()? as usize;
+                let data = &body[4..];
+                packets.push(Packet {
+                    timestamp: last_timestamp,
+                    link_type: interface.link_type,
+                    data: &data[..original_len.min(data.len())],
+                });
+            }
+            _ => (),
+        }
+
+        pos += block_len;
+    }
+
+    Ok(packets)
+}
+
+/// A TCP segment
+struct Segment<'a> {
+    source: SocketAddr,
+    destination: SocketAddr,
+    seq: u32,
+    syn: bool,
+    payload: &'a [u8],
+}
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+
+/// Parses a packet starting at the link layer, returning the contained TCP segment, if any
+fn parse_link(link_type: u32, data: &[u8]) -> Option<Segment<'_>> {
+    let be16 = |data: &[u8], pos: usize| {
+        data.get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    };
+
+    match link_type {
+        // BSD loopback, the address family is in host byte order
+        0 => {
+            let family = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+            let family = if family > 0xffff {
+                family.swap_bytes()
+            } else {
+                family
+            };
+            match family {
+                2 => parse_ip(ETHERTYPE_IPV4, &data[4..]),
+                24 | 28 | 30 => parse_ip(ETHERTYPE_IPV6, &data[4..]),
+                _ => None,
+            }
+        }
+        // Ethernet, possibly with VLAN tags
+        1 => {
+            let mut pos = 12;
+            let mut ethertype = be16(data, pos)?;
+            while [0x8100, 0x88a8, 0x9100].contains(&ethertype) {
+                pos += 4;
+                ethertype = be16(data, pos)?;
+            }
+            parse_ip(ethertype, data.get(pos + 2..)?)
+        }
+        // Raw IP
+        12 | 14 | 101 => match data.first()? >> 4 {
+            4 => parse_ip(ETHERTYPE_IPV4, data),
+            6 => parse_ip(ETHERTYPE_IPV6, data),
+            _ => None,
+        },
+        // Linux cooked capture v1 and v2
+        113 => parse_ip(be16(data, 14)?, data.get(16..)?),
+        276 => parse_ip(be16(data, 0)?, data.get(20..)?),
+        228 => parse_ip(ETHERTYPE_IPV4, data),
+        229 => parse_ip(ETHERTYPE_IPV6, data),
+        _ => None,
+    }
+}
+
+/// Parses an IPv4 or IPv6 packet, returning the contained TCP segment, if any
+fn parse_ip(ethertype: u16, data: &[u8]) -> Option<Segment<'_>> {
+    const TCP: u8 = 6;
+
+    let (source, destination, payload) = match ethertype {
+        ETHERTYPE_IPV4 => {
+            let header_len = usize::from(data.first()? & 0x0f) * 4;
+            let total_len = usize::from(u16::from_be_bytes(data.get(2..4)?.try_into().ok()?));
+            // Only the first fragment contains the TCP header
+            let fragment_offset = u16::from_be_bytes(data.get(6..8)?.try_into().ok()?) & 0x1fff;
+            if data.get(9)? != &TCP || fragment_offset != 0 {
+                return None;
+            }
+            let source: [u8; 4] = data.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = data.get(16..20)?.try_into().ok()?;
+            // Ethernet frames may be padded, and with segmentation offloading the total length
+            // may be missing
+            let end = if total_len == 0 {
+                data.len()
+            } else {
+                total_len.min(data.len())
+            };
+            (
+                IpAddr::V4(Ipv4Addr::from(source)),
+                IpAddr::V4(Ipv4Addr::from(destination)),
+                data.get(header_len..end)?,
+            )
+        }
+        ETHERTYPE_IPV6 => {
+            let payload_len = usize::from(u16::from_be_bytes(data.get(4..6)?.try_into().ok()?));
+            let source: [u8; 16] = data.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = data.get(24..40)?.try_into().ok()?;
+            let mut next_header = *data.get(6)?;
+            let mut payload = data.get(40..(40 + payload_len).min(data.len()))?;
+
+            // Skip the extension headers
+            loop {
+                match next_header {
+                    TCP => break,
+                    0 | 43 | 60 => {
+                        let len = (usize::from(*payload.get(1)?) + 1) * 8;
+                        next_header = *payload.first()?;
+                        payload = payload.get(len..)?;
+                    }
+                    44 => {
+                        let fragment_offset =
+                            u16::from_be_bytes(payload.get(2..4)?.try_into().ok()?) >> 3;
+                        if fragment_offset != 0 {
+                            return None;
+                        }
+                        next_header = *payload.first()?;
+                        payload = payload.get(8..)?;
+                    }
+                    _ => return None,
+                }
+            }
+            (
+                IpAddr::V6(Ipv6Addr::from(source)),
+                IpAddr::V6(Ipv6Addr::from(destination)),
+                payload,
+            )
+        }
+        _ => return None,
+    };
+
+    let source_port = u16::from_be_bytes(payload.get(0..2)?.try_into().ok()?);
+    let destination_port = u16::from_be_bytes(payload.get(2..4)?.try_into().ok()?);
+    let seq = u32::from_be_bytes(payload.get(4..8)?.try_into().ok()?);
+    let header_len = usize::from(payload.get(12)? >> 4) * 4;
+    let flags = payload.get(13)?;
+
+    Some(Segment {
+        source: SocketAddr::new(source, source_port),
+        destination: SocketAddr::new(destination, destination_port),
+        seq,
+        syn: flags & 0x02 != 0,
+        payload: payload.get(header_len..)?,
+    })
+}
+
+/// The reassembled data of one direction of a TCP connection
+#[derive(Default)]
+struct StreamData {
+    data: Vec<u8>,
+    /// Offsets into the data along with the time of the packet containing the data from there on
+    timestamps: Vec<(usize, Duration)>,
+}
+
+impl StreamData {
+    fn push(&mut self, data: &[u8], timestamp: Duration) {
+        // Data received out of order only becomes available with the data preceding it
+        let timestamp = self
+            .timestamps
+            .last()
+            .map_or(timestamp, |(_, last)| timestamp.max(*last));
+        self.timestamps.push((self.data.len(), timestamp));
+        self.data.extend_from_slice(data);
+    }
+
+    /// Returns the time of the packet containing the byte at the given position
+    fn timestamp_at(&self, pos: usize) -> Duration {
+        let index = self
+            .timestamps
+            .partition_point(|(offset, _)| *offset <= pos);
+        self.timestamps
+            .get(index.saturating_sub(1))
+            .map_or(Duration::ZERO, |(_, timestamp)| *timestamp)
+    }
+}
+
+/// One direction of a TCP connection
+struct Stream {
+    source: SocketAddr,
+    destination: SocketAddr,
+    next_seq: Option<u32>,
+    /// Segments received ahead of the expected sequence number
+    pending: BTreeMap<u32, (Vec<u8>, Duration)>,
+    stream: StreamData,
+    /// Index of the stream in the opposite direction
+    peer: Option<usize>,
+}
+
+/// All TCP streams of a capture
+#[derive(Default)]
+struct Streams {
+    streams: Vec<Stream>,
+    /// Index of the current stream of every direction of a connection
+    current: HashMap<(SocketAddr, SocketAddr), usize>,
+}
+
+impl Streams {
+    fn peer(&self, index: usize) -> Option<&Stream> {
+        self.streams[index].peer.map(|peer| &self.streams[peer])
+    }
+
+    fn add(&mut self, segment: &Segment<'_>, timestamp: Duration) {
+        let key = (segment.source, segment.destination);
+        let index = match self.current.get(&key) {
+            // A SYN on a used connection starts a new one with the same endpoints
+            Some(&index)
+                if !(segment.syn
+                    && self.streams[index]
+                        .next_seq
+                        .is_some_and(|seq| seq != segment.seq.wrapping_add(1))) =>
+            {
+                index
+            }
+            _ => {
+                let index = self.streams.len();
+                let peer = self
+                    .current
+                    .get(&(segment.destination, segment.source))
+                    .copied()
+                    .filter(|&peer| self.streams[peer].peer.is_none());
+                if let Some(peer) = peer {
+                    self.streams[peer].peer = Some(index);
+                }
+                self.streams.push(Stream {
+                    source: segment.source,
+                    destination: segment.destination,
+                    next_seq: None,
+                    pending: BTreeMap::new(),
+                    stream: StreamData::default(),
+                    peer,
+                });
+                self.current.insert(key, index);
+                index
+            }
+        };
+
+        let stream = &mut self.streams[index];
+        let seq = if segment.syn {
+            segment.seq.wrapping_add(1)
+        } else {
+            segment.seq
+        };
+        // Without the handshake the stream starts with the first segment
+        let next_seq = *stream.next_seq.get_or_insert(seq);
+        if segment.payload.is_empty() {
+            return;
+        }
+
+        // The sequence numbers wrap around, so their distance is interpreted as signed number
+        #[allow(clippy::cast_possible_wrap)]
+        let distance = seq.wrapping_sub(next_seq) as i32;
+        if distance > 0 {
+            stream
+                .pending
+                .entry(seq)
+                .or_insert_with(|| (segment.payload.to_vec(), timestamp));
+        } else {
+            stream.append(seq, segment.payload, timestamp);
+        }
+    }
+}
+
+impl Stream {
+    /// Appends a segment starting at or before the next expected sequence number, skipping
+    /// retransmitted data, then appends all pending segments which fit
+    fn append(&mut self, seq: u32, payload: &[u8], timestamp: Duration) {
+        let Some(next_seq) = self.next_seq else {
+            return;
+        };
+        let known = next_seq.wrapping_sub(seq) as usize;
+        if let Some(new) = payload.get(known..).filter(|new| !new.is_empty()) {
+            self.stream.push(new, timestamp);
+            #[allow(clippy::cast_possible_truncation)]
+            let next_seq = next_seq.wrapping_add(new.len() as u32);
+            self.next_seq = Some(next_seq);
+        }
+
+        // The retransmission of a pending segment may have filled the gap in the meantime
+        let next_seq = self.next_seq.unwrap_or(next_seq);
+        let ready = self
+            .pending
+            .keys()
+            .copied()
+            .find(|&seq| next_seq.wrapping_sub(seq) < 0x8000_0000);
+        if let Some(seq) = ready {
+            if let Some((payload, timestamp)) = self.pending.remove(&seq) {
+                self.append(seq, &payload, timestamp);
+            }
+        }
+    }
+}
+
+/// Returns the position after the end of the HTTP header at the start of the data, if any
+fn http_header_end(data: &[u8]) -> Option<usize> {
+    data.windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+/// Returns the value of an HTTP header field, the name is matched case-insensitively
+fn http_header<'a>(header: &'a [u8], name: &str) -> Option<&'a str> {
+    std::str::from_utf8(header)
+        .ok()?
+        .lines()
+        .skip(1)
+        .find_map(|line| {
+            let (field, value) = line.split_once(':')?;
+            field
+                .trim()
+                .eq_ignore_ascii_case(name)
+                .then_some(value.trim())
+        })
+}
+
+/// Checks whether the stream starts with an MQTT CONNECT packet and returns the protocol level
+fn mqtt_connect_level(data: &[u8]) -> Option<u8> {
+    if data.first()? != &0x10 {
+        return None;
+    }
+    let (_, header_len) = mqtt_remaining_length(data.get(1..)?)?;
+    let body = data.get(1 + header_len..)?;
+    let name_len = usize::from(u16::from_be_bytes(body.get(..2)?.try_into().ok()?));
+    let name = body.get(2..2 + name_len)?;
+    (name == b"MQTT" || name == b"MQIsdp").then_some(*body.get(2 + name_len)?)
+}
+
+/// Checks whether the stream starts with a STOMP frame sent when establishing a connection
+fn is_stomp(data: &[u8]) -> bool {
+    let start = data
+        .iter()
+        .position(|b| !b"\r\n".contains(b))
+        .unwrap_or(data.len());
+    let command = data[start..]
+        .split(|b| *b == b'\n')
+        .next()
+        .unwrap_or_default();
+    let command = command.strip_suffix(b"\r").unwrap_or(command);
+    [&b"CONNECT"[..], b"STOMP", b"CONNECTED"].contains(&command)
+}
+
+/// Extracts the messages carried in a stream, detecting the message transfer protocol by the start
+/// of the stream and of the stream in the opposite direction
+fn extract_payloads(stream: &Stream, peer: Option<&Stream>) -> Vec<(Transport, Duration, Vec<u8>)> {
+    let data = &stream.stream.data;
+    let peer_data = peer.map_or(&[][..], |peer| &peer.stream.data[..]);
+
+    // WebSocket connections are opened by an HTTP upgrade request and response
+    if data.starts_with(b"GET ") || data.starts_with(b"HTTP/1.1 101") {
+        let Some(header_end) = http_header_end(data) else {
+            return vec![];
+        };
+        let header = &data[..header_end];
+        let is_upgrade = http_header(header, "upgrade")
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"));
+        if !is_upgrade {
+            return vec![];
+        }
+
+        // The subprotocol is negotiated by the server, the client offers a list of them
+        let protocol = if data.starts_with(b"GET ") {
+            http_header_end(peer_data)
+                .and_then(|end| http_header(&peer_data[..end], "sec-websocket-protocol"))
+        } else {
+            http_header(header, "sec-websocket-protocol")
+        }
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+        let messages = websocket_messages(&stream.stream, header_end);
+        if protocol.contains("mqtt") {
+            // The protocol level is sent by the client only
+            let level = if data.starts_with(b"GET ") {
+                messages.first().and_then(|(_, m)| mqtt_connect_level(m))
+            } else {
+                peer.and_then(|peer| {
+                    let start = http_header_end(peer_data)?;
+                    let messages = websocket_messages(&peer.stream, start);
+                    mqtt_connect_level(&messages.first()?.1)
+                })
+            };
+            return mqtt_payloads(&reframe(messages), level);
+        }
+        if protocol.contains("stomp") {
+            return stomp_payloads(&reframe(messages));
+        }
+        return messages
+            .into_iter()
+            .map(|(timestamp, payload)| (Transport::WebSocket, timestamp, payload))
+            .collect();
+    }
+
+    if let Some(level) = mqtt_connect_level(data) {
+        return mqtt_payloads(&stream.stream, Some(level));
+    }
+    if data.first() == Some(&0x20) {
+        if let Some(level) = mqtt_connect_level(peer_data) {
+            return mqtt_payloads(&stream.stream, Some(level));
+        }
+    }
+
+    if is_stomp(data) {
+        return stomp_payloads(&stream.stream);
+    }
+
+    vec![]
+}
+
+/// Joins messages into a stream, e.g. the MQTT packets carried in WebSocket messages
+fn reframe(messages: Vec<(Duration, Vec<u8>)>) -> StreamData {
+    let mut stream = StreamData::default();
+    for (timestamp, message) in messages {
+        stream.push(&message, timestamp);
+    }
+    stream
+}
+
+/// Extracts all complete text and binary messages from a stream of WebSocket frames, joining
+/// fragmented messages and unmasking the data sent by clients
+fn websocket_messages(stream: &StreamData, start: usize) -> Vec<(Duration, Vec<u8>)> {
+    let data = &stream.data;
+    let mut messages = vec![];
+    let mut message: Option<Vec<u8>> = None;
+    let mut pos = start;
+
+    while let Some(&[first, second]) = data.get(pos..pos + 2) {
+        let fin = first & 0x80 != 0;
+        let opcode = first & 0x0f;
+        let masked = second & 0x80 != 0;
+        let mut header_len = 2;
+        let len = match second & 0x7f {
+            126 => {
+                header_len += 2;
+                data.get(pos + 2..pos + 4)
+                    .map(|b| u64::from(u16::from_be_bytes([b[0], b[1]])))
+            }
+            127 => {
+                header_len += 8;
+                data.get(pos + 2..pos + 10)
+                    .and_then(|b| b.try_into().ok())
+                    .map(u64::from_be_bytes)
+            }
+            len => Some(u64::from(len)),
+        };
+        let mask = if masked {
+            header_len += 4;
+            data.get(pos + header_len - 4..pos + header_len)
+        } else {
+            Some(&[0, 0, 0, 0][..])
+        };
+        let (Some(len), Some(mask)) = (len.and_then(|len| usize::try_from(len).ok()), mask) else {
+            break;
+        };
+        let Some(payload) = data.get(pos + header_len..pos + header_len + len) else {
+            break;
+        };
+        let payload = payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m);
+        pos += header_len + len;
+
+        match opcode {
+            // Continuation frame
+            0 => {
+                if let Some(message) = message.as_mut() {
+                    message.extend(payload);
+                }
+            }
+            // Text and binary frames
+            1 | 2 => message = Some(payload.collect()),
+            // Control frames, which may be interleaved with fragments
+            _ => continue,
+        }
+
+        if fin {
+            if let Some(message) = message.take() {
+                messages.push((stream.timestamp_at(pos - 1), message));
+            }
+        }
+    }
+
+    messages
+}
+
+/// Decodes the MQTT remaining length, returning it and the number of bytes used to encode it
+fn mqtt_remaining_length(data: &[u8]) -> Option<(usize, usize)> {
+    let mut len = 0;
+    for (i, byte) in data.iter().take(4).enumerate() {
+        len |= usize::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((len, i + 1));
+        }
+    }
+    None
+}
+
+/// Extracts the payload of all PUBLISH packets from a stream of MQTT packets, starting with
+/// protocol level 5 the packets contain properties which are skipped
+fn mqtt_payloads(stream: &StreamData, level: Option<u8>) -> Vec<(Transport, Duration, Vec<u8>)> {
+    let data = &stream.data;
+    let has_properties = level.is_some_and(|level| level >= 5);
+    let mut payloads = vec![];
+    let mut pos = 0;
+
+    while let Some(&first) = data.get(pos) {
+        let Some((len, len_size)) = data.get(pos + 1..).and_then(mqtt_remaining_length) else {
+            break;
+        };
+        let start = pos + 1 + len_size;
+        let Some(packet) = data.get(start..start + len) else {
+            break;
+        };
+        pos = start + len;
+
+        if first >> 4 != 3 {
+            continue;
+        }
+        let qos = (first >> 1) & 0x03;
+        let payload = (|| {
+            let topic_len = usize::from(u16::from_be_bytes(packet.get(..2)?.try_into().ok()?));
+            let mut offset = 2 + topic_len + if qos > 0 { 2 } else { 0 };
+            if has_properties {
+                let (properties_len, properties_len_size) =
+                    mqtt_remaining_length(packet.get(offset..)?)?;
+                offset += properties_len_size + properties_len;
+            }
+            packet.get(offset..)
+        })();
+
+        if let Some(payload) = payload {
+            payloads.push((
+                Transport::Mqtt,
+                stream.timestamp_at(pos - 1),
+                payload.to_vec(),
+            ));
+        }
+    }
+
+    payloads
+}
+
+/// Extracts the body of all SEND and MESSAGE frames from a stream of STOMP frames
+fn stomp_payloads(stream: &StreamData) -> Vec<(Transport, Duration, Vec<u8>)> {
+    let data = &stream.data;
+    let mut payloads = vec![];
+    let mut pos = 0;
+
+    loop {
+        // Heart-beats are sent as empty lines between frames
+        while data.get(pos).is_some_and(|b| b"\r\n".contains(b)) {
+            pos += 1;
+        }
+        let rest = &data[pos..];
+        let lf = rest.windows(2).position(|w| w == b"\n\n").map(|p| p + 2);
+        let crlf = rest
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .map(|p| p + 4);
+        let Some(header_len) = lf.into_iter().chain(crlf).min() else {
+            break;
+        };
+        let Ok(header) = std::str::from_utf8(&data[pos..pos + header_len]) else {
+            break;
+        };
+        let mut lines = header.lines();
+        let command = lines.next().unwrap_or_default().trim_end().to_string();
+        let content_length = lines
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| *name == "content-length")
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok());
+
+        let body_start = pos + header_len;
+        let body_end = match content_length {
+            Some(len) if data.len() > body_start + len => body_start + len,
+            Some(_) => break,
+            None => match data[body_start..].iter().position(|b| *b == 0) {
+                Some(len) => body_start + len,
+                None => break,
+            },
+        };
+        pos = body_end + 1;
+
+        if command == "SEND" || command == "MESSAGE" {
+            payloads.push((
+                Transport::Stomp,
+                stream.timestamp_at(body_end),
+                data[body_start..body_end].to_vec(),
+            ));
+        }
+    }
+
+    payloads
+}
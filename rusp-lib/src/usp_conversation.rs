@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::error::Result;
+use crate::usp::mod_Header::MsgType;
+use crate::usp::Msg;
+use crate::usp_capture::CapturedRecord;
+use crate::usp_decoder::try_decode_msg;
+use crate::usp_record::mod_Record::OneOfrecord_type;
+use crate::usp_record::Record;
+
+/// A USP Record taken from a log, e.g. read from a file or extracted from a network capture
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedRecord {
+    /// The time the Record was sent or received, if known
+    pub timestamp: Option<Duration>,
+    /// The logged Record
+    pub record: Record,
+}
+
+impl LoggedRecord {
+    /// Creates a new [`LoggedRecord`] without a timestamp
+    #[must_use]
+    pub const fn new(record: Record) -> Self {
+        Self {
+            timestamp: None,
+            record,
+        }
+    }
+
+    /// Sets the time the Record was sent or received
+    #[must_use]
+    pub const fn with_timestamp(mut self, timestamp: Duration) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
+
+impl From<CapturedRecord> for LoggedRecord {
+    fn from(captured: CapturedRecord) -> Self {
+        Self::new(captured.record).with_timestamp(captured.timestamp)
+    }
+}
+
+/// A noteworthy observation about an [`Exchange`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The request was never answered
+    Unanswered,
+    /// The requester used a `msg_id` it had already used before
+    DuplicateMsgId,
+    /// The request was answered with an Error Msg
+    ErrorResponse { err_code: u32, err_msg: String },
+    /// A Notify requesting a response via `send_resp` was never acknowledged
+    UnackedNotify,
+    /// A response or Error Msg was received without an outstanding request
+    UnexpectedResponse,
+}
+
+impl Issue {
+    /// Returns the name of the kind of issue, as used in the JSON representation
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Unanswered => "unanswered",
+            Self::DuplicateMsgId => "duplicate_msg_id",
+            Self::ErrorResponse { .. } => "error_response",
+            Self::UnackedNotify => "unacked_notify",
+            Self::UnexpectedResponse => "unexpected_response",
+        }
+    }
+}
+
+impl Serialize for Issue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Issue", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        if let Self::ErrorResponse { err_code, err_msg } = self {
+            state.serialize_field("err_code", err_code)?;
+            state.serialize_field("err_msg", err_msg)?;
+        }
+        state.end()
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unanswered => write!(f, "unanswered"),
+            Self::DuplicateMsgId => write!(f, "duplicate msg_id"),
+            Self::ErrorResponse { err_code, err_msg } => write!(f, "error {err_code}: {err_msg}"),
+            Self::UnackedNotify => write!(f, "unacked Notify"),
+            Self::UnexpectedResponse => write!(f, "unexpected response"),
+        }
+    }
+}
+
+/// A request along with its response, or a response without a matching request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    /// The `msg_id` shared by the request and the response
+    pub msg_id: String,
+    /// The endpoint ID of the sender of the request
+    pub requester: String,
+    /// The type of the request, if it was logged
+    pub request: Option<MsgType>,
+    /// The time the request was logged at, if known
+    pub request_time: Option<Duration>,
+    /// The type of the response, if one was logged
+    pub response: Option<MsgType>,
+    /// The time the response was logged at, if known
+    pub response_time: Option<Duration>,
+    /// The issues found with this exchange
+    pub issues: Vec<Issue>,
+}
+
+impl Exchange {
+    /// Returns the time between the request and the response, if both were logged with a timestamp
+    #[must_use]
+    pub fn latency(&self) -> Option<Duration> {
+        self.response_time?.checked_sub(self.request_time?)
+    }
+
+    /// Returns the time of the first logged Msg of the exchange, if known
+    #[must_use]
+    pub fn time(&self) -> Option<Duration> {
+        self.request_time.or(self.response_time)
+    }
+
+    const fn is_pending(&self) -> bool {
+        self.request.is_some() && self.response.is_none()
+    }
+}
+
+impl Serialize for Exchange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Exchange", 8)?;
+        state.serialize_field("msg_id", &self.msg_id)?;
+        state.serialize_field("requester", &self.requester)?;
+        state.serialize_field("request", &self.request)?;
+        state.serialize_field(
+            "request_time",
+            &self.request_time.map(|time| time.as_secs_f64()),
+        )?;
+        state.serialize_field("response", &self.response)?;
+        state.serialize_field(
+            "response_time",
+            &self.response_time.map(|time| time.as_secs_f64()),
+        )?;
+        state.serialize_field("latency", &self.latency().map(|time| time.as_secs_f64()))?;
+        state.serialize_field("issues", &self.issues)?;
+        state.end()
+    }
+}
+
+/// The Msgs exchanged between two endpoints, either outside of or within a single session context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversation {
+    /// The endpoint IDs of both participants, in lexicographical order
+    pub endpoints: [String; 2],
+    /// The ID of the session context, if the Msgs were exchanged within one
+    pub session_id: Option<u64>,
+    /// The exchanges in the order of their first logged Msg
+    pub exchanges: Vec<Exchange>,
+    /// The number of Records whose payload could not be decoded, e.g. because it is encrypted or
+    /// segmented
+    pub undecodable: usize,
+}
+
+impl Conversation {
+    /// Returns the number of issues found in this conversation
+    #[must_use]
+    pub fn issue_count(&self) -> usize {
+        self.exchanges
+            .iter()
+            .map(|exchange| exchange.issues.len())
+            .sum()
+    }
+
+    /// Records a request, returning the index of its exchange
+    fn request(&mut self, msg: &Msg, from_id: &str, timestamp: Option<Duration>) -> usize {
+        let msg_id = msg.msg_id();
+        let mut issues = vec![];
+        if self
+            .exchanges
+            .iter()
+            .any(|exchange| exchange.msg_id == msg_id && exchange.requester == from_id)
+        {
+            issues.push(Issue::DuplicateMsgId);
+        }
+
+        self.exchanges.push(Exchange {
+            msg_id: msg_id.into(),
+            requester: from_id.into(),
+            request: Some(msg_type(msg)),
+            request_time: timestamp,
+            response: None,
+            response_time: None,
+            issues,
+        });
+        self.exchanges.len() - 1
+    }
+
+    /// Records a response or Error, pairing it with the latest pending request of the receiver
+    fn response(&mut self, msg: &Msg, to_id: &str, timestamp: Option<Duration>) {
+        let msg_id = msg.msg_id();
+        let pending = self.exchanges.iter_mut().rev().find(|exchange| {
+            exchange.is_pending() && exchange.msg_id == msg_id && exchange.requester == to_id
+        });
+
+        let exchange = match pending {
+            Some(exchange) => exchange,
+            None => {
+                self.exchanges.push(Exchange {
+                    msg_id: msg_id.into(),
+                    requester: to_id.into(),
+                    request: None,
+                    request_time: None,
+                    response: None,
+                    response_time: None,
+                    issues: vec![Issue::UnexpectedResponse],
+                });
+                self.exchanges
+                    .last_mut()
+                    .expect("an exchange was just added")
+            }
+        };
+
+        exchange.response = Some(msg_type(msg));
+        exchange.response_time = timestamp;
+        if let Some(error) = msg.get_error() {
+            exchange.issues.push(Issue::ErrorResponse {
+                err_code: error.err_code,
+                err_msg: error.err_msg,
+            });
+        }
+    }
+}
+
+impl Serialize for Conversation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Conversation", 4)?;
+        state.serialize_field("endpoints", &self.endpoints)?;
+        state.serialize_field("session_id", &self.session_id)?;
+        state.serialize_field("exchanges", &self.exchanges)?;
+        state.serialize_field("undecodable", &self.undecodable)?;
+        state.end()
+    }
+}
+
+/// Renders the conversation as a table with one row per exchange, the direction column denotes
+/// whether the request was sent by the first (`->`) or the second (`<-`) endpoint
+impl fmt::Display for Conversation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <-> {}", self.endpoints[0], self.endpoints[1])?;
+        if let Some(session_id) = self.session_id {
+            write!(f, " (session {session_id})")?;
+        }
+        writeln!(f)?;
+
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "-".into());
+        let mut rows = vec![[
+            "TIME".to_string(),
+            String::new(),
+            "MSG_ID".into(),
+            "REQUEST".into(),
+            "RESPONSE".into(),
+            "LATENCY".into(),
+            "ISSUES".into(),
+        ]];
+        for exchange in &self.exchanges {
+            let direction = if exchange.requester == self.endpoints[0] {
+                "->"
+            } else {
+                "<-"
+            };
+            let issues: Vec<_> = exchange.issues.iter().map(ToString::to_string).collect();
+            rows.push([
+                optional(
+                    exchange
+                        .time()
+                        .map(|time| format!("{:.3}", time.as_secs_f64())),
+                ),
+                direction.into(),
+                exchange.msg_id.clone(),
+                optional(exchange.request.map(|msg_type| format!("{msg_type:?}"))),
+                optional(exchange.response.map(|msg_type| format!("{msg_type:?}"))),
+                optional(
+                    exchange
+                        .latency()
+                        .map(|latency| format!("{:.1} ms", latency.as_secs_f64() * 1000.0)),
+                ),
+                issues.join(", "),
+            ]);
+        }
+
+        let mut widths = [0; 7];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        for row in &rows {
+            let line = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "  {}", line.trim_end())?;
+        }
+
+        if self.undecodable > 0 {
+            writeln!(
+                f,
+                "  {} Record(s) with undecodable payload",
+                self.undecodable
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn msg_type(msg: &Msg) -> MsgType {
+    msg.header
+        .as_ref()
+        .map_or(MsgType::ERROR, |header| header.msg_type)
+}
+
+/// Decodes the Msg carried by a Record, returning `None` for Records without payload and `Some`
+/// error for payloads which cannot be decoded
+fn record_msg(record: &Record) -> Option<Result<Msg>> {
+    let payload = match &record.record_type {
+        OneOfrecord_type::no_session_context(context) => context.payload.clone(),
+        OneOfrecord_type::session_context(context) => context.payload.concat(),
+        _ => return None,
+    };
+    Some(try_decode_msg(&payload))
+}
+
+/// Reconstructs the conversations contained in a log of USP Records
+///
+/// The Records are grouped by the pair of endpoints exchanging them and, for Records using a
+/// session context, by the session. Within every conversation, requests are paired with the
+/// responses and Errors carrying the same `msg_id` and sent back to the requester. The following
+/// [`Issue`]s are flagged:
+/// * requests without response, except for Notify messages not requesting one via `send_resp`
+/// * Notify messages requesting a response via `send_resp` which was never sent
+/// * requests reusing a `msg_id` previously used by the same requester
+/// * requests answered with an Error
+/// * responses without an outstanding request
+///
+/// Records carrying no Msg, e.g. MTP connect Records, are ignored. A payload segmented across
+/// several Records cannot be decoded and is only counted.
+///
+/// # Arguments
+///
+/// * `records` - The logged Records in chronological order
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+/// use rusp_lib::usp_conversation::{reconstruct_conversations, Issue, LoggedRecord};
+///
+/// let body = GetBuilder::new()
+///     .with_params(vec!["Device.".into()])
+///     .build()
+///     .unwrap();
+/// let msg = MsgBuilder::new()
+///     .with_msg_id("get".into())
+///     .with_body(body)
+///     .build()
+///     .unwrap();
+/// let record = RecordBuilder::new()
+///     .with_version("1.3".into())
+///     .with_to_id("proto::agent".into())
+///     .with_from_id("proto::controller".into())
+///     .with_no_session_context_payload(&msg)
+///     .build()
+///     .unwrap();
+///
+/// let conversations = reconstruct_conversations(&[LoggedRecord::new(record)]);
+/// assert_eq!(conversations.len(), 1);
+/// assert_eq!(conversations[0].endpoints, ["proto::agent", "proto::controller"]);
+/// assert_eq!(conversations[0].exchanges[0].issues, [Issue::Unanswered]);
+/// ```
+#[must_use]
+pub fn reconstruct_conversations(records: &[LoggedRecord]) -> Vec<Conversation> {
+    let mut conversations: Vec<Conversation> = vec![];
+    // Whether a Notify requested a response, by conversation and exchange index
+    let mut notifies = HashMap::new();
+
+    for LoggedRecord { timestamp, record } in records {
+        let session_id = match &record.record_type {
+            OneOfrecord_type::session_context(context) => Some(context.session_id),
+            _ => None,
+        };
+        let Some(msg) = record_msg(record) else {
+            continue;
+        };
+
+        let mut endpoints = [record.from_id.clone(), record.to_id.clone()];
+        endpoints.sort();
+        let index = conversations
+            .iter()
+            .position(|conversation| {
+                conversation.endpoints == endpoints && conversation.session_id == session_id
+            })
+            .unwrap_or_else(|| {
+                conversations.push(Conversation {
+                    endpoints,
+                    session_id,
+                    exchanges: vec![],
+                    undecodable: 0,
+                });
+                conversations.len() - 1
+            });
+        let conversation = &mut conversations[index];
+
+        match msg {
+            Ok(msg) if msg.is_request() => {
+                let exchange = conversation.request(&msg, &record.from_id, *timestamp);
+                if let Some(notify) = msg.get_notify_request() {
+                    notifies.insert((index, exchange), notify.send_resp);
+                }
+            }
+            Ok(msg) if msg.is_response() || msg.is_error() => {
+                conversation.response(&msg, &record.to_id, *timestamp);
+            }
+            _ => conversation.undecodable += 1,
+        }
+    }
+
+    for (index, conversation) in conversations.iter_mut().enumerate() {
+        for (exchange_index, exchange) in conversation.exchanges.iter_mut().enumerate() {
+            if exchange.is_pending() {
+                match notifies.get(&(index, exchange_index)) {
+                    Some(true) => exchange.issues.push(Issue::UnackedNotify),
+                    Some(false) => (),
+                    None => exchange.issues.push(Issue::Unanswered),
+                }
+            }
+        }
+    }
+
+    conversations
+}
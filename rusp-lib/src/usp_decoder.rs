@@ -1,10 +1,234 @@
-use anyhow::{anyhow, Context, Result};
-use quick_protobuf::message::MessageRead;
-use quick_protobuf::BytesReader;
+use quick_protobuf::message::{MessageRead, MessageWrite};
+use quick_protobuf::{BytesReader, Writer};
 
-use crate::usp::{self, Error, Msg, Notify};
+use crate::error::{Error, Result, Target};
+use crate::usp::{self, Msg, Notify};
 use crate::usp_record::mod_Record::OneOfrecord_type;
 use crate::usp_record::{NoSessionContextRecord, Record, SessionContextRecord};
+use crate::usp_schema::{
+    self, FieldKind, MessageDescriptor, WireError, WireReader, WireType, WireValue,
+};
+use crate::usp_unknown::Lenient;
+
+/// The cause of a [`DecodeError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    /// The data ended in the middle of a field
+    Truncated,
+    /// A varint was longer than 10 bytes
+    InvalidVarint,
+    /// A tag contained an invalid or unsupported wire type
+    InvalidWireType(u8),
+    /// A tag contained the reserved field number 0
+    InvalidFieldNumber,
+    /// A string field contained invalid UTF-8
+    InvalidUtf8,
+    /// A known field was encoded with the contained wire type instead of the expected one
+    UnexpectedWireType(u8),
+    /// An enumeration field contained a value not known to the schema
+    UnknownEnumValue(i32),
+}
+
+impl std::fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let wire_type_name =
+            |wt: u8| WireType::from_tag(u64::from(wt)).map_or("INVALID", WireType::name);
+        match self {
+            Self::Truncated => write!(f, "unexpected end of data"),
+            Self::InvalidVarint => write!(f, "invalid varint"),
+            Self::InvalidWireType(wt) => {
+                write!(f, "invalid wire type {wt} ({})", wire_type_name(*wt))
+            }
+            Self::InvalidFieldNumber => write!(f, "invalid field number 0"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+            Self::UnexpectedWireType(wt) => {
+                write!(f, "unexpected wire type {wt} ({})", wire_type_name(*wt))
+            }
+            Self::UnknownEnumValue(v) => write!(f, "unknown enum value {v}"),
+        }
+    }
+}
+
+impl From<WireError> for DecodeErrorKind {
+    fn from(e: WireError) -> Self {
+        match e {
+            WireError::Truncated => Self::Truncated,
+            WireError::InvalidVarint => Self::InvalidVarint,
+            #[allow(clippy::cast_possible_truncation)]
+            WireError::InvalidWireType(wt) => Self::InvalidWireType(wt as u8),
+            WireError::InvalidFieldNumber => Self::InvalidFieldNumber,
+        }
+    }
+}
+
+/// A detailed description of the location and cause of a failure to decode a Protobuf encoded
+/// USP Record or Msg
+///
+/// When decoding fails, the [`Error::Decode`] returned by [`try_decode_msg`] and
+/// [`try_decode_record`] contains a `DecodeError` which can also be retrieved via
+/// [`Error::decode_details`].
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::{try_decode_msg, DecodeError, DecodeErrorKind};
+/// // A Get message whose param_paths string is cut off after 3 bytes
+/// let err = try_decode_msg(&[
+///     0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10,
+///     0x01, 0x12, 0x09, 0x0a, 0x07, 0x0a, 0x05, 0x0a,
+///     0x08, 0x44, 0x65, 0x76,
+/// ]).unwrap_err();
+/// let details = err.decode_details().unwrap();
+/// assert_eq!(details.offset, 16);
+/// assert_eq!(details.path, "Msg.body.request.get.param_paths[0]");
+/// assert_eq!(details.expected.as_deref(), Some("LEN (string)"));
+/// assert_eq!(details.kind, DecodeErrorKind::Truncated);
+/// assert_eq!(
+///     err.to_string(),
+///     "while parsing protobuf as USP Message: unexpected end of data at offset 16 while decoding \
+///      `Msg.body.request.get.param_paths[0]`, expected LEN (string)"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// The offset of the offending data from the start of the decoded bytes
+    pub offset: usize,
+    /// The path of the field being decoded, e.g. `Msg.body.request.set.update_objs[1]`
+    pub path: String,
+    /// The wire type and type of the field according to the schema, if the field is known
+    pub expected: Option<String>,
+    /// What was found instead
+    pub kind: DecodeErrorKind,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at offset {} while decoding `{}`",
+            self.kind, self.offset, self.path
+        )?;
+        if let Some(expected) = &self.expected {
+            write!(f, ", expected {expected}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Walks the Protobuf encoded message in `bytes`, which starts at `base` in the decoded data, and
+/// returns the first problem found. In `strict` mode, unexpected wire types of known fields and
+/// unknown enumeration values are reported as well.
+fn diagnose(
+    bytes: &[u8],
+    base: usize,
+    descriptor: &MessageDescriptor,
+    path: &str,
+    strict: bool,
+) -> Result<(), DecodeError> {
+    let mut reader = WireReader::new(bytes);
+    let mut indices = std::collections::HashMap::<u64, usize>::new();
+
+    while !reader.is_eof() {
+        let (number, wire_type) = reader.read_tag().map_err(|e| DecodeError {
+            offset: base + reader.position(),
+            path: path.into(),
+            expected: None,
+            kind: e.into(),
+        })?;
+
+        let field = descriptor.field_by_number(number);
+        let index = indices.entry(number).or_default();
+        let field_path = match field {
+            Some(fd) if fd.repeated => format!("{path}.{}[{index}]", fd.name),
+            Some(fd) => format!("{path}.{}", fd.name),
+            None => format!("{path}.{number}"),
+        };
+        *index += 1;
+        let expected =
+            field.map(|fd| format!("{} ({})", fd.kind.wire_type().name(), fd.kind.type_name()));
+
+        let value_start = reader.position();
+        let value = reader.read_value(wire_type).map_err(|e| DecodeError {
+            offset: base + value_start,
+            path: field_path.clone(),
+            expected: expected.clone(),
+            kind: e.into(),
+        })?;
+
+        let Some(field) = field else {
+            continue;
+        };
+        let error = |offset: usize, kind| DecodeError {
+            offset: base + offset,
+            path: field_path.clone(),
+            expected: expected.clone(),
+            kind,
+        };
+
+        if field.kind.wire_type() != wire_type {
+            if strict {
+                #[allow(clippy::cast_possible_truncation)]
+                return Err(error(
+                    value_start,
+                    DecodeErrorKind::UnexpectedWireType(wire_type.as_u64() as u8),
+                ));
+            }
+            continue;
+        }
+
+        match (field.kind, value) {
+            (FieldKind::String, WireValue::LengthDelimited(data))
+                if std::str::from_utf8(data).is_err() =>
+            {
+                return Err(error(
+                    reader.position() - data.len(),
+                    DecodeErrorKind::InvalidUtf8,
+                ));
+            }
+            (FieldKind::Message(_) | FieldKind::Map, WireValue::LengthDelimited(data)) => {
+                let nested = field
+                    .kind
+                    .message()
+                    .unwrap_or(&usp_schema::STRING_MAP_ENTRY);
+                let data_start = base + reader.position() - data.len();
+                diagnose(data, data_start, nested, &field_path, strict)?;
+            }
+            (FieldKind::Enum(descriptor), WireValue::Varint(v)) if strict => {
+                // Enums are encoded as sign extended int32 values
+                #[allow(clippy::cast_possible_truncation)]
+                let v = v as i32;
+                if descriptor.name_of(v).is_none() {
+                    return Err(error(value_start, DecodeErrorKind::UnknownEnumValue(v)));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Turns an error returned by `quick_protobuf` into an [`Error::Decode`], including a more
+/// detailed [`DecodeError`] if possible
+fn decode_error(
+    err: &quick_protobuf::Error,
+    bytes: &[u8],
+    descriptor: &MessageDescriptor,
+    target: Target,
+    root: &str,
+) -> Error {
+    let details = diagnose(bytes, 0, descriptor, root, false).err();
+    let reason = details
+        .as_ref()
+        .map_or_else(|| err.to_string(), ToString::to_string);
+    Error::Decode {
+        target,
+        details,
+        reason,
+    }
+}
 
 /// Decodes a slice of bytes containing a Protobuf encoded USP Record into a Record structure for
 /// further processing
@@ -32,7 +256,8 @@ use crate::usp_record::{NoSessionContextRecord, Record, SessionContextRecord};
 /// ```
 pub fn try_decode_record(bytes: &[u8]) -> Result<Record> {
     let mut reader = BytesReader::from_bytes(bytes);
-    Record::from_reader(&mut reader, bytes).context("while parsing protobuf as USP Record")
+    Record::from_reader(&mut reader, bytes)
+        .map_err(|e| decode_error(&e, bytes, &usp_schema::RECORD, Target::Record, "Record"))
 }
 
 /// Decodes a slice of bytes containing a Protobuf encoded USP Msg into a Msg structure for further
@@ -61,7 +286,340 @@ pub fn try_decode_record(bytes: &[u8]) -> Result<Record> {
 /// ```
 pub fn try_decode_msg(bytes: &[u8]) -> Result<Msg> {
     let mut reader = BytesReader::from_bytes(bytes);
-    Msg::from_reader(&mut reader, bytes).context("while parsing protobuf as USP Message")
+    Msg::from_reader(&mut reader, bytes)
+        .map_err(|e| decode_error(&e, bytes, &usp_schema::MSG, Target::Msg, "Msg"))
+}
+
+/// A USP Record borrowing its strings and bytes from the decoded data, see [`try_decode_record_ref`]
+pub use crate::usp_record_ref::Record as RecordRef;
+
+/// A USP Msg borrowing its strings and bytes from the decoded data, see [`try_decode_msg_ref`]
+pub use crate::usp_ref::Msg as MsgRef;
+
+/// Decodes a slice of bytes containing a Protobuf encoded USP Record into a [`RecordRef`], which
+/// references the strings and bytes contained in `bytes` instead of copying them
+///
+/// This avoids most allocations of [`try_decode_record`], e.g. when only inspecting the header
+/// fields of a Record or when passing its payload on to [`try_decode_msg_ref`]. The Record can be
+/// converted into an owned [`Record`] via [`RecordRef::into_owned`].
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Record
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+/// use rusp_lib::usp_decoder::{try_decode_record, try_decode_record_ref};
+/// use rusp_lib::usp_record_ref::mod_Record::OneOfrecord_type;
+/// let bytes = [
+///     0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+///     0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09,
+///     0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f,
+///     0x6d, 0x3a, 0x02, 0x12, 0x00,
+/// ];
+/// let record = try_decode_record_ref(&bytes).unwrap();
+/// assert!(matches!(record.to_id, Cow::Borrowed("doc::to")));
+/// let OneOfrecord_type::no_session_context(ref no_session) = record.record_type else {
+///     panic!("Expected a NoSessionContext Record");
+/// };
+/// assert!(no_session.payload.is_empty());
+/// assert_eq!(record.into_owned(), try_decode_record(&bytes).unwrap());
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` in the same cases as [`try_decode_record`]
+pub fn try_decode_record_ref(bytes: &[u8]) -> Result<RecordRef<'_>> {
+    let mut reader = BytesReader::from_bytes(bytes);
+    RecordRef::from_reader(&mut reader, bytes)
+        .map_err(|e| decode_error(&e, bytes, &usp_schema::RECORD, Target::Record, "Record"))
+}
+
+/// Decodes a slice of bytes containing a Protobuf encoded USP Msg into a [`MsgRef`], which
+/// references the strings and bytes contained in `bytes` instead of copying them
+///
+/// This avoids most allocations of [`try_decode_msg`], which is especially useful for large
+/// responses like a GetSupportedDMResp covering a whole data model. The Msg can be converted into
+/// an owned [`Msg`] via [`MsgRef::into_owned`].
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Message
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::{try_decode_msg, try_decode_msg_ref};
+/// let bytes = [
+///     0x0a, 0x08, 0x0a, 0x04, 0x74, 0x65, 0x73, 0x74,
+///     0x10, 0x03, 0x12, 0x28, 0x0a, 0x26, 0x42, 0x24,
+///     0x0a, 0x05, 0x6e, 0x6f, 0x74, 0x69, 0x66, 0x10,
+///     0x01, 0x42, 0x19, 0x0a, 0x06, 0x30, 0x30, 0x34,
+///     0x34, 0x46, 0x46, 0x12, 0x03, 0x46, 0x6f, 0x6f,
+///     0x1a, 0x05, 0x30, 0x31, 0x32, 0x33, 0x34, 0x22,
+///     0x03, 0x31, 0x2e, 0x33,
+/// ];
+/// let msg = try_decode_msg_ref(&bytes).unwrap();
+/// assert_eq!(msg.msg_id(), "test");
+/// assert_eq!(msg.into_owned(), try_decode_msg(&bytes).unwrap());
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` in the same cases as [`try_decode_msg`]
+pub fn try_decode_msg_ref(bytes: &[u8]) -> Result<MsgRef<'_>> {
+    let mut reader = BytesReader::from_bytes(bytes);
+    MsgRef::from_reader(&mut reader, bytes)
+        .map_err(|e| decode_error(&e, bytes, &usp_schema::MSG, Target::Msg, "Msg"))
+}
+
+impl MsgRef<'_> {
+    /// Retrieves the message ID of the Msg
+    #[must_use]
+    pub fn msg_id(&self) -> &str {
+        self.header
+            .as_ref()
+            .map_or("", |header| header.msg_id.as_ref())
+    }
+
+    /// Converts the Msg into an owned [`Msg`], copying all borrowed strings and bytes
+    #[must_use]
+    pub fn into_owned(self) -> Msg {
+        self.into()
+    }
+}
+
+impl RecordRef<'_> {
+    /// Converts the Record into an owned [`Record`], copying all borrowed strings and bytes
+    #[must_use]
+    pub fn into_owned(self) -> Record {
+        self.into()
+    }
+}
+
+impl From<MsgRef<'_>> for Msg {
+    fn from(msg: MsgRef<'_>) -> Self {
+        to_owned_message(&msg)
+    }
+}
+
+impl From<RecordRef<'_>> for Record {
+    fn from(record: RecordRef<'_>) -> Self {
+        to_owned_message(&record)
+    }
+}
+
+/// Converts a message of the borrowed bindings into the corresponding owned one by encoding it and
+/// decoding the result, so the conversion does not depend on the layout of either type
+fn to_owned_message<T: for<'a> MessageRead<'a>>(msg: &impl MessageWrite) -> T {
+    let mut buf = Vec::with_capacity(msg.get_size());
+    msg.write_message(&mut Writer::new(&mut buf))
+        .expect("encoding into a Vec cannot fail");
+    T::from_reader(&mut BytesReader::from_bytes(&buf), &buf)
+        .expect("the encoding of a borrowed message is valid for the owned one")
+}
+
+/// Decodes a slice of bytes containing a Protobuf encoded USP Record into a Record structure,
+/// retaining all fields unknown to this library
+///
+/// Unlike [`try_decode_record`], which skips unknown fields, e.g. fields added in a newer version
+/// of the USP specification, this function returns the Record wrapped in a [`Lenient`], which
+/// keeps the unknown fields in a side table. Encoding the Record again via [`Lenient::to_vec`]
+/// re-emits them, which allows relaying Records without losing information.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Record
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::{try_decode_record, try_decode_record_lenient};
+/// // A WebSocketConnect Record containing the unknown field 20 in the Record
+/// // and the unknown field 1 in the WebSocketConnectRecord
+/// let raw = [
+///     0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+///     0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09,
+///     0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f,
+///     0x6d, 0x4a, 0x02, 0x08, 0x01, 0xa2, 0x01, 0x03,
+///     0x6e, 0x65, 0x77,
+/// ];
+/// let record = try_decode_record_lenient(&raw).unwrap();
+/// assert_eq!(record.unknown_fields().at("").next().unwrap().number, 20);
+/// assert_eq!(record.to_vec().unwrap(), raw);
+///
+/// let record = try_decode_record(&raw).unwrap();
+/// assert_ne!(record.to_vec().unwrap(), raw);
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` in the same cases as [`try_decode_record`]
+pub fn try_decode_record_lenient(bytes: &[u8]) -> Result<Lenient<Record>> {
+    let record = try_decode_record(bytes)?;
+    Ok(Lenient::decoded(record, bytes, &usp_schema::RECORD))
+}
+
+/// Decodes a slice of bytes containing a Protobuf encoded USP Msg into a Msg structure, retaining
+/// all fields unknown to this library
+///
+/// See [`try_decode_record_lenient`] for details.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Message
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::try_decode_msg_lenient;
+/// // A Get message with the unknown field 15 in the Get
+/// let raw = [
+///     0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10,
+///     0x01, 0x12, 0x0f, 0x0a, 0x0d, 0x0a, 0x0b, 0x0a,
+///     0x07, 0x44, 0x65, 0x76, 0x69, 0x63, 0x65, 0x2e,
+///     0x78, 0x2a,
+/// ];
+/// let msg = try_decode_msg_lenient(&raw).unwrap();
+/// assert_eq!(msg.msg_id(), "get");
+/// assert_eq!(msg.to_vec().unwrap(), raw);
+/// let json = serde_json::to_string(&msg).unwrap();
+/// assert!(json.contains(
+///     r#""unknown_fields":[{"path":"body.request.get","position":1,"number":15,"wire_type":"VARINT","value":42}]"#
+/// ));
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` in the same cases as [`try_decode_msg`]
+pub fn try_decode_msg_lenient(bytes: &[u8]) -> Result<Lenient<Msg>> {
+    let msg = try_decode_msg(bytes)?;
+    Ok(Lenient::decoded(msg, bytes, &usp_schema::MSG))
+}
+
+/// Checks a slice of bytes containing a Protobuf encoded USP Msg for encoding problems
+///
+/// Besides the problems which make [`try_decode_msg`] fail, this also reports problems which
+/// decoding silently tolerates, i.e. known fields encoded with an unexpected wire type (which are
+/// skipped) and unknown enumeration values (which are replaced by the default value).
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Message
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::{check_msg_encoding, try_decode_msg, DecodeErrorKind};
+/// // A message with msg_type 42
+/// let raw = [0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10, 0x2a];
+/// assert!(try_decode_msg(&raw).is_ok());
+/// let err = check_msg_encoding(&raw).unwrap_err();
+/// assert_eq!(err.path, "Msg.header.msg_type");
+/// assert_eq!(err.kind, DecodeErrorKind::UnknownEnumValue(42));
+/// ```
+///
+/// # Errors
+///
+/// This function will return the first [`DecodeError`] found
+pub fn check_msg_encoding(bytes: &[u8]) -> Result<(), DecodeError> {
+    diagnose(bytes, 0, &usp_schema::MSG, "Msg", true)
+}
+
+/// Checks a slice of bytes containing a Protobuf encoded USP Record for encoding problems
+///
+/// See [`check_msg_encoding`] for details, the payload of the Record is not checked.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Record
+///
+/// # Errors
+///
+/// This function will return the first [`DecodeError`] found
+pub fn check_record_encoding(bytes: &[u8]) -> Result<(), DecodeError> {
+    diagnose(bytes, 0, &usp_schema::RECORD, "Record", true)
+}
+
+/// Decodes a base64 encoded string, e.g. a USP Record copied from a log or a JSON API, into a
+/// vector of bytes
+///
+/// Both the standard and the URL-safe alphabet are accepted, with or without padding. Whitespace,
+/// like line breaks inserted by e.g. `base64` or MIME encoders, is ignored.
+///
+/// # Arguments
+///
+/// * `text` - The base64 encoded string
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::decode_base64;
+/// assert_eq!(decode_base64("CgMx\nLjM=").unwrap(), [0x0a, 0x03, 0x31, 0x2e, 0x33]);
+/// assert_eq!(decode_base64("-_8").unwrap(), [0xfb, 0xff]);
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// the string is not valid base64
+pub fn decode_base64(text: &str) -> Result<Vec<u8>> {
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+    use base64::{alphabet, Engine as _};
+
+    const CONFIG: GeneralPurposeConfig =
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    const STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, CONFIG);
+    const URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, CONFIG);
+
+    let text: String = text.split_ascii_whitespace().collect();
+    if text.contains(['-', '_']) {
+        URL_SAFE.decode(text)
+    } else {
+        STANDARD.decode(text)
+    }
+    .map_err(|e| Error::InvalidEncoding {
+        encoding: "base64",
+        reason: e.to_string(),
+    })
+}
+
+/// Decodes a hex encoded string into a vector of bytes
+///
+/// Whitespace and `:` separators between the bytes, as well as an optional `0x` prefix, are
+/// ignored, so hex dumps copied from e.g. network analyzers can be used directly.
+///
+/// # Arguments
+///
+/// * `text` - The hex encoded string
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_decoder::decode_hex;
+/// assert_eq!(decode_hex("0a03 312E33").unwrap(), [0x0a, 0x03, 0x31, 0x2e, 0x33]);
+/// assert_eq!(decode_hex("0x0a:03").unwrap(), [0x0a, 0x03]);
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// the string is not valid hex
+pub fn decode_hex(text: &str) -> Result<Vec<u8>> {
+    let text = text.trim();
+    let text: String = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text)
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && *c != ':')
+        .collect();
+    hex::decode(text).map_err(|e| Error::InvalidEncoding {
+        encoding: "hex",
+        reason: e.to_string(),
+    })
 }
 
 /// Implementation of some extension methods for `Msg`s
@@ -99,6 +657,56 @@ impl Msg {
         Ok(this)
     }
 
+    /// Tries to decode a base64 encoded string containing a Protobuf encoded USP Message, see
+    /// [`decode_base64`] for the accepted formats
+    ///
+    /// This function also performs additional checks required by the USP specification, see also
+    /// [`Msg::check_validity`]
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The base64 encoded string
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp::Msg;
+    /// let msg = Msg::from_base64("CggKBHRlc3QQAxIoCiZCJAoFbm90aWYQAUIZCgYwMDQ0RkYSA0ZvbxoFMDEyMzQiAzEuMw==").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the string is not valid base64 or doesn't contain a valid USP Message
+    pub fn from_base64(text: &str) -> Result<Self> {
+        Self::from_bytes(&decode_base64(text)?)
+    }
+
+    /// Tries to decode a hex encoded string containing a Protobuf encoded USP Message, see
+    /// [`decode_hex`] for the accepted formats
+    ///
+    /// This function also performs additional checks required by the USP specification, see also
+    /// [`Msg::check_validity`]
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The hex encoded string
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp::Msg;
+    /// let msg = Msg::from_hex("0a080a0474657374100312280a2642240a056e6f746966100142190a063030343446461203466f6f1a0530313233342203312e33").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the string is not valid hex or doesn't contain a valid USP Message
+    pub fn from_hex(text: &str) -> Result<Self> {
+        Self::from_bytes(&decode_hex(text)?)
+    }
+
     /// Retrieves the message ID from a Msg structure
     ///
     /// # Arguments
@@ -430,7 +1038,7 @@ impl Msg {
     /// assert!(msg.get_error().is_some());
     /// ```
     #[must_use]
-    pub fn get_error(&self) -> Option<Error> {
+    pub fn get_error(&self) -> Option<usp::Error> {
         if let Some(body) = self.body.as_ref() {
             if let usp::mod_Body::OneOfmsg_body::error(error) = &body.msg_body {
                 return Some(error.clone());
@@ -462,24 +1070,32 @@ impl Msg {
         use crate::usp::mod_Response::OneOfresp_type;
         use crate::usp::{Request, Response};
 
+        let invalid = |field, reason: &str| Error::Validation {
+            target: Target::Msg,
+            field,
+            reason: reason.into(),
+        };
+
         self.header
             .as_ref()
             .filter(|h| !h.msg_id.is_empty())
-            .ok_or_else(|| anyhow!("Empty message ID"))?;
+            .ok_or_else(|| invalid("header.msg_id", "Empty message ID"))?;
 
         let body = self
             .body
             .as_ref()
             .filter(|b| !matches!(b.msg_body, OneOfmsg_body::None))
-            .ok_or_else(|| anyhow!("Invalid message body"))?;
+            .ok_or_else(|| invalid("body", "Invalid message body"))?;
 
         match body.msg_body {
             OneOfmsg_body::request(Request {
                 req_type: OneOfreq_type::None,
-            }) => Err(anyhow!("Invalid Request message")),
+                ..
+            }) => Err(invalid("body.request", "Invalid Request message")),
             OneOfmsg_body::response(Response {
                 resp_type: OneOfresp_type::None,
-            }) => Err(anyhow!("Invalid Response message")),
+                ..
+            }) => Err(invalid("body.response", "Invalid Response message")),
             _ => Ok(()),
         }
     }
@@ -531,6 +1147,56 @@ impl Record {
         this.check_validity()?;
         Ok(this)
     }
+
+    /// Tries to decode a base64 encoded string containing a Protobuf encoded USP Record, see
+    /// [`decode_base64`] for the accepted formats
+    ///
+    /// This function also performs additional checks required by the USP specification, see also
+    /// [`Record::check_validity`]
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The base64 encoded string
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_record::Record;
+    /// let record = Record::from_base64("CgMxLjMSB2RvYzo6dG8aCWRvYzo6ZnJvbVIJCAESBXRvcGlj").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the string is not valid base64 or doesn't contain a valid USP Record
+    pub fn from_base64(text: &str) -> Result<Self> {
+        Self::from_bytes(&decode_base64(text)?)
+    }
+
+    /// Tries to decode a hex encoded string containing a Protobuf encoded USP Record, see
+    /// [`decode_hex`] for the accepted formats
+    ///
+    /// This function also performs additional checks required by the USP specification, see also
+    /// [`Record::check_validity`]
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The hex encoded string
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_record::Record;
+    /// let record = Record::from_hex("0a03312e331207646f633a3a746f1a09646f633a3a66726f6d520908011205746f706963").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the string is not valid hex or doesn't contain a valid USP Record
+    pub fn from_hex(text: &str) -> Result<Self> {
+        Self::from_bytes(&decode_hex(text)?)
+    }
     /// Checks the validity of this [`Record`] according to the USP specification
     ///
     /// Although the type itself guarantees its validity against the protobuf schema, the USP
@@ -550,24 +1216,31 @@ impl Record {
     /// assert!(no_session_empty_payload.check_validity().is_err());
     /// ```
     pub fn check_validity(&self) -> Result<()> {
+        let invalid = |field, reason: &str| Error::Validation {
+            target: Target::Record,
+            field,
+            reason: reason.into(),
+        };
+
         if self.version.is_empty() {
-            return Err(anyhow!("Invalid USP version"));
+            return Err(invalid("version", "Invalid USP version"));
         }
 
         if self.to_id.is_empty() {
-            return Err(anyhow!("Invalid to_id field in Record"));
+            return Err(invalid("to_id", "Invalid to_id field in Record"));
         }
         if self.from_id.is_empty() {
-            return Err(anyhow!("Invalid from_id field in Record"));
+            return Err(invalid("from_id", "Invalid from_id field in Record"));
         }
 
         match &self.record_type {
-            OneOfrecord_type::None => Err(anyhow!("Invalid Record type")),
-            OneOfrecord_type::no_session_context(NoSessionContextRecord { payload })
+            OneOfrecord_type::None => Err(invalid("record_type", "Invalid Record type")),
+            OneOfrecord_type::no_session_context(NoSessionContextRecord { payload, .. })
                 if payload.is_empty() =>
             {
-                Err(anyhow!(
-                    "NoSessionContext Record containing an empty payload"
+                Err(invalid(
+                    "no_session_context.payload",
+                    "NoSessionContext Record containing an empty payload",
                 ))
             }
             _ => Ok(()),
@@ -619,8 +1292,9 @@ mod tests {
             .expect("raw should be a valid Record according to the protobuf schema");
         assert!(record.check_validity().is_err());
 
-        let OneOfrecord_type::no_session_context(NoSessionContextRecord { payload: msg_raw }) =
-            record.record_type
+        let OneOfrecord_type::no_session_context(NoSessionContextRecord {
+            payload: msg_raw, ..
+        }) = record.record_type
         else {
             panic!("Record should have a NoSessionContext type");
         };
@@ -643,8 +1317,9 @@ mod tests {
             .expect("raw should be a valid Record according to the protobuf schema");
         assert!(record.check_validity().is_err());
 
-        let OneOfrecord_type::no_session_context(NoSessionContextRecord { payload: msg_raw }) =
-            record.record_type
+        let OneOfrecord_type::no_session_context(NoSessionContextRecord {
+            payload: msg_raw, ..
+        }) = record.record_type
         else {
             panic!("Record should have a NoSessionContext type");
         };
@@ -682,4 +1357,65 @@ mod tests {
         let flatten = record.payload_flatten().unwrap();
         assert_eq!(flatten, &payload);
     }
+
+    #[test]
+    fn decode_error_details() {
+        use crate::usp_builder::{MsgBuilder, SetBuilder, UpdateObjectBuilder};
+
+        let body = SetBuilder::new()
+            .with_update_objs(vec![
+                UpdateObjectBuilder::new("Device.A.".into()),
+                UpdateObjectBuilder::new("Device.Z.".into()),
+            ])
+            .build()
+            .unwrap();
+        let mut raw = MsgBuilder::new()
+            .with_msg_id("set".into())
+            .with_body(body)
+            .build()
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        // Turn the second obj_path into invalid UTF-8
+        let pos = raw.iter().position(|b| *b == b'Z').unwrap();
+        raw[pos] = 0xff;
+
+        let err = try_decode_msg(&raw).unwrap_err();
+        let details = err.decode_details().unwrap();
+        assert_eq!(details.path, "Msg.body.request.set.update_objs[1].obj_path");
+        assert_eq!(details.offset, pos - 7);
+        assert_eq!(details.kind, DecodeErrorKind::InvalidUtf8);
+
+        // Cut off the data in the middle of the second update object
+        let err = try_decode_msg(&raw[..pos]).unwrap_err();
+        let details = err.decode_details().unwrap();
+        assert_eq!(details.path, "Msg.body");
+        assert_eq!(details.expected.as_deref(), Some("LEN (usp.Body)"));
+        assert_eq!(details.kind, DecodeErrorKind::Truncated);
+
+        // Append an unterminated varint to the otherwise valid message
+        raw[pos] = b'Z';
+        raw.extend_from_slice(&[0x80; 11]);
+        let err = try_decode_msg(&raw).unwrap_err();
+        let details = err.decode_details().unwrap();
+        assert_eq!(details.path, "Msg");
+        assert_eq!(details.kind, DecodeErrorKind::InvalidVarint);
+        assert!(check_msg_encoding(&raw).is_err());
+    }
+
+    #[test]
+    fn strict_encoding_check() {
+        // Header with msg_id encoded as varint
+        let raw = [0x0a, 0x02, 0x08, 0x01];
+        assert!(try_decode_msg(&raw).is_ok());
+        let err = check_msg_encoding(&raw).unwrap_err();
+        assert_eq!(err.path, "Msg.header.msg_id");
+        assert_eq!(err.offset, 3);
+        assert_eq!(err.kind, DecodeErrorKind::UnexpectedWireType(0));
+        assert_eq!(
+            err.to_string(),
+            "unexpected wire type 0 (VARINT) at offset 3 while decoding `Msg.header.msg_id`, expected LEN (string)"
+        );
+    }
 }
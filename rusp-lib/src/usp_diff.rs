@@ -0,0 +1,382 @@
+use std::fmt;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{Map, Value};
+
+use crate::error::{Error, Result};
+use crate::usp::Msg;
+use crate::usp_decoder::try_decode_msg;
+use crate::usp_record::mod_Record::OneOfrecord_type::session_context;
+use crate::usp_record::Record;
+
+/// Fields identifying an element of a repeated message, e.g. a supported object by its path, in
+/// order of preference. Elements carrying the same identity are compared field by field instead of
+/// being reported as removed and added.
+const IDENTITY_FIELDS: &[&str] = &[
+    "requested_path",
+    "req_obj_path",
+    "supported_obj_path",
+    "obj_path",
+    "param_name",
+    "command_name",
+    "event_name",
+    "resolved_path",
+    "instantiated_path",
+    "affected_path",
+    "unaffected_path",
+    "param_path",
+    "param",
+    "path",
+    "registered_path",
+    "deregistered_path",
+];
+
+/// A single difference between two USP Records or Msgs
+///
+/// The path names the differing element, starting at the top level of the compared Record or Msg.
+/// Fields are separated by `.`, map keys containing characters other than letters, digits and `_`
+/// are quoted in brackets, and elements of repeated fields are denoted by their identifying field,
+/// e.g. `supported_objs[supported_obj_path=Device.WiFi.]`, or by their index, e.g.
+/// `param_paths[1]`.
+///
+/// Serialized, every difference is an object with the fields `kind` (`added`, `removed` or
+/// `changed`) and `path`, accompanied by `value` or `before` and `after` respectively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// An element only present in the new Record or Msg
+    Added { path: String, value: Value },
+    /// An element only present in the old Record or Msg
+    Removed { path: String, value: Value },
+    /// An element present in both, but with a different value
+    Changed {
+        path: String,
+        before: Value,
+        after: Value,
+    },
+}
+
+impl Difference {
+    /// Returns the path of the differing element
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added { path, .. } | Self::Removed { path, .. } | Self::Changed { path, .. } => {
+                path
+            }
+        }
+    }
+}
+
+impl Serialize for Difference {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Difference", 3)?;
+        match self {
+            Self::Added { path, value } => {
+                state.serialize_field("kind", "added")?;
+                state.serialize_field("path", path)?;
+                state.serialize_field("value", value)?;
+            }
+            Self::Removed { path, value } => {
+                state.serialize_field("kind", "removed")?;
+                state.serialize_field("path", path)?;
+                state.serialize_field("value", value)?;
+            }
+            Self::Changed {
+                path,
+                before,
+                after,
+            } => {
+                state.serialize_field("kind", "changed")?;
+                state.serialize_field("path", path)?;
+                state.serialize_field("before", before)?;
+                state.serialize_field("after", after)?;
+            }
+        }
+        state.end()
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { path, value } => write!(f, "+ {path}: {value}"),
+            Self::Removed { path, value } => write!(f, "- {path}: {value}"),
+            Self::Changed {
+                path,
+                before,
+                after,
+            } => write!(f, "~ {path}: {before} -> {after}"),
+        }
+    }
+}
+
+/// Options for the comparison of USP Records and Msgs
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    ignore_msg_id: bool,
+}
+
+impl DiffOptions {
+    /// Creates the default options, comparing all fields
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            ignore_msg_id: false,
+        }
+    }
+
+    /// Sets whether the `msg_id` of the compared Msgs should be ignored, e.g. when comparing the
+    /// responses to the same request sent twice
+    #[must_use]
+    pub const fn with_ignore_msg_id(mut self, ignore_msg_id: bool) -> Self {
+        self.ignore_msg_id = ignore_msg_id;
+        self
+    }
+
+    /// Prepares the JSON representation of a Msg for the comparison
+    fn prepare_msg(&self, msg: &mut Value) {
+        if self.ignore_msg_id {
+            if let Some(header) = msg.get_mut("Header").and_then(Value::as_object_mut) {
+                header.remove("msg_id");
+            }
+        }
+    }
+}
+
+/// Compares two USP Msgs structurally and returns their differences
+///
+/// Map fields like `result_params`, `unique_keys` or `input_args` as well as repeated fields are
+/// compared as sets, i.e. the order of their elements is irrelevant. Elements of repeated fields
+/// which are identified by a path or name, e.g. the results of a `GetResp` by their
+/// `requested_path`, are compared field by field.
+///
+/// # Arguments
+///
+/// * `old` - The USP Msg to compare against
+/// * `new` - The USP Msg to compare
+/// * `options` - The [`DiffOptions`] to use for the comparison
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_builder::{GetBuilder, MsgBuilder};
+/// use rusp_lib::usp_diff::{diff_msg, DiffOptions};
+///
+/// let get = |msg_id: &str, params: Vec<String>| {
+///     let body = GetBuilder::new().with_params(params).build().unwrap();
+///     MsgBuilder::new()
+///         .with_msg_id(msg_id.into())
+///         .with_body(body)
+///         .build()
+///         .unwrap()
+/// };
+///
+/// let old = get("1", vec!["Device.DeviceInfo.".into(), "Device.WiFi.".into()]);
+/// let new = get("2", vec!["Device.WiFi.".into(), "Device.IP.".into()]);
+///
+/// let diff = diff_msg(&old, &new, &DiffOptions::new().with_ignore_msg_id(true)).unwrap();
+/// let diff: Vec<_> = diff.iter().map(ToString::to_string).collect();
+/// assert_eq!(
+///     diff,
+///     [
+///         "- Body.Request.Get.param_paths[0]: \"Device.DeviceInfo.\"",
+///         "+ Body.Request.Get.param_paths[1]: \"Device.IP.\"",
+///     ]
+/// );
+/// ```
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// one of the Msgs cannot be serialized
+pub fn diff_msg(old: &Msg, new: &Msg, options: &DiffOptions) -> Result<Vec<Difference>> {
+    let mut old = to_value(old)?;
+    let mut new = to_value(new)?;
+    options.prepare_msg(&mut old);
+    options.prepare_msg(&mut new);
+
+    let mut differences = vec![];
+    diff_value(&mut differences, "", &old, &new);
+    Ok(differences)
+}
+
+/// Compares two USP Records structurally and returns their differences
+///
+/// Works like [`diff_msg`], the Msgs carried in the payload of the Records are compared
+/// structurally as well, provided they can be decoded.
+///
+/// # Arguments
+///
+/// * `old` - The USP Record to compare against
+/// * `new` - The USP Record to compare
+/// * `options` - The [`DiffOptions`] to use for the comparison
+///
+/// # Errors
+///
+/// This function will return `Err` containing a textual description of the encountered error if
+/// one of the Records cannot be serialized
+pub fn diff_record(old: &Record, new: &Record, options: &DiffOptions) -> Result<Vec<Difference>> {
+    let old = record_to_value(old, options)?;
+    let new = record_to_value(new, options)?;
+
+    let mut differences = vec![];
+    diff_value(&mut differences, "", &old, &new);
+    Ok(differences)
+}
+
+/// Turns a Record into its JSON representation, replacing the payload of a session context by the
+/// carried Msgs where possible
+fn record_to_value(record: &Record, options: &DiffOptions) -> Result<Value> {
+    let mut value = to_value(record)?;
+
+    if let Some(msg) = value.get_mut("payload") {
+        options.prepare_msg(msg);
+    }
+
+    if let session_context(context) = &record.record_type {
+        let payload = context
+            .payload
+            .iter()
+            .map(|payload| match try_decode_msg(payload) {
+                Ok(msg) => {
+                    let mut msg = to_value(&msg)?;
+                    options.prepare_msg(&mut msg);
+                    Ok(msg)
+                }
+                Err(_) => to_value(payload),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        value["session_context"]["payload"] = Value::Array(payload);
+    }
+
+    Ok(value)
+}
+
+/// Turns a USP type into its JSON representation
+fn to_value(value: &impl Serialize) -> Result<Value> {
+    serde_json::to_value(value).map_err(|e| Error::Json {
+        reason: e.to_string(),
+    })
+}
+
+/// Appends the path of an object member to the given path
+fn member_path(path: &str, key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        if path.is_empty() {
+            key.into()
+        } else {
+            format!("{path}.{key}")
+        }
+    } else {
+        format!("{path}[{}]", Value::from(key))
+    }
+}
+
+/// Returns the identifying field and its value of an element of a repeated field, if any
+fn identity(value: &Value) -> Option<(&'static str, &str)> {
+    let object = value.as_object()?;
+    IDENTITY_FIELDS
+        .iter()
+        .find_map(|field| Some((*field, object.get(*field)?.as_str()?)))
+}
+
+/// Returns whether an array consists of numbers only, e.g. the bytes of a certificate, and thus
+/// has to be compared in order
+fn is_numeric(array: &[Value]) -> bool {
+    array.iter().all(Value::is_number)
+}
+
+fn diff_value(differences: &mut Vec<Difference>, path: &str, old: &Value, new: &Value) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_object(differences, path, old, new),
+        (Value::Array(old), Value::Array(new)) if !(is_numeric(old) && is_numeric(new)) => {
+            diff_array(differences, path, old, new);
+        }
+        (old, new) if old != new => differences.push(Difference::Changed {
+            path: path.into(),
+            before: old.clone(),
+            after: new.clone(),
+        }),
+        _ => (),
+    }
+}
+
+fn diff_object(
+    differences: &mut Vec<Difference>,
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+) {
+    for (key, old_value) in old {
+        let path = member_path(path, key);
+        match new.get(key) {
+            Some(new_value) => diff_value(differences, &path, old_value, new_value),
+            None => differences.push(Difference::Removed {
+                path,
+                value: old_value.clone(),
+            }),
+        }
+    }
+
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            differences.push(Difference::Added {
+                path: member_path(path, key),
+                value: new_value.clone(),
+            });
+        }
+    }
+}
+
+fn diff_array(differences: &mut Vec<Difference>, path: &str, old: &[Value], new: &[Value]) {
+    // Elements present in both arrays are irrelevant regardless of their position
+    let mut unmatched_new: Vec<(usize, &Value)> = new.iter().enumerate().collect();
+    let mut unmatched_old = vec![];
+    for (index, value) in old.iter().enumerate() {
+        match unmatched_new.iter().position(|(_, new)| *new == value) {
+            Some(position) => {
+                unmatched_new.remove(position);
+            }
+            None => unmatched_old.push((index, value)),
+        }
+    }
+
+    // The remaining elements are compared by their identity, if they have one
+    for (index, old_value) in unmatched_old {
+        let paired = identity(old_value).and_then(|old_identity| {
+            unmatched_new
+                .iter()
+                .position(|(_, new)| identity(new) == Some(old_identity))
+                .map(|position| (old_identity, unmatched_new.remove(position).1))
+        });
+
+        match paired {
+            Some(((field, id), new_value)) => {
+                let path = format!("{path}[{field}={id}]");
+                diff_value(differences, &path, old_value, new_value);
+            }
+            None => differences.push(Difference::Removed {
+                path: element_path(path, index, old_value),
+                value: old_value.clone(),
+            }),
+        }
+    }
+
+    for (index, new_value) in unmatched_new {
+        differences.push(Difference::Added {
+            path: element_path(path, index, new_value),
+            value: new_value.clone(),
+        });
+    }
+}
+
+/// Returns the path of an element of a repeated field
+fn element_path(path: &str, index: usize, value: &Value) -> String {
+    identity(value).map_or_else(
+        || format!("{path}[{index}]"),
+        |(field, id)| format!("{path}[{field}={id}]"),
+    )
+}
@@ -1,9 +1,48 @@
-use quick_protobuf::{message::MessageWrite, Writer};
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::Write;
+
+use quick_protobuf::{message::MessageWrite, sizeofs::sizeof_len, Writer, WriterBackend};
 
 use crate::usp::Msg;
-use crate::usp_record::{Record, SessionContextRecord};
+use crate::usp_c_header::c_identifier;
+use crate::usp_record::mod_Record::OneOfrecord_type;
+use crate::usp_record::{NoSessionContextRecord, Record, SessionContextRecord};
+use crate::usp_record_ref;
+
+use crate::error::{Error, Result, Target};
+
+/// The programming languages Protobuf encoded data can be rendered into byte literals for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceLanguage {
+    /// A Python `bytes` literal
+    Python,
+    /// A Go `[]byte` slice
+    Go,
+    /// A Rust `&[u8]` constant initialised with an array expression
+    Rust,
+    /// A Rust `&[u8]` constant initialised with a `b"..."` byte string literal
+    RustByteString,
+    /// A JavaScript/TypeScript `Uint8Array`
+    JavaScript,
+}
+
+impl std::str::FromStr for SourceLanguage {
+    type Err = Error;
 
-use anyhow::{Context, Result};
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "python" | "py" => Ok(Self::Python),
+            "go" => Ok(Self::Go),
+            "rust" | "rs" => Ok(Self::Rust),
+            "rust-bytes" | "rs-bytes" => Ok(Self::RustByteString),
+            "javascript" | "js" | "typescript" | "ts" => Ok(Self::JavaScript),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unsupported language {s}, expected one of python, go, rust, rust-bytes or javascript"
+            ))),
+        }
+    }
+}
 
 impl SessionContextRecord {
     /// Creates a new [`SessionContextRecord`] with an unfragmented payload
@@ -57,14 +96,220 @@ impl Record {
     /// This function will return `Err` containing a textual description of the encountered error if
     /// the `Record` cannot be serialized into a Protobuf representation
     pub fn to_vec(&self) -> Result<Vec<u8>> {
-        let mut buf = Vec::new();
-        let mut writer = Writer::new(&mut buf);
-        self.write_message(&mut writer)
-            .context("Failed serializing USP Record to Protobuf")?;
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        self.write_to(&mut buf)?;
 
         Ok(buf)
     }
 
+    /// Returns the length of the Protobuf encoding of the `Record` in bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        self.get_size()
+    }
+
+    /// Encode the `Record` into a Protobuf byte stream written directly into `w`
+    ///
+    /// The encoding is written piecewise, so unbuffered writers like files or sockets should be
+    /// wrapped into a [`BufWriter`](std::io::BufWriter).
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure
+    /// * `w` - The writer to write the encoded `Record` into
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_record;
+    /// let bytes = &[
+    ///         0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+    ///         0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09,
+    ///         0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f,
+    ///         0x6d, 0x52, 0x09, 0x08, 0x01, 0x12, 0x05, 0x74,
+    ///         0x6f, 0x70, 0x69, 0x63,
+    ///     ];
+    /// let record = try_decode_record(bytes).unwrap();
+    /// let mut out = std::io::Cursor::new(Vec::new());
+    /// record.write_to(&mut out).unwrap();
+    /// assert_eq!(out.into_inner(), bytes);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Record` cannot be serialized into a Protobuf representation or writing to `w` fails
+    pub fn write_to<W: Write>(&self, w: &mut W) -> Result<()> {
+        let mut writer = Writer::new(w);
+        self.write_message(&mut writer)
+            .map_err(encode_error(Target::Record))
+    }
+
+    /// Encode the `Record` into a Protobuf byte stream stored in `buf`, replacing its previous
+    /// contents
+    ///
+    /// This allows reusing the allocation of `buf` when encoding many `Record`s.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure
+    /// * `buf` - The buffer to store the encoded `Record` in
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Record` cannot be serialized into a Protobuf representation
+    pub fn encode_into(&self, buf: &mut Vec<u8>) -> Result<()> {
+        buf.clear();
+        buf.reserve(self.encoded_len());
+        self.write_to(buf)
+    }
+
+    /// Encode the `Record` with `msg` as its payload into a Protobuf byte stream written directly
+    /// into `w`
+    ///
+    /// The `Record` must be a NoSessionContext or SessionContext Record without payload, e.g. built
+    /// via [`RecordBuilder::with_no_session_context_payload_bytes`] with an empty `Vec`. Unlike
+    /// [`RecordBuilder::with_no_session_context_payload`], which requires encoding the `msg` into a
+    /// temporary buffer which is then copied into the `Record`, the `msg` is encoded in place. This
+    /// allows sending many Msgs to the same endpoint without building a `Record` for each of them.
+    ///
+    /// [`RecordBuilder::with_no_session_context_payload_bytes`]: crate::usp_builder::RecordBuilder::with_no_session_context_payload_bytes
+    /// [`RecordBuilder::with_no_session_context_payload`]: crate::usp_builder::RecordBuilder::with_no_session_context_payload
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure without payload
+    /// * `msg` - The USP `Msg` to encode as payload of the `Record`
+    /// * `w` - The writer to write the encoded `Record` into
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    /// let msg = MsgBuilder::new()
+    ///     .with_msg_id("get".into())
+    ///     .with_body(GetBuilder::new().with_params(vec!["Device.".into()]).build().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let record = RecordBuilder::new()
+    ///     .with_to_id("proto::to".into())
+    ///     .with_from_id("proto::from".into())
+    ///     .with_no_session_context_payload_bytes(Vec::new())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut buf = Vec::new();
+    /// record.write_with_msg_to(&msg, &mut buf).unwrap();
+    ///
+    /// let expected = RecordBuilder::new()
+    ///     .with_to_id("proto::to".into())
+    ///     .with_from_id("proto::from".into())
+    ///     .with_no_session_context_payload(&msg)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(buf, expected.to_vec().unwrap());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the `Record` is not a NoSessionContext or SessionContext
+    /// Record without payload, or if it cannot be serialized into a Protobuf representation or
+    /// writing to `w` fails
+    pub fn write_with_msg_to<W: Write>(&self, msg: &Msg, w: &mut W) -> Result<()> {
+        let record = match &self.record_type {
+            OneOfrecord_type::no_session_context(record) if record.payload.is_empty() => {
+                WithPayload::NoSessionContext(record)
+            }
+            OneOfrecord_type::session_context(record) if record.payload.is_empty() => {
+                WithPayload::SessionContext(record)
+            }
+            _ => {
+                return Err(Error::Validation {
+                    target: Target::Record,
+                    field: "record_type",
+                    reason: "Only NoSessionContext and SessionContext Records without payload can carry a Msg".into(),
+                })
+            }
+        };
+
+        let payload = MsgPayload {
+            record,
+            msg,
+            msg_len: msg.encoded_len(),
+        };
+
+        let mut writer = Writer::new(w);
+        envelope(self)
+            .write_message(&mut writer)
+            .and_then(|()| writer.write_with_tag(record.tags().0, |w| w.write_message(&payload)))
+            .map_err(encode_error(Target::Record))
+    }
+
+    /// Render the `Record` into a base64 encoded string of its Protobuf encoding, using the standard
+    /// alphabet with padding
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_record;
+    /// let record =
+    ///     try_decode_record(&[
+    ///         0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+    ///         0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09,
+    ///         0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f,
+    ///         0x6d, 0x52, 0x09, 0x08, 0x01, 0x12, 0x05, 0x74,
+    ///         0x6f, 0x70, 0x69, 0x63,
+    ///     ]).unwrap();
+    /// assert_eq!(record.to_base64().unwrap(), "CgMxLjMSB2RvYzo6dG8aCWRvYzo6ZnJvbVIJCAESBXRvcGlj");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Record` cannot be serialized into Protobuf
+    pub fn to_base64(&self) -> Result<String> {
+        use base64::Engine as _;
+        Ok(base64::engine::general_purpose::STANDARD.encode(self.to_vec()?))
+    }
+
+    /// Render the `Record` into a lowercase hex encoded string of its Protobuf encoding
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_record;
+    /// let record =
+    ///     try_decode_record(&[
+    ///         0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+    ///         0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09,
+    ///         0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f,
+    ///         0x6d, 0x52, 0x09, 0x08, 0x01, 0x12, 0x05, 0x74,
+    ///         0x6f, 0x70, 0x69, 0x63,
+    ///     ]).unwrap();
+    /// assert_eq!(record.to_hex().unwrap(), "0a03312e331207646f633a3a746f1a09646f633a3a66726f6d520908011205746f706963");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Record` cannot be serialized into Protobuf
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(hex::encode(self.to_vec()?))
+    }
+
     /// Render the `Record` into a raw C string representation
     ///
     /// # Arguments
@@ -91,31 +336,7 @@ impl Record {
     /// This function will return `Err` containing a textual description of the encountered error if
     /// the `Record` cannot be serialized into a C string representation
     pub fn to_c_str(&self) -> Result<String> {
-        use std::fmt::Write as _;
-
-        const fn check_printable(c: u8) -> bool {
-            match c as char {
-                ' ' | '.' | '!' | '(' | ')' | '\'' | ',' | '*' | '[' | ']' | '=' | '<' | '>'
-                | '-' | '_' => true,
-                _ if c.is_ascii_alphanumeric() => true,
-                _ => false,
-            }
-        }
-
-        let data = self.to_vec()?;
-        let mut out = String::new();
-
-        write!(out, "\"")?;
-        for i in &data {
-            if check_printable(*i) {
-                write!(out, "{}", char::from(*i))?;
-            } else {
-                write!(out, "\\x{i:02x}")?;
-            }
-        }
-        writeln!(out, "\"")?;
-
-        Ok(out)
+        render_c_str(&self.to_vec()?).map_err(encode_error(Target::Record))
     }
 
     /// Render the `Record` into a raw C array representation
@@ -174,48 +395,44 @@ impl Record {
     /// This function will return `Err` containing a textual description of the encountered error if
     /// the `Record` cannot be serialized into a C array representation
     pub fn to_c_array_custom(&self, name: &str) -> Result<String> {
-        const CHUNK_LEN: usize = 8;
-        use std::fmt::Write as _;
-
-        const fn check_printable(c: u8) -> bool {
-            match c as char {
-                ' ' | '.' | '!' | '(' | ')' | '\'' | '"' | ',' | '*' | '[' | ']' | '=' | '<'
-                | '>' | '-' | '_' => true,
-                _ if c.is_ascii_alphanumeric() => true,
-                _ => false,
-            }
-        }
-
-        let data = self.to_vec()?;
-        let mut out = String::new();
-
-        writeln!(out, "unsigned int {name}_len = {};", data.len())?;
-        writeln!(out, "const char {name}[] = {{")?;
-        for chunk in data.chunks(CHUNK_LEN) {
-            write!(out, "  ")?;
-            for i in chunk {
-                write!(out, "0x{i:02x}, ")?;
-            }
-
-            for _ in chunk.len()..CHUNK_LEN {
-                write!(out, "      ")?;
-            }
-
-            write!(out, "/* ")?;
-            for i in chunk {
-                if check_printable(*i) {
-                    write!(out, "{}", char::from(*i))?;
-                } else {
-                    write!(out, "_")?;
-                }
-            }
-            write!(out, " */")?;
-
-            writeln!(out)?;
-        }
-        writeln!(out, "}};")?;
+        render_c_array(name, &self.to_vec()?).map_err(encode_error(Target::Record))
+    }
 
-        Ok(out)
+    /// Render the `Record` into a byte literal of the given programming language, preceded by a
+    /// comment containing the JSON representation of the `Record`
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure
+    /// * `language` - The programming language to render the byte literal for
+    /// * `name` - The name of the variable or constant defined by the rendered output, an
+    ///   underscore is appended if it is a reserved word of the language
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_record;
+    /// use rusp_lib::usp_encoder::SourceLanguage;
+    /// let record =
+    ///     try_decode_record(&[
+    ///         0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+    ///         0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09,
+    ///         0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f,
+    ///         0x6d, 0x52, 0x09, 0x08, 0x01, 0x12, 0x05, 0x74,
+    ///         0x6f, 0x70, 0x69, 0x63,
+    ///     ]).unwrap();
+    /// let go = record.to_source(SourceLanguage::Go, "record").unwrap();
+    /// assert!(go.starts_with("// {\n//   \"version\": \"1.3\",\n"));
+    /// assert!(go.ends_with("var record = []byte{\n\t0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64, /* __1.3__d */\n\t0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09, /* oc__to__ */\n\t0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f, /* doc__fro */\n\t0x6d, 0x52, 0x09, 0x08, 0x01, 0x12, 0x05, 0x74, /* mR_____t */\n\t0x6f, 0x70, 0x69, 0x63,                         /* opic */\n}\n"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Record` cannot be serialized into a byte literal
+    pub fn to_source(&self, language: SourceLanguage, name: &str) -> Result<String> {
+        let json = serde_json::to_string_pretty(self).map_err(encode_error(Target::Record))?;
+        render_source(language, name, &self.to_vec()?, &json).map_err(encode_error(Target::Record))
     }
 }
 
@@ -249,14 +466,139 @@ impl Msg {
     /// This function will return `Err` containing a textual description of the encountered error if
     /// the `Msg` cannot be serialized into a Protobuf representation
     pub fn to_vec(&self) -> Result<Vec<u8>> {
-        let mut buf = Vec::new();
-        let mut writer = Writer::new(&mut buf);
-        self.write_message(&mut writer)
-            .context("Failed serializing USP Msg to Protobuf")?;
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        self.write_to(&mut buf)?;
 
         Ok(buf)
     }
 
+    /// Returns the length of the Protobuf encoding of the `Msg` in bytes
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Msg` structure
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        self.get_size()
+    }
+
+    /// Encode the `Msg` into a Protobuf byte stream written directly into `w`
+    ///
+    /// See [`Record::write_to`] for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Msg` structure
+    /// * `w` - The writer to write the encoded `Msg` into
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_builder::{GetBuilder, MsgBuilder};
+    /// let msg = MsgBuilder::new()
+    ///     .with_msg_id("get".into())
+    ///     .with_body(GetBuilder::new().with_params(vec!["Device.".into()]).build().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// let mut out = std::io::BufWriter::new(Vec::new());
+    /// msg.write_to(&mut out).unwrap();
+    /// assert_eq!(out.into_inner().unwrap(), msg.to_vec().unwrap());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Msg` cannot be serialized into a Protobuf representation or writing to `w` fails
+    pub fn write_to<W: Write>(&self, w: &mut W) -> Result<()> {
+        let mut writer = Writer::new(w);
+        self.write_message(&mut writer)
+            .map_err(encode_error(Target::Msg))
+    }
+
+    /// Encode the `Msg` into a Protobuf byte stream stored in `buf`, replacing its previous
+    /// contents
+    ///
+    /// This allows reusing the allocation of `buf` when encoding many `Msg`s.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Msg` structure
+    /// * `buf` - The buffer to store the encoded `Msg` in
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Msg` cannot be serialized into a Protobuf representation
+    pub fn encode_into(&self, buf: &mut Vec<u8>) -> Result<()> {
+        buf.clear();
+        buf.reserve(self.encoded_len());
+        self.write_to(buf)
+    }
+
+    /// Render the `Msg` into a base64 encoded string of its Protobuf encoding, using the standard
+    /// alphabet with padding
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Msg` structure
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_msg;
+    /// let msg =
+    ///     try_decode_msg(&[
+    ///         0x0a, 0x08, 0x0a, 0x04, 0x74, 0x65, 0x73, 0x74,
+    ///         0x10, 0x03, 0x12, 0x28, 0x0a, 0x26, 0x42, 0x24,
+    ///         0x0a, 0x05, 0x6e, 0x6f, 0x74, 0x69, 0x66, 0x10,
+    ///         0x01, 0x42, 0x19, 0x0a, 0x06, 0x30, 0x30, 0x34,
+    ///         0x34, 0x46, 0x46, 0x12, 0x03, 0x46, 0x6f, 0x6f,
+    ///         0x1a, 0x05, 0x30, 0x31, 0x32, 0x33, 0x34, 0x22,
+    ///         0x03, 0x31, 0x2e, 0x33,
+    ///     ]).unwrap();
+    /// assert_eq!(msg.to_base64().unwrap(), "CggKBHRlc3QQAxIoCiZCJAoFbm90aWYQAUIZCgYwMDQ0RkYSA0ZvbxoFMDEyMzQiAzEuMw==");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Msg` cannot be serialized into Protobuf
+    pub fn to_base64(&self) -> Result<String> {
+        use base64::Engine as _;
+        Ok(base64::engine::general_purpose::STANDARD.encode(self.to_vec()?))
+    }
+
+    /// Render the `Msg` into a lowercase hex encoded string of its Protobuf encoding
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Msg` structure
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_msg;
+    /// let msg =
+    ///     try_decode_msg(&[
+    ///         0x0a, 0x08, 0x0a, 0x04, 0x74, 0x65, 0x73, 0x74,
+    ///         0x10, 0x03, 0x12, 0x28, 0x0a, 0x26, 0x42, 0x24,
+    ///         0x0a, 0x05, 0x6e, 0x6f, 0x74, 0x69, 0x66, 0x10,
+    ///         0x01, 0x42, 0x19, 0x0a, 0x06, 0x30, 0x30, 0x34,
+    ///         0x34, 0x46, 0x46, 0x12, 0x03, 0x46, 0x6f, 0x6f,
+    ///         0x1a, 0x05, 0x30, 0x31, 0x32, 0x33, 0x34, 0x22,
+    ///         0x03, 0x31, 0x2e, 0x33,
+    ///     ]).unwrap();
+    /// assert_eq!(msg.to_hex().unwrap(), "0a080a0474657374100312280a2642240a056e6f746966100142190a063030343446461203466f6f1a0530313233342203312e33");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Msg` cannot be serialized into Protobuf
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(hex::encode(self.to_vec()?))
+    }
+
     /// Render the `Msg` into a raw C string representation
     ///
     /// # Arguments
@@ -285,31 +627,7 @@ impl Msg {
     /// This function will return `Err` containing a textual description of the encountered error if
     /// the `Msg` cannot be serialized into a C string representation
     pub fn to_c_str(&self) -> Result<String> {
-        use std::fmt::Write as _;
-
-        const fn check_printable(c: u8) -> bool {
-            match c as char {
-                ' ' | '.' | '!' | '(' | ')' | '\'' | ',' | '*' | '[' | ']' | '=' | '<' | '>'
-                | '-' | '_' => true,
-                _ if c.is_ascii_alphanumeric() => true,
-                _ => false,
-            }
-        }
-
-        let data = self.to_vec()?;
-        let mut out = String::new();
-
-        write!(out, "\"")?;
-        for i in &data {
-            if check_printable(*i) {
-                write!(out, "{}", char::from(*i))?;
-            } else {
-                write!(out, "\\x{i:02x}")?;
-            }
-        }
-        writeln!(out, "\"")?;
-
-        Ok(out)
+        render_c_str(&self.to_vec()?).map_err(encode_error(Target::Msg))
     }
 
     /// Render the `Msg` into a raw C array representation
@@ -372,47 +690,377 @@ impl Msg {
     /// This function will return `Err` containing a textual description of the encountered error if
     /// the `Msg` cannot be serialized into a C array representation
     pub fn to_c_array_custom(&self, name: &str) -> Result<String> {
-        const CHUNK_LEN: usize = 8;
-        use std::fmt::Write as _;
-
-        const fn check_printable(c: u8) -> bool {
-            match c as char {
-                ' ' | '.' | '!' | '(' | ')' | '\'' | '"' | ',' | '*' | '[' | ']' | '=' | '<'
-                | '>' | '-' | '_' => true,
-                _ if c.is_ascii_alphanumeric() => true,
-                _ => false,
-            }
+        render_c_array(name, &self.to_vec()?).map_err(encode_error(Target::Msg))
+    }
+
+    /// Render the `Msg` into a byte literal of the given programming language, preceded by a
+    /// comment containing the JSON representation of the `Msg`
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Msg` structure
+    /// * `language` - The programming language to render the byte literal for
+    /// * `name` - The name of the variable or constant defined by the rendered output, an
+    ///   underscore is appended if it is a reserved word of the language
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_msg;
+    /// use rusp_lib::usp_encoder::SourceLanguage;
+    /// let bytes = &[
+    ///         0x0a, 0x08, 0x0a, 0x04, 0x74, 0x65, 0x73, 0x74,
+    ///         0x10, 0x03, 0x12, 0x28, 0x0a, 0x26, 0x42, 0x24,
+    ///         0x0a, 0x05, 0x6e, 0x6f, 0x74, 0x69, 0x66, 0x10,
+    ///         0x01, 0x42, 0x19, 0x0a, 0x06, 0x30, 0x30, 0x34,
+    ///         0x34, 0x46, 0x46, 0x12, 0x03, 0x46, 0x6f, 0x6f,
+    ///         0x1a, 0x05, 0x30, 0x31, 0x32, 0x33, 0x34, 0x22,
+    ///         0x03, 0x31, 0x2e, 0x33,
+    ///     ];
+    /// let msg = try_decode_msg(bytes).unwrap();
+    /// let python = msg.to_source(SourceLanguage::Python, "notify").unwrap();
+    /// assert!(python.starts_with("# {\n#   \"Header\": {\n#     \"msg_id\": \"test\",\n"));
+    /// assert!(python.ends_with("NOTIFY = (\n    b\"\\x0a\\x08\\x0a\\x04test\\x10\\x03\\x12(\\x0a&B$\"\n    b\"\\x0a\\x05notif\\x10\\x01B\\x19\\x0a\\x06004\"\n    b\"4FF\\x12\\x03Foo\\x1a\\x0501234\\x22\"\n    b\"\\x031.3\"\n)\n"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Msg` cannot be serialized into a byte literal
+    pub fn to_source(&self, language: SourceLanguage, name: &str) -> Result<String> {
+        let json = serde_json::to_string_pretty(self).map_err(encode_error(Target::Msg))?;
+        render_source(language, name, &self.to_vec()?, &json).map_err(encode_error(Target::Msg))
+    }
+}
+
+/// Appends the lines of an array initialiser containing `data` to the output, including a
+/// C style comment with the printable characters of each line
+pub(crate) fn write_array_lines(out: &mut String, indent: &str, data: &[u8]) -> std::fmt::Result {
+    const CHUNK_LEN: usize = 8;
+    use std::fmt::Write as _;
+
+    const fn check_printable(c: u8) -> bool {
+        match c as char {
+            ' ' | '.' | '!' | '(' | ')' | '\'' | '"' | ',' | '*' | '[' | ']' | '=' | '<' | '>'
+            | '-' | '_' => true,
+            _ if c.is_ascii_alphanumeric() => true,
+            _ => false,
         }
+    }
 
-        let data = self.to_vec()?;
-        let mut out = String::new();
+    for chunk in data.chunks(CHUNK_LEN) {
+        write!(out, "{indent}")?;
+        for i in chunk {
+            write!(out, "0x{i:02x}, ")?;
+        }
 
-        writeln!(out, "unsigned int {name}_len = {};", data.len())?;
-        writeln!(out, "const char {name}[] = {{")?;
-        for chunk in data.chunks(CHUNK_LEN) {
-            write!(out, "  ")?;
-            for i in chunk {
-                write!(out, "0x{i:02x}, ")?;
-            }
+        for _ in chunk.len()..CHUNK_LEN {
+            write!(out, "      ")?;
+        }
 
-            for _ in chunk.len()..CHUNK_LEN {
-                write!(out, "      ")?;
+        write!(out, "/* ")?;
+        for i in chunk {
+            if check_printable(*i) {
+                write!(out, "{}", char::from(*i))?;
+            } else {
+                write!(out, "_")?;
             }
+        }
+        write!(out, " */")?;
 
-            write!(out, "/* ")?;
-            for i in chunk {
-                if check_printable(*i) {
-                    write!(out, "{}", char::from(*i))?;
-                } else {
-                    write!(out, "_")?;
-                }
-            }
-            write!(out, " */")?;
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+/// Returns a function turning a failure to encode the `target` into an [`Error::Encode`]
+fn encode_error<E: Display>(target: Target) -> impl FnOnce(E) -> Error {
+    move |err| Error::Encode {
+        target,
+        reason: err.to_string(),
+    }
+}
+
+/// A NoSessionContext or SessionContext Record without payload, see [`Record::write_with_msg_to`]
+#[derive(Clone, Copy)]
+enum WithPayload<'a> {
+    NoSessionContext(&'a NoSessionContextRecord),
+    SessionContext(&'a SessionContextRecord),
+}
+
+impl WithPayload<'_> {
+    /// Returns the tags of the record type within the `Record` and of the payload within the
+    /// record type
+    const fn tags(self) -> (u32, u32) {
+        match self {
+            Self::NoSessionContext(_) => (58, 18),
+            Self::SessionContext(_) => (66, 58),
+        }
+    }
+}
+
+/// The record type of a `Record` carrying a `Msg` as payload, which is encoded in place
+struct MsgPayload<'a> {
+    record: WithPayload<'a>,
+    msg: &'a Msg,
+    msg_len: usize,
+}
+
+impl MessageWrite for MsgPayload<'_> {
+    fn get_size(&self) -> usize {
+        let record_len = match self.record {
+            WithPayload::NoSessionContext(record) => record.get_size(),
+            WithPayload::SessionContext(record) => record.get_size(),
+        };
+        record_len + 1 + sizeof_len(self.msg_len)
+    }
 
-            writeln!(out)?;
+    fn write_message<W: WriterBackend>(&self, w: &mut Writer<W>) -> quick_protobuf::Result<()> {
+        // The payload is the last field of both record types, so it can simply be appended
+        match self.record {
+            WithPayload::NoSessionContext(record) => record.write_message(w)?,
+            WithPayload::SessionContext(record) => record.write_message(w)?,
         }
-        writeln!(out, "}};")?;
+        w.write_tag(self.record.tags().1)?;
+        w.write_varint(self.msg_len as u64)?;
+        self.msg.write_message(w)
+    }
+}
 
-        Ok(out)
+/// Borrows all fields of `record` except for the record type, so they can be encoded by the
+/// generated bindings ahead of a record type encoded in place
+///
+/// The record type is the last field of a `Record`, so the encoding of the returned `Record` is
+/// the start of the encoding of `record`.
+fn envelope(record: &Record) -> usp_record_ref::Record<'_> {
+    usp_record_ref::Record {
+        version: Cow::Borrowed(&record.version),
+        to_id: Cow::Borrowed(&record.to_id),
+        from_id: Cow::Borrowed(&record.from_id),
+        originator_id: Cow::Borrowed(&record.originator_id),
+        destination_id: Cow::Borrowed(&record.destination_id),
+        payload_security: usp_record_ref::mod_Record::PayloadSecurity::from(
+            record.payload_security as i32,
+        ),
+        mac_signature: Cow::Borrowed(&record.mac_signature),
+        sender_cert: Cow::Borrowed(&record.sender_cert),
+        record_type: usp_record_ref::mod_Record::OneOfrecord_type::None,
     }
 }
+
+/// Renders `data` into a C string literal
+fn render_c_str(data: &[u8]) -> Result<String, std::fmt::Error> {
+    use std::fmt::Write as _;
+
+    const fn check_printable(c: u8) -> bool {
+        match c as char {
+            ' ' | '.' | '!' | '(' | ')' | '\'' | ',' | '*' | '[' | ']' | '=' | '<' | '>' | '-'
+            | '_' => true,
+            _ if c.is_ascii_alphanumeric() => true,
+            _ => false,
+        }
+    }
+
+    let mut out = String::new();
+
+    write!(out, "\"")?;
+    for i in data {
+        if check_printable(*i) {
+            write!(out, "{}", char::from(*i))?;
+        } else {
+            write!(out, "\\x{i:02x}")?;
+        }
+    }
+    writeln!(out, "\"")?;
+
+    Ok(out)
+}
+
+/// Renders `data` into a C array named `name`, accompanied by its length
+fn render_c_array(name: &str, data: &[u8]) -> Result<String, std::fmt::Error> {
+    use std::fmt::Write as _;
+
+    let mut out = String::new();
+
+    writeln!(out, "unsigned int {name}_len = {};", data.len())?;
+    writeln!(out, "const char {name}[] = {{")?;
+    write_array_lines(&mut out, "  ", data)?;
+    writeln!(out, "}};")?;
+
+    Ok(out)
+}
+
+/// Turns `name` into an identifier of the given language, appending an underscore to names
+/// which are reserved in that language
+fn source_identifier(language: SourceLanguage, name: &str) -> String {
+    // Python and Rust constants are rendered in upper case, which no reserved word is
+    const GO_RESERVED: &[&str] = &[
+        "_",
+        "break",
+        "case",
+        "chan",
+        "const",
+        "continue",
+        "default",
+        "defer",
+        "else",
+        "fallthrough",
+        "for",
+        "func",
+        "go",
+        "goto",
+        "if",
+        "import",
+        "interface",
+        "map",
+        "package",
+        "range",
+        "return",
+        "select",
+        "struct",
+        "switch",
+        "type",
+        "var",
+    ];
+    const RUST_RESERVED: &[&str] = &["_"];
+    const JAVASCRIPT_RESERVED: &[&str] = &[
+        "arguments",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "eval",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typeof",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ];
+
+    let name = c_identifier(name);
+    let reserved = match language {
+        SourceLanguage::Python => &[][..],
+        SourceLanguage::Go => GO_RESERVED,
+        SourceLanguage::Rust | SourceLanguage::RustByteString => RUST_RESERVED,
+        SourceLanguage::JavaScript => JAVASCRIPT_RESERVED,
+    };
+    if reserved.contains(&name.as_str()) {
+        name + "_"
+    } else {
+        name
+    }
+}
+
+/// Renders `data` into a byte literal of the given language, preceded by `json` as a comment
+fn render_source(
+    language: SourceLanguage,
+    name: &str,
+    data: &[u8],
+    json: &str,
+) -> Result<String, std::fmt::Error> {
+    use std::fmt::Write as _;
+
+    let comment = if language == SourceLanguage::Python {
+        "#"
+    } else {
+        "//"
+    };
+    let name = source_identifier(language, name);
+    let mut out = String::new();
+
+    for line in json.lines() {
+        writeln!(out, "{comment} {line}")?;
+    }
+
+    match language {
+        SourceLanguage::Python => {
+            writeln!(out, "{} = (", name.to_uppercase())?;
+            for chunk in data.chunks(16) {
+                write!(out, "    b\"")?;
+                for i in chunk {
+                    match i {
+                        b'"' | b'\\' => write!(out, "\\x{i:02x}")?,
+                        0x20..=0x7e => write!(out, "{}", char::from(*i))?,
+                        _ => write!(out, "\\x{i:02x}")?,
+                    }
+                }
+                writeln!(out, "\"")?;
+            }
+            writeln!(out, ")")?;
+        }
+        SourceLanguage::Go => {
+            writeln!(out, "var {name} = []byte{{")?;
+            write_array_lines(&mut out, "\t", data)?;
+            writeln!(out, "}}")?;
+        }
+        SourceLanguage::Rust => {
+            writeln!(out, "pub const {}: &[u8] = &[", name.to_uppercase())?;
+            write_array_lines(&mut out, "    ", data)?;
+            writeln!(out, "];")?;
+        }
+        SourceLanguage::RustByteString => {
+            writeln!(out, "pub const {}: &[u8] = b\"\\", name.to_uppercase())?;
+            for (line, chunk) in data.chunks(16).enumerate() {
+                if line > 0 {
+                    writeln!(out, "\\")?;
+                }
+                write!(out, "    ")?;
+                for (pos, i) in chunk.iter().enumerate() {
+                    match i {
+                        // Leading whitespace is skipped after a line continuation
+                        b' ' if pos == 0 => write!(out, "\\x20")?,
+                        b'"' | b'\\' => write!(out, "\\x{i:02x}")?,
+                        0x20..=0x7e => write!(out, "{}", char::from(*i))?,
+                        _ => write!(out, "\\x{i:02x}")?,
+                    }
+                }
+            }
+            writeln!(out, "\";")?;
+        }
+        SourceLanguage::JavaScript => {
+            writeln!(out, "const {name} = new Uint8Array([")?;
+            write_array_lines(&mut out, "  ", data)?;
+            writeln!(out, "]);")?;
+        }
+    }
+
+    Ok(out)
+}
@@ -1,3 +1,11 @@
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::usp::mod_Header::MsgType;
+use crate::usp::{Body, Msg};
+use crate::usp_record::mod_Record::OneOfrecord_type;
+use crate::usp_record::Record;
+
 /// Gets an USP error message from the error code, returning an empty str for unknown codes
 #[must_use]
 pub const fn get_err_msg(code: u32) -> &'static str {
@@ -46,3 +54,536 @@ pub const fn get_err_msg(code: u32) -> &'static str {
         _ => "",
     }
 }
+
+/// The category of a USP error code, determining where it may be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Errors reported in USP Msgs, codes 7000 to 7099
+    Message,
+    /// Errors of the Record layer, e.g. in a `DisconnectRecord`, codes 7100 to 7199
+    Record,
+    /// Vendor specific errors, codes 7800 to 7999
+    Vendor,
+}
+
+macro_rules! usp_error_codes {
+    ($($(#[$doc:meta])* $name:ident = $code:literal,)*) => {
+        /// A USP error code as defined by the USP specification
+        ///
+        /// Vendor specific codes are represented by [`UspErrorCode::Vendor`], codes within the
+        /// range of USP error codes not known to this version by [`UspErrorCode::Unknown`]. Both
+        /// can only be obtained via [`UspErrorCode::try_from`], which checks the range of the code.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use rusp_lib::usp_errors::{ErrorCategory, UspErrorCode};
+        ///
+        /// let code = UspErrorCode::try_from(7004).unwrap();
+        /// assert_eq!(code, UspErrorCode::InvalidArguments);
+        /// assert_eq!(code.to_string(), "7004 Invalid arguments");
+        /// assert!(matches!(UspErrorCode::try_from(7801).unwrap(), UspErrorCode::Vendor(_)));
+        /// assert_eq!(UspErrorCode::try_from(7801).unwrap().code(), 7801);
+        /// assert_eq!(UspErrorCode::SecureSessionRequired.category(), Some(ErrorCategory::Record));
+        /// assert!(UspErrorCode::try_from(404).is_err());
+        /// ```
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum UspErrorCode {
+            $($(#[$doc])* $name,)*
+            /// A vendor specific error code in the range of 7800 to 7999
+            Vendor(u16),
+            /// An error code within the range of USP error codes not defined by the specification
+            Unknown(u32),
+        }
+
+        impl UspErrorCode {
+            /// Returns the numeric error code
+            #[must_use]
+            pub const fn code(self) -> u32 {
+                match self {
+                    $(Self::$name => $code,)*
+                    Self::Vendor(code) => code as u32,
+                    Self::Unknown(code) => code,
+                }
+            }
+        }
+
+        impl TryFrom<u32> for UspErrorCode {
+            type Error = Error;
+
+            /// Converts a numeric code into a [`UspErrorCode`]
+            ///
+            /// # Errors
+            ///
+            /// This function will return `Err` if the code is outside of the range of USP error
+            /// codes from 7000 to 7999
+            fn try_from(code: u32) -> Result<Self> {
+                match code {
+                    $($code => Ok(Self::$name),)*
+                    7800..=7999 => Ok(Self::Vendor(code as u16)),
+                    7000..=7799 => Ok(Self::Unknown(code)),
+                    _ => Err(Error::InvalidErrCode(code)),
+                }
+            }
+        }
+    };
+}
+
+usp_error_codes! {
+    /// 7000: Message failed
+    MessageFailed = 7000,
+    /// 7001: Message not supported
+    MessageNotSupported = 7001,
+    /// 7002: Request denied (no reason specified)
+    RequestDenied = 7002,
+    /// 7003: Internal error
+    InternalError = 7003,
+    /// 7004: Invalid arguments
+    InvalidArguments = 7004,
+    /// 7005: Resources exceeded
+    ResourcesExceeded = 7005,
+    /// 7006: Permission denied
+    PermissionDenied = 7006,
+    /// 7007: Invalid configuration
+    InvalidConfiguration = 7007,
+    /// 7008: Invalid path syntax
+    InvalidPathSyntax = 7008,
+    /// 7009: Parameter action failed
+    ParameterActionFailed = 7009,
+    /// 7010: Unsupported parameter
+    UnsupportedParameter = 7010,
+    /// 7011: Invalid type
+    InvalidType = 7011,
+    /// 7012: Invalid value
+    InvalidValue = 7012,
+    /// 7013: Attempt to update non-writeable parameter
+    ParameterNotWritable = 7013,
+    /// 7014: Value conflict
+    ValueConflict = 7014,
+    /// 7015: Operation error
+    OperationError = 7015,
+    /// 7016: Object does not exist
+    ObjectDoesNotExist = 7016,
+    /// 7017: Object could not be created
+    ObjectNotCreated = 7017,
+    /// 7018: Object is not a table
+    ObjectNotTable = 7018,
+    /// 7019: Attempt to create non-creatable Object
+    ObjectNotCreatable = 7019,
+    /// 7020: Object could not be updated
+    ObjectNotUpdated = 7020,
+    /// 7021: Required parameter failed
+    RequiredParameterFailed = 7021,
+    /// 7022: Command failure
+    CommandFailure = 7022,
+    /// 7023: Command canceled
+    CommandCanceled = 7023,
+    /// 7024: Delete failure
+    DeleteFailure = 7024,
+    /// 7025: Object exists with duplicate key
+    DuplicateUniqueKey = 7025,
+    /// 7026: Invalid path
+    InvalidPath = 7026,
+    /// 7027: Invalid command arguments
+    InvalidCommandArguments = 7027,
+    /// 7028: Register failure
+    RegisterFailure = 7028,
+    /// 7029: Already in use
+    AlreadyInUse = 7029,
+    /// 7030: Deregister failure
+    DeregisterFailure = 7030,
+    /// 7031: Path already registered
+    PathAlreadyRegistered = 7031,
+    /// 7100: Record could not be parsed
+    RecordNotParsed = 7100,
+    /// 7101: Secure session required
+    SecureSessionRequired = 7101,
+    /// 7102: Secure session not supported
+    SecureSessionNotSupported = 7102,
+    /// 7103: Segmentation and reassembly not supported
+    SegmentationNotSupported = 7103,
+    /// 7104: Invalid Record value
+    InvalidRecordValue = 7104,
+    /// 7105: Session Context terminated
+    SessionContextTerminated = 7105,
+    /// 7106: Session Context not allowed
+    SessionContextNotAllowed = 7106,
+}
+
+impl UspErrorCode {
+    /// Returns the error message defined for the code, an empty string for unknown codes
+    #[must_use]
+    pub const fn message(self) -> &'static str {
+        get_err_msg(self.code())
+    }
+
+    /// Returns the category of the code or `None` for unknown codes outside of the ranges of the
+    /// categories
+    #[must_use]
+    pub const fn category(self) -> Option<ErrorCategory> {
+        match self.code() {
+            7000..=7099 => Some(ErrorCategory::Message),
+            7100..=7199 => Some(ErrorCategory::Record),
+            7800..=7999 => Some(ErrorCategory::Vendor),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the code may be carried by a USP Msg of the given type, e.g. as
+    /// `err_code` of a failure in a `SetResp`
+    ///
+    /// Errors of the Record layer are never allowed in Msgs. Most Msg errors are specific to the
+    /// responses of some requests, while general errors like [`UspErrorCode::InternalError`] and
+    /// vendor specific or unknown codes are allowed in all Msgs. An `Error` Msg may carry all Msg
+    /// errors, as it is sent in response to any request.
+    ///
+    /// ```
+    /// use rusp_lib::usp::mod_Header::MsgType;
+    /// use rusp_lib::usp_errors::UspErrorCode;
+    ///
+    /// assert!(UspErrorCode::InvalidValue.is_allowed_in(MsgType::SET_RESP));
+    /// assert!(!UspErrorCode::InvalidValue.is_allowed_in(MsgType::GET_RESP));
+    /// assert!(!UspErrorCode::SecureSessionRequired.is_allowed_in(MsgType::SET_RESP));
+    /// assert!(UspErrorCode::CommandFailure.is_allowed_in(MsgType::ERROR));
+    /// ```
+    #[must_use]
+    pub const fn is_allowed_in(self, msg_type: MsgType) -> bool {
+        use MsgType::{
+            ADD_RESP, DELETE_RESP, DEREGISTER_RESP, ERROR, GET_INSTANCES_RESP, GET_RESP,
+            GET_SUPPORTED_DM_RESP, NOTIFY, OPERATE_RESP, REGISTER_RESP, SET_RESP,
+        };
+
+        if matches!(self.category(), Some(ErrorCategory::Record)) {
+            return false;
+        }
+        if matches!(msg_type, ERROR) {
+            return true;
+        }
+
+        match self {
+            Self::MessageNotSupported => false,
+            Self::InvalidConfiguration => matches!(msg_type, ADD_RESP | SET_RESP | OPERATE_RESP),
+            Self::InvalidPathSyntax | Self::ObjectDoesNotExist | Self::InvalidPath => matches!(
+                msg_type,
+                GET_RESP
+                    | SET_RESP
+                    | ADD_RESP
+                    | DELETE_RESP
+                    | OPERATE_RESP
+                    | GET_INSTANCES_RESP
+                    | GET_SUPPORTED_DM_RESP
+                    | REGISTER_RESP
+                    | DEREGISTER_RESP
+                    | NOTIFY
+            ),
+            Self::ParameterActionFailed | Self::ObjectNotUpdated => matches!(msg_type, SET_RESP),
+            Self::UnsupportedParameter
+            | Self::InvalidType
+            | Self::InvalidValue
+            | Self::ParameterNotWritable
+            | Self::ValueConflict
+            | Self::RequiredParameterFailed
+            | Self::DuplicateUniqueKey => matches!(msg_type, ADD_RESP | SET_RESP),
+            Self::OperationError => matches!(msg_type, ADD_RESP | SET_RESP | DELETE_RESP),
+            Self::ObjectNotCreated | Self::ObjectNotCreatable => matches!(msg_type, ADD_RESP),
+            Self::ObjectNotTable => matches!(msg_type, ADD_RESP | DELETE_RESP | GET_INSTANCES_RESP),
+            Self::CommandFailure | Self::CommandCanceled | Self::InvalidCommandArguments => {
+                matches!(msg_type, OPERATE_RESP | NOTIFY)
+            }
+            Self::DeleteFailure => matches!(msg_type, DELETE_RESP),
+            Self::RegisterFailure | Self::AlreadyInUse | Self::PathAlreadyRegistered => {
+                matches!(msg_type, REGISTER_RESP)
+            }
+            Self::DeregisterFailure => matches!(msg_type, DEREGISTER_RESP),
+            _ => true,
+        }
+    }
+
+    /// Returns whether the code may be used in a Record, e.g. as `reason_code` of a
+    /// `DisconnectRecord`
+    ///
+    /// Besides the errors of the Record layer, general errors like
+    /// [`UspErrorCode::InternalError`] and vendor specific or unknown codes are allowed.
+    #[must_use]
+    pub const fn is_allowed_in_record(self) -> bool {
+        match self.category() {
+            Some(ErrorCategory::Message) => matches!(
+                self,
+                Self::MessageFailed
+                    | Self::RequestDenied
+                    | Self::InternalError
+                    | Self::InvalidArguments
+                    | Self::ResourcesExceeded
+                    | Self::PermissionDenied
+                    | Self::Unknown(_)
+            ),
+            _ => true,
+        }
+    }
+}
+
+impl From<UspErrorCode> for u32 {
+    fn from(code: UspErrorCode) -> Self {
+        code.code()
+    }
+}
+
+impl fmt::Display for UspErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.message() {
+            "" => write!(f, "{} Unknown error", self.code()),
+            message => write!(f, "{} {message}", self.code()),
+        }
+    }
+}
+
+/// Types accepted as error code by the builders, i.e. [`UspErrorCode`] and plain `u32` codes
+pub trait IntoErrCode {
+    /// Returns the numeric error code
+    fn into_err_code(self) -> u32;
+}
+
+impl IntoErrCode for u32 {
+    fn into_err_code(self) -> u32 {
+        self
+    }
+}
+
+impl IntoErrCode for UspErrorCode {
+    fn into_err_code(self) -> u32 {
+        self.code()
+    }
+}
+
+/// Checks that an error code may be carried by a USP Msg of the given type, see
+/// [`UspErrorCode::is_allowed_in`]
+///
+/// Codes outside of the range of USP error codes are accepted, as they are not known to be wrong.
+///
+/// # Errors
+///
+/// This function will return [`Error::ErrCodeNotAllowed`] if the code is not allowed in the given
+/// type of Msg
+pub fn check_err_code(code: u32, msg_type: MsgType) -> Result<()> {
+    match UspErrorCode::try_from(code) {
+        Ok(code) if !code.is_allowed_in(msg_type) => Err(Error::ErrCodeNotAllowed {
+            code,
+            msg_type: Some(msg_type),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that an error code may be used in a Record, see
+/// [`UspErrorCode::is_allowed_in_record`]
+///
+/// # Errors
+///
+/// This function will return [`Error::ErrCodeNotAllowed`] if the code is not allowed in a Record
+pub fn check_record_err_code(code: u32) -> Result<()> {
+    match UspErrorCode::try_from(code) {
+        Ok(code) if !code.is_allowed_in_record() => Err(Error::ErrCodeNotAllowed {
+            code,
+            msg_type: None,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the type of the Msg carrying the body together with all error codes in it
+fn body_err_codes(body: &Body) -> Option<(MsgType, Vec<u32>)> {
+    use crate::usp::mod_AddResp::mod_CreatedObjectResult::mod_OperationStatus::OneOfoper_status as add_status;
+    use crate::usp::mod_Body::OneOfmsg_body;
+    use crate::usp::mod_DeleteResp::mod_DeletedObjectResult::mod_OperationStatus::OneOfoper_status as delete_status;
+    use crate::usp::mod_DeregisterResp::mod_DeregisteredPathResult::mod_OperationStatus::OneOfoper_status as deregister_status;
+    use crate::usp::mod_Notify::mod_OperationComplete::OneOfoperation_resp as notify_resp;
+    use crate::usp::mod_Notify::OneOfnotification;
+    use crate::usp::mod_OperateResp::mod_OperationResult::OneOfoperation_resp as operate_resp;
+    use crate::usp::mod_RegisterResp::mod_RegisteredPathResult::mod_OperationStatus::OneOfoper_status as register_status;
+    use crate::usp::mod_Request::OneOfreq_type;
+    use crate::usp::mod_Response::OneOfresp_type;
+    use crate::usp::mod_SetResp::mod_UpdatedObjectResult::mod_OperationStatus::OneOfoper_status as set_status;
+
+    let codes = match &body.msg_body {
+        OneOfmsg_body::error(error) => (
+            MsgType::ERROR,
+            std::iter::once(error.err_code)
+                .chain(error.param_errs.iter().map(|e| e.err_code))
+                .collect(),
+        ),
+        OneOfmsg_body::request(request) => match &request.req_type {
+            OneOfreq_type::notify(notify) => (
+                MsgType::NOTIFY,
+                match &notify.notification {
+                    OneOfnotification::oper_complete(complete) => match &complete.operation_resp {
+                        notify_resp::cmd_failure(failure) => vec![failure.err_code],
+                        _ => vec![],
+                    },
+                    _ => vec![],
+                },
+            ),
+            _ => return None,
+        },
+        OneOfmsg_body::response(response) => match &response.resp_type {
+            OneOfresp_type::get_resp(resp) => (
+                MsgType::GET_RESP,
+                resp.req_path_results.iter().map(|r| r.err_code).collect(),
+            ),
+            OneOfresp_type::get_supported_dm_resp(resp) => (
+                MsgType::GET_SUPPORTED_DM_RESP,
+                resp.req_obj_results.iter().map(|r| r.err_code).collect(),
+            ),
+            OneOfresp_type::get_instances_resp(resp) => (
+                MsgType::GET_INSTANCES_RESP,
+                resp.req_path_results.iter().map(|r| r.err_code).collect(),
+            ),
+            OneOfresp_type::add_resp(resp) => (
+                MsgType::ADD_RESP,
+                resp.created_obj_results
+                    .iter()
+                    .filter_map(|r| r.oper_status.as_ref())
+                    .flat_map(|s| match &s.oper_status {
+                        add_status::oper_failure(f) => vec![f.err_code],
+                        add_status::oper_success(s) => {
+                            s.param_errs.iter().map(|e| e.err_code).collect()
+                        }
+                        add_status::None => vec![],
+                    })
+                    .collect(),
+            ),
+            OneOfresp_type::delete_resp(resp) => (
+                MsgType::DELETE_RESP,
+                resp.deleted_obj_results
+                    .iter()
+                    .filter_map(|r| r.oper_status.as_ref())
+                    .flat_map(|s| match &s.oper_status {
+                        delete_status::oper_failure(f) => vec![f.err_code],
+                        delete_status::oper_success(s) => {
+                            s.unaffected_path_errs.iter().map(|e| e.err_code).collect()
+                        }
+                        delete_status::None => vec![],
+                    })
+                    .collect(),
+            ),
+            OneOfresp_type::set_resp(resp) => (
+                MsgType::SET_RESP,
+                resp.updated_obj_results
+                    .iter()
+                    .filter_map(|r| r.oper_status.as_ref())
+                    .flat_map(|s| match &s.oper_status {
+                        set_status::oper_failure(f) => std::iter::once(f.err_code)
+                            .chain(
+                                f.updated_inst_failures
+                                    .iter()
+                                    .flat_map(|i| &i.param_errs)
+                                    .map(|e| e.err_code),
+                            )
+                            .collect(),
+                        set_status::oper_success(s) => s
+                            .updated_inst_results
+                            .iter()
+                            .flat_map(|i| &i.param_errs)
+                            .map(|e| e.err_code)
+                            .collect(),
+                        set_status::None => vec![],
+                    })
+                    .collect(),
+            ),
+            OneOfresp_type::operate_resp(resp) => (
+                MsgType::OPERATE_RESP,
+                resp.operation_results
+                    .iter()
+                    .filter_map(|r| match &r.operation_resp {
+                        operate_resp::cmd_failure(failure) => Some(failure.err_code),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            OneOfresp_type::register_resp(resp) => (
+                MsgType::REGISTER_RESP,
+                resp.registered_path_results
+                    .iter()
+                    .filter_map(|r| match &r.oper_status.as_ref()?.oper_status {
+                        register_status::oper_failure(f) => Some(f.err_code),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            OneOfresp_type::deregister_resp(resp) => (
+                MsgType::DEREGISTER_RESP,
+                resp.deregistered_path_results
+                    .iter()
+                    .filter_map(|r| match &r.oper_status.as_ref()?.oper_status {
+                        deregister_status::oper_failure(f) => Some(f.err_code),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            _ => return None,
+        },
+        OneOfmsg_body::None => return None,
+    };
+
+    Some(codes)
+}
+
+impl Msg {
+    /// Checks that all error codes carried by this [`Msg`] are allowed in its type of Msg, see
+    /// [`check_err_code`]
+    ///
+    /// The builders accept any error code, so this check can be used to make sure a Msg only
+    /// reports errors the USP specification permits for it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_builder::{ErrorBuilder, MsgBuilder};
+    /// use rusp_lib::usp_errors::UspErrorCode;
+    ///
+    /// let msg = MsgBuilder::new()
+    ///     .with_msg_id("error".into())
+    ///     .with_body(
+    ///         ErrorBuilder::new()
+    ///             .set_err(UspErrorCode::SecureSessionRequired, None)
+    ///             .build()
+    ///             .unwrap(),
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(msg.check_err_codes().is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::ErrCodeNotAllowed`] for the first error code which is
+    /// not allowed in this type of Msg
+    pub fn check_err_codes(&self) -> Result<()> {
+        match self.body.as_ref().and_then(body_err_codes) {
+            Some((msg_type, codes)) => codes
+                .into_iter()
+                .try_for_each(|code| check_err_code(code, msg_type)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Record {
+    /// Checks that the error code carried by a DisconnectRecord is allowed in a Record, see
+    /// [`check_record_err_code`]
+    ///
+    /// The payload of the Record is not checked, use [`Msg::check_err_codes`] for the Msg carried
+    /// in it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::ErrCodeNotAllowed`] if the error code is not allowed in
+    /// a Record
+    pub fn check_err_codes(&self) -> Result<()> {
+        match &self.record_type {
+            OneOfrecord_type::disconnect(disconnect) => {
+                check_record_err_code(disconnect.reason_code)
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::usp_prototxt::{escape_bytes, escape_str};
+use crate::usp_schema::{
+    self, FieldDescriptor, FieldKind, MessageDescriptor, WireReader, WireValue,
+};
+
+/// Number of bytes shown per line of the dump
+const BYTES_PER_LINE: usize = 16;
+
+/// Appends one or more lines containing the hex representation of `bytes` to the output; the
+/// annotation is shown on the first line only
+fn write_lines(out: &mut String, offset: usize, bytes: &[u8], depth: usize, annotation: &str) {
+    let mut chunks = bytes.chunks(BYTES_PER_LINE);
+    let first = chunks.next().unwrap_or_default();
+    write_line(out, offset, first, depth, annotation);
+
+    let mut offset = offset + first.len();
+    for chunk in chunks {
+        write_line(out, offset, chunk, depth, "");
+        offset += chunk.len();
+    }
+}
+
+fn write_line(out: &mut String, offset: usize, bytes: &[u8], depth: usize, annotation: &str) {
+    let hex = bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = format!(
+        "{offset:06x}  {hex:<width$}  {:indent$}{annotation}",
+        "",
+        width = BYTES_PER_LINE * 3 - 1,
+        indent = depth * 2
+    );
+    let _ = writeln!(out, "{}", line.trim_end());
+}
+
+/// Returns the descriptor of a message embedded in a `bytes` field, USP Records carry the
+/// Protobuf encoded USP Msg this way
+fn embedded_message(
+    parent: &MessageDescriptor,
+    field: &FieldDescriptor,
+) -> Option<&'static MessageDescriptor> {
+    let carries_msg = std::ptr::eq(parent, &usp_schema::NO_SESSION_CONTEXT_RECORD)
+        || std::ptr::eq(parent, &usp_schema::SESSION_CONTEXT_RECORD);
+    (carries_msg && field.name == "payload").then_some(&usp_schema::MSG)
+}
+
+/// Annotates all fields of the Protobuf encoded message in `bytes`, which starts at `base` in the
+/// dumped data. Returns `false` if not all of the data could be decoded.
+fn dump_message(
+    out: &mut String,
+    bytes: &[u8],
+    base: usize,
+    descriptor: &MessageDescriptor,
+    depth: usize,
+) -> bool {
+    let mut reader = WireReader::new(bytes);
+    let mut indices: HashMap<u64, usize> = HashMap::new();
+    let mut complete = true;
+
+    while !reader.is_eof() {
+        let start = reader.position();
+        let result =
+            reader
+                .read_tag()
+                .map_err(|e| e.to_string())
+                .and_then(|(number, wire_type)| {
+                    reader
+                        .read_value(wire_type)
+                        .map(|value| (number, value))
+                        .map_err(|e| {
+                            let field = descriptor.field_by_number(number).map_or_else(
+                                || number.to_string(),
+                                |fd| format!("{number}: {}", fd.name),
+                            );
+                            format!("{} field ({field}): {e}", wire_type.name())
+                        })
+                });
+
+        match result {
+            Ok((number, value)) => {
+                let index = indices.entry(number).or_default();
+                let raw = &bytes[start..reader.position()];
+                // The length of the field is known, so continue with the next field even if the
+                // contents could not be decoded completely
+                complete &= dump_field(
+                    out,
+                    raw,
+                    base + start,
+                    descriptor,
+                    number,
+                    &value,
+                    *index,
+                    depth,
+                );
+                *index += 1;
+            }
+            Err(e) => {
+                let rest = &bytes[start..];
+                let annotation = format!("!! {e}, {} byte(s) not decoded", rest.len());
+                write_lines(out, base + start, rest, depth, &annotation);
+                return false;
+            }
+        }
+    }
+
+    complete
+}
+
+/// Annotates a single field, `raw` contains the complete encoding of the field including the tag.
+/// Returns `false` if the contents of the field could not be decoded completely.
+#[allow(clippy::too_many_arguments)]
+fn dump_field(
+    out: &mut String,
+    raw: &[u8],
+    offset: usize,
+    descriptor: &MessageDescriptor,
+    number: u64,
+    value: &WireValue,
+    index: usize,
+    depth: usize,
+) -> bool {
+    let header_len = match value {
+        WireValue::LengthDelimited(data) => raw.len() - data.len(),
+        _ => raw.len(),
+    };
+    let (header, data) = raw.split_at(header_len);
+    let data_offset = offset + header_len;
+
+    let Some(field) = descriptor
+        .field_by_number(number)
+        .filter(|fd| fd.kind.wire_type() == value.wire_type())
+    else {
+        let annotation = match descriptor.field_by_number(number) {
+            Some(fd) => format!(
+                "{number}: {} ({}) !! unexpected wire type {}, expected {}",
+                fd.name,
+                fd.kind.type_name(),
+                value.wire_type().name(),
+                fd.kind.wire_type().name()
+            ),
+            None => format!("{number}: !! unknown field ({})", value.wire_type().name()),
+        };
+        return dump_raw_value(
+            out,
+            header,
+            offset,
+            data,
+            data_offset,
+            value,
+            depth,
+            &annotation,
+        );
+    };
+
+    let name = if field.repeated {
+        format!("{}[{index}]", field.name)
+    } else {
+        field.name.to_string()
+    };
+    let type_name = field.kind.type_name();
+
+    match (field.kind, *value) {
+        (FieldKind::String, WireValue::LengthDelimited(data)) => {
+            let text = std::str::from_utf8(data).map_or_else(
+                |_| format!("\"{}\" !! invalid UTF-8", escape_bytes(data)),
+                |s| format!("\"{}\"", escape_str(s)),
+            );
+            let annotation = format!(
+                "{number}: {name} ({type_name}), {} bytes: {text}",
+                data.len()
+            );
+            write_lines(out, offset, header, depth, &annotation);
+            if !data.is_empty() {
+                write_lines(out, data_offset, data, depth + 1, "");
+            }
+        }
+        (FieldKind::Bytes, WireValue::LengthDelimited(data)) => {
+            if let Some(embedded) = embedded_message(descriptor, field) {
+                // Only annotate the contents if they are a complete message, a payload might
+                // also be encrypted or segmented
+                let mut nested = String::new();
+                if !data.is_empty()
+                    && dump_message(&mut nested, data, data_offset, embedded, depth + 1)
+                {
+                    let annotation = format!(
+                        "{number}: {name} ({type_name}), {} bytes containing {}",
+                        data.len(),
+                        embedded.name
+                    );
+                    write_lines(out, offset, header, depth, &annotation);
+                    out.push_str(&nested);
+                    return true;
+                }
+            }
+
+            let annotation = format!("{number}: {name} ({type_name}), {} bytes", data.len());
+            write_lines(out, offset, header, depth, &annotation);
+            if !data.is_empty() {
+                write_lines(out, data_offset, data, depth + 1, "");
+            }
+        }
+        (FieldKind::Message(_) | FieldKind::Map, WireValue::LengthDelimited(data)) => {
+            let nested = field
+                .kind
+                .message()
+                .unwrap_or(&usp_schema::STRING_MAP_ENTRY);
+            let annotation = format!("{number}: {name} ({type_name}), {} bytes", data.len());
+            write_lines(out, offset, header, depth, &annotation);
+            return dump_message(out, data, data_offset, nested, depth + 1);
+        }
+        (FieldKind::Bool, WireValue::Varint(v)) => {
+            let annotation = format!("{number}: {name} ({type_name}): {}", v != 0);
+            write_lines(out, offset, raw, depth, &annotation);
+        }
+        (FieldKind::Uint64, WireValue::Varint(v)) => {
+            let annotation = format!("{number}: {name} ({type_name}): {v}");
+            write_lines(out, offset, raw, depth, &annotation);
+        }
+        (FieldKind::Fixed32, WireValue::Fixed32(v)) => {
+            let annotation = format!("{number}: {name} ({type_name}): {v}");
+            write_lines(out, offset, raw, depth, &annotation);
+        }
+        (FieldKind::Enum(descriptor), WireValue::Varint(v)) => {
+            // Enums are encoded as sign extended int32 values
+            #[allow(clippy::cast_possible_truncation)]
+            let v = v as i32;
+            let annotation = match descriptor.name_of(v) {
+                Some(symbol) => format!("{number}: {name} ({type_name}): {symbol} ({v})"),
+                None => format!("{number}: {name} ({type_name}): {v} !! unknown enum value"),
+            };
+            write_lines(out, offset, raw, depth, &annotation);
+        }
+        _ => unreachable!("wire types of known fields are checked above"),
+    }
+
+    true
+}
+
+/// Annotates a field which cannot be interpreted according to the schema
+#[allow(clippy::too_many_arguments)]
+fn dump_raw_value(
+    out: &mut String,
+    header: &[u8],
+    offset: usize,
+    data: &[u8],
+    data_offset: usize,
+    value: &WireValue,
+    depth: usize,
+    annotation: &str,
+) -> bool {
+    let annotation = match value {
+        WireValue::Varint(v) => format!("{annotation}: {v}"),
+        WireValue::Fixed64(v) => format!("{annotation}: 0x{v:016x}"),
+        WireValue::Fixed32(v) => format!("{annotation}: 0x{v:08x}"),
+        WireValue::LengthDelimited(data) => format!("{annotation}, {} bytes", data.len()),
+    };
+    write_lines(out, offset, header, depth, &annotation);
+    if !data.is_empty() {
+        write_lines(out, data_offset, data, depth + 1, "");
+    }
+
+    true
+}
+
+fn hexdump(bytes: &[u8], descriptor: &MessageDescriptor) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{}, {} bytes", descriptor.name, bytes.len());
+    dump_message(&mut out, bytes, 0, descriptor, 0);
+    out
+}
+
+/// Renders an annotated hex dump of a Protobuf encoded USP Msg
+///
+/// Every field is shown with its offset, the raw bytes, field number, name, type and value, the
+/// fields of nested messages are indented. Unlike [`try_decode_msg`] this function does not bail
+/// out on malformed data but decodes as much as possible; unknown fields, unexpected wire types
+/// and undecodable trailing bytes are flagged with `!!`.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Message
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_hexdump::hexdump_msg;
+/// let dump = hexdump_msg(&[
+///     0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10,
+///     0x01, 0x12, 0x0d, 0x0a, 0x0b, 0x0a, 0x09, 0x0a,
+///     0x07, 0x44, 0x65, 0x76, 0x69, 0x63, 0x65, 0x2e,
+/// ]);
+/// assert_eq!(dump, "usp.Msg, 24 bytes
+/// 000000  0a 07                                            1: header (usp.Header), 7 bytes
+/// 000002  0a 03                                              1: msg_id (string), 3 bytes: \"get\"
+/// 000004  67 65 74
+/// 000007  10 01                                              2: msg_type (usp.Header.MsgType): GET (1)
+/// 000009  12 0d                                            2: body (usp.Body), 13 bytes
+/// 00000b  0a 0b                                              1: request (usp.Request), 11 bytes
+/// 00000d  0a 09                                                1: get (usp.Get), 9 bytes
+/// 00000f  0a 07                                                  1: param_paths[0] (string), 7 bytes: \"Device.\"
+/// 000011  44 65 76 69 63 65 2e
+/// ");
+/// ```
+///
+/// [`try_decode_msg`]: crate::usp_decoder::try_decode_msg
+#[must_use]
+pub fn hexdump_msg(bytes: &[u8]) -> String {
+    hexdump(bytes, &usp_schema::MSG)
+}
+
+/// Renders an annotated hex dump of a Protobuf encoded USP Record
+///
+/// Works like [`hexdump_msg`], additionally the payload of a Record is annotated as USP Msg if it
+/// can be decoded as one.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes containing the Protobuf encoded USP Record
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_hexdump::hexdump_record;
+/// // A truncated USP Record
+/// let dump = hexdump_record(&[
+///     0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+///     0x6f, 0x63, 0x3a, 0x3a, 0x74,
+/// ]);
+/// assert_eq!(dump, "usp_record.Record, 13 bytes
+/// 000000  0a 03                                            1: version (string), 3 bytes: \"1.3\"
+/// 000002  31 2e 33
+/// 000005  12 07 64 6f 63 3a 3a 74                          !! LEN field (2: to_id): unexpected end of data, 8 byte(s) not decoded
+/// ");
+/// ```
+#[must_use]
+pub fn hexdump_record(bytes: &[u8]) -> String {
+    hexdump(bytes, &usp_schema::RECORD)
+}
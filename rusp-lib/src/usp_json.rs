@@ -15,57 +15,130 @@ use crate::usp_record::{
     WebSocketConnectRecord,
 };
 
-use anyhow::Context;
+use crate::usp_unknown::{Lenient, UnknownField, UnknownFields, UnknownValue};
+
+use crate::usp_decoder::{try_decode_msg, try_decode_record};
+use crate::usp_schema::{
+    self, write_tag, write_varint, FieldDescriptor, FieldKind, MessageDescriptor, WireType,
+};
+
+use serde::de::{Deserialize, Deserializer};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::Value;
+
+impl Serialize for UnknownField {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("UnknownField", 5)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field("position", &self.position)?;
+        state.serialize_field("number", &self.number)?;
+        match &self.value {
+            UnknownValue::Varint(v) => {
+                state.serialize_field("wire_type", "VARINT")?;
+                state.serialize_field("value", v)?;
+            }
+            UnknownValue::Fixed64(v) => {
+                state.serialize_field("wire_type", "I64")?;
+                state.serialize_field("value", v)?;
+            }
+            UnknownValue::LengthDelimited(data) => {
+                state.serialize_field("wire_type", "LEN")?;
+                state.serialize_field("value", data)?;
+            }
+            UnknownValue::Fixed32(v) => {
+                state.serialize_field("wire_type", "I32")?;
+                state.serialize_field("value", v)?;
+            }
+        }
+        state.end()
+    }
+}
+
+impl Serialize for UnknownFields {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self)
+    }
+}
+
+impl Serialize for Lenient<Record> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_record(self, self.unknown_fields(), serializer)
+    }
+}
 
 impl Serialize for Record {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        use crate::usp_decoder::try_decode_msg;
-        use mod_Record::OneOfrecord_type::{
-            disconnect, mqtt_connect, no_session_context, session_context, stomp_connect,
-            websocket_connect,
-        };
+        serialize_record(self, &UnknownFields::default(), serializer)
+    }
+}
 
-        let mut state = serializer.serialize_struct("Record", 7)?;
-        state.serialize_field("version", &self.version)?;
-        state.serialize_field("to_id", &self.to_id)?;
-        state.serialize_field("from_id", &self.from_id)?;
-        state.serialize_field("originator_id", &self.originator_id)?;
-        state.serialize_field("destination_id", &self.destination_id)?;
-        state.serialize_field("payload_security", &self.payload_security)?;
-        state.serialize_field("mac_signature", &self.mac_signature)?;
-        state.serialize_field("sender_cert", &self.sender_cert)?;
-
-        match &self.record_type {
-            no_session_context(context) => {
-                let msg = try_decode_msg(&context.payload);
-                if let Ok(msg) = msg {
-                    state.serialize_field("payload", &msg)?;
-                } else {
-                    Err(serde::ser::Error::custom(format!(
-                        "{:?}",
-                        msg.context("Interpreting USP Record payload as USP Msg")
-                            .unwrap_err()
-                    )))?;
-                }
-            }
-            session_context(context) => {
-                state.serialize_field("session_context", context)?;
+/// Serialises a Record, listing the unknown fields retained by lenient decoding after the known
+/// ones
+fn serialize_record<S>(
+    record: &Record,
+    unknown_fields: &UnknownFields,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    use crate::usp_decoder::try_decode_msg_lenient;
+    use mod_Record::OneOfrecord_type::{
+        disconnect, mqtt_connect, no_session_context, session_context, stomp_connect,
+        websocket_connect,
+    };
+
+    let mut state = serializer.serialize_struct("Record", 7)?;
+    state.serialize_field("version", &record.version)?;
+    state.serialize_field("to_id", &record.to_id)?;
+    state.serialize_field("from_id", &record.from_id)?;
+    state.serialize_field("originator_id", &record.originator_id)?;
+    state.serialize_field("destination_id", &record.destination_id)?;
+    state.serialize_field("payload_security", &record.payload_security)?;
+    state.serialize_field("mac_signature", &record.mac_signature)?;
+    state.serialize_field("sender_cert", &record.sender_cert)?;
+
+    match &record.record_type {
+        no_session_context(context) => {
+            // Show the unknown fields of the payload as well
+            let msg = try_decode_msg_lenient(&context.payload);
+            if let Ok(msg) = msg {
+                state.serialize_field("payload", &msg)?;
+            } else {
+                Err(serde::ser::Error::custom(format!(
+                    "Interpreting USP Record payload as USP Msg: {}",
+                    msg.unwrap_err()
+                )))?;
             }
-            websocket_connect(ws) => state.serialize_field("websocket_connect", ws)?,
-            mqtt_connect(mqtt) => state.serialize_field("mqtt_connect", mqtt)?,
-            stomp_connect(stomp) => state.serialize_field("stomp_connect", stomp)?,
-            disconnect(disc) => state.serialize_field("disconnect", disc)?,
-            _ => Err(serde::ser::Error::custom(
-                "Unknown/Unsupported record type!",
-            ))?,
         }
+        session_context(context) => {
+            state.serialize_field("session_context", context)?;
+        }
+        websocket_connect(ws) => state.serialize_field("websocket_connect", ws)?,
+        mqtt_connect(mqtt) => state.serialize_field("mqtt_connect", mqtt)?,
+        stomp_connect(stomp) => state.serialize_field("stomp_connect", stomp)?,
+        disconnect(disc) => state.serialize_field("disconnect", disc)?,
+        _ => Err(serde::ser::Error::custom(
+            "Unknown/Unsupported record type!",
+        ))?,
+    }
 
-        state.end()
+    if !unknown_fields.is_empty() {
+        state.serialize_field("unknown_fields", unknown_fields)?;
     }
+    state.end()
 }
 
 impl Serialize for mod_Record::PayloadSecurity {
@@ -187,14 +260,77 @@ impl Serialize for DisconnectRecord {
     }
 }
 
+impl Serialize for Lenient<Msg> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_msg(self, self.unknown_fields(), serializer)
+    }
+}
+
 impl Serialize for Msg {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Msg", 2)?;
-        state.serialize_field("Header", &self.header)?;
-        state.serialize_field("Body", &self.body)?;
+        serialize_msg(self, &UnknownFields::default(), serializer)
+    }
+}
+
+/// Serialises a Msg, listing the unknown fields retained by lenient decoding after the known ones
+fn serialize_msg<S>(
+    msg: &Msg,
+    unknown_fields: &UnknownFields,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut state = serializer.serialize_struct("Msg", 2)?;
+    state.serialize_field("Header", &msg.header)?;
+    match msg.body.as_ref().and_then(UnknownBody::of) {
+        // Leniently decoded Msgs of newer USP versions may carry a body unknown to us, which is
+        // listed among the unknown fields
+        Some(body) if !unknown_fields.is_empty() => state.serialize_field("Body", &body)?,
+        _ => state.serialize_field("Body", &msg.body)?,
+    }
+    if !unknown_fields.is_empty() {
+        state.serialize_field("unknown_fields", unknown_fields)?;
+    }
+    state.end()
+}
+
+/// The body of a Msg whose type is unknown to this library, serialised as far as it is known
+struct UnknownBody(Option<&'static str>);
+
+impl UnknownBody {
+    /// Returns the known part of the body if its type is unknown
+    fn of(body: &Body) -> Option<Self> {
+        use mod_Body::OneOfmsg_body::{request, response, None};
+
+        match &body.msg_body {
+            request(req) if matches!(req.req_type, mod_Request::OneOfreq_type::None) => {
+                Some(Self(Some("Request")))
+            }
+            response(resp) if matches!(resp.resp_type, mod_Response::OneOfresp_type::None) => {
+                Some(Self(Some("Response")))
+            }
+            None => Some(Self(Option::None)),
+            _ => Option::None,
+        }
+    }
+}
+
+impl Serialize for UnknownBody {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Body", 1)?;
+        if let Some(kind) = self.0 {
+            state.serialize_field(kind, &serde_json::Map::new())?;
+        }
         state.end()
     }
 }
@@ -1475,3 +1611,443 @@ impl Serialize for mod_AddResp::ParameterError {
         state.end()
     }
 }
+
+/// Returns the unqualified name of a message type, e.g. `GetResp` for `usp.GetResp`
+fn short_name(descriptor: &MessageDescriptor) -> &'static str {
+    descriptor
+        .name
+        .rsplit('.')
+        .next()
+        .unwrap_or(descriptor.name)
+}
+
+/// Encodes the JSON representation of a message described by `descriptor` into Protobuf data
+///
+/// Members are matched against the field names of the schema and, for the fields carrying the
+/// oneof bodies of a Msg like `Request` or `GetResp`, against the name of their message type. Like
+/// in the encoding of the native types, fields holding their default value are omitted and all
+/// fields are written in the order of their field numbers.
+fn encode_json(value: &Value, descriptor: &'static MessageDescriptor) -> Result<Vec<u8>, String> {
+    let Value::Object(object) = value else {
+        return Err(format!(
+            "expected an object for {}, found {value}",
+            descriptor.name
+        ));
+    };
+
+    let mut fields = vec![];
+    let mut unknown_fields = UnknownFields::default();
+    for (key, value) in object {
+        let mut buf = vec![];
+        if key == "unknown_fields" {
+            unknown_fields =
+                json_unknown_fields(value).map_err(|e| format!("in `unknown_fields`: {e}"))?;
+            continue;
+        }
+
+        // The Msg carried by a Record without session context is represented in decoded form
+        if key == "payload" && std::ptr::eq(descriptor, &usp_schema::RECORD) {
+            let (Some(field), Some(payload)) = (
+                descriptor.field_by_name("no_session_context"),
+                usp_schema::NO_SESSION_CONTEXT_RECORD.field_by_name("payload"),
+            ) else {
+                return Err("Record schema without `no_session_context` payload".into());
+            };
+            let msg =
+                encode_json(value, &usp_schema::MSG).map_err(|e| format!("in `payload`: {e}"))?;
+            let mut context = vec![];
+            write_tag(&mut context, payload.number, WireType::LengthDelimited);
+            write_varint(&mut context, msg.len() as u64);
+            context.extend_from_slice(&msg);
+            write_tag(&mut buf, field.number, WireType::LengthDelimited);
+            write_varint(&mut buf, context.len() as u64);
+            buf.extend_from_slice(&context);
+            fields.push((field.number, buf));
+            continue;
+        }
+
+        let field = descriptor
+            .field_by_name(key)
+            .or_else(|| {
+                descriptor.fields.iter().find(|field| {
+                    matches!(field.kind, FieldKind::Message(message) if short_name(message) == key)
+                })
+            })
+            .ok_or_else(|| format!("unknown field `{key}` in {}", descriptor.name))?;
+
+        encode_json_field(value, field, &mut buf).map_err(|e| format!("in `{key}`: {e}"))?;
+        fields.push((field.number, buf));
+    }
+
+    fields.sort_by_key(|(number, _)| *number);
+    let bytes: Vec<u8> = fields.into_iter().flat_map(|(_, buf)| buf).collect();
+    if unknown_fields.is_empty() {
+        return Ok(bytes);
+    }
+    Ok(unknown_fields.insert_into(&bytes, descriptor))
+}
+
+fn encode_json_field(
+    value: &Value,
+    field: &'static FieldDescriptor,
+    buf: &mut Vec<u8>,
+) -> Result<(), String> {
+    match (value, field.kind) {
+        // Messages without fields like `WebSocketConnectRecord` are represented as `null`
+        (Value::Null, FieldKind::Message(message))
+            if message.fields.is_empty() && !field.repeated =>
+        {
+            write_tag(buf, field.number, WireType::LengthDelimited);
+            write_varint(buf, 0);
+            Ok(())
+        }
+        (Value::Null, _) => Ok(()),
+        (Value::Object(entries), FieldKind::Map) => {
+            for (key, value) in entries {
+                let value = value
+                    .as_str()
+                    .ok_or_else(|| format!("expected a string for key `{key}`, found {value}"))?;
+                let mut entry = vec![];
+                for (number, data) in [(1, key.as_bytes()), (2, value.as_bytes())] {
+                    write_tag(&mut entry, number, WireType::LengthDelimited);
+                    write_varint(&mut entry, data.len() as u64);
+                    entry.extend_from_slice(data);
+                }
+                write_tag(buf, field.number, WireType::LengthDelimited);
+                write_varint(buf, entry.len() as u64);
+                buf.extend_from_slice(&entry);
+            }
+            Ok(())
+        }
+        (Value::Array(items), _) if field.repeated => {
+            items.iter().enumerate().try_for_each(|(index, item)| {
+                encode_json_value(item, field, buf, false).map_err(|e| format!("at [{index}]: {e}"))
+            })
+        }
+        (value, _) if field.repeated || matches!(field.kind, FieldKind::Map) => Err(format!(
+            "expected {} for repeated field of type {}, found {value}",
+            if field.repeated {
+                "an array"
+            } else {
+                "an object"
+            },
+            field.kind.type_name()
+        )),
+        (value, _) => encode_json_value(value, field, buf, true),
+    }
+}
+
+/// Encodes a single value of a field, omitting default values of singular fields if `implicit`
+/// is set
+fn encode_json_value(
+    value: &Value,
+    field: &'static FieldDescriptor,
+    buf: &mut Vec<u8>,
+    implicit: bool,
+) -> Result<(), String> {
+    let invalid = || {
+        format!(
+            "invalid value {value} for field of type {}",
+            field.kind.type_name()
+        )
+    };
+
+    match field.kind {
+        FieldKind::String | FieldKind::Bytes => {
+            let data = match value {
+                Value::String(s) => s.as_bytes().to_vec(),
+                Value::Array(_) if matches!(field.kind, FieldKind::Bytes) => {
+                    json_bytes(value).ok_or_else(invalid)?
+                }
+                _ => return Err(invalid()),
+            };
+            if !(implicit && data.is_empty()) {
+                write_tag(buf, field.number, WireType::LengthDelimited);
+                write_varint(buf, data.len() as u64);
+                buf.extend_from_slice(&data);
+            }
+        }
+        FieldKind::Bool => {
+            let value = value.as_bool().ok_or_else(invalid)?;
+            if value || !implicit {
+                write_tag(buf, field.number, WireType::Varint);
+                write_varint(buf, u64::from(value));
+            }
+        }
+        FieldKind::Fixed32 => {
+            let value = value
+                .as_u64()
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or_else(invalid)?;
+            if !(implicit && value == 0) {
+                write_tag(buf, field.number, WireType::Fixed32);
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        FieldKind::Uint64 => {
+            let value = value.as_u64().ok_or_else(invalid)?;
+            if !(implicit && value == 0) {
+                write_tag(buf, field.number, WireType::Varint);
+                write_varint(buf, value);
+            }
+        }
+        FieldKind::Enum(descriptor) => {
+            let value = match value {
+                Value::String(name) => descriptor.value_of(name),
+                value => value.as_i64().and_then(|value| i32::try_from(value).ok()),
+            }
+            .ok_or_else(invalid)?;
+            if !(implicit && value == 0) {
+                write_tag(buf, field.number, WireType::Varint);
+                // Negative enumeration values are sign extended, like for int32 fields
+                write_varint(buf, i64::from(value) as u64);
+            }
+        }
+        FieldKind::Message(descriptor) => {
+            let data = encode_json(value, descriptor)?;
+            write_tag(buf, field.number, WireType::LengthDelimited);
+            write_varint(buf, data.len() as u64);
+            buf.extend_from_slice(&data);
+        }
+        FieldKind::Map => return Err(invalid()),
+    }
+
+    Ok(())
+}
+
+/// Converts an array of byte values into bytes
+fn json_bytes(value: &Value) -> Option<Vec<u8>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+        .collect()
+}
+
+/// Converts the list of unknown fields retained by lenient decoding back into its native form
+fn json_unknown_fields(value: &Value) -> Result<UnknownFields, String> {
+    let fields = value
+        .as_array()
+        .ok_or_else(|| format!("expected an array, found {value}"))?;
+    fields
+        .iter()
+        .map(|field| {
+            let invalid = || format!("invalid unknown field {field}");
+            let number = field
+                .get("number")
+                .and_then(Value::as_u64)
+                .and_then(|number| u32::try_from(number).ok())
+                .ok_or_else(|| format!("invalid field number in {field}"))?;
+            let path = match field.get("path") {
+                None => String::new(),
+                Some(path) => path.as_str().ok_or_else(invalid)?.to_owned(),
+            };
+            let position = match field.get("position") {
+                None => usize::MAX,
+                Some(position) => position
+                    .as_u64()
+                    .and_then(|position| usize::try_from(position).ok())
+                    .ok_or_else(invalid)?,
+            };
+            let data = field.get("value").unwrap_or(&Value::Null);
+            let value = match field.get("wire_type").and_then(Value::as_str) {
+                Some("VARINT") => UnknownValue::Varint(data.as_u64().ok_or_else(invalid)?),
+                Some("I64") => UnknownValue::Fixed64(data.as_u64().ok_or_else(invalid)?),
+                Some("LEN") => UnknownValue::LengthDelimited(json_bytes(data).ok_or_else(invalid)?),
+                Some("I32") => UnknownValue::Fixed32(
+                    data.as_u64()
+                        .and_then(|data| u32::try_from(data).ok())
+                        .ok_or_else(invalid)?,
+                ),
+                _ => return Err(invalid()),
+            };
+            Ok(UnknownField {
+                path,
+                position,
+                number,
+                value,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map(UnknownFields::from)
+}
+
+/// Implementation of JSON support for `Msg`s
+impl Msg {
+    /// Parses a `Msg` from its JSON representation, as produced by serialising it via serde
+    ///
+    /// Like [`try_decode_msg`] this function does not perform the additional checks required by
+    /// the USP specification, use [`Msg::check_validity`] for that.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The JSON representation of the USP Message
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp::Msg;
+    /// let msg = Msg::from_json(r#"{
+    ///     "Header": { "msg_id": "get", "msg_type": "GET" },
+    ///     "Body": {
+    ///       "Request": {
+    ///         "Get": { "param_paths": ["Device.DeviceInfo."], "max_depth": 1 }
+    ///       }
+    ///     }
+    /// }"#).unwrap();
+    /// assert_eq!(msg.msg_id(), "get");
+    /// assert!(msg.is_request());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the text is not valid JSON or does not represent a USP Msg
+    pub fn from_json(text: &str) -> crate::Result<Self> {
+        let value: Value = serde_json::from_str(text).map_err(|e| crate::Error::Json {
+            reason: format!("while parsing JSON: {e}"),
+        })?;
+        Self::from_json_value(&value)
+    }
+
+    /// Converts the JSON representation of a `Msg`, e.g. obtained via [`serde_json::to_value`],
+    /// back into a `Msg`
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The JSON representation of the USP Message
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the value does not represent a USP Msg
+    pub fn from_json_value(value: &Value) -> crate::Result<Self> {
+        let bytes = encode_json(value, &usp_schema::MSG).map_err(|e| crate::Error::Json {
+            reason: format!("while converting JSON into USP Message: {e}"),
+        })?;
+        try_decode_msg(&bytes)
+    }
+}
+
+/// Implementation of JSON support for `Record`s
+impl Record {
+    /// Parses a `Record` from its JSON representation, as produced by serialising it via serde
+    ///
+    /// Like [`try_decode_record`] this function does not perform the additional checks required
+    /// by the USP specification, use [`Record::check_validity`] for that.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The JSON representation of the USP Record
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_record::Record;
+    /// let record = Record::from_json(r#"{
+    ///     "version": "1.3",
+    ///     "to_id": "doc::to",
+    ///     "from_id": "doc::from",
+    ///     "disconnect": { "reason": "Bye", "reason_code": 7003 }
+    /// }"#).unwrap();
+    /// assert_eq!(record.to_id, "doc::to");
+    /// assert!(record.check_validity().is_ok());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the text is not valid JSON or does not represent a USP Record
+    pub fn from_json(text: &str) -> crate::Result<Self> {
+        let value: Value = serde_json::from_str(text).map_err(|e| crate::Error::Json {
+            reason: format!("while parsing JSON: {e}"),
+        })?;
+        Self::from_json_value(&value)
+    }
+
+    /// Converts the JSON representation of a `Record`, e.g. obtained via
+    /// [`serde_json::to_value`], back into a `Record`
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The JSON representation of the USP Record
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the value does not represent a USP Record
+    pub fn from_json_value(value: &Value) -> crate::Result<Self> {
+        let bytes = encode_json(value, &usp_schema::RECORD).map_err(|e| crate::Error::Json {
+            reason: format!("while converting JSON into USP Record: {e}"),
+        })?;
+        try_decode_record(&bytes)
+    }
+}
+
+/// Implementation of JSON support for `Body`s
+impl Body {
+    /// Converts the JSON representation of a `Body`, e.g. obtained via
+    /// [`serde_json::to_value`], back into a `Body`
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The JSON representation of the USP Message body
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp::Body;
+    /// let body = Body::from_json_value(&serde_json::json!({
+    ///     "Request": { "Delete": { "allow_partial": true, "obj_paths": ["Device.Foo.1."] } }
+    /// }))
+    /// .unwrap();
+    /// assert_eq!(serde_json::to_value(&body).unwrap()["Request"]["Delete"]["allow_partial"], true);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error
+    /// if the value does not represent a USP Msg body
+    pub fn from_json_value(value: &Value) -> crate::Result<Self> {
+        let body = encode_json(value, &usp_schema::BODY).map_err(|e| crate::Error::Json {
+            reason: format!("while converting JSON into USP Message body: {e}"),
+        })?;
+
+        // Decode the body as part of an otherwise empty Msg to get the same error reporting
+        let field = usp_schema::MSG
+            .field_by_name("body")
+            .ok_or_else(|| crate::Error::Json {
+                reason: "Msg schema without `body`".into(),
+            })?;
+        let mut bytes = vec![];
+        write_tag(&mut bytes, field.number, WireType::LengthDelimited);
+        write_varint(&mut bytes, body.len() as u64);
+        bytes.extend_from_slice(&body);
+
+        try_decode_msg(&bytes)?
+            .body
+            .ok_or_else(|| crate::Error::Json {
+                reason: "USP Message body is missing".into(),
+            })
+    }
+}
+
+impl<'de> Deserialize<'de> for Msg {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_json_value(&value).map_err(|e| serde::de::Error::custom(e.to_string()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Record {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_json_value(&value).map_err(|e| serde::de::Error::custom(e.to_string()))
+    }
+}
@@ -0,0 +1,788 @@
+//! An implementation of the Protobuf text format for USP Records and Messages
+//!
+//! The output is compatible with the format produced by `protoc --decode usp.Msg` and
+//! `protoc --decode usp_record.Record`, field names match the ones used in the
+//! `usp-msg-1-5.proto` and `usp-record-1-5.proto` schemas.
+
+use std::fmt::{self, Write as _};
+
+use crate::error::{Error, Result, Target};
+use crate::usp::Msg;
+use crate::usp_decoder::{try_decode_msg, try_decode_record};
+use crate::usp_record::Record;
+use crate::usp_schema::{
+    self, write_tag, write_varint, FieldDescriptor, FieldKind, MessageDescriptor, WireField,
+    WireReader, WireType, WireValue,
+};
+
+/// A failure to render or parse the text format, carrying a description including its location
+#[derive(Debug)]
+struct TextError(String);
+
+impl From<fmt::Error> for TextError {
+    fn from(e: fmt::Error) -> Self {
+        Self(e.to_string())
+    }
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+type TextResult<T> = std::result::Result<T, TextError>;
+
+/// Renders Protobuf encoded data described by `descriptor` into text format
+fn print_message(
+    out: &mut String,
+    bytes: &[u8],
+    descriptor: &MessageDescriptor,
+    indent: usize,
+) -> TextResult<()> {
+    let mut reader = WireReader::new(bytes);
+    let mut fields = Vec::new();
+    while !reader.is_eof() {
+        let field = reader
+            .read_field()
+            .map_err(|e| TextError(format!("{e} at offset {}", reader.position())))?;
+        fields.push(field);
+    }
+
+    // Like protoc, print known fields in the order of their field numbers and unknown ones last
+    let known = |f: &WireField| {
+        descriptor
+            .field_by_number(f.number)
+            .filter(|d| d.kind.wire_type() == f.value.wire_type())
+    };
+    fields.sort_by_key(|f| (known(f).is_none(), f.number));
+
+    for field in fields {
+        write!(out, "{:indent$}", "")?;
+        match known(&field) {
+            Some(fd) => print_field(out, fd, &field.value, indent)?,
+            None => print_unknown_field(out, &field)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn print_field(
+    out: &mut String,
+    field: &FieldDescriptor,
+    value: &WireValue,
+    indent: usize,
+) -> TextResult<()> {
+    match (field.kind, *value) {
+        (FieldKind::String, WireValue::LengthDelimited(data)) => match std::str::from_utf8(data) {
+            Ok(s) => writeln!(out, "{}: \"{}\"", field.name, escape_str(s))?,
+            Err(_) => writeln!(out, "{}: \"{}\"", field.name, escape_bytes(data))?,
+        },
+        (FieldKind::Bytes, WireValue::LengthDelimited(data)) => {
+            writeln!(out, "{}: \"{}\"", field.name, escape_bytes(data))?;
+        }
+        (FieldKind::Bool, WireValue::Varint(v)) => writeln!(out, "{}: {}", field.name, v != 0)?,
+        (FieldKind::Fixed32, WireValue::Fixed32(v)) => writeln!(out, "{}: {v}", field.name)?,
+        (FieldKind::Uint64, WireValue::Varint(v)) => writeln!(out, "{}: {v}", field.name)?,
+        (FieldKind::Enum(descriptor), WireValue::Varint(v)) => {
+            // Enums are encoded as sign extended int32 values
+            #[allow(clippy::cast_possible_truncation)]
+            let v = v as i32;
+            match descriptor.name_of(v) {
+                Some(name) => writeln!(out, "{}: {name}", field.name)?,
+                None => writeln!(out, "{}: {v}", field.name)?,
+            }
+        }
+        (FieldKind::Message(_) | FieldKind::Map, WireValue::LengthDelimited(data)) => {
+            let descriptor = field
+                .kind
+                .message()
+                .unwrap_or(&usp_schema::STRING_MAP_ENTRY);
+            writeln!(out, "{} {{", field.name)?;
+            print_message(out, data, descriptor, indent + 2)
+                .map_err(|e| TextError(format!("while printing field {}: {e}", field.name)))?;
+            writeln!(out, "{:indent$}}}", "")?;
+        }
+        _ => unreachable!("wire types of known fields are checked by the caller"),
+    }
+
+    Ok(())
+}
+
+fn print_unknown_field(out: &mut String, field: &WireField) -> TextResult<()> {
+    match field.value {
+        WireValue::Varint(v) => writeln!(out, "{}: {v}", field.number)?,
+        WireValue::Fixed64(v) => writeln!(out, "{}: 0x{v:016x}", field.number)?,
+        WireValue::Fixed32(v) => writeln!(out, "{}: 0x{v:08x}", field.number)?,
+        WireValue::LengthDelimited(data) => {
+            writeln!(out, "{}: \"{}\"", field.number, escape_bytes(data))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Escapes a string for use in a text format string literal, keeping printable UTF-8 intact
+pub(crate) fn escape_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '"' => out.push_str("\\\""),
+            '\'' => out.push_str("\\'"),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_ascii_control() => {
+                let _ = write!(out, "\\{:03o}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes arbitrary bytes for use in a text format string literal
+pub(crate) fn escape_bytes(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len());
+    for &b in data {
+        match b {
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            b'"' => out.push_str("\\\""),
+            b'\'' => out.push_str("\\'"),
+            b'\\' => out.push_str("\\\\"),
+            0x20..=0x7e => out.push(char::from(b)),
+            _ => {
+                let _ = write!(out, "\\{b:03o}");
+            }
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Str(Vec<u8>),
+    Symbol(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ident(s) | Self::Number(s) => write!(f, "`{s}`"),
+            Self::Str(s) => write!(f, "\"{}\"", escape_bytes(s)),
+            Self::Symbol(c) => write!(f, "`{c}`"),
+        }
+    }
+}
+
+/// A tokenizer and recursive descent parser for the Protobuf text format
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    line: usize,
+    column: usize,
+    peeked: Option<(Token, usize, usize)>,
+}
+
+impl<'a> Parser<'a> {
+    const fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            line: 1,
+            column: 1,
+            peeked: None,
+        }
+    }
+
+    fn error(&self, line: usize, column: usize, msg: impl fmt::Display) -> TextError {
+        TextError(format!("line {line}, column {column}: {msg}"))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.input[self.pos..].chars().next()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek_char() {
+            if c == '#' {
+                while self.peek_char().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn lex(&mut self) -> TextResult<Option<(Token, usize, usize)>> {
+        self.skip_whitespace();
+        let (line, column) = (self.line, self.column);
+        let Some(c) = self.peek_char() else {
+            return Ok(None);
+        };
+
+        let token = if c.is_ascii_alphabetic() || c == '_' {
+            let start = self.pos;
+            while self
+                .peek_char()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+            {
+                self.bump();
+            }
+            Token::Ident(self.input[start..self.pos].to_string())
+        } else if c.is_ascii_digit() || c == '-' || c == '+' {
+            let start = self.pos;
+            self.bump();
+            while self.peek_char().is_some_and(|c| c.is_ascii_alphanumeric()) {
+                self.bump();
+            }
+            Token::Number(self.input[start..self.pos].to_string())
+        } else if c == '"' || c == '\'' {
+            Token::Str(self.lex_string()?)
+        } else {
+            self.bump();
+            Token::Symbol(c)
+        };
+
+        Ok(Some((token, line, column)))
+    }
+
+    fn lex_string(&mut self) -> TextResult<Vec<u8>> {
+        let (line, column) = (self.line, self.column);
+        let quote = self.bump();
+        let mut out = Vec::new();
+        loop {
+            let Some(c) = self.bump() else {
+                return Err(self.error(line, column, "unterminated string literal"));
+            };
+            match c {
+                '\n' => return Err(self.error(line, column, "unterminated string literal")),
+                c if Some(c) == quote => break,
+                '\\' => {
+                    let Some(esc) = self.bump() else {
+                        return Err(self.error(line, column, "unterminated string literal"));
+                    };
+                    match esc {
+                        'n' => out.push(b'\n'),
+                        'r' => out.push(b'\r'),
+                        't' => out.push(b'\t'),
+                        'a' => out.push(0x07),
+                        'b' => out.push(0x08),
+                        'f' => out.push(0x0c),
+                        'v' => out.push(0x0b),
+                        '?' | '"' | '\'' | '\\' => out.push(esc as u8),
+                        '0'..='7' => {
+                            let mut value = esc.to_digit(8).unwrap_or_default();
+                            for _ in 0..2 {
+                                match self.peek_char().and_then(|c| c.to_digit(8)) {
+                                    Some(d) => {
+                                        self.bump();
+                                        value = value * 8 + d;
+                                    }
+                                    None => break,
+                                }
+                            }
+                            let byte = u8::try_from(value).map_err(|_| {
+                                self.error(line, column, "octal escape sequence out of range")
+                            })?;
+                            out.push(byte);
+                        }
+                        'x' | 'X' => {
+                            let mut value = 0;
+                            let mut digits = 0;
+                            while digits < 2 {
+                                match self.peek_char().and_then(|c| c.to_digit(16)) {
+                                    Some(d) => {
+                                        self.bump();
+                                        value = value * 16 + d;
+                                        digits += 1;
+                                    }
+                                    None => break,
+                                }
+                            }
+                            if digits == 0 {
+                                return Err(self.error(
+                                    line,
+                                    column,
+                                    "invalid hex escape sequence",
+                                ));
+                            }
+                            out.push(value as u8);
+                        }
+                        c => {
+                            return Err(self.error(
+                                line,
+                                column,
+                                format!("invalid escape sequence `\\{c}`"),
+                            ))
+                        }
+                    }
+                }
+                c => {
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn peek(&mut self) -> TextResult<Option<&Token>> {
+        if self.peeked.is_none() {
+            self.peeked = self.lex()?;
+        }
+        Ok(self.peeked.as_ref().map(|(t, _, _)| t))
+    }
+
+    fn next(&mut self) -> TextResult<Option<(Token, usize, usize)>> {
+        match self.peeked.take() {
+            Some(t) => Ok(Some(t)),
+            None => self.lex(),
+        }
+    }
+
+    fn expect_next(&mut self, what: &str) -> TextResult<(Token, usize, usize)> {
+        self.next()?
+            .ok_or_else(|| self.error(self.line, self.column, format!("expected {what}")))
+    }
+
+    fn eat_symbol(&mut self, symbol: char) -> TextResult<bool> {
+        if self.peek()? == Some(&Token::Symbol(symbol)) {
+            self.peeked = None;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Parses the fields of a message until the `end` symbol (or the end of input if `None`) and
+    /// returns the Protobuf encoding of it
+    fn parse_message(
+        &mut self,
+        descriptor: &MessageDescriptor,
+        end: Option<char>,
+    ) -> TextResult<Vec<u8>> {
+        let mut buf = Vec::new();
+        loop {
+            match (self.peek()?, end) {
+                (None, None) => break,
+                (None, Some(end)) => {
+                    return Err(self.error(self.line, self.column, format!("expected `{end}`")))
+                }
+                (Some(Token::Symbol(c)), Some(end)) if *c == end => {
+                    self.peeked = None;
+                    break;
+                }
+                _ => (),
+            }
+
+            let (token, line, column) = self.expect_next("field name")?;
+            let field = match &token {
+                Token::Ident(name) => descriptor.field_by_name(name),
+                Token::Number(number) => number
+                    .parse::<u64>()
+                    .ok()
+                    .and_then(|n| descriptor.field_by_number(n)),
+                _ => {
+                    return Err(self.error(
+                        line,
+                        column,
+                        format!("expected field name, found {token}"),
+                    ))
+                }
+            }
+            .ok_or_else(|| {
+                self.error(
+                    line,
+                    column,
+                    format!("unknown field {token} in message {}", descriptor.name),
+                )
+            })?;
+
+            self.parse_field(field, &mut buf)?;
+
+            // Fields may optionally be separated by `,` or `;`
+            if !self.eat_symbol(',')? {
+                self.eat_symbol(';')?;
+            }
+        }
+
+        Ok(buf)
+    }
+
+    fn parse_field(&mut self, field: &FieldDescriptor, buf: &mut Vec<u8>) -> TextResult<()> {
+        let has_colon = self.eat_symbol(':')?;
+        if field.kind.message().is_none() && !has_colon {
+            return Err(self.error(
+                self.line,
+                self.column,
+                format!("expected `:` after field `{}`", field.name),
+            ));
+        }
+
+        if self.eat_symbol('[')? {
+            if !field.repeated {
+                return Err(self.error(
+                    self.line,
+                    self.column,
+                    format!("field `{}` is not repeated", field.name),
+                ));
+            }
+            if self.eat_symbol(']')? {
+                return Ok(());
+            }
+            loop {
+                self.parse_value(field, buf)?;
+                if self.eat_symbol(']')? {
+                    break;
+                }
+                if !self.eat_symbol(',')? {
+                    return Err(self.error(self.line, self.column, "expected `,` or `]`"));
+                }
+            }
+            return Ok(());
+        }
+
+        self.parse_value(field, buf)
+    }
+
+    fn parse_value(&mut self, field: &FieldDescriptor, buf: &mut Vec<u8>) -> TextResult<()> {
+        if let Some(descriptor) = field.kind.message() {
+            let end = if self.eat_symbol('{')? {
+                '}'
+            } else if self.eat_symbol('<')? {
+                '>'
+            } else {
+                return Err(self.error(
+                    self.line,
+                    self.column,
+                    format!("expected `{{` after field `{}`", field.name),
+                ));
+            };
+            let data = self.parse_message(descriptor, Some(end))?;
+            write_tag(buf, field.number, WireType::LengthDelimited);
+            write_varint(buf, data.len() as u64);
+            buf.extend_from_slice(&data);
+            return Ok(());
+        }
+
+        let (token, line, column) = self.expect_next("value")?;
+        let invalid = |this: &Self| {
+            this.error(
+                line,
+                column,
+                format!(
+                    "invalid value {token} for field `{}` of type {}",
+                    field.name,
+                    field.kind.type_name()
+                ),
+            )
+        };
+
+        match field.kind {
+            FieldKind::String | FieldKind::Bytes => {
+                let Token::Str(mut data) = token.clone() else {
+                    return Err(invalid(self));
+                };
+                // Adjacent string literals are concatenated
+                while let Some(Token::Str(_)) = self.peek()? {
+                    if let Some((Token::Str(more), _, _)) = self.next()? {
+                        data.extend(more);
+                    }
+                }
+                if matches!(field.kind, FieldKind::String) && std::str::from_utf8(&data).is_err() {
+                    return Err(self.error(
+                        line,
+                        column,
+                        format!("invalid UTF-8 in string field `{}`", field.name),
+                    ));
+                }
+                write_tag(buf, field.number, WireType::LengthDelimited);
+                write_varint(buf, data.len() as u64);
+                buf.extend_from_slice(&data);
+            }
+            FieldKind::Bool => {
+                let value = match &token {
+                    Token::Ident(s) if matches!(s.as_str(), "true" | "True" | "t") => true,
+                    Token::Ident(s) if matches!(s.as_str(), "false" | "False" | "f") => false,
+                    Token::Number(s) if s == "1" => true,
+                    Token::Number(s) if s == "0" => false,
+                    _ => return Err(invalid(self)),
+                };
+                write_tag(buf, field.number, WireType::Varint);
+                write_varint(buf, u64::from(value));
+            }
+            FieldKind::Fixed32 => {
+                let value = match &token {
+                    Token::Number(s) => parse_uint(s).and_then(|v| u32::try_from(v).ok()),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid(self))?;
+                write_tag(buf, field.number, WireType::Fixed32);
+                buf.extend_from_slice(&value.to_le_bytes());
+            }
+            FieldKind::Uint64 => {
+                let value = match &token {
+                    Token::Number(s) => parse_uint(s),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid(self))?;
+                write_tag(buf, field.number, WireType::Varint);
+                write_varint(buf, value);
+            }
+            FieldKind::Enum(descriptor) => {
+                let value = match &token {
+                    Token::Ident(s) => descriptor.value_of(s),
+                    Token::Number(s) => s.parse::<i32>().ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid(self))?;
+                write_tag(buf, field.number, WireType::Varint);
+                // Negative enum values are sign extended to 64 bits on the wire
+                write_varint(buf, i64::from(value) as u64);
+            }
+            FieldKind::Message(_) | FieldKind::Map => unreachable!("handled above"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses an unsigned integer in decimal, hexadecimal (`0x`) or octal (leading `0`) notation
+fn parse_uint(s: &str) -> Option<u64> {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if s.len() > 1 && s.starts_with('0') {
+        u64::from_str_radix(&s[1..], 8).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// Parses a text format representation of a message described by `descriptor` into its
+/// Protobuf encoding
+fn parse_prototxt(text: &str, descriptor: &MessageDescriptor) -> TextResult<Vec<u8>> {
+    Parser::new(text).parse_message(descriptor, None)
+}
+
+/// Renders Protobuf encoded data of the message described by `descriptor` into text format
+fn render_prototxt(bytes: &[u8], descriptor: &MessageDescriptor) -> TextResult<String> {
+    let mut out = String::new();
+    print_message(&mut out, bytes, descriptor, 0)?;
+    Ok(out)
+}
+
+/// Implementation of Protobuf text format support for `Msg`s
+impl Msg {
+    /// Render the `Msg` into the Protobuf text format, like `protoc --decode usp.Msg` does
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Msg` structure
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_msg;
+    /// let msg =
+    ///     try_decode_msg(&[
+    ///         0x0a, 0x08, 0x0a, 0x04, 0x74, 0x65, 0x73, 0x74,
+    ///         0x10, 0x03, 0x12, 0x28, 0x0a, 0x26, 0x42, 0x24,
+    ///         0x0a, 0x05, 0x6e, 0x6f, 0x74, 0x69, 0x66, 0x10,
+    ///         0x01, 0x42, 0x19, 0x0a, 0x06, 0x30, 0x30, 0x34,
+    ///         0x34, 0x46, 0x46, 0x12, 0x03, 0x46, 0x6f, 0x6f,
+    ///         0x1a, 0x05, 0x30, 0x31, 0x32, 0x33, 0x34, 0x22,
+    ///         0x03, 0x31, 0x2e, 0x33,
+    ///     ]).unwrap();
+    /// assert_eq!(msg.to_prototxt().unwrap(), "header {\n  msg_id: \"test\"\n  msg_type: NOTIFY\n}\nbody {\n  request {\n    notify {\n      subscription_id: \"notif\"\n      send_resp: true\n      on_board_req {\n        oui: \"0044FF\"\n        product_class: \"Foo\"\n        serial_number: \"01234\"\n        agent_supported_protocol_versions: \"1.3\"\n      }\n    }\n  }\n}\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Msg` cannot be serialized into a text format representation
+    pub fn to_prototxt(&self) -> Result<String> {
+        render_prototxt(&self.to_vec()?, &usp_schema::MSG).map_err(|e| Error::Encode {
+            target: Target::Msg,
+            reason: format!("while rendering Protobuf text format: {e}"),
+        })
+    }
+
+    /// Parses a `Msg` from the Protobuf text format, like `protoc --encode usp.Msg` does
+    ///
+    /// Like [`try_decode_msg`] this function does not perform the additional checks required by
+    /// the USP specification, use [`Msg::check_validity`] for that.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text format representation of the USP Message
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp::Msg;
+    /// let msg = Msg::from_prototxt(r#"
+    ///     header { msg_id: "get" msg_type: GET }
+    ///     body {
+    ///       request {
+    ///         get {
+    ///           param_paths: "Device.DeviceInfo."
+    ///           max_depth: 1
+    ///         }
+    ///       }
+    ///     }
+    /// "#).unwrap();
+    /// assert_eq!(msg.msg_id(), "get");
+    /// assert!(msg.is_request());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description including the position
+    /// of the encountered error if the text cannot be parsed as a USP Msg
+    pub fn from_prototxt(text: &str) -> Result<Self> {
+        let bytes = parse_prototxt(text, &usp_schema::MSG).map_err(|e| Error::Prototxt {
+            target: Target::Msg,
+            reason: e.to_string(),
+        })?;
+        try_decode_msg(&bytes)
+    }
+}
+
+/// Implementation of Protobuf text format support for `Record`s
+impl Record {
+    /// Render the `Record` into the Protobuf text format, like `protoc --decode usp_record.Record`
+    /// does
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - A USP `Record` structure
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_record;
+    /// let record =
+    ///     try_decode_record(&[
+    ///         0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64,
+    ///         0x6f, 0x63, 0x3a, 0x3a, 0x74, 0x6f, 0x1a, 0x09,
+    ///         0x64, 0x6f, 0x63, 0x3a, 0x3a, 0x66, 0x72, 0x6f,
+    ///         0x6d, 0x52, 0x09, 0x08, 0x01, 0x12, 0x05, 0x74,
+    ///         0x6f, 0x70, 0x69, 0x63,
+    ///     ]).unwrap();
+    /// assert_eq!(record.to_prototxt().unwrap(), "version: \"1.3\"\nto_id: \"doc::to\"\nfrom_id: \"doc::from\"\nmqtt_connect {\n  version: V5\n  subscribed_topic: \"topic\"\n}\n");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the `Record` cannot be serialized into a text format representation
+    pub fn to_prototxt(&self) -> Result<String> {
+        render_prototxt(&self.to_vec()?, &usp_schema::RECORD).map_err(|e| Error::Encode {
+            target: Target::Record,
+            reason: format!("while rendering Protobuf text format: {e}"),
+        })
+    }
+
+    /// Parses a `Record` from the Protobuf text format, like
+    /// `protoc --encode usp_record.Record` does
+    ///
+    /// Like [`try_decode_record`] this function does not perform the additional checks required
+    /// by the USP specification, use [`Record::check_validity`] for that.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text format representation of the USP Record
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_record::Record;
+    /// let record = Record::from_prototxt(r#"
+    ///     version: "1.3"
+    ///     to_id: "doc::to"
+    ///     from_id: "doc::from"
+    ///     disconnect { reason: "Bye" reason_code: 7003 }
+    /// "#).unwrap();
+    /// assert_eq!(record.to_id, "doc::to");
+    /// assert!(record.check_validity().is_ok());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description including the position
+    /// of the encountered error if the text cannot be parsed as a USP Record
+    pub fn from_prototxt(text: &str) -> Result<Self> {
+        let bytes = parse_prototxt(text, &usp_schema::RECORD).map_err(|e| Error::Prototxt {
+            target: Target::Record,
+            reason: e.to_string(),
+        })?;
+        try_decode_record(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_escaping() {
+        let s = "a\"b'c\\d\ne\u{1}f\u{e4}";
+        let escaped = escape_str(s);
+        assert_eq!(escaped, "a\\\"b\\'c\\\\d\\ne\\001f\u{e4}");
+
+        let input = format!("\"{escaped}\"");
+        let mut parser = Parser::new(&input);
+        let Some((Token::Str(data), _, _)) = parser.next().unwrap() else {
+            panic!("Expected a string token");
+        };
+        assert_eq!(data, s.as_bytes());
+    }
+
+    #[test]
+    fn bytes_escaping() {
+        let data = [0x00, 0x0a, 0x41, 0xff, 0x22];
+        let escaped = escape_bytes(&data);
+        assert_eq!(escaped, "\\000\\nA\\377\\\"");
+
+        let input = format!("'{escaped}'");
+        let mut parser = Parser::new(&input);
+        let Some((Token::Str(parsed), _, _)) = parser.next().unwrap() else {
+            panic!("Expected a string token");
+        };
+        assert_eq!(parsed, data);
+    }
+
+    #[test]
+    fn unknown_field_position() {
+        let err = Msg::from_prototxt("header {\n  msg_id: \"a\"\n  foo: 1\n}\n").unwrap_err();
+        assert!(format!("{err:#}").contains("line 3, column 3: unknown field `foo`"));
+    }
+
+    #[test]
+    fn invalid_enum_value() {
+        let err = Msg::from_prototxt("header { msg_type: FOO }").unwrap_err();
+        assert!(format!("{err:#}").contains("invalid value `FOO` for field `msg_type`"));
+    }
+}
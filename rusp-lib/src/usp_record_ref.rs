@@ -0,0 +1,3 @@
+pub mod usp_record;
+
+pub use self::usp_record::*;
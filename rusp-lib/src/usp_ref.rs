@@ -0,0 +1,3 @@
+pub mod usp;
+
+pub use self::usp::*;
@@ -0,0 +1,1489 @@
+//! Static descriptions of the USP Msg and Record Protobuf schemas
+//!
+//! The generated bindings in [`crate::usp`] and [`crate::usp_record`] only know how to move bytes
+//! in and out of the native types, they do not retain any information about the schema they were
+//! generated from. Some functionality, like the text format or the annotated hex dump, needs to
+//! walk Protobuf encoded data generically and map field numbers to their names and types, which
+//! is what the descriptors in this module are for.
+//!
+//! The descriptors mirror `usp-msg-1-5.proto` and `usp-record-1-5.proto` and need to be kept in
+//! sync with them whenever the bindings are regenerated.
+
+/// The Protobuf wire type of an encoded field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WireType {
+    Varint,
+    Fixed64,
+    LengthDelimited,
+    StartGroup,
+    EndGroup,
+    Fixed32,
+}
+
+impl WireType {
+    /// Extracts the wire type from a field tag
+    pub(crate) const fn from_tag(tag: u64) -> Option<Self> {
+        match tag & 0x7 {
+            0 => Some(Self::Varint),
+            1 => Some(Self::Fixed64),
+            2 => Some(Self::LengthDelimited),
+            3 => Some(Self::StartGroup),
+            4 => Some(Self::EndGroup),
+            5 => Some(Self::Fixed32),
+            _ => None,
+        }
+    }
+
+    /// Returns the numeric representation used in the tag
+    pub(crate) const fn as_u64(self) -> u64 {
+        match self {
+            Self::Varint => 0,
+            Self::Fixed64 => 1,
+            Self::LengthDelimited => 2,
+            Self::StartGroup => 3,
+            Self::EndGroup => 4,
+            Self::Fixed32 => 5,
+        }
+    }
+
+    /// Returns the name of the wire type as used in the Protobuf encoding documentation
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Varint => "VARINT",
+            Self::Fixed64 => "I64",
+            Self::LengthDelimited => "LEN",
+            Self::StartGroup => "SGROUP",
+            Self::EndGroup => "EGROUP",
+            Self::Fixed32 => "I32",
+        }
+    }
+}
+
+/// The type of a field as declared in the schema
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum FieldKind {
+    String,
+    Bytes,
+    Bool,
+    Fixed32,
+    Uint64,
+    Enum(&'static EnumDescriptor),
+    Message(&'static MessageDescriptor),
+    /// A `map<string, string>`, encoded as a repeated [`STRING_MAP_ENTRY`] message
+    Map,
+}
+
+impl FieldKind {
+    /// Returns the wire type a field of this kind is encoded with
+    pub(crate) const fn wire_type(self) -> WireType {
+        match self {
+            Self::String | Self::Bytes | Self::Message(_) | Self::Map => WireType::LengthDelimited,
+            Self::Bool | Self::Uint64 | Self::Enum(_) => WireType::Varint,
+            Self::Fixed32 => WireType::Fixed32,
+        }
+    }
+
+    /// Returns the descriptor of the embedded message, if this kind has one
+    pub(crate) const fn message(self) -> Option<&'static MessageDescriptor> {
+        match self {
+            Self::Message(descriptor) => Some(descriptor),
+            Self::Map => Some(&STRING_MAP_ENTRY),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the type as written in the schema
+    pub(crate) const fn type_name(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Bytes => "bytes",
+            Self::Bool => "bool",
+            Self::Fixed32 => "fixed32",
+            Self::Uint64 => "uint64",
+            Self::Enum(descriptor) => descriptor.name,
+            Self::Message(descriptor) => descriptor.name,
+            Self::Map => "map<string, string>",
+        }
+    }
+}
+
+/// The description of a single field of a message
+#[derive(Debug)]
+pub(crate) struct FieldDescriptor {
+    pub(crate) name: &'static str,
+    pub(crate) number: u32,
+    pub(crate) kind: FieldKind,
+    pub(crate) repeated: bool,
+}
+
+impl FieldDescriptor {
+    const fn single(name: &'static str, number: u32, kind: FieldKind) -> Self {
+        Self {
+            name,
+            number,
+            kind,
+            repeated: false,
+        }
+    }
+
+    const fn repeated(name: &'static str, number: u32, kind: FieldKind) -> Self {
+        Self {
+            name,
+            number,
+            kind,
+            repeated: true,
+        }
+    }
+}
+
+/// The description of a message type
+#[derive(Debug)]
+pub(crate) struct MessageDescriptor {
+    /// The fully qualified name of the message, e.g. `usp.Get`
+    pub(crate) name: &'static str,
+    pub(crate) fields: &'static [FieldDescriptor],
+}
+
+impl MessageDescriptor {
+    /// Looks up a field by its number
+    pub(crate) fn field_by_number(&self, number: u64) -> Option<&'static FieldDescriptor> {
+        self.fields.iter().find(|f| u64::from(f.number) == number)
+    }
+
+    /// Looks up a field by its name
+    pub(crate) fn field_by_name(&self, name: &str) -> Option<&'static FieldDescriptor> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// The description of an enumeration type
+#[derive(Debug)]
+pub(crate) struct EnumDescriptor {
+    /// The fully qualified name of the enumeration, e.g. `usp.Header.MsgType`
+    pub(crate) name: &'static str,
+    pub(crate) values: &'static [(&'static str, i32)],
+}
+
+impl EnumDescriptor {
+    /// Looks up the symbolic name of a numeric value
+    pub(crate) fn name_of(&self, value: i32) -> Option<&'static str> {
+        self.values
+            .iter()
+            .find(|(_, v)| *v == value)
+            .map(|(n, _)| *n)
+    }
+
+    /// Looks up the numeric value of a symbolic name
+    pub(crate) fn value_of(&self, name: &str) -> Option<i32> {
+        self.values
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| *v)
+    }
+}
+
+/// An error encountered while walking Protobuf encoded data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WireError {
+    /// The data ended in the middle of a field
+    Truncated,
+    /// A varint was longer than 10 bytes
+    InvalidVarint,
+    /// A tag contained one of the reserved wire types 6 or 7
+    InvalidWireType(u64),
+    /// A tag contained the reserved field number 0
+    InvalidFieldNumber,
+}
+
+impl std::fmt::Display for WireError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "unexpected end of data"),
+            Self::InvalidVarint => write!(f, "invalid varint"),
+            Self::InvalidWireType(wt) => write!(f, "invalid wire type {wt}"),
+            Self::InvalidFieldNumber => write!(f, "invalid field number 0"),
+        }
+    }
+}
+
+/// The raw value of a field as found on the wire
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum WireValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    LengthDelimited(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl WireValue<'_> {
+    pub(crate) const fn wire_type(&self) -> WireType {
+        match self {
+            Self::Varint(_) => WireType::Varint,
+            Self::Fixed64(_) => WireType::Fixed64,
+            Self::LengthDelimited(_) => WireType::LengthDelimited,
+            Self::Fixed32(_) => WireType::Fixed32,
+        }
+    }
+}
+
+/// A single field as found on the wire
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WireField<'a> {
+    pub(crate) number: u64,
+    pub(crate) value: WireValue<'a>,
+}
+
+/// A minimal, schema agnostic reader for Protobuf encoded data
+pub(crate) struct WireReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WireReader<'a> {
+    pub(crate) const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Returns the offset of the next byte to be read
+    pub(crate) const fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub(crate) const fn is_eof(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub(crate) fn read_varint(&mut self) -> Result<u64, WireError> {
+        let mut value = 0u64;
+        for i in 0..10 {
+            let byte = *self.bytes.get(self.pos).ok_or(WireError::Truncated)?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(WireError::InvalidVarint)
+    }
+
+    fn read_slice(&mut self, len: usize) -> Result<&'a [u8], WireError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(WireError::Truncated)?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Reads the next tag, returning the field number and the wire type
+    pub(crate) fn read_tag(&mut self) -> Result<(u64, WireType), WireError> {
+        let tag = self.read_varint()?;
+        let wire_type = WireType::from_tag(tag).ok_or(WireError::InvalidWireType(tag & 0x7))?;
+        let number = tag >> 3;
+        if number == 0 {
+            return Err(WireError::InvalidFieldNumber);
+        }
+        Ok((number, wire_type))
+    }
+
+    /// Reads the value of a field with the given wire type
+    pub(crate) fn read_value(&mut self, wire_type: WireType) -> Result<WireValue<'a>, WireError> {
+        match wire_type {
+            WireType::Varint => self.read_varint().map(WireValue::Varint),
+            WireType::Fixed64 => self
+                .read_slice(8)
+                .map(|s| WireValue::Fixed64(u64::from_le_bytes(s.try_into().unwrap_or_default()))),
+            WireType::Fixed32 => self
+                .read_slice(4)
+                .map(|s| WireValue::Fixed32(u32::from_le_bytes(s.try_into().unwrap_or_default()))),
+            WireType::LengthDelimited => {
+                let len = self.read_varint()?;
+                let len = usize::try_from(len).map_err(|_| WireError::Truncated)?;
+                self.read_slice(len).map(WireValue::LengthDelimited)
+            }
+            // Groups are deprecated and not used by any USP schema
+            WireType::StartGroup | WireType::EndGroup => {
+                Err(WireError::InvalidWireType(wire_type.as_u64()))
+            }
+        }
+    }
+
+    /// Reads the next complete field
+    pub(crate) fn read_field(&mut self) -> Result<WireField<'a>, WireError> {
+        let (number, wire_type) = self.read_tag()?;
+        let value = self.read_value(wire_type)?;
+        Ok(WireField { number, value })
+    }
+}
+
+/// Appends a varint to the buffer
+pub(crate) fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Appends a tag for the given field number and wire type to the buffer
+pub(crate) fn write_tag(buf: &mut Vec<u8>, number: u32, wire_type: WireType) {
+    write_varint(buf, (u64::from(number) << 3) | wire_type.as_u64());
+}
+
+/// The implicit entry message used by the encoding of `map<string, string>` fields
+pub(crate) static STRING_MAP_ENTRY: MessageDescriptor = MessageDescriptor {
+    name: "MapEntry",
+    fields: &[
+        FieldDescriptor::single("key", 1, FieldKind::String),
+        FieldDescriptor::single("value", 2, FieldKind::String),
+    ],
+};
+
+// usp-msg-1-5.proto
+
+pub(crate) static MSG: MessageDescriptor = MessageDescriptor {
+    name: "usp.Msg",
+    fields: &[
+        FieldDescriptor::single("header", 1, FieldKind::Message(&HEADER)),
+        FieldDescriptor::single("body", 2, FieldKind::Message(&BODY)),
+    ],
+};
+
+pub(crate) static HEADER: MessageDescriptor = MessageDescriptor {
+    name: "usp.Header",
+    fields: &[
+        FieldDescriptor::single("msg_id", 1, FieldKind::String),
+        FieldDescriptor::single("msg_type", 2, FieldKind::Enum(&HEADER_MSG_TYPE)),
+    ],
+};
+
+pub(crate) static BODY: MessageDescriptor = MessageDescriptor {
+    name: "usp.Body",
+    fields: &[
+        FieldDescriptor::single("request", 1, FieldKind::Message(&REQUEST)),
+        FieldDescriptor::single("response", 2, FieldKind::Message(&RESPONSE)),
+        FieldDescriptor::single("error", 3, FieldKind::Message(&ERROR)),
+    ],
+};
+
+pub(crate) static REQUEST: MessageDescriptor = MessageDescriptor {
+    name: "usp.Request",
+    fields: &[
+        FieldDescriptor::single("get", 1, FieldKind::Message(&GET)),
+        FieldDescriptor::single("get_supported_dm", 2, FieldKind::Message(&GET_SUPPORTED_DM)),
+        FieldDescriptor::single("get_instances", 3, FieldKind::Message(&GET_INSTANCES)),
+        FieldDescriptor::single("set", 4, FieldKind::Message(&SET)),
+        FieldDescriptor::single("add", 5, FieldKind::Message(&ADD)),
+        FieldDescriptor::single("delete", 6, FieldKind::Message(&DELETE)),
+        FieldDescriptor::single("operate", 7, FieldKind::Message(&OPERATE)),
+        FieldDescriptor::single("notify", 8, FieldKind::Message(&NOTIFY)),
+        FieldDescriptor::single(
+            "get_supported_protocol",
+            9,
+            FieldKind::Message(&GET_SUPPORTED_PROTOCOL),
+        ),
+        FieldDescriptor::single("register", 10, FieldKind::Message(&REGISTER)),
+        FieldDescriptor::single("deregister", 11, FieldKind::Message(&DEREGISTER)),
+    ],
+};
+
+pub(crate) static RESPONSE: MessageDescriptor = MessageDescriptor {
+    name: "usp.Response",
+    fields: &[
+        FieldDescriptor::single("get_resp", 1, FieldKind::Message(&GET_RESP)),
+        FieldDescriptor::single(
+            "get_supported_dm_resp",
+            2,
+            FieldKind::Message(&GET_SUPPORTED_DM_RESP),
+        ),
+        FieldDescriptor::single(
+            "get_instances_resp",
+            3,
+            FieldKind::Message(&GET_INSTANCES_RESP),
+        ),
+        FieldDescriptor::single("set_resp", 4, FieldKind::Message(&SET_RESP)),
+        FieldDescriptor::single("add_resp", 5, FieldKind::Message(&ADD_RESP)),
+        FieldDescriptor::single("delete_resp", 6, FieldKind::Message(&DELETE_RESP)),
+        FieldDescriptor::single("operate_resp", 7, FieldKind::Message(&OPERATE_RESP)),
+        FieldDescriptor::single("notify_resp", 8, FieldKind::Message(&NOTIFY_RESP)),
+        FieldDescriptor::single(
+            "get_supported_protocol_resp",
+            9,
+            FieldKind::Message(&GET_SUPPORTED_PROTOCOL_RESP),
+        ),
+        FieldDescriptor::single("register_resp", 10, FieldKind::Message(&REGISTER_RESP)),
+        FieldDescriptor::single("deregister_resp", 11, FieldKind::Message(&DEREGISTER_RESP)),
+    ],
+};
+
+pub(crate) static ERROR: MessageDescriptor = MessageDescriptor {
+    name: "usp.Error",
+    fields: &[
+        FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 2, FieldKind::String),
+        FieldDescriptor::repeated("param_errs", 3, FieldKind::Message(&ERROR_PARAM_ERROR)),
+    ],
+};
+
+pub(crate) static ERROR_PARAM_ERROR: MessageDescriptor = MessageDescriptor {
+    name: "usp.Error.ParamError",
+    fields: &[
+        FieldDescriptor::single("param_path", 1, FieldKind::String),
+        FieldDescriptor::single("err_code", 2, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 3, FieldKind::String),
+    ],
+};
+
+pub(crate) static GET: MessageDescriptor = MessageDescriptor {
+    name: "usp.Get",
+    fields: &[
+        FieldDescriptor::repeated("param_paths", 1, FieldKind::String),
+        FieldDescriptor::single("max_depth", 2, FieldKind::Fixed32),
+    ],
+};
+
+pub(crate) static GET_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetResp",
+    fields: &[FieldDescriptor::repeated(
+        "req_path_results",
+        1,
+        FieldKind::Message(&GET_RESP_REQUESTED_PATH_RESULT),
+    )],
+};
+
+pub(crate) static GET_RESP_REQUESTED_PATH_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetResp.RequestedPathResult",
+    fields: &[
+        FieldDescriptor::single("requested_path", 1, FieldKind::String),
+        FieldDescriptor::single("err_code", 2, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 3, FieldKind::String),
+        FieldDescriptor::repeated(
+            "resolved_path_results",
+            4,
+            FieldKind::Message(&GET_RESP_RESOLVED_PATH_RESULT),
+        ),
+    ],
+};
+
+pub(crate) static GET_RESP_RESOLVED_PATH_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetResp.ResolvedPathResult",
+    fields: &[
+        FieldDescriptor::single("resolved_path", 1, FieldKind::String),
+        FieldDescriptor::repeated("result_params", 2, FieldKind::Map),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_DM: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetSupportedDM",
+    fields: &[
+        FieldDescriptor::repeated("obj_paths", 1, FieldKind::String),
+        FieldDescriptor::single("first_level_only", 2, FieldKind::Bool),
+        FieldDescriptor::single("return_commands", 3, FieldKind::Bool),
+        FieldDescriptor::single("return_events", 4, FieldKind::Bool),
+        FieldDescriptor::single("return_params", 5, FieldKind::Bool),
+        FieldDescriptor::single("return_unique_key_sets", 6, FieldKind::Bool),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_DM_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetSupportedDMResp",
+    fields: &[FieldDescriptor::repeated(
+        "req_obj_results",
+        1,
+        FieldKind::Message(&GET_SUPPORTED_DM_RESP_REQUESTED_OBJECT_RESULT),
+    )],
+};
+
+pub(crate) static GET_SUPPORTED_DM_RESP_REQUESTED_OBJECT_RESULT: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.GetSupportedDMResp.RequestedObjectResult",
+        fields: &[
+            FieldDescriptor::single("req_obj_path", 1, FieldKind::String),
+            FieldDescriptor::single("err_code", 2, FieldKind::Fixed32),
+            FieldDescriptor::single("err_msg", 3, FieldKind::String),
+            FieldDescriptor::single("data_model_inst_uri", 4, FieldKind::String),
+            FieldDescriptor::repeated(
+                "supported_objs",
+                5,
+                FieldKind::Message(&GET_SUPPORTED_DM_RESP_SUPPORTED_OBJECT_RESULT),
+            ),
+        ],
+    };
+
+pub(crate) static GET_SUPPORTED_DM_RESP_SUPPORTED_OBJECT_RESULT: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.GetSupportedDMResp.SupportedObjectResult",
+        fields: &[
+            FieldDescriptor::single("supported_obj_path", 1, FieldKind::String),
+            FieldDescriptor::single(
+                "access",
+                2,
+                FieldKind::Enum(&GET_SUPPORTED_DM_RESP_OBJ_ACCESS_TYPE),
+            ),
+            FieldDescriptor::single("is_multi_instance", 3, FieldKind::Bool),
+            FieldDescriptor::repeated(
+                "supported_commands",
+                4,
+                FieldKind::Message(&GET_SUPPORTED_DM_RESP_SUPPORTED_COMMAND_RESULT),
+            ),
+            FieldDescriptor::repeated(
+                "supported_events",
+                5,
+                FieldKind::Message(&GET_SUPPORTED_DM_RESP_SUPPORTED_EVENT_RESULT),
+            ),
+            FieldDescriptor::repeated(
+                "supported_params",
+                6,
+                FieldKind::Message(&GET_SUPPORTED_DM_RESP_SUPPORTED_PARAM_RESULT),
+            ),
+            FieldDescriptor::repeated("divergent_paths", 7, FieldKind::String),
+            FieldDescriptor::repeated(
+                "unique_key_sets",
+                8,
+                FieldKind::Message(&GET_SUPPORTED_DM_RESP_SUPPORTED_UNIQUE_KEY_SET),
+            ),
+        ],
+    };
+
+pub(crate) static GET_SUPPORTED_DM_RESP_SUPPORTED_PARAM_RESULT: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.GetSupportedDMResp.SupportedParamResult",
+        fields: &[
+            FieldDescriptor::single("param_name", 1, FieldKind::String),
+            FieldDescriptor::single(
+                "access",
+                2,
+                FieldKind::Enum(&GET_SUPPORTED_DM_RESP_PARAM_ACCESS_TYPE),
+            ),
+            FieldDescriptor::single(
+                "value_type",
+                3,
+                FieldKind::Enum(&GET_SUPPORTED_DM_RESP_PARAM_VALUE_TYPE),
+            ),
+            FieldDescriptor::single(
+                "value_change",
+                4,
+                FieldKind::Enum(&GET_SUPPORTED_DM_RESP_VALUE_CHANGE_TYPE),
+            ),
+        ],
+    };
+
+pub(crate) static GET_SUPPORTED_DM_RESP_SUPPORTED_COMMAND_RESULT: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.GetSupportedDMResp.SupportedCommandResult",
+        fields: &[
+            FieldDescriptor::single("command_name", 1, FieldKind::String),
+            FieldDescriptor::repeated("input_arg_names", 2, FieldKind::String),
+            FieldDescriptor::repeated("output_arg_names", 3, FieldKind::String),
+            FieldDescriptor::single(
+                "command_type",
+                4,
+                FieldKind::Enum(&GET_SUPPORTED_DM_RESP_CMD_TYPE),
+            ),
+        ],
+    };
+
+pub(crate) static GET_SUPPORTED_DM_RESP_SUPPORTED_EVENT_RESULT: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.GetSupportedDMResp.SupportedEventResult",
+        fields: &[
+            FieldDescriptor::single("event_name", 1, FieldKind::String),
+            FieldDescriptor::repeated("arg_names", 2, FieldKind::String),
+        ],
+    };
+
+pub(crate) static GET_SUPPORTED_DM_RESP_SUPPORTED_UNIQUE_KEY_SET: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.GetSupportedDMResp.SupportedUniqueKeySet",
+        fields: &[FieldDescriptor::repeated("key_names", 1, FieldKind::String)],
+    };
+
+pub(crate) static GET_INSTANCES: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetInstances",
+    fields: &[
+        FieldDescriptor::repeated("obj_paths", 1, FieldKind::String),
+        FieldDescriptor::single("first_level_only", 2, FieldKind::Bool),
+    ],
+};
+
+pub(crate) static GET_INSTANCES_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetInstancesResp",
+    fields: &[FieldDescriptor::repeated(
+        "req_path_results",
+        1,
+        FieldKind::Message(&GET_INSTANCES_RESP_REQUESTED_PATH_RESULT),
+    )],
+};
+
+pub(crate) static GET_INSTANCES_RESP_REQUESTED_PATH_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetInstancesResp.RequestedPathResult",
+    fields: &[
+        FieldDescriptor::single("requested_path", 1, FieldKind::String),
+        FieldDescriptor::single("err_code", 2, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 3, FieldKind::String),
+        FieldDescriptor::repeated(
+            "curr_insts",
+            4,
+            FieldKind::Message(&GET_INSTANCES_RESP_CURR_INSTANCE),
+        ),
+    ],
+};
+
+pub(crate) static GET_INSTANCES_RESP_CURR_INSTANCE: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetInstancesResp.CurrInstance",
+    fields: &[
+        FieldDescriptor::single("instantiated_obj_path", 1, FieldKind::String),
+        FieldDescriptor::repeated("unique_keys", 2, FieldKind::Map),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_PROTOCOL: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetSupportedProtocol",
+    fields: &[FieldDescriptor::single(
+        "controller_supported_protocol_versions",
+        1,
+        FieldKind::String,
+    )],
+};
+
+pub(crate) static GET_SUPPORTED_PROTOCOL_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.GetSupportedProtocolResp",
+    fields: &[FieldDescriptor::single(
+        "agent_supported_protocol_versions",
+        1,
+        FieldKind::String,
+    )],
+};
+
+pub(crate) static ADD: MessageDescriptor = MessageDescriptor {
+    name: "usp.Add",
+    fields: &[
+        FieldDescriptor::single("allow_partial", 1, FieldKind::Bool),
+        FieldDescriptor::repeated("create_objs", 2, FieldKind::Message(&ADD_CREATE_OBJECT)),
+    ],
+};
+
+pub(crate) static ADD_CREATE_OBJECT: MessageDescriptor = MessageDescriptor {
+    name: "usp.Add.CreateObject",
+    fields: &[
+        FieldDescriptor::single("obj_path", 1, FieldKind::String),
+        FieldDescriptor::repeated(
+            "param_settings",
+            2,
+            FieldKind::Message(&ADD_CREATE_PARAM_SETTING),
+        ),
+    ],
+};
+
+pub(crate) static ADD_CREATE_PARAM_SETTING: MessageDescriptor = MessageDescriptor {
+    name: "usp.Add.CreateParamSetting",
+    fields: &[
+        FieldDescriptor::single("param", 1, FieldKind::String),
+        FieldDescriptor::single("value", 2, FieldKind::String),
+        FieldDescriptor::single("required", 3, FieldKind::Bool),
+    ],
+};
+
+pub(crate) static ADD_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.AddResp",
+    fields: &[FieldDescriptor::repeated(
+        "created_obj_results",
+        1,
+        FieldKind::Message(&ADD_RESP_CREATED_OBJECT_RESULT),
+    )],
+};
+
+pub(crate) static ADD_RESP_CREATED_OBJECT_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.AddResp.CreatedObjectResult",
+    fields: &[
+        FieldDescriptor::single("requested_path", 1, FieldKind::String),
+        FieldDescriptor::single(
+            "oper_status",
+            2,
+            FieldKind::Message(&ADD_RESP_CREATED_OBJECT_RESULT_OPERATION_STATUS),
+        ),
+    ],
+};
+
+pub(crate) static ADD_RESP_CREATED_OBJECT_RESULT_OPERATION_STATUS: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.AddResp.CreatedObjectResult.OperationStatus",
+        fields: &[
+            FieldDescriptor::single(
+                "oper_failure",
+                1,
+                FieldKind::Message(
+                    &ADD_RESP_CREATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_FAILURE,
+                ),
+            ),
+            FieldDescriptor::single(
+                "oper_success",
+                2,
+                FieldKind::Message(
+                    &ADD_RESP_CREATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_SUCCESS,
+                ),
+            ),
+        ],
+    };
+
+pub(crate) static ADD_RESP_CREATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_FAILURE:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.AddResp.CreatedObjectResult.OperationStatus.OperationFailure",
+    fields: &[
+        FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static ADD_RESP_CREATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_SUCCESS:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.AddResp.CreatedObjectResult.OperationStatus.OperationSuccess",
+    fields: &[
+        FieldDescriptor::single("instantiated_path", 1, FieldKind::String),
+        FieldDescriptor::repeated(
+            "param_errs",
+            2,
+            FieldKind::Message(&ADD_RESP_PARAMETER_ERROR),
+        ),
+        FieldDescriptor::repeated("unique_keys", 3, FieldKind::Map),
+    ],
+};
+
+pub(crate) static ADD_RESP_PARAMETER_ERROR: MessageDescriptor = MessageDescriptor {
+    name: "usp.AddResp.ParameterError",
+    fields: &[
+        FieldDescriptor::single("param", 1, FieldKind::String),
+        FieldDescriptor::single("err_code", 2, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 3, FieldKind::String),
+    ],
+};
+
+pub(crate) static DELETE: MessageDescriptor = MessageDescriptor {
+    name: "usp.Delete",
+    fields: &[
+        FieldDescriptor::single("allow_partial", 1, FieldKind::Bool),
+        FieldDescriptor::repeated("obj_paths", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static DELETE_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.DeleteResp",
+    fields: &[FieldDescriptor::repeated(
+        "deleted_obj_results",
+        1,
+        FieldKind::Message(&DELETE_RESP_DELETED_OBJECT_RESULT),
+    )],
+};
+
+pub(crate) static DELETE_RESP_DELETED_OBJECT_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.DeleteResp.DeletedObjectResult",
+    fields: &[
+        FieldDescriptor::single("requested_path", 1, FieldKind::String),
+        FieldDescriptor::single(
+            "oper_status",
+            2,
+            FieldKind::Message(&DELETE_RESP_DELETED_OBJECT_RESULT_OPERATION_STATUS),
+        ),
+    ],
+};
+
+pub(crate) static DELETE_RESP_DELETED_OBJECT_RESULT_OPERATION_STATUS: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.DeleteResp.DeletedObjectResult.OperationStatus",
+        fields: &[
+            FieldDescriptor::single(
+                "oper_failure",
+                1,
+                FieldKind::Message(
+                    &DELETE_RESP_DELETED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_FAILURE,
+                ),
+            ),
+            FieldDescriptor::single(
+                "oper_success",
+                2,
+                FieldKind::Message(
+                    &DELETE_RESP_DELETED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_SUCCESS,
+                ),
+            ),
+        ],
+    };
+
+pub(crate) static DELETE_RESP_DELETED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_FAILURE:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.DeleteResp.DeletedObjectResult.OperationStatus.OperationFailure",
+    fields: &[
+        FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static DELETE_RESP_DELETED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_SUCCESS:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.DeleteResp.DeletedObjectResult.OperationStatus.OperationSuccess",
+    fields: &[
+        FieldDescriptor::repeated("affected_paths", 1, FieldKind::String),
+        FieldDescriptor::repeated(
+            "unaffected_path_errs",
+            2,
+            FieldKind::Message(&DELETE_RESP_UNAFFECTED_PATH_ERROR),
+        ),
+    ],
+};
+
+pub(crate) static DELETE_RESP_UNAFFECTED_PATH_ERROR: MessageDescriptor = MessageDescriptor {
+    name: "usp.DeleteResp.UnaffectedPathError",
+    fields: &[
+        FieldDescriptor::single("unaffected_path", 1, FieldKind::String),
+        FieldDescriptor::single("err_code", 2, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 3, FieldKind::String),
+    ],
+};
+
+pub(crate) static SET: MessageDescriptor = MessageDescriptor {
+    name: "usp.Set",
+    fields: &[
+        FieldDescriptor::single("allow_partial", 1, FieldKind::Bool),
+        FieldDescriptor::repeated("update_objs", 2, FieldKind::Message(&SET_UPDATE_OBJECT)),
+    ],
+};
+
+pub(crate) static SET_UPDATE_OBJECT: MessageDescriptor = MessageDescriptor {
+    name: "usp.Set.UpdateObject",
+    fields: &[
+        FieldDescriptor::single("obj_path", 1, FieldKind::String),
+        FieldDescriptor::repeated(
+            "param_settings",
+            2,
+            FieldKind::Message(&SET_UPDATE_PARAM_SETTING),
+        ),
+    ],
+};
+
+pub(crate) static SET_UPDATE_PARAM_SETTING: MessageDescriptor = MessageDescriptor {
+    name: "usp.Set.UpdateParamSetting",
+    fields: &[
+        FieldDescriptor::single("param", 1, FieldKind::String),
+        FieldDescriptor::single("value", 2, FieldKind::String),
+        FieldDescriptor::single("required", 3, FieldKind::Bool),
+    ],
+};
+
+pub(crate) static SET_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.SetResp",
+    fields: &[FieldDescriptor::repeated(
+        "updated_obj_results",
+        1,
+        FieldKind::Message(&SET_RESP_UPDATED_OBJECT_RESULT),
+    )],
+};
+
+pub(crate) static SET_RESP_UPDATED_OBJECT_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.SetResp.UpdatedObjectResult",
+    fields: &[
+        FieldDescriptor::single("requested_path", 1, FieldKind::String),
+        FieldDescriptor::single(
+            "oper_status",
+            2,
+            FieldKind::Message(&SET_RESP_UPDATED_OBJECT_RESULT_OPERATION_STATUS),
+        ),
+    ],
+};
+
+pub(crate) static SET_RESP_UPDATED_OBJECT_RESULT_OPERATION_STATUS: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.SetResp.UpdatedObjectResult.OperationStatus",
+        fields: &[
+            FieldDescriptor::single(
+                "oper_failure",
+                1,
+                FieldKind::Message(
+                    &SET_RESP_UPDATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_FAILURE,
+                ),
+            ),
+            FieldDescriptor::single(
+                "oper_success",
+                2,
+                FieldKind::Message(
+                    &SET_RESP_UPDATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_SUCCESS,
+                ),
+            ),
+        ],
+    };
+
+pub(crate) static SET_RESP_UPDATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_FAILURE:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.SetResp.UpdatedObjectResult.OperationStatus.OperationFailure",
+    fields: &[
+        FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 2, FieldKind::String),
+        FieldDescriptor::repeated(
+            "updated_inst_failures",
+            3,
+            FieldKind::Message(&SET_RESP_UPDATED_INSTANCE_FAILURE),
+        ),
+    ],
+};
+
+pub(crate) static SET_RESP_UPDATED_OBJECT_RESULT_OPERATION_STATUS_OPERATION_SUCCESS:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.SetResp.UpdatedObjectResult.OperationStatus.OperationSuccess",
+    fields: &[FieldDescriptor::repeated(
+        "updated_inst_results",
+        1,
+        FieldKind::Message(&SET_RESP_UPDATED_INSTANCE_RESULT),
+    )],
+};
+
+pub(crate) static SET_RESP_UPDATED_INSTANCE_FAILURE: MessageDescriptor = MessageDescriptor {
+    name: "usp.SetResp.UpdatedInstanceFailure",
+    fields: &[
+        FieldDescriptor::single("affected_path", 1, FieldKind::String),
+        FieldDescriptor::repeated(
+            "param_errs",
+            2,
+            FieldKind::Message(&SET_RESP_PARAMETER_ERROR),
+        ),
+    ],
+};
+
+pub(crate) static SET_RESP_UPDATED_INSTANCE_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.SetResp.UpdatedInstanceResult",
+    fields: &[
+        FieldDescriptor::single("affected_path", 1, FieldKind::String),
+        FieldDescriptor::repeated(
+            "param_errs",
+            2,
+            FieldKind::Message(&SET_RESP_PARAMETER_ERROR),
+        ),
+        FieldDescriptor::repeated("updated_params", 3, FieldKind::Map),
+    ],
+};
+
+pub(crate) static SET_RESP_PARAMETER_ERROR: MessageDescriptor = MessageDescriptor {
+    name: "usp.SetResp.ParameterError",
+    fields: &[
+        FieldDescriptor::single("param", 1, FieldKind::String),
+        FieldDescriptor::single("err_code", 2, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 3, FieldKind::String),
+    ],
+};
+
+pub(crate) static OPERATE: MessageDescriptor = MessageDescriptor {
+    name: "usp.Operate",
+    fields: &[
+        FieldDescriptor::single("command", 1, FieldKind::String),
+        FieldDescriptor::single("command_key", 2, FieldKind::String),
+        FieldDescriptor::single("send_resp", 3, FieldKind::Bool),
+        FieldDescriptor::repeated("input_args", 4, FieldKind::Map),
+    ],
+};
+
+pub(crate) static OPERATE_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.OperateResp",
+    fields: &[FieldDescriptor::repeated(
+        "operation_results",
+        1,
+        FieldKind::Message(&OPERATE_RESP_OPERATION_RESULT),
+    )],
+};
+
+pub(crate) static OPERATE_RESP_OPERATION_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.OperateResp.OperationResult",
+    fields: &[
+        FieldDescriptor::single("executed_command", 1, FieldKind::String),
+        FieldDescriptor::single("req_obj_path", 2, FieldKind::String),
+        FieldDescriptor::single(
+            "req_output_args",
+            3,
+            FieldKind::Message(&OPERATE_RESP_OPERATION_RESULT_OUTPUT_ARGS),
+        ),
+        FieldDescriptor::single(
+            "cmd_failure",
+            4,
+            FieldKind::Message(&OPERATE_RESP_OPERATION_RESULT_COMMAND_FAILURE),
+        ),
+    ],
+};
+
+pub(crate) static OPERATE_RESP_OPERATION_RESULT_OUTPUT_ARGS: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.OperateResp.OperationResult.OutputArgs",
+        fields: &[FieldDescriptor::repeated("output_args", 1, FieldKind::Map)],
+    };
+
+pub(crate) static OPERATE_RESP_OPERATION_RESULT_COMMAND_FAILURE: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.OperateResp.OperationResult.CommandFailure",
+        fields: &[
+            FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+            FieldDescriptor::single("err_msg", 2, FieldKind::String),
+        ],
+    };
+
+pub(crate) static NOTIFY: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify",
+    fields: &[
+        FieldDescriptor::single("subscription_id", 1, FieldKind::String),
+        FieldDescriptor::single("send_resp", 2, FieldKind::Bool),
+        FieldDescriptor::single("event", 3, FieldKind::Message(&NOTIFY_EVENT)),
+        FieldDescriptor::single("value_change", 4, FieldKind::Message(&NOTIFY_VALUE_CHANGE)),
+        FieldDescriptor::single(
+            "obj_creation",
+            5,
+            FieldKind::Message(&NOTIFY_OBJECT_CREATION),
+        ),
+        FieldDescriptor::single(
+            "obj_deletion",
+            6,
+            FieldKind::Message(&NOTIFY_OBJECT_DELETION),
+        ),
+        FieldDescriptor::single(
+            "oper_complete",
+            7,
+            FieldKind::Message(&NOTIFY_OPERATION_COMPLETE),
+        ),
+        FieldDescriptor::single(
+            "on_board_req",
+            8,
+            FieldKind::Message(&NOTIFY_ON_BOARD_REQUEST),
+        ),
+    ],
+};
+
+pub(crate) static NOTIFY_EVENT: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify.Event",
+    fields: &[
+        FieldDescriptor::single("obj_path", 1, FieldKind::String),
+        FieldDescriptor::single("event_name", 2, FieldKind::String),
+        FieldDescriptor::repeated("params", 3, FieldKind::Map),
+    ],
+};
+
+pub(crate) static NOTIFY_VALUE_CHANGE: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify.ValueChange",
+    fields: &[
+        FieldDescriptor::single("param_path", 1, FieldKind::String),
+        FieldDescriptor::single("param_value", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static NOTIFY_OBJECT_CREATION: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify.ObjectCreation",
+    fields: &[
+        FieldDescriptor::single("obj_path", 1, FieldKind::String),
+        FieldDescriptor::repeated("unique_keys", 2, FieldKind::Map),
+    ],
+};
+
+pub(crate) static NOTIFY_OBJECT_DELETION: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify.ObjectDeletion",
+    fields: &[FieldDescriptor::single("obj_path", 1, FieldKind::String)],
+};
+
+pub(crate) static NOTIFY_OPERATION_COMPLETE: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify.OperationComplete",
+    fields: &[
+        FieldDescriptor::single("obj_path", 1, FieldKind::String),
+        FieldDescriptor::single("command_name", 2, FieldKind::String),
+        FieldDescriptor::single("command_key", 3, FieldKind::String),
+        FieldDescriptor::single(
+            "req_output_args",
+            4,
+            FieldKind::Message(&NOTIFY_OPERATION_COMPLETE_OUTPUT_ARGS),
+        ),
+        FieldDescriptor::single(
+            "cmd_failure",
+            5,
+            FieldKind::Message(&NOTIFY_OPERATION_COMPLETE_COMMAND_FAILURE),
+        ),
+    ],
+};
+
+pub(crate) static NOTIFY_OPERATION_COMPLETE_OUTPUT_ARGS: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify.OperationComplete.OutputArgs",
+    fields: &[FieldDescriptor::repeated("output_args", 1, FieldKind::Map)],
+};
+
+pub(crate) static NOTIFY_OPERATION_COMPLETE_COMMAND_FAILURE: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.Notify.OperationComplete.CommandFailure",
+        fields: &[
+            FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+            FieldDescriptor::single("err_msg", 2, FieldKind::String),
+        ],
+    };
+
+pub(crate) static NOTIFY_ON_BOARD_REQUEST: MessageDescriptor = MessageDescriptor {
+    name: "usp.Notify.OnBoardRequest",
+    fields: &[
+        FieldDescriptor::single("oui", 1, FieldKind::String),
+        FieldDescriptor::single("product_class", 2, FieldKind::String),
+        FieldDescriptor::single("serial_number", 3, FieldKind::String),
+        FieldDescriptor::single("agent_supported_protocol_versions", 4, FieldKind::String),
+    ],
+};
+
+pub(crate) static NOTIFY_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.NotifyResp",
+    fields: &[FieldDescriptor::single(
+        "subscription_id",
+        1,
+        FieldKind::String,
+    )],
+};
+
+pub(crate) static REGISTER: MessageDescriptor = MessageDescriptor {
+    name: "usp.Register",
+    fields: &[
+        FieldDescriptor::single("allow_partial", 1, FieldKind::Bool),
+        FieldDescriptor::repeated(
+            "reg_paths",
+            2,
+            FieldKind::Message(&REGISTER_REGISTRATION_PATH),
+        ),
+    ],
+};
+
+pub(crate) static REGISTER_REGISTRATION_PATH: MessageDescriptor = MessageDescriptor {
+    name: "usp.Register.RegistrationPath",
+    fields: &[FieldDescriptor::single("path", 1, FieldKind::String)],
+};
+
+pub(crate) static REGISTER_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.RegisterResp",
+    fields: &[FieldDescriptor::repeated(
+        "registered_path_results",
+        1,
+        FieldKind::Message(&REGISTER_RESP_REGISTERED_PATH_RESULT),
+    )],
+};
+
+pub(crate) static REGISTER_RESP_REGISTERED_PATH_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.RegisterResp.RegisteredPathResult",
+    fields: &[
+        FieldDescriptor::single("requested_path", 1, FieldKind::String),
+        FieldDescriptor::single(
+            "oper_status",
+            2,
+            FieldKind::Message(&REGISTER_RESP_REGISTERED_PATH_RESULT_OPERATION_STATUS),
+        ),
+    ],
+};
+
+pub(crate) static REGISTER_RESP_REGISTERED_PATH_RESULT_OPERATION_STATUS: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.RegisterResp.RegisteredPathResult.OperationStatus",
+        fields: &[
+            FieldDescriptor::single(
+                "oper_failure",
+                1,
+                FieldKind::Message(
+                    &REGISTER_RESP_REGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_FAILURE,
+                ),
+            ),
+            FieldDescriptor::single(
+                "oper_success",
+                2,
+                FieldKind::Message(
+                    &REGISTER_RESP_REGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_SUCCESS,
+                ),
+            ),
+        ],
+    };
+
+pub(crate) static REGISTER_RESP_REGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_FAILURE:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.RegisterResp.RegisteredPathResult.OperationStatus.OperationFailure",
+    fields: &[
+        FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static REGISTER_RESP_REGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_SUCCESS:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.RegisterResp.RegisteredPathResult.OperationStatus.OperationSuccess",
+    fields: &[FieldDescriptor::single(
+        "registered_path",
+        1,
+        FieldKind::String,
+    )],
+};
+
+pub(crate) static DEREGISTER: MessageDescriptor = MessageDescriptor {
+    name: "usp.Deregister",
+    fields: &[FieldDescriptor::repeated("paths", 1, FieldKind::String)],
+};
+
+pub(crate) static DEREGISTER_RESP: MessageDescriptor = MessageDescriptor {
+    name: "usp.DeregisterResp",
+    fields: &[FieldDescriptor::repeated(
+        "deregistered_path_results",
+        1,
+        FieldKind::Message(&DEREGISTER_RESP_DEREGISTERED_PATH_RESULT),
+    )],
+};
+
+pub(crate) static DEREGISTER_RESP_DEREGISTERED_PATH_RESULT: MessageDescriptor = MessageDescriptor {
+    name: "usp.DeregisterResp.DeregisteredPathResult",
+    fields: &[
+        FieldDescriptor::single("requested_path", 1, FieldKind::String),
+        FieldDescriptor::single(
+            "oper_status",
+            2,
+            FieldKind::Message(&DEREGISTER_RESP_DEREGISTERED_PATH_RESULT_OPERATION_STATUS),
+        ),
+    ],
+};
+
+pub(crate) static DEREGISTER_RESP_DEREGISTERED_PATH_RESULT_OPERATION_STATUS: MessageDescriptor =
+    MessageDescriptor {
+        name: "usp.DeregisterResp.DeregisteredPathResult.OperationStatus",
+        fields: &[
+            FieldDescriptor::single(
+                "oper_failure",
+                1,
+                FieldKind::Message(
+                    &DEREGISTER_RESP_DEREGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_FAILURE,
+                ),
+            ),
+            FieldDescriptor::single(
+                "oper_success",
+                2,
+                FieldKind::Message(
+                    &DEREGISTER_RESP_DEREGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_SUCCESS,
+                ),
+            ),
+        ],
+    };
+
+pub(crate) static DEREGISTER_RESP_DEREGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_FAILURE:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.DeregisterResp.DeregisteredPathResult.OperationStatus.OperationFailure",
+    fields: &[
+        FieldDescriptor::single("err_code", 1, FieldKind::Fixed32),
+        FieldDescriptor::single("err_msg", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static DEREGISTER_RESP_DEREGISTERED_PATH_RESULT_OPERATION_STATUS_OPERATION_SUCCESS:
+    MessageDescriptor = MessageDescriptor {
+    name: "usp.DeregisterResp.DeregisteredPathResult.OperationStatus.OperationSuccess",
+    fields: &[FieldDescriptor::repeated(
+        "deregistered_path",
+        1,
+        FieldKind::String,
+    )],
+};
+
+pub(crate) static HEADER_MSG_TYPE: EnumDescriptor = EnumDescriptor {
+    name: "usp.Header.MsgType",
+    values: &[
+        ("ERROR", 0),
+        ("GET", 1),
+        ("GET_RESP", 2),
+        ("NOTIFY", 3),
+        ("SET", 4),
+        ("SET_RESP", 5),
+        ("OPERATE", 6),
+        ("OPERATE_RESP", 7),
+        ("ADD", 8),
+        ("ADD_RESP", 9),
+        ("DELETE", 10),
+        ("DELETE_RESP", 11),
+        ("GET_SUPPORTED_DM", 12),
+        ("GET_SUPPORTED_DM_RESP", 13),
+        ("GET_INSTANCES", 14),
+        ("GET_INSTANCES_RESP", 15),
+        ("NOTIFY_RESP", 16),
+        ("GET_SUPPORTED_PROTO", 17),
+        ("GET_SUPPORTED_PROTO_RESP", 18),
+        ("REGISTER", 19),
+        ("REGISTER_RESP", 20),
+        ("DEREGISTER", 21),
+        ("DEREGISTER_RESP", 22),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_DM_RESP_PARAM_ACCESS_TYPE: EnumDescriptor = EnumDescriptor {
+    name: "usp.GetSupportedDMResp.ParamAccessType",
+    values: &[
+        ("PARAM_READ_ONLY", 0),
+        ("PARAM_READ_WRITE", 1),
+        ("PARAM_WRITE_ONLY", 2),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_DM_RESP_OBJ_ACCESS_TYPE: EnumDescriptor = EnumDescriptor {
+    name: "usp.GetSupportedDMResp.ObjAccessType",
+    values: &[
+        ("OBJ_READ_ONLY", 0),
+        ("OBJ_ADD_DELETE", 1),
+        ("OBJ_ADD_ONLY", 2),
+        ("OBJ_DELETE_ONLY", 3),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_DM_RESP_PARAM_VALUE_TYPE: EnumDescriptor = EnumDescriptor {
+    name: "usp.GetSupportedDMResp.ParamValueType",
+    values: &[
+        ("PARAM_UNKNOWN", 0),
+        ("PARAM_BASE_64", 1),
+        ("PARAM_BOOLEAN", 2),
+        ("PARAM_DATE_TIME", 3),
+        ("PARAM_DECIMAL", 4),
+        ("PARAM_HEX_BINARY", 5),
+        ("PARAM_INT", 6),
+        ("PARAM_LONG", 7),
+        ("PARAM_STRING", 8),
+        ("PARAM_UNSIGNED_INT", 9),
+        ("PARAM_UNSIGNED_LONG", 10),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_DM_RESP_VALUE_CHANGE_TYPE: EnumDescriptor = EnumDescriptor {
+    name: "usp.GetSupportedDMResp.ValueChangeType",
+    values: &[
+        ("VALUE_CHANGE_UNKNOWN", 0),
+        ("VALUE_CHANGE_ALLOWED", 1),
+        ("VALUE_CHANGE_WILL_IGNORE", 2),
+    ],
+};
+
+pub(crate) static GET_SUPPORTED_DM_RESP_CMD_TYPE: EnumDescriptor = EnumDescriptor {
+    name: "usp.GetSupportedDMResp.CmdType",
+    values: &[("CMD_UNKNOWN", 0), ("CMD_SYNC", 1), ("CMD_ASYNC", 2)],
+};
+
+// usp-record-1-5.proto
+
+pub(crate) static RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.Record",
+    fields: &[
+        FieldDescriptor::single("version", 1, FieldKind::String),
+        FieldDescriptor::single("to_id", 2, FieldKind::String),
+        FieldDescriptor::single("from_id", 3, FieldKind::String),
+        FieldDescriptor::single("originator_id", 14, FieldKind::String),
+        FieldDescriptor::single("destination_id", 15, FieldKind::String),
+        FieldDescriptor::single(
+            "payload_security",
+            4,
+            FieldKind::Enum(&RECORD_PAYLOAD_SECURITY),
+        ),
+        FieldDescriptor::single("mac_signature", 5, FieldKind::Bytes),
+        FieldDescriptor::single("sender_cert", 6, FieldKind::Bytes),
+        FieldDescriptor::single(
+            "no_session_context",
+            7,
+            FieldKind::Message(&NO_SESSION_CONTEXT_RECORD),
+        ),
+        FieldDescriptor::single(
+            "session_context",
+            8,
+            FieldKind::Message(&SESSION_CONTEXT_RECORD),
+        ),
+        FieldDescriptor::single(
+            "websocket_connect",
+            9,
+            FieldKind::Message(&WEB_SOCKET_CONNECT_RECORD),
+        ),
+        FieldDescriptor::single("mqtt_connect", 10, FieldKind::Message(&MQTT_CONNECT_RECORD)),
+        FieldDescriptor::single(
+            "stomp_connect",
+            11,
+            FieldKind::Message(&STOMP_CONNECT_RECORD),
+        ),
+        FieldDescriptor::single("disconnect", 12, FieldKind::Message(&DISCONNECT_RECORD)),
+        FieldDescriptor::single("uds_connect", 13, FieldKind::Message(&UDS_CONNECT_RECORD)),
+    ],
+};
+
+pub(crate) static NO_SESSION_CONTEXT_RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.NoSessionContextRecord",
+    fields: &[FieldDescriptor::single("payload", 2, FieldKind::Bytes)],
+};
+
+pub(crate) static SESSION_CONTEXT_RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.SessionContextRecord",
+    fields: &[
+        FieldDescriptor::single("session_id", 1, FieldKind::Uint64),
+        FieldDescriptor::single("sequence_id", 2, FieldKind::Uint64),
+        FieldDescriptor::single("expected_id", 3, FieldKind::Uint64),
+        FieldDescriptor::single("retransmit_id", 4, FieldKind::Uint64),
+        FieldDescriptor::single(
+            "payload_sar_state",
+            5,
+            FieldKind::Enum(&SESSION_CONTEXT_RECORD_PAYLOAD_SAR_STATE),
+        ),
+        FieldDescriptor::single(
+            "payloadrec_sar_state",
+            6,
+            FieldKind::Enum(&SESSION_CONTEXT_RECORD_PAYLOAD_SAR_STATE),
+        ),
+        FieldDescriptor::repeated("payload", 7, FieldKind::Bytes),
+    ],
+};
+
+pub(crate) static WEB_SOCKET_CONNECT_RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.WebSocketConnectRecord",
+    fields: &[],
+};
+
+pub(crate) static MQTT_CONNECT_RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.MQTTConnectRecord",
+    fields: &[
+        FieldDescriptor::single(
+            "version",
+            1,
+            FieldKind::Enum(&MQTT_CONNECT_RECORD_MQTT_VERSION),
+        ),
+        FieldDescriptor::single("subscribed_topic", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static STOMP_CONNECT_RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.STOMPConnectRecord",
+    fields: &[
+        FieldDescriptor::single(
+            "version",
+            1,
+            FieldKind::Enum(&STOMP_CONNECT_RECORD_STOMP_VERSION),
+        ),
+        FieldDescriptor::single("subscribed_destination", 2, FieldKind::String),
+    ],
+};
+
+pub(crate) static UDS_CONNECT_RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.UDSConnectRecord",
+    fields: &[],
+};
+
+pub(crate) static DISCONNECT_RECORD: MessageDescriptor = MessageDescriptor {
+    name: "usp_record.DisconnectRecord",
+    fields: &[
+        FieldDescriptor::single("reason", 1, FieldKind::String),
+        FieldDescriptor::single("reason_code", 2, FieldKind::Fixed32),
+    ],
+};
+
+pub(crate) static RECORD_PAYLOAD_SECURITY: EnumDescriptor = EnumDescriptor {
+    name: "usp_record.Record.PayloadSecurity",
+    values: &[("PLAINTEXT", 0), ("TLS12", 1)],
+};
+
+pub(crate) static SESSION_CONTEXT_RECORD_PAYLOAD_SAR_STATE: EnumDescriptor = EnumDescriptor {
+    name: "usp_record.SessionContextRecord.PayloadSARState",
+    values: &[("NONE", 0), ("BEGIN", 1), ("INPROCESS", 2), ("COMPLETE", 3)],
+};
+
+pub(crate) static MQTT_CONNECT_RECORD_MQTT_VERSION: EnumDescriptor = EnumDescriptor {
+    name: "usp_record.MQTTConnectRecord.MQTTVersion",
+    values: &[("V3_1_1", 0), ("V5", 1)],
+};
+
+pub(crate) static STOMP_CONNECT_RECORD_STOMP_VERSION: EnumDescriptor = EnumDescriptor {
+    name: "usp_record.STOMPConnectRecord.STOMPVersion",
+    values: &[("V1_2", 0)],
+};
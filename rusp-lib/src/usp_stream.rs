@@ -0,0 +1,350 @@
+//! Streaming readers and writers for sequences of USP Records and Msgs
+//!
+//! Traffic logs often contain many Records in a single file. Two framings are supported:
+//! * Length-delimited Protobuf, where every message is prefixed by its length encoded as varint,
+//!   as written by `writeDelimitedTo` of the official Protobuf libraries
+//! * JSON lines, where every line contains the JSON representation of one message
+//!
+//! Readers decode one message at a time, so arbitrarily large streams can be processed without
+//! loading them into memory.
+
+use std::io::{BufRead, Read, Write};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::usp::Msg;
+use crate::usp_decoder::{try_decode_msg, try_decode_record};
+use crate::usp_record::Record;
+
+/// The framing of a stream of USP Records or Msgs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Protobuf encoded messages, each prefixed by its length encoded as varint
+    LengthDelimited,
+    /// One JSON representation of a message per line
+    JsonLines,
+}
+
+impl FromStr for Framing {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "length-delimited" | "delimited" => Ok(Self::LengthDelimited),
+            "json-lines" | "jsonl" => Ok(Self::JsonLines),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unsupported framing {s}, expected one of length-delimited or json-lines"
+            ))),
+        }
+    }
+}
+
+/// A USP type which can be read from and written to a stream, i.e. [`Msg`] and [`Record`]
+pub trait StreamItem: Sized + Serialize {
+    /// The name of the type used in error messages
+    const NAME: &'static str;
+    /// The member wrapping the item in JSON lines carrying additional data, e.g. the output of
+    /// `rusp-run capture`
+    const WRAPPER: &'static str;
+
+    /// Decodes the item from Protobuf data
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the data cannot be decoded
+    fn decode(bytes: &[u8]) -> Result<Self>;
+
+    /// Encodes the item into Protobuf data
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the item cannot be encoded
+    fn encode(&self) -> Result<Vec<u8>>;
+
+    /// Converts the JSON representation of the item back into the item
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the value doesn't represent the item
+    fn from_json_value(value: &Value) -> Result<Self>;
+}
+
+impl StreamItem for Msg {
+    const NAME: &'static str = "Msg";
+    const WRAPPER: &'static str = "msg";
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        try_decode_msg(bytes)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        self.to_vec()
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self> {
+        Self::from_json_value(value)
+    }
+}
+
+impl StreamItem for Record {
+    const NAME: &'static str = "Record";
+    const WRAPPER: &'static str = "record";
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        try_decode_record(bytes)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        self.to_vec()
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self> {
+        Self::from_json_value(value)
+    }
+}
+
+/// An iterator lazily reading USP Records or Msgs from a stream
+///
+/// Every item is read and decoded on demand. An item which cannot be decoded is returned as `Err`
+/// and reading continues with the next one, unless the framing itself is broken, e.g. by a
+/// truncated stream, in which case the iteration ends after the error.
+///
+/// # Example
+///
+/// ```
+/// use rusp_lib::usp_builder::RecordBuilder;
+/// use rusp_lib::usp_stream::{Framing, RecordReader, StreamWriter};
+///
+/// let record = RecordBuilder::new()
+///     .with_version("1.3".into())
+///     .with_to_id("proto::to".into())
+///     .with_from_id("proto::from".into())
+///     .as_disconnect_record("Bye".into(), 7003)
+///     .build()
+///     .unwrap();
+///
+/// let mut writer = StreamWriter::new(vec![], Framing::LengthDelimited);
+/// writer.write(&record).unwrap();
+/// writer.write(&record).unwrap();
+/// let data = writer.into_inner();
+///
+/// let records = RecordReader::new(&data[..], Framing::LengthDelimited)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(records, [record.clone(), record]);
+/// ```
+pub struct StreamReader<R, T> {
+    reader: R,
+    framing: Framing,
+    /// The number of items or lines read so far, used to locate errors
+    position: usize,
+    done: bool,
+    line: String,
+    item: PhantomData<T>,
+}
+
+/// A [`StreamReader`] reading USP Records
+pub type RecordReader<R> = StreamReader<R, Record>;
+
+/// A [`StreamReader`] reading USP Msgs
+pub type MsgReader<R> = StreamReader<R, Msg>;
+
+impl<R: BufRead, T: StreamItem> StreamReader<R, T> {
+    /// Creates a new reader for a stream with the given framing
+    ///
+    /// Wrap unbuffered readers like [`std::fs::File`] in a [`std::io::BufReader`], as the varint
+    /// length prefixes are read byte by byte.
+    pub const fn new(reader: R, framing: Framing) -> Self {
+        Self {
+            reader,
+            framing,
+            position: 0,
+            done: false,
+            line: String::new(),
+            item: PhantomData,
+        }
+    }
+
+    /// Returns the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads a varint length prefix, returning `None` at the end of the stream
+    fn read_length(&mut self) -> std::result::Result<Option<u64>, String> {
+        let mut length = 0;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0];
+            if self.reader.read(&mut byte).map_err(|e| e.to_string())? == 0 {
+                if shift == 0 {
+                    return Ok(None);
+                }
+                return Err("Stream ends within a length prefix".into());
+            }
+            length |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(length));
+            }
+        }
+        Err("Invalid length prefix exceeding 64 bits".into())
+    }
+
+    /// Creates an [`Error::Stream`] located at the current position of the stream
+    fn error(&self, location: String, reason: impl ToString) -> Error {
+        Error::Stream {
+            location: Some(location),
+            reason: reason.to_string(),
+        }
+    }
+
+    fn next_delimited(&mut self) -> Option<Result<T>> {
+        self.position += 1;
+        let data = self.read_length().and_then(|length| {
+            let Some(length) = length else {
+                return Ok(None);
+            };
+            // The data is not allocated upfront, so bogus lengths don't exhaust the memory
+            let mut data = vec![];
+            (&mut self.reader)
+                .take(length)
+                .read_to_end(&mut data)
+                .map_err(|e| e.to_string())?;
+            if data.len() as u64 != length {
+                return Err(format!(
+                    "Stream is truncated, expected {length} bytes but got {}",
+                    data.len()
+                ));
+            }
+            Ok(Some(data))
+        });
+
+        let location = format!("while reading {} {}", T::NAME, self.position);
+        match data {
+            Ok(Some(data)) => Some(T::decode(&data).map_err(|err| self.error(location, err))),
+            Ok(None) => None,
+            Err(err) => {
+                self.done = true;
+                Some(Err(self.error(location, err)))
+            }
+        }
+    }
+
+    fn next_json_line(&mut self) -> Option<Result<T>> {
+        loop {
+            self.line.clear();
+            self.position += 1;
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) if self.line.trim().is_empty() => continue,
+                Ok(_) => break,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(self.error(format!("line {}", self.position), err)));
+                }
+            }
+        }
+
+        let item = serde_json::from_str::<Value>(&self.line)
+            .map_err(|e| Error::Json {
+                reason: format!("while parsing JSON: {e}"),
+            })
+            .and_then(|value| match value.get(T::WRAPPER) {
+                Some(item) => T::from_json_value(item),
+                None => T::from_json_value(&value),
+            })
+            .map_err(|err| self.error(format!("line {}", self.position), err));
+        Some(item)
+    }
+}
+
+impl<R: BufRead, T: StreamItem> Iterator for StreamReader<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.framing {
+            Framing::LengthDelimited => self.next_delimited(),
+            Framing::JsonLines => self.next_json_line(),
+        }
+    }
+}
+
+/// A writer for streams of USP Records or Msgs
+pub struct StreamWriter<W> {
+    writer: W,
+    framing: Framing,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Creates a new writer for a stream with the given framing
+    pub const fn new(writer: W, framing: Framing) -> Self {
+        Self { writer, framing }
+    }
+
+    /// Appends a USP Record or Msg to the stream
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - The [`Record`] or [`Msg`] to write
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` containing a textual description of the encountered error if
+    /// the item cannot be encoded or written
+    pub fn write<T: StreamItem>(&mut self, item: &T) -> Result<()> {
+        match self.framing {
+            Framing::LengthDelimited => {
+                let data = item.encode()?;
+                let mut length = data.len() as u64;
+                let mut prefix = vec![];
+                while length >= 0x80 {
+                    prefix.push((length as u8) | 0x80);
+                    length >>= 7;
+                }
+                prefix.push(length as u8);
+                self.writer
+                    .write_all(&prefix)
+                    .and_then(|()| self.writer.write_all(&data))
+                    .map_err(write_error)
+            }
+            Framing::JsonLines => {
+                serde_json::to_writer(&mut self.writer, item).map_err(write_error)?;
+                self.writer.write_all(b"\n").map_err(write_error)
+            }
+        }
+    }
+
+    /// Flushes the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` if the underlying writer fails to flush
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush().map_err(write_error)
+    }
+
+    /// Returns a mutable reference to the underlying writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Creates an [`Error::Stream`] describing a failure to write to a stream
+fn write_error(err: impl ToString) -> Error {
+    Error::Stream {
+        location: None,
+        reason: err.to_string(),
+    }
+}
@@ -0,0 +1,424 @@
+//! The generated bindings in [`crate::usp`] and [`crate::usp_record`] skip all fields which are
+//! not part of the schema they were generated from, e.g. fields added in a newer version of the
+//! USP specification. The lenient decoding functions [`try_decode_msg_lenient`] and
+//! [`try_decode_record_lenient`] instead return the decoded message wrapped in a [`Lenient`],
+//! which keeps the encoded data next to a side table of the unknown fields it contains.
+//!
+//! As long as the message is not modified, encoding it again yields the original data. Otherwise
+//! the unknown fields are inserted into the new encoding at the position they were found at,
+//! i.e. after the same number of known fields of the same (embedded) message.
+//!
+//! [`try_decode_msg_lenient`]: crate::usp_decoder::try_decode_msg_lenient
+//! [`try_decode_record_lenient`]: crate::usp_decoder::try_decode_record_lenient
+//! [`Lenient`]: crate::usp_unknown::Lenient
+
+use crate::error::Result;
+use crate::usp::Msg;
+use crate::usp_record::Record;
+use crate::usp_schema::{
+    self, write_tag, write_varint, FieldDescriptor, FieldKind, MessageDescriptor, WireReader,
+    WireType, WireValue,
+};
+
+/// The raw value of a field unknown to the schema this library was generated from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnknownValue {
+    /// A value encoded with the VARINT wire type
+    Varint(u64),
+    /// A value encoded with the I64 wire type
+    Fixed64(u64),
+    /// A value encoded with the LEN wire type, e.g. a string, bytes or an embedded message
+    LengthDelimited(Vec<u8>),
+    /// A value encoded with the I32 wire type
+    Fixed32(u32),
+}
+
+impl UnknownValue {
+    /// Returns the numeric Protobuf wire type of this value
+    #[must_use]
+    pub const fn wire_type(&self) -> u32 {
+        match self {
+            Self::Varint(_) => 0,
+            Self::Fixed64(_) => 1,
+            Self::LengthDelimited(_) => 2,
+            Self::Fixed32(_) => 5,
+        }
+    }
+}
+
+/// A field unknown to the schema this library was generated from, e.g. a field added in a newer
+/// version of the USP specification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// The path of the message containing the field, relative to the decoded message
+    ///
+    /// The path consists of the names of the fields leading to the embedded message, separated
+    /// by dots and with the index of the occurrence for repeated fields, e.g.
+    /// `body.response.get_resp.req_path_results[1]`. It is empty for fields of the decoded
+    /// message itself.
+    pub path: String,
+    /// The number of known fields preceding the field in the containing message
+    pub position: usize,
+    /// The field number
+    pub number: u32,
+    /// The raw value of the field
+    pub value: UnknownValue,
+}
+
+impl UnknownField {
+    /// Appends the encoding of the field to the buffer
+    fn write(&self, buf: &mut Vec<u8>) {
+        match &self.value {
+            UnknownValue::Varint(v) => {
+                write_tag(buf, self.number, WireType::Varint);
+                write_varint(buf, *v);
+            }
+            UnknownValue::Fixed64(v) => {
+                write_tag(buf, self.number, WireType::Fixed64);
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+            UnknownValue::LengthDelimited(data) => {
+                write_tag(buf, self.number, WireType::LengthDelimited);
+                write_varint(buf, data.len() as u64);
+                buf.extend_from_slice(data);
+            }
+            UnknownValue::Fixed32(v) => {
+                write_tag(buf, self.number, WireType::Fixed32);
+                buf.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+}
+
+/// The unknown fields found while decoding a message, in the order they were encountered
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UnknownFields(Vec<UnknownField>);
+
+impl UnknownFields {
+    /// Collects the unknown fields of an encoded message and all of its embedded messages
+    pub(crate) fn collect(bytes: &[u8], descriptor: &'static MessageDescriptor) -> Self {
+        let mut fields = Self::default();
+        fields.collect_message(bytes, descriptor, "");
+        fields
+    }
+
+    fn collect_message(
+        &mut self,
+        bytes: &[u8],
+        descriptor: &'static MessageDescriptor,
+        path: &str,
+    ) {
+        let mut reader = WireReader::new(bytes);
+        let mut occurrences = Occurrences::default();
+        let mut position = 0;
+
+        // The data has been decoded successfully before, so errors can only be caused by
+        // constructs the generic reader doesn't support and which can't be retained anyway
+        while let Ok(field) = reader.read_field() {
+            let Some(known) = descriptor.field_by_number(field.number) else {
+                let Ok(number) = u32::try_from(field.number) else {
+                    continue;
+                };
+                let value = match field.value {
+                    WireValue::Varint(v) => UnknownValue::Varint(v),
+                    WireValue::Fixed64(v) => UnknownValue::Fixed64(v),
+                    WireValue::LengthDelimited(data) => {
+                        UnknownValue::LengthDelimited(data.to_vec())
+                    }
+                    WireValue::Fixed32(v) => UnknownValue::Fixed32(v),
+                };
+                self.0.push(UnknownField {
+                    path: path.to_owned(),
+                    position,
+                    number,
+                    value,
+                });
+                continue;
+            };
+
+            let index = occurrences.next(known);
+            position += 1;
+            if let (FieldKind::Message(message), WireValue::LengthDelimited(data)) =
+                (known.kind, field.value)
+            {
+                self.collect_message(data, message, &child_path(path, known, index));
+            }
+        }
+    }
+
+    /// Inserts the unknown fields into the encoding of a message which doesn't contain them
+    pub(crate) fn insert_into(
+        &self,
+        bytes: &[u8],
+        descriptor: &'static MessageDescriptor,
+    ) -> Vec<u8> {
+        let mut out = Vec::with_capacity(bytes.len() + self.encoded_len());
+        self.insert_into_message(bytes, descriptor, "", &mut out);
+        out
+    }
+
+    fn insert_into_message(
+        &self,
+        bytes: &[u8],
+        descriptor: &'static MessageDescriptor,
+        path: &str,
+        out: &mut Vec<u8>,
+    ) {
+        let mut pending = self.iter().filter(|field| field.path == path).peekable();
+        let mut reader = WireReader::new(bytes);
+        let mut occurrences = Occurrences::default();
+        let mut position = 0;
+
+        while !reader.is_eof() {
+            let start = reader.position();
+            let Ok(field) = reader.read_field() else {
+                out.extend_from_slice(&bytes[start..]);
+                break;
+            };
+            while let Some(unknown) = pending.next_if(|unknown| unknown.position <= position) {
+                unknown.write(out);
+            }
+
+            let Some(known) = descriptor.field_by_number(field.number) else {
+                out.extend_from_slice(&bytes[start..reader.position()]);
+                continue;
+            };
+            let index = occurrences.next(known);
+            position += 1;
+
+            if let (FieldKind::Message(message), WireValue::LengthDelimited(data)) =
+                (known.kind, field.value)
+            {
+                let child = child_path(path, known, index);
+                if self.iter().any(|unknown| is_within(&unknown.path, &child)) {
+                    let mut nested = Vec::with_capacity(data.len());
+                    self.insert_into_message(data, message, &child, &mut nested);
+                    write_tag(out, known.number, WireType::LengthDelimited);
+                    write_varint(out, nested.len() as u64);
+                    out.extend_from_slice(&nested);
+                    continue;
+                }
+            }
+            out.extend_from_slice(&bytes[start..reader.position()]);
+        }
+
+        for unknown in pending {
+            unknown.write(out);
+        }
+    }
+
+    /// Returns the size of the encoding of all unknown fields, excluding the additional length
+    /// prefixes of the messages containing them
+    fn encoded_len(&self) -> usize {
+        let mut buf = Vec::new();
+        for field in self {
+            field.write(&mut buf);
+        }
+        buf.len()
+    }
+
+    /// Returns `true` if no unknown fields were found
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of unknown fields
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns an iterator over the unknown fields
+    pub fn iter(&self) -> std::slice::Iter<'_, UnknownField> {
+        self.0.iter()
+    }
+
+    /// Returns an iterator over the unknown fields of the message at the given path
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rusp_lib::usp_decoder::try_decode_msg_lenient;
+    /// use rusp_lib::usp_unknown::UnknownValue;
+    /// // A Get message with the unknown field 15 in the Get
+    /// let raw = [
+    ///     0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10,
+    ///     0x01, 0x12, 0x0f, 0x0a, 0x0d, 0x0a, 0x0b, 0x0a,
+    ///     0x07, 0x44, 0x65, 0x76, 0x69, 0x63, 0x65, 0x2e,
+    ///     0x78, 0x2a,
+    /// ];
+    /// let msg = try_decode_msg_lenient(&raw).unwrap();
+    /// let field = msg.unknown_fields().at("body.request.get").next().unwrap();
+    /// assert_eq!(field.number, 15);
+    /// assert_eq!(field.value, UnknownValue::Varint(42));
+    /// ```
+    pub fn at<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a UnknownField> + 'a {
+        self.iter().filter(move |field| field.path == path)
+    }
+}
+
+impl From<Vec<UnknownField>> for UnknownFields {
+    fn from(fields: Vec<UnknownField>) -> Self {
+        Self(fields)
+    }
+}
+
+impl<'a> IntoIterator for &'a UnknownFields {
+    type Item = &'a UnknownField;
+    type IntoIter = std::slice::Iter<'a, UnknownField>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Counts the occurrences of the fields of a message to determine the index of repeated ones
+#[derive(Default)]
+struct Occurrences(Vec<(u32, usize)>);
+
+impl Occurrences {
+    fn next(&mut self, field: &FieldDescriptor) -> usize {
+        if let Some((_, count)) = self
+            .0
+            .iter_mut()
+            .find(|(number, _)| *number == field.number)
+        {
+            *count += 1;
+            *count - 1
+        } else {
+            self.0.push((field.number, 1));
+            0
+        }
+    }
+}
+
+/// Returns the path of the message embedded in `field` of the message at `path`
+fn child_path(path: &str, field: &FieldDescriptor, index: usize) -> String {
+    let separator = if path.is_empty() { "" } else { "." };
+    if field.repeated {
+        format!("{path}{separator}{}[{index}]", field.name)
+    } else {
+        format!("{path}{separator}{}", field.name)
+    }
+}
+
+/// Returns `true` if `path` denotes the message at `parent` or one embedded in it
+fn is_within(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// A decoded USP Record or Msg together with the fields unknown to this library
+///
+/// Returned by [`try_decode_msg_lenient`] and [`try_decode_record_lenient`]. The wrapped message
+/// can be accessed via [`Deref`](std::ops::Deref) and modified via [`Lenient::message_mut`].
+///
+/// # Limitations
+///
+/// The generated bindings don't retain unknown fields themselves, they are only kept by this
+/// wrapper:
+/// * Only [`Lenient::to_vec`] re-emits the unknown fields, encoding the unwrapped message, e.g.
+///   via [`Msg::to_vec`] or after [`Lenient::into_inner`], drops them
+/// * The original data is only returned as long as the message has not been modified. Afterwards
+///   the unknown fields are inserted into the new encoding after the same number of known fields
+///   of the same embedded message they followed originally. If known fields are added, removed
+///   or reordered, they may therefore end up next to different known fields than before
+/// * Unknown fields of embedded messages which no longer exist are dropped
+///
+/// [`try_decode_msg_lenient`]: crate::usp_decoder::try_decode_msg_lenient
+/// [`try_decode_record_lenient`]: crate::usp_decoder::try_decode_record_lenient
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lenient<T> {
+    message: T,
+    unknown_fields: UnknownFields,
+    /// The data the message was decoded from, until the message is modified
+    encoded: Option<Vec<u8>>,
+}
+
+impl<T> Lenient<T> {
+    /// Wraps a message decoded from `bytes`, collecting the unknown fields from the data
+    pub(crate) fn decoded(
+        message: T,
+        bytes: &[u8],
+        descriptor: &'static MessageDescriptor,
+    ) -> Self {
+        Self {
+            message,
+            unknown_fields: UnknownFields::collect(bytes, descriptor),
+            encoded: Some(bytes.to_vec()),
+        }
+    }
+
+    /// Returns the unknown fields found in the decoded data
+    #[must_use]
+    pub const fn unknown_fields(&self) -> &UnknownFields {
+        &self.unknown_fields
+    }
+
+    /// Returns a mutable reference to the wrapped message
+    ///
+    /// Since the message may be changed through the reference, encoding it afterwards will no
+    /// longer return the original data but a new encoding with the unknown fields inserted.
+    /// Unknown fields of embedded messages which were removed are dropped.
+    pub fn message_mut(&mut self) -> &mut T {
+        self.encoded = None;
+        &mut self.message
+    }
+
+    /// Unwraps the message, dropping the unknown fields
+    pub fn into_inner(self) -> T {
+        self.message
+    }
+
+    /// Splits the wrapper into the message and its unknown fields
+    pub fn into_parts(self) -> (T, UnknownFields) {
+        (self.message, self.unknown_fields)
+    }
+
+    fn encode(
+        &self,
+        encode: impl FnOnce(&T) -> Result<Vec<u8>>,
+        descriptor: &'static MessageDescriptor,
+    ) -> Result<Vec<u8>> {
+        if let Some(encoded) = &self.encoded {
+            return Ok(encoded.clone());
+        }
+
+        let bytes = encode(&self.message)?;
+        if self.unknown_fields.is_empty() {
+            return Ok(bytes);
+        }
+        Ok(self.unknown_fields.insert_into(&bytes, descriptor))
+    }
+}
+
+impl<T> std::ops::Deref for Lenient<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.message
+    }
+}
+
+impl Lenient<Msg> {
+    /// Encodes the Msg including the unknown fields
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` in the same cases as [`Msg::to_vec`]
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        self.encode(Msg::to_vec, &usp_schema::MSG)
+    }
+}
+
+impl Lenient<Record> {
+    /// Encodes the Record including the unknown fields
+    ///
+    /// # Errors
+    ///
+    /// This function will return `Err` in the same cases as [`Record::to_vec`]
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        self.encode(Record::to_vec, &usp_schema::RECORD)
+    }
+}
@@ -0,0 +1,69 @@
+mod tests {
+    use rusp_lib::usp::Msg;
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    use rusp_lib::usp_decoder::{decode_base64, decode_hex};
+    use rusp_lib::usp_record::Record;
+
+    fn get_msg() -> Msg {
+        let body = GetBuilder::new()
+            .with_params(vec!["Device.LocalAgent.".into()])
+            .build()
+            .unwrap();
+        MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let msg = get_msg();
+        let record = RecordBuilder::new()
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap();
+
+        assert_eq!(Msg::from_base64(&msg.to_base64().unwrap()).unwrap(), msg);
+        assert_eq!(Msg::from_hex(&msg.to_hex().unwrap()).unwrap(), msg);
+        assert_eq!(
+            Record::from_base64(&record.to_base64().unwrap()).unwrap(),
+            record
+        );
+        assert_eq!(Record::from_hex(&record.to_hex().unwrap()).unwrap(), record);
+    }
+
+    #[test]
+    fn lenient_input() {
+        let bytes = get_msg().to_vec().unwrap();
+
+        // Wrapped base64 as produced by MIME encoders, without padding
+        let base64 = get_msg().to_base64().unwrap();
+        let wrapped = format!(
+            "{}\r\n{}\n",
+            &base64[..16],
+            base64[16..].trim_end_matches('=')
+        );
+        assert_eq!(decode_base64(&wrapped).unwrap(), bytes);
+
+        // Hex dump with separators and uppercase digits
+        let hex = bytes
+            .iter()
+            .map(|b| format!("{b:02X}"))
+            .collect::<Vec<_>>()
+            .join(":");
+        assert_eq!(decode_hex(&format!("0x{hex}\n")).unwrap(), bytes);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(decode_base64("not base64!").is_err());
+        assert!(decode_hex("0a0").is_err());
+        assert!(decode_hex("zz").is_err());
+        // Valid encodings, but no valid Msg or Record
+        assert!(Msg::from_hex("0a05").is_err());
+        assert!(Record::from_base64(&get_msg().to_base64().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+mod tests {
+    use std::borrow::Cow;
+
+    use rusp_lib::usp::Msg;
+    use rusp_lib::usp_builder::{
+        GSDMParamResult, GSDMReqObjectResultBuilder, GSDMSupportedObjectResultBuilder, GetBuilder,
+        GetReqPathResultBuilder, GetRespBuilder, GetSupportedDMRespBuilder, MsgBuilder,
+        RecordBuilder, ResolvedPathResultBuilder, SessionContextBuilder,
+    };
+    use rusp_lib::usp_decoder::{
+        try_decode_msg, try_decode_msg_ref, try_decode_record, try_decode_record_ref,
+    };
+    use rusp_lib::usp_record_ref::mod_Record::OneOfrecord_type;
+    use rusp_lib::usp_ref::mod_Body::OneOfmsg_body;
+    use rusp_lib::usp_ref::mod_Response::OneOfresp_type;
+
+    fn get_resp() -> Msg {
+        let resolved = ResolvedPathResultBuilder::new("Device.WiFi.SSID.1.".into())
+            .with_result_params(vec![
+                ("Enable".into(), "true".into()),
+                ("SSID".into(), "Homenetwork".into()),
+            ]);
+        let path_result = GetReqPathResultBuilder::new("Device.WiFi.SSID.*.".into())
+            .with_res_path_results(vec![resolved]);
+        MsgBuilder::new()
+            .with_msg_id("get-rsp".into())
+            .with_body(
+                GetRespBuilder::new()
+                    .with_req_path_results(vec![path_result])
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn gsdm_resp() -> Msg {
+        let supported_obj = GSDMSupportedObjectResultBuilder::new("Device.WiFi.Radio.{i}.".into())
+            .set_access_read_only()
+            .with_is_multi_instance(true)
+            .with_supported_params(vec![GSDMParamResult::new("Channel".into())
+                .set_access_read_write()
+                .set_type_unsigned_int()])
+            .with_unique_key_sets(vec![vec!["Alias".into()]]);
+        let req_obj = GSDMReqObjectResultBuilder::new("Device.WiFi.".into())
+            .with_data_model_inst_uri("urn:broadband-forum-org:tr-181-2-16-0".into())
+            .with_supported_objs(vec![supported_obj]);
+        MsgBuilder::new()
+            .with_msg_id("gsdm-rsp".into())
+            .with_body(
+                GetSupportedDMRespBuilder::new()
+                    .with_req_obj_results(vec![req_obj])
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn msg_equals_owned() {
+        for msg in [get_resp(), gsdm_resp()] {
+            let bytes = msg.to_vec().unwrap();
+            let msg_ref = try_decode_msg_ref(&bytes).unwrap();
+            assert_eq!(msg_ref.msg_id(), msg.msg_id());
+            assert_eq!(msg_ref.into_owned(), try_decode_msg(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn msg_borrows() {
+        let bytes = get_resp().to_vec().unwrap();
+        let msg = try_decode_msg_ref(&bytes).unwrap();
+
+        let OneOfmsg_body::response(ref response) = msg.body.as_ref().unwrap().msg_body else {
+            panic!("Expected a Response");
+        };
+        let OneOfresp_type::get_resp(ref get_resp) = response.resp_type else {
+            panic!("Expected a GetResp");
+        };
+        let params = &get_resp.req_path_results[0].resolved_path_results[0].result_params;
+        let Cow::Borrowed(ssid) = params[&Cow::Borrowed("SSID")] else {
+            panic!("Expected a borrowed value");
+        };
+        assert_eq!(ssid, "Homenetwork");
+        assert!(bytes.as_ptr_range().contains(&ssid.as_ptr()));
+    }
+
+    #[test]
+    fn record_equals_owned() {
+        let msg = MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(
+                GetBuilder::new()
+                    .with_params(vec!["Device.".into()])
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        let no_session = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap();
+        let session = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_session_context_builder(
+                SessionContextBuilder::new()
+                    .with_session_id(1)
+                    .with_sequence_id(2)
+                    .with_expected_id(3)
+                    .with_payload(msg.to_vec().unwrap()),
+            )
+            .build()
+            .unwrap();
+        let disconnect = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .as_disconnect_record("Bye".into(), 7003)
+            .build()
+            .unwrap();
+
+        for record in [no_session, session, disconnect] {
+            let bytes = record.to_vec().unwrap();
+            let record_ref = try_decode_record_ref(&bytes).unwrap();
+            assert_eq!(record_ref.into_owned(), try_decode_record(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn record_payload() {
+        let msg = get_resp();
+        let record = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap();
+        let bytes = record.to_vec().unwrap();
+
+        let record = try_decode_record_ref(&bytes).unwrap();
+        let OneOfrecord_type::no_session_context(ref no_session) = record.record_type else {
+            panic!("Expected a NoSessionContext Record");
+        };
+        let Cow::Borrowed(payload) = no_session.payload else {
+            panic!("Expected a borrowed payload");
+        };
+        assert_eq!(try_decode_msg_ref(payload).unwrap().into_owned(), msg);
+    }
+
+    #[test]
+    fn unknown_fields_skipped() {
+        let mut bytes = get_resp().to_vec().unwrap();
+        // Unknown string field 15 in the Msg
+        bytes.extend_from_slice(&[0x7a, 0x03, 0x6e, 0x65, 0x77]);
+        assert_eq!(
+            try_decode_msg_ref(&bytes).unwrap().into_owned(),
+            try_decode_msg(&bytes).unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            try_decode_msg_ref(&[0x0a, 0x05]).unwrap_err(),
+            try_decode_msg(&[0x0a, 0x05]).unwrap_err()
+        );
+        assert_eq!(
+            try_decode_record_ref(&[0x0a, 0x05, 0x31]).unwrap_err(),
+            try_decode_record(&[0x0a, 0x05, 0x31]).unwrap_err()
+        );
+    }
+}
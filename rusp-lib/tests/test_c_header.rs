@@ -0,0 +1,100 @@
+mod tests {
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    use rusp_lib::usp_c_header::CHeaderBuilder;
+
+    fn get_msg(path: &str) -> rusp_lib::usp::Msg {
+        let body = GetBuilder::new()
+            .with_params(vec![path.into()])
+            .build()
+            .unwrap();
+        MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn table_of_msgs_and_records() {
+        let msg = get_msg("Device.");
+        let record = RecordBuilder::new()
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap();
+        let record_len = record.to_vec().unwrap().len();
+
+        let header = CHeaderBuilder::new("usp-vectors.h")
+            .with_msg("get msg", msg)
+            .with_record("get_record", record)
+            .with_table(true)
+            .build()
+            .unwrap();
+
+        assert!(header.starts_with("#ifndef USP_VECTORS_H_H\n"));
+        assert!(header.contains("static const uint8_t get_msg[] = {\n"));
+        assert!(header.contains(&format!(
+            "static const size_t get_record_len = {record_len};\n"
+        )));
+        assert!(header.contains(" *   \"to_id\": \"proto::to\",\n"));
+        assert!(header.contains("} usp_vectors_h_entry_t;\n"));
+        assert!(header.contains(&format!(
+            "static const usp_vectors_h_entry_t usp_vectors_h[] = {{\n  {{ \"get_msg\", get_msg, 24 }},\n  {{ \"get_record\", get_record, {record_len} }},\n}};\nstatic const size_t usp_vectors_h_len = 2;\n"
+        )));
+    }
+
+    #[test]
+    fn comment_terminators_are_escaped() {
+        let header = CHeaderBuilder::new("vectors")
+            .with_msg("get", get_msg("Device.*/"))
+            .build()
+            .unwrap();
+
+        assert!(header.contains("\"Device.*\\/\""));
+        let doc = &header[header.find("/**").unwrap()..header.find("static").unwrap()];
+        assert_eq!(doc.matches("*/").count(), 1);
+        assert!(!header.contains("typedef"));
+    }
+
+    #[test]
+    fn invalid_headers() {
+        assert!(CHeaderBuilder::new("vectors").build().is_err());
+        assert!(CHeaderBuilder::new("vectors")
+            .with_msg("get", get_msg("Device."))
+            .with_msg("get", get_msg("Device.DeviceInfo."))
+            .build()
+            .is_err());
+        assert!(CHeaderBuilder::new("vectors")
+            .with_msg("vectors", get_msg("Device."))
+            .with_table(true)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn length_collisions() {
+        let err = CHeaderBuilder::new("vectors")
+            .with_msg("get", get_msg("Device."))
+            .with_msg("get_len", get_msg("Device.DeviceInfo."))
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("get_len generated for get_len"));
+
+        // The order doesn't matter
+        assert!(CHeaderBuilder::new("vectors")
+            .with_msg("get_len", get_msg("Device."))
+            .with_msg("get", get_msg("Device.DeviceInfo."))
+            .build()
+            .is_err());
+
+        // Names of the table only collide if it is rendered
+        let colliding = || {
+            CHeaderBuilder::new("vectors")
+                .with_msg("vectors_len", get_msg("Device."))
+                .with_msg("vectors_entry_t", get_msg("Device.DeviceInfo."))
+        };
+        assert!(colliding().build().is_ok());
+        assert!(colliding().with_table(true).build().is_err());
+    }
+}
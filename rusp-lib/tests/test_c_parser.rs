@@ -0,0 +1,91 @@
+mod tests {
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    use rusp_lib::usp_c_header::CHeaderBuilder;
+    use rusp_lib::usp_c_parser::{
+        parse_c_bytes, parse_c_literals, try_decode_msg_c, try_decode_record_c,
+    };
+
+    fn get_msg() -> rusp_lib::usp::Msg {
+        let body = GetBuilder::new()
+            .with_params(vec!["Device.DeviceInfo.".into()])
+            .with_max_depth(1)
+            .build()
+            .unwrap();
+        MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let msg = get_msg();
+        let record = RecordBuilder::new()
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap();
+
+        assert_eq!(try_decode_msg_c(&msg.to_c_array().unwrap()).unwrap(), msg);
+        assert_eq!(try_decode_msg_c(&msg.to_c_str().unwrap()).unwrap(), msg);
+        assert_eq!(
+            try_decode_record_c(&record.to_c_array_custom("record").unwrap()).unwrap(),
+            record
+        );
+        assert_eq!(
+            try_decode_record_c(&record.to_c_str().unwrap()).unwrap(),
+            record
+        );
+    }
+
+    #[test]
+    fn c_header() {
+        let msg = get_msg();
+        let header = CHeaderBuilder::new("vectors")
+            .with_msg("first", msg.clone())
+            .with_msg("second", msg.clone())
+            .with_table(true)
+            .build()
+            .unwrap();
+
+        // The table of the header is skipped, since it doesn't contain bytes
+        let literals = parse_c_literals(&header).unwrap();
+        assert_eq!(literals.len(), 2);
+        assert_eq!(literals[0].name.as_deref(), Some("first"));
+        assert_eq!(literals[1].name.as_deref(), Some("second"));
+        assert_eq!(literals[1].data, msg.to_vec().unwrap());
+
+        assert!(parse_c_bytes(&header).is_err());
+    }
+
+    #[test]
+    fn number_and_escape_formats() {
+        let source = r#"
+            // Legacy test vector
+            static unsigned char vector[4] = {
+                10, 0x0B, 014, '\r', // decimal, hex, octal and character
+            };
+            int vector_len = sizeof vector / sizeof vector[0];
+        "#;
+        assert_eq!(parse_c_bytes(source).unwrap(), [10, 11, 12, 13]);
+
+        let source = "char *s =\n  \"\\101\\x4\" /* split */\n  \"2\\n\\\\\";";
+        assert_eq!(parse_c_bytes(source).unwrap(), b"A\x042\n\\");
+
+        assert_eq!(
+            parse_c_bytes("0x0a, 0x00, /* bare */").unwrap(),
+            [0x0a, 0x00]
+        );
+    }
+
+    #[test]
+    fn invalid_sources() {
+        assert!(parse_c_bytes("").is_err());
+        assert!(parse_c_bytes("const char pb[] = { 0x0a, 0x00").is_err());
+        assert!(parse_c_bytes("const char *pb = \"\\x0a").is_err());
+        assert!(parse_c_bytes("/* unterminated").is_err());
+        assert!(try_decode_msg_c("const char pb[] = { 0x0a, 0x05 };").is_err());
+    }
+}
@@ -0,0 +1,263 @@
+mod tests {
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    use rusp_lib::usp_capture::{extract_records, Transport};
+
+    const CLIENT: &str = "192.168.1.10:40000";
+    const SERVER: &str = "192.168.1.1:8080";
+
+    fn record(to_id: &str) -> Vec<u8> {
+        let body = GetBuilder::new()
+            .with_params(vec!["Device.".into()])
+            .build()
+            .unwrap();
+        let msg = MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(body)
+            .build()
+            .unwrap();
+        RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id(to_id.into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap()
+            .to_vec()
+            .unwrap()
+    }
+
+    /// Builds an Ethernet frame containing an IPv4 packet with a TCP segment
+    fn tcp(from: &str, to: &str, seq: u32, syn: bool, payload: &[u8]) -> Vec<u8> {
+        let (SocketAddr::V4(from), SocketAddr::V4(to)) =
+            (from.parse().unwrap(), to.parse().unwrap())
+        else {
+            unreachable!()
+        };
+
+        let mut frame = vec![0; 12];
+        frame.extend([0x08, 0x00]);
+        let total_len = u16::try_from(20 + 20 + payload.len()).unwrap();
+        frame.extend([0x45, 0]);
+        frame.extend(total_len.to_be_bytes());
+        frame.extend([0, 0, 0x40, 0, 64, 6, 0, 0]);
+        frame.extend(from.ip().octets());
+        frame.extend(to.ip().octets());
+        frame.extend(from.port().to_be_bytes());
+        frame.extend(to.port().to_be_bytes());
+        frame.extend(seq.to_be_bytes());
+        frame.extend([0, 0, 0, 0, 0x50, if syn { 0x02 } else { 0x18 }, 0xff, 0xff]);
+        frame.extend([0, 0, 0, 0]);
+        frame.extend(payload);
+        frame
+    }
+
+    fn pcap(packets: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut capture = vec![];
+        capture.extend(0xa1b2_c3d4_u32.to_le_bytes());
+        capture.extend([2, 0, 4, 0]);
+        capture.extend([0; 8]);
+        capture.extend(65535_u32.to_le_bytes());
+        capture.extend(1_u32.to_le_bytes());
+        for (millis, packet) in packets {
+            let len = u32::try_from(packet.len()).unwrap();
+            capture.extend((millis / 1000).to_le_bytes());
+            capture.extend((millis % 1000 * 1000).to_le_bytes());
+            capture.extend(len.to_le_bytes());
+            capture.extend(len.to_le_bytes());
+            capture.extend(packet);
+        }
+        capture
+    }
+
+    /// Builds a pcapng capture with a single big endian section and nanosecond timestamps
+    fn pcapng(packets: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        fn block(capture: &mut Vec<u8>, block_type: u32, body: &[u8]) {
+            let padding = (4 - body.len() % 4) % 4;
+            let len = u32::try_from(12 + body.len() + padding).unwrap();
+            capture.extend(block_type.to_be_bytes());
+            capture.extend(len.to_be_bytes());
+            capture.extend(body);
+            capture.extend(vec![0; padding]);
+            capture.extend(len.to_be_bytes());
+        }
+
+        let mut capture = vec![];
+        let mut section = vec![];
+        section.extend(0x1a2b_3c4d_u32.to_be_bytes());
+        section.extend([0, 1, 0, 0]);
+        section.extend(u64::MAX.to_be_bytes());
+        block(&mut capture, 0x0a0d_0d0a, &section);
+
+        let mut interface = vec![0, 1, 0, 0];
+        interface.extend(65535_u32.to_be_bytes());
+        interface.extend([0, 9, 0, 1, 9, 0, 0, 0, 0, 0, 0, 0]);
+        block(&mut capture, 1, &interface);
+
+        for (millis, packet) in packets {
+            let nanos = u64::from(*millis) * 1_000_000;
+            let len = u32::try_from(packet.len()).unwrap();
+            let mut body = vec![0, 0, 0, 0];
+            body.extend(u32::try_from(nanos >> 32).unwrap().to_be_bytes());
+            body.extend(u32::try_from(nanos & 0xffff_ffff).unwrap().to_be_bytes());
+            body.extend(len.to_be_bytes());
+            body.extend(len.to_be_bytes());
+            body.extend(packet);
+            block(&mut capture, 6, &body);
+        }
+        capture
+    }
+
+    /// Encodes a WebSocket frame, masked if a key is given
+    fn ws_frame(fin: bool, opcode: u8, mask: Option<[u8; 4]>, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![if fin { 0x80 | opcode } else { opcode }];
+        let mask_bit = if mask.is_some() { 0x80 } else { 0 };
+        if payload.len() < 126 {
+            frame.push(mask_bit | u8::try_from(payload.len()).unwrap());
+        } else {
+            frame.push(mask_bit | 126);
+            frame.extend(u16::try_from(payload.len()).unwrap().to_be_bytes());
+        }
+        match mask {
+            Some(mask) => {
+                frame.extend(mask);
+                frame.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+            }
+            None => frame.extend(payload),
+        }
+        frame
+    }
+
+    #[test]
+    fn websocket_fragmented_and_reordered() {
+        let request = b"GET /usp HTTP/1.1\r\nHost: agent\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Protocol: v1.usp\r\n\r\n";
+        let response = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Protocol: v1.usp\r\n\r\n";
+
+        let to_agent = record("proto::agent");
+        let (first, second) = to_agent.split_at(10);
+        let mut client_data = ws_frame(false, 2, Some([1, 2, 3, 4]), first);
+        client_data.extend(ws_frame(true, 9, Some([5, 6, 7, 8]), b"ping"));
+        client_data.extend(ws_frame(true, 0, Some([9, 10, 11, 12]), second));
+        let (head, tail) = client_data.split_at(20);
+
+        let to_controller = record("proto::controller");
+        let server_data = ws_frame(true, 2, None, &to_controller);
+
+        let client_seq = 1000 + 1 + u32::try_from(request.len()).unwrap();
+        let server_seq = 5000 + 1 + u32::try_from(response.len()).unwrap();
+        let capture = pcap(&[
+            (1000, tcp(CLIENT, SERVER, 1000, true, b"")),
+            (1001, tcp(SERVER, CLIENT, 5000, true, b"")),
+            (1002, tcp(CLIENT, SERVER, 1001, false, request)),
+            (1003, tcp(SERVER, CLIENT, 5001, false, response)),
+            // The end of the message arrives first, the start is retransmitted
+            (1004, tcp(CLIENT, SERVER, client_seq + 20, false, tail)),
+            (1005, tcp(CLIENT, SERVER, client_seq, false, head)),
+            (1006, tcp(CLIENT, SERVER, client_seq, false, head)),
+            (1500, tcp(SERVER, CLIENT, server_seq, false, &server_data)),
+        ]);
+
+        let records = extract_records(&capture).unwrap();
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(records[0].timestamp, Duration::from_millis(1005));
+        assert_eq!(records[0].source, CLIENT.parse().unwrap());
+        assert_eq!(records[0].destination, SERVER.parse().unwrap());
+        assert_eq!(records[0].transport, Transport::WebSocket);
+        assert_eq!(records[0].record.to_id, "proto::agent");
+        assert_eq!(records[0].record.to_vec().unwrap(), to_agent);
+
+        assert_eq!(records[1].timestamp, Duration::from_millis(1500));
+        assert_eq!(records[1].source, SERVER.parse().unwrap());
+        assert_eq!(records[1].record.to_id, "proto::controller");
+
+        let json = serde_json::to_value(&records[1]).unwrap();
+        assert_eq!(json["timestamp"], 1.5);
+        assert_eq!(json["source"], SERVER);
+        assert_eq!(json["transport"], "websocket");
+        assert_eq!(json["record"]["to_id"], "proto::controller");
+    }
+
+    #[test]
+    fn mqtt5_publish_in_pcapng() {
+        let mut connect = vec![0x10, 18, 0, 4];
+        connect.extend(b"MQTT");
+        connect.extend([5, 0x02, 0, 60, 0, 0, 5]);
+        connect.extend(b"agent");
+        let connack = [0x20, 3, 0, 0, 0];
+
+        let to_controller = record("proto::controller");
+        let topic = b"usp/controller";
+        // PUBLISH with QoS 1, a packet identifier and a content type property
+        let properties = [0x03, 0, 4, b'u', b's', b'p', b'!'];
+        let mut publish = vec![0x32];
+        let len = 2 + topic.len() + 2 + 1 + properties.len() + to_controller.len();
+        publish.push(u8::try_from(len).unwrap());
+        publish.extend(u16::try_from(topic.len()).unwrap().to_be_bytes());
+        publish.extend(topic);
+        publish.extend([0, 1, u8::try_from(properties.len()).unwrap()]);
+        publish.extend(properties);
+        publish.extend(&to_controller);
+
+        let client_seq = 2 + u32::try_from(connect.len()).unwrap();
+        let capture = pcapng(&[
+            (10, tcp(CLIENT, SERVER, 1, false, &connect)),
+            (20, tcp(SERVER, CLIENT, 100, false, &connack)),
+            (30, tcp(CLIENT, SERVER, client_seq - 1, false, &publish)),
+        ]);
+
+        let records = extract_records(&capture).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp, Duration::from_millis(30));
+        assert_eq!(records[0].transport, Transport::Mqtt);
+        assert_eq!(records[0].record.to_vec().unwrap(), to_controller);
+    }
+
+    #[test]
+    fn stomp_send_and_message() {
+        let to_agent = record("proto::agent");
+        let to_controller = record("proto::controller");
+
+        let mut client_data = b"CONNECT\naccept-version:1.2\nhost:broker\n\n\0".to_vec();
+        client_data.extend(
+            format!(
+                "SEND\ndestination:/agent\ncontent-type:application/vnd.bbf.usp.msg\ncontent-length:{}\n\n",
+                to_agent.len()
+            )
+            .as_bytes(),
+        );
+        client_data.extend(&to_agent);
+        client_data.extend(b"\0\n");
+
+        let mut server_data = b"CONNECTED\r\nversion:1.2\r\n\r\n\0".to_vec();
+        server_data.extend(
+            format!(
+                "MESSAGE\r\ndestination:/controller\r\ncontent-length:{}\r\n\r\n",
+                to_controller.len()
+            )
+            .as_bytes(),
+        );
+        server_data.extend(&to_controller);
+        server_data.push(0);
+
+        let capture = pcap(&[
+            (50, tcp(SERVER, CLIENT, 7, false, &server_data)),
+            (40, tcp(CLIENT, SERVER, 3, false, &client_data)),
+        ]);
+
+        let records = extract_records(&capture).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].transport, Transport::Stomp);
+        assert_eq!(records[0].record.to_id, "proto::agent");
+        assert_eq!(records[1].record.to_id, "proto::controller");
+    }
+
+    #[test]
+    fn invalid_capture() {
+        assert!(extract_records(b"not a capture").is_err());
+        assert!(extract_records(&pcap(&[(1, vec![0; 20])])[..40]).is_err());
+        assert!(extract_records(&pcap(&[])).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+mod tests {
+    use std::time::Duration;
+
+    use rusp_lib::usp::mod_Header::MsgType;
+    use rusp_lib::usp::{Body, Msg};
+    use rusp_lib::usp_builder::{
+        ErrorBuilder, GetBuilder, GetRespBuilder, MsgBuilder, NotifyBuilder, NotifyRespBuilder,
+        RecordBuilder, SessionContextBuilder,
+    };
+    use rusp_lib::usp_conversation::{reconstruct_conversations, Issue, LoggedRecord};
+    use rusp_lib::usp_record::Record;
+    use serde_json::json;
+
+    const AGENT: &str = "proto::agent";
+    const CONTROLLER: &str = "proto::controller";
+
+    fn msg(msg_id: &str, body: Body) -> Msg {
+        MsgBuilder::new()
+            .with_msg_id(msg_id.into())
+            .with_body(body)
+            .build()
+            .unwrap()
+    }
+
+    fn get(msg_id: &str) -> Msg {
+        let body = GetBuilder::new()
+            .with_params(vec!["Device.".into()])
+            .build()
+            .unwrap();
+        msg(msg_id, body)
+    }
+
+    fn get_resp(msg_id: &str) -> Msg {
+        msg(msg_id, GetRespBuilder::new().build().unwrap())
+    }
+
+    fn error(msg_id: &str) -> Msg {
+        let body = ErrorBuilder::new()
+            .set_err(7004, Some("Invalid arguments".into()))
+            .build()
+            .unwrap();
+        msg(msg_id, body)
+    }
+
+    fn notify(msg_id: &str, send_resp: bool) -> Msg {
+        let body = NotifyBuilder::new("sub".into())
+            .with_send_resp(send_resp)
+            .with_value_change("Device.Test".into(), "1".into())
+            .build()
+            .unwrap();
+        msg(msg_id, body)
+    }
+
+    fn record(from_id: &str, to_id: &str, msg: &Msg) -> Record {
+        RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id(to_id.into())
+            .with_from_id(from_id.into())
+            .with_no_session_context_payload(msg)
+            .build()
+            .unwrap()
+    }
+
+    fn logged(millis: u64, from_id: &str, to_id: &str, msg: &Msg) -> LoggedRecord {
+        LoggedRecord::new(record(from_id, to_id, msg)).with_timestamp(Duration::from_millis(millis))
+    }
+
+    #[test]
+    fn requests_are_paired_with_responses() {
+        let log = [
+            logged(1000, CONTROLLER, AGENT, &get("1")),
+            logged(1100, CONTROLLER, AGENT, &get("2")),
+            logged(1250, AGENT, CONTROLLER, &get_resp("2")),
+            logged(1300, AGENT, CONTROLLER, &error("1")),
+            logged(1400, AGENT, CONTROLLER, &notify("3", true)),
+            logged(1500, AGENT, CONTROLLER, &notify("4", false)),
+            logged(1600, CONTROLLER, AGENT, &get("5")),
+        ];
+
+        let conversations = reconstruct_conversations(&log);
+        assert_eq!(conversations.len(), 1);
+        let conversation = &conversations[0];
+        assert_eq!(conversation.endpoints, [AGENT, CONTROLLER]);
+        assert_eq!(conversation.session_id, None);
+        assert_eq!(conversation.exchanges.len(), 5);
+        assert_eq!(conversation.issue_count(), 3);
+
+        let first = &conversation.exchanges[0];
+        assert_eq!(first.msg_id, "1");
+        assert_eq!(first.requester, CONTROLLER);
+        assert_eq!(first.request, Some(MsgType::GET));
+        assert_eq!(first.response, Some(MsgType::ERROR));
+        assert_eq!(first.latency(), Some(Duration::from_millis(300)));
+        assert_eq!(
+            first.issues,
+            [Issue::ErrorResponse {
+                err_code: 7004,
+                err_msg: "Invalid arguments".into()
+            }]
+        );
+
+        let second = &conversation.exchanges[1];
+        assert_eq!(second.response, Some(MsgType::GET_RESP));
+        assert_eq!(second.latency(), Some(Duration::from_millis(150)));
+        assert!(second.issues.is_empty());
+
+        assert_eq!(conversation.exchanges[2].requester, AGENT);
+        assert_eq!(conversation.exchanges[2].issues, [Issue::UnackedNotify]);
+        assert!(conversation.exchanges[3].issues.is_empty());
+        assert_eq!(conversation.exchanges[4].issues, [Issue::Unanswered]);
+
+        let json = serde_json::to_value(conversation).unwrap();
+        assert_eq!(
+            json["exchanges"][1],
+            json!({
+                "msg_id": "2",
+                "requester": CONTROLLER,
+                "request": "GET",
+                "request_time": 1.1,
+                "response": "GET_RESP",
+                "response_time": 1.25,
+                "latency": 0.15,
+                "issues": [],
+            })
+        );
+        assert_eq!(
+            json["exchanges"][0]["issues"],
+            json!([{"kind": "error_response", "err_code": 7004, "err_msg": "Invalid arguments"}])
+        );
+
+        let table = conversation.to_string();
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines[0], "proto::agent <-> proto::controller");
+        assert!(lines[1].trim_start().starts_with("TIME"));
+        assert!(lines[2].contains("<-  1"));
+        assert!(lines[2].ends_with("300.0 ms  error 7004: Invalid arguments"));
+        assert!(lines[4].contains("->  3"));
+    }
+
+    #[test]
+    fn duplicate_msg_ids_and_unexpected_responses() {
+        let log = [
+            logged(1, CONTROLLER, AGENT, &get("1")),
+            logged(2, AGENT, CONTROLLER, &get_resp("1")),
+            logged(3, CONTROLLER, AGENT, &get("1")),
+            logged(4, AGENT, CONTROLLER, &get_resp("1")),
+            logged(5, AGENT, CONTROLLER, &get_resp("1")),
+            // The same msg_id used by the other endpoint is fine
+            logged(6, AGENT, CONTROLLER, &notify("1", true)),
+            logged(
+                7,
+                CONTROLLER,
+                AGENT,
+                &msg("1", NotifyRespBuilder::new("sub".into()).build().unwrap()),
+            ),
+        ];
+
+        let conversation = &reconstruct_conversations(&log)[0];
+        let issues: Vec<_> = conversation
+            .exchanges
+            .iter()
+            .map(|exchange| exchange.issues.clone())
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                vec![],
+                vec![Issue::DuplicateMsgId],
+                vec![Issue::UnexpectedResponse],
+                vec![],
+            ]
+        );
+        assert_eq!(conversation.exchanges[2].request, None);
+        assert_eq!(
+            conversation.exchanges[3].response,
+            Some(MsgType::NOTIFY_RESP)
+        );
+    }
+
+    #[test]
+    fn grouped_by_endpoints_and_session() {
+        let session = |session_id: u64, from_id: &str, to_id: &str, msg: &Msg| {
+            let record = RecordBuilder::new()
+                .with_version("1.3".into())
+                .with_to_id(to_id.into())
+                .with_from_id(from_id.into())
+                .with_session_context_builder(
+                    SessionContextBuilder::new()
+                        .with_session_id(session_id)
+                        .with_sequence_id(1)
+                        .with_expected_id(2)
+                        .with_payload(msg.to_vec().unwrap()),
+                )
+                .build()
+                .unwrap();
+            LoggedRecord::new(record)
+        };
+
+        let mut garbage = record(CONTROLLER, AGENT, &get("x"));
+        garbage.payload_flatten().unwrap().truncate(3);
+
+        let log = [
+            session(1, CONTROLLER, AGENT, &get("1")),
+            logged(2, CONTROLLER, "proto::other", &get("1")),
+            session(2, AGENT, CONTROLLER, &get_resp("1")),
+            session(1, AGENT, CONTROLLER, &get_resp("1")),
+            LoggedRecord::new(garbage),
+            LoggedRecord::new(
+                RecordBuilder::new()
+                    .with_version("1.3".into())
+                    .with_to_id(AGENT.into())
+                    .with_from_id(CONTROLLER.into())
+                    .as_websocket_connect_record()
+                    .build()
+                    .unwrap(),
+            ),
+        ];
+
+        let conversations = reconstruct_conversations(&log);
+        assert_eq!(conversations.len(), 4);
+        assert_eq!(conversations[0].session_id, Some(1));
+        assert_eq!(conversations[0].issue_count(), 0);
+        assert_eq!(conversations[0].exchanges[0].latency(), None);
+        assert_eq!(conversations[1].endpoints, [CONTROLLER, "proto::other"]);
+        assert_eq!(conversations[2].session_id, Some(2));
+        assert_eq!(
+            conversations[2].exchanges[0].issues,
+            [Issue::UnexpectedResponse]
+        );
+        assert_eq!(conversations[3].session_id, None);
+        assert_eq!(conversations[3].undecodable, 1);
+        assert!(conversations[3].exchanges.is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+mod tests {
+    use rusp_lib::usp::Msg;
+    use rusp_lib::usp_builder::{
+        GetReqPathResultBuilder, GetRespBuilder, MsgBuilder, RecordBuilder,
+        ResolvedPathResultBuilder,
+    };
+    use rusp_lib::usp_diff::{diff_msg, diff_record, DiffOptions, Difference};
+    use serde_json::json;
+
+    fn get_resp(msg_id: &str, results: Vec<(&str, Vec<(&str, &str)>)>) -> Msg {
+        let req_path_results = results
+            .into_iter()
+            .map(|(path, params)| {
+                GetReqPathResultBuilder::new(path.into()).with_res_path_results(vec![
+                    ResolvedPathResultBuilder::new(path.into()).with_result_params(
+                        params
+                            .into_iter()
+                            .map(|(k, v)| (k.into(), v.into()))
+                            .collect(),
+                    ),
+                ])
+            })
+            .collect();
+        let body = GetRespBuilder::new()
+            .with_req_path_results(req_path_results)
+            .build()
+            .unwrap();
+        MsgBuilder::new()
+            .with_msg_id(msg_id.into())
+            .with_body(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn identical_msgs_in_different_order() {
+        let old = get_resp(
+            "1",
+            vec![
+                (
+                    "Device.DeviceInfo.",
+                    vec![("Manufacturer", "ACME"), ("SoftwareVersion", "1.0")],
+                ),
+                ("Device.WiFi.", vec![("RadioNumberOfEntries", "2")]),
+            ],
+        );
+        let new = get_resp(
+            "1",
+            vec![
+                ("Device.WiFi.", vec![("RadioNumberOfEntries", "2")]),
+                (
+                    "Device.DeviceInfo.",
+                    vec![("SoftwareVersion", "1.0"), ("Manufacturer", "ACME")],
+                ),
+            ],
+        );
+
+        assert!(diff_msg(&old, &new, &DiffOptions::new())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn changed_result_params() {
+        let old = get_resp(
+            "1",
+            vec![
+                (
+                    "Device.DeviceInfo.",
+                    vec![("Manufacturer", "ACME"), ("SoftwareVersion", "1.0")],
+                ),
+                ("Device.WiFi.", vec![("RadioNumberOfEntries", "2")]),
+            ],
+        );
+        let new = get_resp(
+            "2",
+            vec![
+                (
+                    "Device.DeviceInfo.",
+                    vec![("SoftwareVersion", "1.1"), ("UpTime", "42")],
+                ),
+                ("Device.WiFi.", vec![("RadioNumberOfEntries", "2")]),
+            ],
+        );
+
+        let prefix = "Body.Response.GetResp.req_path_results[requested_path=Device.DeviceInfo.].resolved_path_results[resolved_path=Device.DeviceInfo.].result_params";
+        let diff = diff_msg(&old, &new, &DiffOptions::new().with_ignore_msg_id(true)).unwrap();
+        assert_eq!(
+            diff,
+            [
+                Difference::Removed {
+                    path: format!("{prefix}.Manufacturer"),
+                    value: json!("ACME"),
+                },
+                Difference::Changed {
+                    path: format!("{prefix}.SoftwareVersion"),
+                    before: json!("1.0"),
+                    after: json!("1.1"),
+                },
+                Difference::Added {
+                    path: format!("{prefix}.UpTime"),
+                    value: json!("42"),
+                },
+            ]
+        );
+
+        let diff = diff_msg(&old, &new, &DiffOptions::new()).unwrap();
+        assert_eq!(diff.len(), 4);
+        assert_eq!(diff[3].to_string(), "~ Header.msg_id: \"1\" -> \"2\"");
+        assert_eq!(
+            serde_json::to_value(&diff[3]).unwrap(),
+            json!({"kind": "changed", "path": "Header.msg_id", "before": "1", "after": "2"})
+        );
+    }
+
+    #[test]
+    fn record_payloads_are_compared() {
+        let record = |msg: &Msg, to_id: &str| {
+            RecordBuilder::new()
+                .with_version("1.3".into())
+                .with_to_id(to_id.into())
+                .with_from_id("proto::agent".into())
+                .with_no_session_context_payload(msg)
+                .build()
+                .unwrap()
+        };
+
+        let old = record(
+            &get_resp(
+                "1",
+                vec![("Device.WiFi.", vec![("SSIDNumberOfEntries", "1")])],
+            ),
+            "proto::controller",
+        );
+        let new = record(
+            &get_resp(
+                "2",
+                vec![("Device.WiFi.", vec![("SSIDNumberOfEntries", "1")])],
+            ),
+            "proto::other",
+        );
+
+        let diff = diff_record(&old, &new, &DiffOptions::new().with_ignore_msg_id(true)).unwrap();
+        assert_eq!(
+            diff,
+            [Difference::Changed {
+                path: "to_id".into(),
+                before: json!("proto::controller"),
+                after: json!("proto::other"),
+            }]
+        );
+
+        let diff = diff_record(&old, &new, &DiffOptions::new()).unwrap();
+        assert_eq!(diff[0].path(), "payload.Header.msg_id");
+    }
+}
@@ -0,0 +1,443 @@
+mod tests {
+    use rusp_lib::usp::mod_Header::MsgType;
+    use rusp_lib::usp::Msg;
+    use rusp_lib::usp_builder::{
+        ErrorBuilder, GetBuilder, MsgBuilder, NotifyBuilder, RecordBuilder, SessionContextBuilder,
+        SetOperationStatus, SetRespBuilder, SetRespParameterError, UpdatedInstanceFailureBuilder,
+        UpdatedObjectResultsBuilder,
+    };
+    use rusp_lib::usp_c_parser::parse_c_bytes;
+    use rusp_lib::usp_capture::extract_records;
+    use rusp_lib::usp_decoder::{decode_hex, try_decode_msg, try_decode_record};
+    use rusp_lib::usp_errors::{get_err_msg, ErrorCategory, UspErrorCode};
+    use rusp_lib::usp_record::mod_Record::OneOfrecord_type;
+    use rusp_lib::usp_record::Record;
+    use rusp_lib::usp_stream::{Framing, MsgReader};
+    use rusp_lib::{Error, Target};
+
+    #[test]
+    fn conversions() {
+        for code in (7000..=7031).chain(7100..=7106) {
+            let err = UspErrorCode::try_from(code).unwrap();
+            assert!(!matches!(err, UspErrorCode::Unknown(_)), "{code}");
+            assert_eq!(u32::from(err), code);
+            assert_eq!(err.message(), get_err_msg(code));
+            assert_eq!(err.to_string(), format!("{code} {}", get_err_msg(code)));
+        }
+
+        let vendor = UspErrorCode::try_from(7999).unwrap();
+        assert!(matches!(vendor, UspErrorCode::Vendor(7999)));
+        let unknown = UspErrorCode::try_from(7050).unwrap();
+        assert!(matches!(unknown, UspErrorCode::Unknown(7050)));
+        assert_eq!(unknown.code(), 7050);
+        assert_eq!(unknown.to_string(), "7050 Unknown error");
+        // Codes of the specification are never represented as unknown
+        assert!(!matches!(
+            UspErrorCode::try_from(7004).unwrap(),
+            UspErrorCode::Unknown(_)
+        ));
+        assert_eq!(UspErrorCode::try_from(0), Err(Error::InvalidErrCode(0)));
+        assert_eq!(
+            UspErrorCode::try_from(8000),
+            Err(Error::InvalidErrCode(8000))
+        );
+    }
+
+    #[test]
+    fn categories() {
+        assert_eq!(
+            UspErrorCode::InvalidPath.category(),
+            Some(ErrorCategory::Message)
+        );
+        assert_eq!(
+            UspErrorCode::SessionContextNotAllowed.category(),
+            Some(ErrorCategory::Record)
+        );
+        assert_eq!(
+            UspErrorCode::try_from(7800).unwrap().category(),
+            Some(ErrorCategory::Vendor)
+        );
+        assert_eq!(UspErrorCode::try_from(7300).unwrap().category(), None);
+    }
+
+    #[test]
+    fn allowed_codes() {
+        assert!(UspErrorCode::InternalError.is_allowed_in(MsgType::GET_RESP));
+        assert!(UspErrorCode::DeleteFailure.is_allowed_in(MsgType::DELETE_RESP));
+        assert!(!UspErrorCode::DeleteFailure.is_allowed_in(MsgType::SET_RESP));
+        assert!(!UspErrorCode::InvalidRecordValue.is_allowed_in(MsgType::ERROR));
+        assert!(UspErrorCode::try_from(7900)
+            .unwrap()
+            .is_allowed_in(MsgType::OPERATE_RESP));
+
+        assert!(UspErrorCode::InvalidRecordValue.is_allowed_in_record());
+        assert!(UspErrorCode::InternalError.is_allowed_in_record());
+        assert!(!UspErrorCode::InvalidValue.is_allowed_in_record());
+    }
+
+    #[test]
+    fn checked_msgs() {
+        let msg = |body| {
+            MsgBuilder::new()
+                .with_msg_id("resp".into())
+                .with_body(body)
+                .build()
+                .unwrap()
+        };
+        let set_resp = |oper_status: SetOperationStatus| {
+            let body = SetRespBuilder::new()
+                .with_updated_obj_results(vec![UpdatedObjectResultsBuilder::new(
+                    "Device.Foo.".into(),
+                    oper_status,
+                )])
+                .build()
+                .unwrap();
+            msg(body).check_err_codes()
+        };
+
+        let failure = SetOperationStatus::new().set_failure(
+            UspErrorCode::ObjectNotUpdated,
+            None,
+            vec![
+                UpdatedInstanceFailureBuilder::new("Device.Foo.1.".into()).with_param_errs(vec![
+                    SetRespParameterError::new("Bar".into(), UspErrorCode::InvalidValue, None),
+                ]),
+            ],
+        );
+        assert!(set_resp(failure).is_ok());
+
+        // Record errors must not be carried by Msgs, but are only rejected on request
+        let failure = SetOperationStatus::new().set_failure(
+            UspErrorCode::SecureSessionRequired,
+            None,
+            vec![],
+        );
+        let err = set_resp(failure).unwrap_err();
+        assert_eq!(
+            err,
+            Error::ErrCodeNotAllowed {
+                code: UspErrorCode::SecureSessionRequired,
+                msg_type: Some(MsgType::SET_RESP),
+            }
+        );
+        assert_eq!(err.err_code(), UspErrorCode::InternalError);
+        assert_eq!(
+            err.to_string(),
+            "Error code 7101 Secure session required is not allowed in a SET_RESP message"
+        );
+
+        // Plain codes are still accepted and checked
+        assert!(set_resp(SetOperationStatus::new().set_failure(7024, None, vec![])).is_err());
+        assert!(set_resp(SetOperationStatus::new().set_failure(7803, None, vec![])).is_ok());
+
+        let error = ErrorBuilder::new()
+            .set_err(UspErrorCode::MessageNotSupported, None)
+            .build()
+            .unwrap();
+        assert!(msg(error).check_err_codes().is_ok());
+        let error = ErrorBuilder::new().set_err(7105, None).build().unwrap();
+        assert!(msg(error).check_err_codes().is_err());
+
+        let record = |code: UspErrorCode| {
+            RecordBuilder::new()
+                .with_to_id("proto::to".into())
+                .with_from_id("proto::from".into())
+                .as_disconnect_record("Bye".into(), code)
+                .build()
+                .unwrap()
+                .check_err_codes()
+        };
+        assert!(record(UspErrorCode::SessionContextTerminated).is_ok());
+        assert!(record(UspErrorCode::CommandCanceled).is_err());
+    }
+
+    #[test]
+    fn structured_errors() {
+        let body = GetBuilder::new()
+            .with_params(vec!["Device.".into()])
+            .build()
+            .unwrap();
+
+        let err = MsgBuilder::new().with_body(body).build().unwrap_err();
+        assert_eq!(
+            err,
+            Error::MissingField {
+                item: "USP Msg",
+                field: "msg_id",
+            }
+        );
+        assert_eq!(err.to_string(), "Cannot produce USP Msg without msg_id");
+        let err = MsgBuilder::new()
+            .with_msg_id("get".into())
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::MissingField {
+                field: "msg_body",
+                ..
+            }
+        ));
+
+        // A Record whose to_id is cut off
+        let err = try_decode_record(&[0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x07, 0x64]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Decode {
+                target: Target::Record,
+                ..
+            }
+        ));
+        assert_eq!(err.decode_details().unwrap().path, "Record.to_id");
+        assert_eq!(err.err_code(), UspErrorCode::RecordNotParsed);
+        assert_eq!(err.err_code().code(), 7100);
+
+        let err = try_decode_msg(&[0x0a, 0x07, 0x0a]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Decode {
+                target: Target::Msg,
+                ..
+            }
+        ));
+        assert_eq!(err.err_code(), UspErrorCode::MessageFailed);
+
+        let err = Record::from_hex("0a03312e33120764").unwrap_err();
+        assert!(matches!(err, Error::Decode { .. }));
+        let err = Record::from_hex("0a03312e3").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidEncoding {
+                encoding: "hex",
+                ..
+            }
+        ));
+        assert!(decode_hex("zz").is_err());
+
+        // A Record without from_id and type
+        let record = try_decode_record(&[0x0a, 0x03, 0x31, 0x2e, 0x33, 0x12, 0x01, 0x61]).unwrap();
+        let err = record.check_validity().unwrap_err();
+        assert_eq!(
+            err,
+            Error::Validation {
+                target: Target::Record,
+                field: "from_id",
+                reason: "Invalid from_id field in Record".into(),
+            }
+        );
+        assert_eq!(err.err_code(), UspErrorCode::InvalidRecordValue);
+
+        let msg = try_decode_msg(&[0x0a, 0x00]).unwrap();
+        let err = msg.check_validity().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Validation {
+                target: Target::Msg,
+                field: "header.msg_id",
+                ..
+            }
+        ));
+        assert_eq!(err.err_code(), UspErrorCode::MessageFailed);
+    }
+
+    #[test]
+    fn textual_errors() {
+        let err = Msg::from_prototxt("header { foo: 1 }").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Prototxt {
+                target: Target::Msg,
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .contains("line 1, column 10: unknown field `foo`"));
+
+        let err = Msg::from_json("{").unwrap_err();
+        assert!(matches!(err, Error::Json { .. }));
+        assert!(err.to_string().starts_with("while parsing JSON: "));
+        let err = Record::from_json(r#"{ "to_id": 1 }"#).unwrap_err();
+        assert!(err.to_string().contains("in `to_id`: invalid value 1"));
+
+        let err = parse_c_bytes("\"\\q\"").unwrap_err();
+        assert!(matches!(err, Error::Parse { .. }));
+        assert_eq!(
+            err.to_string(),
+            "while parsing C source: Unsupported escape sequence \\q"
+        );
+
+        let err = extract_records(b"no capture").unwrap_err();
+        assert!(matches!(err, Error::Capture { .. }));
+
+        let mut reader = MsgReader::new(&b"\x05\x0a"[..], Framing::LengthDelimited);
+        let err = reader.next().unwrap().unwrap_err();
+        assert!(matches!(err, Error::Stream { .. }));
+        assert!(err
+            .to_string()
+            .starts_with("while reading Msg 1: Stream is truncated"));
+        assert!(reader.next().is_none());
+
+        let err = "foo".parse::<Framing>().unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)));
+        assert_eq!(err.err_code(), UspErrorCode::InternalError);
+    }
+
+    #[test]
+    fn error_responses() {
+        let msg = MsgBuilder::new()
+            .with_msg_id("get-1".into())
+            .with_body(
+                GetBuilder::new()
+                    .with_params(vec!["Device.".into()])
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let resp = msg
+            .error_response_for(&Error::UnsupportedMsg(MsgType::GET))
+            .unwrap();
+        assert_eq!(resp.msg_id(), "get-1");
+        let error = resp.get_error().unwrap();
+        assert_eq!(error.err_code, 7001);
+        assert_eq!(error.err_msg, "Unsupported Msg type GET");
+        assert!(resp.check_validity().is_ok());
+
+        // Record errors cannot be reported in a Msg
+        let err = msg
+            .error_response_for(&Error::Validation {
+                target: Target::Record,
+                field: "to_id",
+                reason: "Invalid to_id field in Record".into(),
+            })
+            .unwrap_err();
+        assert!(matches!(err, Error::ErrCodeNotAllowed { .. }));
+
+        // Errors, responses and Notify Msgs not requesting a response are never answered
+        let err = Error::UnsupportedMsg(MsgType::GET);
+        assert!(matches!(
+            resp.error_response_for(&err),
+            Err(Error::InvalidArgument(_))
+        ));
+        let set_resp = MsgBuilder::new()
+            .with_msg_id("set-1".into())
+            .with_body(SetRespBuilder::new().build().unwrap())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            set_resp.error_response_for(&err),
+            Err(Error::InvalidArgument(_))
+        ));
+        let notify = |send_resp| {
+            MsgBuilder::new()
+                .with_msg_id("notify-1".into())
+                .with_body(
+                    NotifyBuilder::new("sub-1".into())
+                        .with_send_resp(send_resp)
+                        .with_object_deletion("Device.Foo.1.".into())
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap()
+        };
+        assert!(matches!(
+            notify(false).error_response_for(&err),
+            Err(Error::InvalidArgument(_))
+        ));
+        let resp = notify(true).error_response_for(&err).unwrap();
+        assert_eq!(resp.msg_id(), "notify-1");
+        assert_eq!(resp.get_error().unwrap().err_code, 7001);
+
+        let record = RecordBuilder::new()
+            .with_to_id("proto::agent".into())
+            .with_from_id("proto::controller".into())
+            .with_originator_id("proto::origin".into())
+            .with_session_context_builder(
+                SessionContextBuilder::new()
+                    .with_session_id(3)
+                    .with_sequence_id(10)
+                    .with_expected_id(5)
+                    .with_payload(msg.to_vec().unwrap()),
+            )
+            .build()
+            .unwrap();
+        let err = Error::Rejected {
+            code: UspErrorCode::RequestDenied,
+            reason: Some("Busy".into()),
+            param_errs: vec![],
+        };
+        let resp = record.error_response_for(&err).unwrap();
+        assert_eq!(resp.to_id, "proto::controller");
+        assert_eq!(resp.from_id, "proto::agent");
+        assert_eq!(resp.destination_id, "proto::origin");
+        let OneOfrecord_type::session_context(session) = &resp.record_type else {
+            panic!("Unexpected Record type {:?}", resp.record_type);
+        };
+        assert_eq!(
+            (session.session_id, session.sequence_id, session.expected_id),
+            (3, 5, 11)
+        );
+        let error = try_decode_msg(&session.payload[0]).unwrap();
+        assert_eq!(error.msg_id(), "get-1");
+        assert_eq!(error.get_error().unwrap().err_code, 7002);
+        assert_eq!(error.get_error().unwrap().err_msg, "Busy");
+
+        // The msg_id is recovered from a Msg with a broken body
+        let mut payload = msg.to_vec().unwrap();
+        payload.truncate(payload.len() - 2);
+        let record = RecordBuilder::new()
+            .with_to_id("proto::agent".into())
+            .with_from_id("proto::controller".into())
+            .with_no_session_context_payload_bytes(payload.clone())
+            .build()
+            .unwrap();
+        let err = try_decode_msg(&payload).unwrap_err();
+        let resp = record.error_response_for(&err).unwrap();
+        let OneOfrecord_type::no_session_context(no_session) = &resp.record_type else {
+            panic!("Unexpected Record type {:?}", resp.record_type);
+        };
+        let error = try_decode_msg(&no_session.payload).unwrap();
+        assert_eq!(error.msg_id(), "get-1");
+        assert_eq!(error.get_error().unwrap().err_code, 7000);
+
+        // Records without payload are always answered with a DisconnectRecord
+        let record = RecordBuilder::new()
+            .with_to_id("proto::agent".into())
+            .with_from_id("proto::controller".into())
+            .as_websocket_connect_record()
+            .build()
+            .unwrap();
+        let err = Error::Validation {
+            target: Target::Record,
+            field: "version",
+            reason: "Unsupported version".into(),
+        };
+        let resp = record.error_response_for(&err).unwrap();
+        let OneOfrecord_type::disconnect(disconnect) = &resp.record_type else {
+            panic!("Unexpected Record type {:?}", resp.record_type);
+        };
+        assert_eq!(disconnect.reason_code, 7104);
+        assert_eq!(disconnect.reason, "Unsupported version");
+
+        // Msg errors are reported as unparsable Record, as they are not allowed in a Record
+        for err in [
+            Error::UnsupportedMsg(MsgType::GET),
+            Error::Rejected {
+                code: UspErrorCode::InternalError,
+                reason: Some("Out of memory".into()),
+                param_errs: vec![],
+            },
+        ] {
+            let resp = record.error_response_for(&err).unwrap();
+            let OneOfrecord_type::disconnect(disconnect) = &resp.record_type else {
+                panic!("Unexpected Record type {:?}", resp.record_type);
+            };
+            assert_eq!(disconnect.reason_code, 7100);
+            assert_eq!(disconnect.reason, err.to_string());
+            assert!(resp.check_err_codes().is_ok());
+        }
+    }
+}
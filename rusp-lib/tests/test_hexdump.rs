@@ -0,0 +1,86 @@
+mod tests {
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    use rusp_lib::usp_hexdump::{hexdump_msg, hexdump_record};
+
+    fn get_msg_bytes() -> Vec<u8> {
+        let body = GetBuilder::new()
+            .with_params(vec!["Device.".into()])
+            .with_max_depth(1)
+            .build()
+            .unwrap();
+        MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(body)
+            .build()
+            .unwrap()
+            .to_vec()
+            .unwrap()
+    }
+
+    #[test]
+    fn record_payload_is_annotated_as_msg() {
+        let msg = rusp_lib::usp_decoder::try_decode_msg(&get_msg_bytes()).unwrap();
+        let record = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        let dump = hexdump_record(&record);
+        assert!(dump.contains("7: no_session_context (usp_record.NoSessionContextRecord)"));
+        assert!(dump.contains("2: payload (bytes), 29 bytes containing usp.Msg\n"));
+        assert!(dump.contains("      1: msg_id (string), 3 bytes: \"get\"\n"));
+        assert!(dump.contains("          2: max_depth (fixed32): 1\n"));
+        assert!(!dump.contains("!!"));
+    }
+
+    #[test]
+    fn unknown_fields_and_wire_types() {
+        let mut bytes = get_msg_bytes();
+        // Unknown field 3 as varint 150
+        bytes.extend_from_slice(&[0x18, 0x96, 0x01]);
+        // Field 1 (header) encoded as varint
+        bytes.extend_from_slice(&[0x08, 0x01]);
+
+        let dump = hexdump_msg(&bytes);
+        assert!(dump.contains(
+            "18 96 01                                         3: !! unknown field (VARINT): 150\n"
+        ));
+        assert!(dump
+            .contains("1: header (usp.Header) !! unexpected wire type VARINT, expected LEN: 1\n"));
+    }
+
+    #[test]
+    fn malformed_nested_message() {
+        // A header containing a truncated msg_id, followed by a valid body
+        let mut bytes = vec![0x0a, 0x04, 0x0a, 0x05, 0x61, 0x62];
+        bytes.extend_from_slice(&get_msg_bytes()[9..]);
+
+        let dump = hexdump_msg(&bytes);
+        assert!(dump.contains(
+            "000002  0a 05 61 62                                        !! LEN field (1: msg_id): unexpected end of data, 4 byte(s) not decoded\n"
+        ));
+        // Decoding continues with the next field of the outer message
+        assert!(dump.contains("1: param_paths[0] (string), 7 bytes: \"Device.\"\n"));
+    }
+
+    #[test]
+    fn trailing_garbage() {
+        let mut bytes = get_msg_bytes();
+        bytes.extend_from_slice(&[0xff; 20]);
+
+        let dump = hexdump_msg(&bytes);
+        let last_lines: Vec<_> = dump.lines().rev().take(2).collect();
+        assert_eq!(
+            last_lines,
+            [
+                "00002d  ff ff ff ff",
+                "00001d  ff ff ff ff ff ff ff ff ff ff ff ff ff ff ff ff  !! invalid varint, 20 byte(s) not decoded"
+            ]
+        );
+    }
+}
@@ -1,5 +1,7 @@
 mod tests {
     use quick_protobuf::{BytesReader, MessageRead};
+    use rusp_lib::usp::{Body, Msg};
+    use rusp_lib::usp_builder::RecordBuilder;
     use rusp_lib::usp_record::Record;
     use serde_json::{json, Value};
 
@@ -225,4 +227,100 @@ mod tests {
 
         assert_eq!(parsed, expected);
     }
+
+    #[test]
+    fn json_roundtrip() {
+        let msgs = [
+            r#"header { msg_id: "get_resp" msg_type: GET_RESP }
+               body { response { get_resp { req_path_results {
+                 requested_path: "Device.DeviceInfo."
+                 resolved_path_results {
+                   resolved_path: "Device.DeviceInfo."
+                   result_params { key: "Manufacturer" value: "Axiros" }
+                   result_params { key: "SerialNumber" value: "0815" }
+                 }
+               } } } }"#,
+            r#"header { msg_id: "set_resp" msg_type: SET_RESP }
+               body { response { set_resp { updated_obj_results {
+                 requested_path: "Device.Foo."
+                 oper_status { oper_failure {
+                   err_code: 7004
+                   err_msg: "Invalid arguments"
+                   updated_inst_failures { affected_path: "Device.Foo.1."
+                     param_errs { param: "Bar" err_code: 7012 } }
+                 } }
+               } } } }"#,
+            r#"header { msg_id: "gsdm" msg_type: GET_SUPPORTED_DM_RESP }
+               body { response { get_supported_dm_resp { req_obj_results {
+                 req_obj_path: "Device." data_model_inst_uri: "urn:broadband-forum-org:tr-181-2-15-0"
+                 supported_objs { supported_obj_path: "Device." access: OBJ_READ_ONLY
+                   supported_params { param_name: "RootDataModelVersion" value_type: PARAM_STRING }
+                   supported_commands { command_name: "Reboot()" command_type: CMD_SYNC }
+                 }
+               } } } }"#,
+            r#"header { msg_id: "error" msg_type: ERROR }
+               body { error { err_code: 7000 err_msg: "Message failed"
+                 param_errs { param_path: "Device.Foo" err_code: 7026 err_msg: "Invalid path" } } }"#,
+        ];
+
+        for text in msgs {
+            let msg = Msg::from_prototxt(text).unwrap();
+            let json = serde_json::to_string(&msg).unwrap();
+            let parsed = Msg::from_json(&json).unwrap();
+            assert_eq!(parsed, msg);
+
+            let record = RecordBuilder::new()
+                .with_version("1.3".into())
+                .with_to_id("proto::to".into())
+                .with_from_id("proto::from".into())
+                .with_no_session_context_payload(&msg)
+                .build()
+                .unwrap();
+            // The payload is re-encoded, so the order of map entries may differ
+            let json = serde_json::to_value(&record).unwrap();
+            let parsed: Record = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+        }
+
+        let record = Record::from_prototxt(
+            r#"version: "1.3" to_id: "proto::to" from_id: "proto::from"
+               mac_signature: "\001\002"
+               session_context { session_id: 3 sequence_id: 1 expected_id: 2
+                 payload_sar_state: BEGIN payload: "\n\x03abc" payload: "def" }"#,
+        )
+        .unwrap();
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(Record::from_json(&json).unwrap(), record);
+
+        let record = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .as_websocket_connect_record()
+            .build()
+            .unwrap();
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(Record::from_json(&json).unwrap(), record);
+
+        let msg = Msg::from_prototxt(msgs[1]).unwrap();
+        let body = msg.body.unwrap();
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(Body::from_json_value(&json).unwrap(), body);
+    }
+
+    #[test]
+    fn json_errors() {
+        let err = Msg::from_json(r#"{"Header": {"msg_id": "a", "foo": 1}}"#).unwrap_err();
+        assert!(format!("{err:#}").contains("in `Header`: unknown field `foo` in usp.Header"));
+
+        let err = Msg::from_json(r#"{"Header": {"msg_type": "FOO"}}"#).unwrap_err();
+        assert!(format!("{err:#}")
+            .contains("invalid value \"FOO\" for field of type usp.Header.MsgType"));
+
+        assert!(Record::from_json("[1, 2]").is_err());
+        assert!(Record::from_json(r#"{"version": 1.3}"#).is_err());
+
+        let err = Body::from_json_value(&json!({"Request": {"Get": {"foo": []}}})).unwrap_err();
+        assert!(format!("{err:#}").contains("unknown field `foo` in usp.Get"));
+    }
 }
@@ -0,0 +1,126 @@
+mod tests {
+    use rusp_lib::usp::Msg;
+    use rusp_lib::usp_builder::{
+        GetReqPathResultBuilder, GetRespBuilder, MsgBuilder, RecordBuilder,
+        ResolvedPathResultBuilder,
+    };
+    use rusp_lib::usp_record::Record;
+
+    fn get_resp_msg() -> Msg {
+        let body = GetRespBuilder::new()
+            .with_req_path_results(vec![GetReqPathResultBuilder::new(
+                "Device.DeviceInfo.".into(),
+            )
+            .with_res_path_results(vec![ResolvedPathResultBuilder::new(
+                "Device.DeviceInfo.".into(),
+            )
+            .with_result_params(vec![
+                ("Manufacturer".into(), "Axiros \"GmbH\"".into()),
+                ("Description".into(), "Line 1\nLine 2".into()),
+            ])])])
+            .build()
+            .unwrap();
+
+        MsgBuilder::new()
+            .with_msg_id("get_resp".into())
+            .with_body(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn msg_roundtrip() {
+        let msg = get_resp_msg();
+        let text = msg.to_prototxt().unwrap();
+        assert!(text.contains("msg_type: GET_RESP\n"));
+        assert!(text.contains("          result_params {\n            key: \"Manufacturer\"\n            value: \"Axiros \\\"GmbH\\\"\"\n          }\n"));
+        assert!(text.contains("value: \"Line 1\\nLine 2\""));
+
+        let parsed = Msg::from_prototxt(&text).unwrap();
+        assert_eq!(parsed, msg);
+    }
+
+    #[test]
+    fn record_roundtrip() {
+        let record = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_originator_id("proto::originator".into())
+            .with_no_session_context_payload(&get_resp_msg())
+            .build()
+            .unwrap();
+
+        let text = record.to_prototxt().unwrap();
+        // Fields are printed in the order of their field numbers, like protoc does
+        assert!(text.starts_with("version: \"1.3\"\nto_id: \"proto::to\"\nfrom_id: \"proto::from\"\nno_session_context {\n  payload: \"\\n"));
+        assert!(text.ends_with("}\noriginator_id: \"proto::originator\"\n"));
+
+        let parsed = Record::from_prototxt(&text).unwrap();
+        assert_eq!(parsed, record);
+    }
+
+    #[test]
+    fn parse_alternative_syntax() {
+        let msg = Msg::from_prototxt(
+            r#"
+            # Comments and alternative delimiters are supported
+            header: < msg_id: 'add' msg_type: 8 >;
+            body {
+              request {
+                add {
+                  allow_partial: t,
+                  create_objs {
+                    obj_path: "Device." "LocalAgent.Controller."
+                    param_settings: [
+                      { param: "Alias" value: "\x74est" required: true },
+                      { param: "EndpointID" value: "\164est" required: 1 }
+                    ]
+                  }
+                }
+              }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let text = msg.to_prototxt().unwrap();
+        assert_eq!(
+            text,
+            r#"header {
+  msg_id: "add"
+  msg_type: ADD
+}
+body {
+  request {
+    add {
+      allow_partial: true
+      create_objs {
+        obj_path: "Device.LocalAgent.Controller."
+        param_settings {
+          param: "Alias"
+          value: "test"
+          required: true
+        }
+        param_settings {
+          param: "EndpointID"
+          value: "test"
+          required: true
+        }
+      }
+    }
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Msg::from_prototxt("header { msg_id: \"a\"").is_err());
+        assert!(Msg::from_prototxt("header { msg_id: 1 }").is_err());
+        assert!(Msg::from_prototxt("header { msg_id: \"\\377\" }").is_err());
+        assert!(Record::from_prototxt("disconnect { reason_code: 4294967296 }").is_err());
+        assert!(Record::from_prototxt("version: [\"1.3\"]").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+mod tests {
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder};
+    use rusp_lib::usp_encoder::SourceLanguage;
+
+    fn get_msg(path: &str) -> rusp_lib::usp::Msg {
+        let body = GetBuilder::new()
+            .with_params(vec![path.into()])
+            .build()
+            .unwrap();
+        MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(body)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn language_names() {
+        assert_eq!(
+            "py".parse::<SourceLanguage>().unwrap(),
+            SourceLanguage::Python
+        );
+        assert_eq!("Go".parse::<SourceLanguage>().unwrap(), SourceLanguage::Go);
+        assert_eq!(
+            "rs".parse::<SourceLanguage>().unwrap(),
+            SourceLanguage::Rust
+        );
+        assert_eq!(
+            "typescript".parse::<SourceLanguage>().unwrap(),
+            SourceLanguage::JavaScript
+        );
+        assert_eq!(
+            "rust-bytes".parse::<SourceLanguage>().unwrap(),
+            SourceLanguage::RustByteString
+        );
+        assert!("c".parse::<SourceLanguage>().is_err());
+    }
+
+    #[test]
+    fn python_escapes() {
+        let msg = get_msg("Device.\"Quoted\\\".");
+        let python = msg.to_source(SourceLanguage::Python, "quoted-get").unwrap();
+
+        assert!(python.contains("#         \"param_paths\": [\n"));
+        assert!(python.contains("QUOTED_GET = (\n"));
+        assert!(python.contains("    b\"\\x11Device.\\x22Quoted\\x5c\"\n    b\"\\x22.\"\n"));
+    }
+
+    #[test]
+    fn array_literals() {
+        let msg = get_msg("Device.");
+        let first_line = "0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10, /* ____get_ */\n";
+
+        let go = msg.to_source(SourceLanguage::Go, "get").unwrap();
+        assert!(go.contains(&format!("\nvar get = []byte{{\n\t{first_line}")));
+        assert!(go.ends_with("/* _Device. */\n}\n"));
+
+        let rust = msg.to_source(SourceLanguage::Rust, "get").unwrap();
+        assert!(rust.contains(&format!("\npub const GET: &[u8] = &[\n    {first_line}")));
+        assert!(rust.ends_with("/* _Device. */\n];\n"));
+
+        let js = msg.to_source(SourceLanguage::JavaScript, "get").unwrap();
+        assert!(js.starts_with("// {\n//   \"Header\": {\n"));
+        assert!(js.contains(&format!("\nconst get = new Uint8Array([\n  {first_line}")));
+        assert!(js.ends_with("/* _Device. */\n]);\n"));
+    }
+
+    #[test]
+    fn rust_byte_string() {
+        // The space starts the third line of the literal
+        let msg = get_msg("Device.Services \"Q\\");
+        let rust = msg
+            .to_source(SourceLanguage::RustByteString, "quoted-get")
+            .unwrap();
+
+        assert!(rust.contains(
+            "\npub const QUOTED_GET: &[u8] = b\"\\\n    \\x0a\\x07\\x0a\\x03get\\x10\\x01"
+        ));
+        assert!(rust.contains("\\\n    \\x13Device.Services\\\n"));
+        assert!(rust.ends_with("\\\n    \\x20\\x22Q\\x5c\";\n"));
+    }
+
+    #[test]
+    fn reserved_identifiers() {
+        let msg = get_msg("Device.");
+
+        let go = msg.to_source(SourceLanguage::Go, "func").unwrap();
+        assert!(go.contains("\nvar func_ = []byte{\n"));
+
+        let js = msg.to_source(SourceLanguage::JavaScript, "var").unwrap();
+        assert!(js.contains("\nconst var_ = new Uint8Array([\n"));
+
+        let rust = msg.to_source(SourceLanguage::Rust, "-").unwrap();
+        assert!(rust.contains("\npub const __: &[u8] = &[\n"));
+
+        // Not reserved in the respective language
+        let go = msg.to_source(SourceLanguage::Go, "let").unwrap();
+        assert!(go.contains("\nvar let = []byte{\n"));
+        let python = msg.to_source(SourceLanguage::Python, "class").unwrap();
+        assert!(python.contains("\nCLASS = (\n"));
+    }
+}
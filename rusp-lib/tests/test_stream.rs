@@ -0,0 +1,106 @@
+mod tests {
+    use rusp_lib::usp::Msg;
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    use rusp_lib::usp_record::Record;
+    use rusp_lib::usp_stream::{Framing, MsgReader, RecordReader, StreamWriter};
+
+    fn get(msg_id: &str) -> Msg {
+        MsgBuilder::new()
+            .with_msg_id(msg_id.into())
+            .with_body(
+                GetBuilder::new()
+                    .with_params(vec!["Device.".into()])
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn record(msg: &Msg) -> Record {
+        RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload(msg)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let msgs: Vec<_> = (0..200).map(|i| get(&i.to_string())).collect();
+        let records: Vec<_> = msgs.iter().map(record).collect();
+
+        for framing in [Framing::LengthDelimited, Framing::JsonLines] {
+            let mut writer = StreamWriter::new(vec![], framing);
+            for msg in &msgs {
+                writer.write(msg).unwrap();
+            }
+            let data = writer.into_inner();
+            let decoded = MsgReader::new(&data[..], framing)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, msgs);
+
+            let mut writer = StreamWriter::new(vec![], framing);
+            for record in &records {
+                writer.write(record).unwrap();
+            }
+            let data = writer.into_inner();
+            let decoded = RecordReader::new(&data[..], framing)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, records);
+        }
+    }
+
+    #[test]
+    fn length_prefix_spans_several_bytes() {
+        let msg = get(&"x".repeat(300));
+        let mut writer = StreamWriter::new(vec![], Framing::LengthDelimited);
+        writer.write(&msg).unwrap();
+        let data = writer.into_inner();
+        assert!(data[0] & 0x80 != 0);
+
+        let decoded: Vec<_> = MsgReader::new(&data[..], Framing::LengthDelimited).collect();
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].as_ref().unwrap(), &msg);
+    }
+
+    #[test]
+    fn json_lines_with_wrapper_and_blank_lines() {
+        let record = record(&get("1"));
+        let json = serde_json::to_string(&record).unwrap();
+        let data = format!("\n{{\"timestamp\": 1.5, \"record\": {json}}}\n\n{json}\n");
+
+        let decoded = RecordReader::new(data.as_bytes(), Framing::JsonLines)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, [record.clone(), record]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut writer = StreamWriter::new(vec![], Framing::LengthDelimited);
+        writer.write(&record(&get("1"))).unwrap();
+        let mut data = writer.into_inner();
+        // An undecodable Record doesn't end the stream, a truncated one does
+        data.extend([2, 0xff, 0xff]);
+        data.extend([10, 1, 2]);
+
+        let decoded: Vec<_> = RecordReader::new(&data[..], Framing::LengthDelimited).collect();
+        assert_eq!(decoded.len(), 3);
+        assert!(decoded[0].is_ok());
+        assert!(format!("{:#}", decoded[1].as_ref().unwrap_err()).contains("Record 2"));
+        assert!(format!("{:#}", decoded[2].as_ref().unwrap_err()).contains("truncated"));
+
+        let data = "{}\nnot json\n";
+        let decoded: Vec<_> = MsgReader::new(data.as_bytes(), Framing::JsonLines).collect();
+        assert_eq!(decoded.len(), 2);
+        assert!(format!("{:#}", decoded[1].as_ref().unwrap_err()).contains("line 2"));
+
+        assert!("xml".parse::<Framing>().is_err());
+        assert_eq!("json-lines".parse::<Framing>().unwrap(), Framing::JsonLines);
+    }
+}
@@ -0,0 +1,211 @@
+mod tests {
+    use std::io::Write;
+
+    use rusp_lib::usp::Msg;
+    use rusp_lib::usp_builder::{MsgBuilder, NotifyBuilder, RecordBuilder, SessionContextBuilder};
+    use rusp_lib::usp_decoder::try_decode_record;
+    use rusp_lib::usp_record::mod_Record::{OneOfrecord_type, PayloadSecurity};
+    use rusp_lib::usp_record::mod_SessionContextRecord::PayloadSARState;
+    use rusp_lib::usp_record::{NoSessionContextRecord, Record, SessionContextRecord};
+    use rusp_lib::{Error, Target};
+
+    fn value_change(id: usize) -> Msg {
+        MsgBuilder::new()
+            .with_msg_id(format!("notify-{id}"))
+            .with_body(
+                NotifyBuilder::new("sub-1".into())
+                    .with_send_resp(false)
+                    .with_value_change("Device.DeviceInfo.UpTime".into(), format!("{}", id * 10))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn envelope() -> RecordBuilder {
+        RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::controller".into())
+            .with_from_id("proto::agent".into())
+    }
+
+    /// A writer which accepts a limited number of bytes
+    struct Limited(usize);
+
+    impl Write for Limited {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if buf.len() > self.0 {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            self.0 -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_to() {
+        let msg = value_change(1);
+        let mut out = Vec::new();
+        msg.write_to(&mut out).unwrap();
+        assert_eq!(out, msg.to_vec().unwrap());
+        assert_eq!(out.len(), msg.encoded_len());
+
+        let record = envelope()
+            .with_no_session_context_payload(&msg)
+            .build()
+            .unwrap();
+        let mut out = Vec::new();
+        record.write_to(&mut out).unwrap();
+        assert_eq!(out, record.to_vec().unwrap());
+        assert_eq!(out.len(), record.encoded_len());
+    }
+
+    #[test]
+    fn write_to_failure() {
+        let msg = value_change(1);
+        let err = msg.write_to(&mut Limited(10)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Encode {
+                target: Target::Msg,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn encode_into() {
+        let mut buf = vec![0xff; 1024];
+        for id in 0..3 {
+            let msg = value_change(id);
+            msg.encode_into(&mut buf).unwrap();
+            assert_eq!(buf, msg.to_vec().unwrap());
+            assert!(buf.capacity() >= 1024);
+
+            let record = envelope()
+                .with_no_session_context_payload(&msg)
+                .build()
+                .unwrap();
+            record.encode_into(&mut buf).unwrap();
+            assert_eq!(buf, record.to_vec().unwrap());
+        }
+    }
+
+    #[test]
+    fn write_with_msg_to() {
+        let no_session = envelope()
+            .with_no_session_context_payload_bytes(Vec::new())
+            .build()
+            .unwrap();
+        let session = || {
+            SessionContextBuilder::new()
+                .with_session_id(4)
+                .with_sequence_id(5)
+                .with_expected_id(6)
+        };
+        let with_session = envelope()
+            .with_session_context_builder(session())
+            .build()
+            .unwrap();
+
+        let mut buf = Vec::new();
+        for id in 0..3 {
+            let msg = value_change(id);
+
+            buf.clear();
+            no_session.write_with_msg_to(&msg, &mut buf).unwrap();
+            let expected = envelope()
+                .with_no_session_context_payload(&msg)
+                .build()
+                .unwrap();
+            assert_eq!(buf, expected.to_vec().unwrap());
+            assert_eq!(try_decode_record(&buf).unwrap(), expected);
+
+            buf.clear();
+            with_session.write_with_msg_to(&msg, &mut buf).unwrap();
+            let expected = envelope()
+                .with_session_context_builder(session().with_payload(msg.to_vec().unwrap()))
+                .build()
+                .unwrap();
+            assert_eq!(buf, expected.to_vec().unwrap());
+        }
+    }
+
+    #[test]
+    fn write_with_msg_to_all_fields() {
+        let msg = value_change(1);
+        let payload = msg.to_vec().unwrap();
+        let record = |record_type| Record {
+            version: "1.3".into(),
+            to_id: "proto::controller".into(),
+            from_id: "proto::agent".into(),
+            originator_id: "proto::originator".into(),
+            destination_id: "proto::destination".into(),
+            payload_security: PayloadSecurity::TLS12,
+            mac_signature: vec![0x01, 0x02, 0x03],
+            sender_cert: vec![0x04, 0x05],
+            record_type,
+        };
+        let session = |payload| SessionContextRecord {
+            session_id: 1,
+            sequence_id: 2,
+            expected_id: 3,
+            retransmit_id: 4,
+            payload_sar_state: PayloadSARState::BEGIN,
+            payloadrec_sar_state: PayloadSARState::COMPLETE,
+            payload,
+        };
+
+        let cases = [
+            (
+                OneOfrecord_type::no_session_context(NoSessionContextRecord::default()),
+                OneOfrecord_type::no_session_context(NoSessionContextRecord {
+                    payload: payload.clone(),
+                }),
+            ),
+            (
+                OneOfrecord_type::session_context(session(vec![])),
+                OneOfrecord_type::session_context(session(vec![payload.clone()])),
+            ),
+        ];
+        for (without_payload, with_payload) in cases {
+            let mut buf = Vec::new();
+            record(without_payload)
+                .write_with_msg_to(&msg, &mut buf)
+                .unwrap();
+            assert_eq!(buf, record(with_payload).to_vec().unwrap());
+        }
+    }
+
+    #[test]
+    fn write_with_msg_to_invalid() {
+        let msg = value_change(1);
+        let records: [Record; 2] = [
+            envelope()
+                .with_no_session_context_payload(&msg)
+                .build()
+                .unwrap(),
+            envelope()
+                .as_disconnect_record("Bye".into(), 7003)
+                .build()
+                .unwrap(),
+        ];
+
+        for record in records {
+            let err = record.write_with_msg_to(&msg, &mut Vec::new()).unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Validation {
+                    target: Target::Record,
+                    field: "record_type",
+                    ..
+                }
+            ));
+        }
+    }
+}
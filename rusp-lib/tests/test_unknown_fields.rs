@@ -0,0 +1,220 @@
+mod tests {
+    use rusp_lib::usp_builder::{GetBuilder, MsgBuilder, RecordBuilder};
+    use rusp_lib::usp_decoder::{
+        try_decode_msg, try_decode_msg_lenient, try_decode_record, try_decode_record_lenient,
+    };
+    use rusp_lib::usp_unknown::{UnknownField, UnknownValue};
+
+    /// A Get Msg with an additional string field 10 in the Header, an additional fixed32 field 3
+    /// in the Get and a request type 42 unknown to this library
+    fn future_msg() -> Vec<u8> {
+        vec![
+            0x0a, 0x0c, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10, 0x01, 0x52, 0x03, 0x6e, 0x65, 0x77,
+            0x12, 0x17, 0x0a, 0x15, 0x0a, 0x0e, 0x0a, 0x07, 0x44, 0x65, 0x76, 0x69, 0x63, 0x65,
+            0x2e, 0x1d, 0x01, 0x02, 0x03, 0x04, 0xd2, 0x02, 0x02, 0x08, 0x01,
+        ]
+    }
+
+    /// A Get Msg with unknown fields between the known ones: field 7 before the Header, field 10
+    /// between `msg_id` and `msg_type` and field 3 between the two `param_paths` of the Get
+    fn interleaved_msg() -> Vec<u8> {
+        vec![
+            0x38, 0x01, 0x0a, 0x0c, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x52, 0x03, 0x6e, 0x65, 0x77,
+            0x10, 0x01, 0x12, 0x18, 0x0a, 0x16, 0x0a, 0x14, 0x0a, 0x07, 0x44, 0x65, 0x76, 0x69,
+            0x63, 0x65, 0x2e, 0x18, 0x05, 0x0a, 0x02, 0x41, 0x2e, 0x15, 0x01, 0x00, 0x00, 0x00,
+        ]
+    }
+
+    #[test]
+    fn msg_roundtrip() {
+        let raw = future_msg();
+
+        let msg = try_decode_msg_lenient(&raw).unwrap();
+        assert_eq!(msg.to_vec().unwrap(), raw);
+        assert_eq!(
+            msg.unknown_fields().at("header").collect::<Vec<_>>(),
+            [&UnknownField {
+                path: "header".into(),
+                position: 2,
+                number: 10,
+                value: UnknownValue::LengthDelimited(b"new".to_vec()),
+            }]
+        );
+        assert_eq!(msg.unknown_fields().len(), 3);
+
+        // Regular decoding still drops unknown fields
+        let msg = try_decode_msg(&raw).unwrap();
+        assert_ne!(msg.to_vec().unwrap(), raw);
+    }
+
+    #[test]
+    fn interleaved_roundtrip() {
+        let raw = interleaved_msg();
+
+        let mut msg = try_decode_msg_lenient(&raw).unwrap();
+        assert_eq!(msg.to_vec().unwrap(), raw);
+        let positions = msg
+            .unknown_fields()
+            .iter()
+            .map(|field| (field.path.as_str(), field.position, field.number))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [("", 0, 7), ("header", 1, 10), ("body.request.get", 1, 3)]
+        );
+
+        // Re-encoding the message inserts the unknown fields at their original positions
+        msg.message_mut();
+        assert_eq!(msg.to_vec().unwrap(), raw);
+    }
+
+    #[test]
+    fn modified_msg() {
+        let raw = interleaved_msg();
+        let mut msg = try_decode_msg_lenient(&raw).unwrap();
+        msg.message_mut().header.as_mut().unwrap().msg_id = "GET".into();
+
+        let mut expected = raw.clone();
+        expected[6..9].copy_from_slice(b"GET");
+        assert_eq!(msg.to_vec().unwrap(), expected);
+
+        msg.message_mut().header.as_mut().unwrap().msg_id = "get-2".into();
+        let encoded = msg.to_vec().unwrap();
+        let decoded = try_decode_msg_lenient(&encoded).unwrap();
+        assert_eq!(decoded.msg_id(), "get-2");
+        assert_eq!(decoded.unknown_fields(), msg.unknown_fields());
+    }
+
+    #[test]
+    fn restructured_msg() {
+        use rusp_lib::usp::mod_Body::OneOfmsg_body::request;
+        use rusp_lib::usp::mod_Request::OneOfreq_type::get;
+
+        let raw = interleaved_msg();
+        let mut msg = try_decode_msg_lenient(&raw).unwrap();
+        let request(req) = &mut msg.message_mut().body.as_mut().unwrap().msg_body else {
+            panic!("Not a request");
+        };
+        let get(get_req) = &mut req.req_type else {
+            panic!("Not a Get");
+        };
+        get_req.param_paths.insert(0, "X.".into());
+
+        // The unknown field keeps its position, i.e. now follows the inserted parameter path
+        let encoded = msg.to_vec().unwrap();
+        assert_ne!(encoded, raw);
+        let decoded = try_decode_msg_lenient(&encoded).unwrap();
+        let json = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(
+            json["Body"]["Request"]["Get"]["param_paths"],
+            serde_json::json!(["X.", "Device.", "A."])
+        );
+        assert_eq!(decoded.unknown_fields(), msg.unknown_fields());
+        let field = decoded
+            .unknown_fields()
+            .at("body.request.get")
+            .next()
+            .unwrap();
+        assert_eq!((field.position, field.number), (1, 3));
+
+        // Encoding the unwrapped message drops the unknown fields
+        let plain = msg.into_inner().to_vec().unwrap();
+        assert!(try_decode_msg_lenient(&plain)
+            .unwrap()
+            .unknown_fields()
+            .is_empty());
+    }
+
+    #[test]
+    fn msg_json() {
+        let msg = try_decode_msg_lenient(&future_msg()).unwrap();
+        let json = serde_json::to_value(&msg).unwrap();
+
+        assert_eq!(
+            json["unknown_fields"][0],
+            serde_json::json!({
+                "path": "header",
+                "position": 2,
+                "number": 10,
+                "wire_type": "LEN",
+                "value": [0x6e, 0x65, 0x77]
+            })
+        );
+        assert_eq!(
+            json["unknown_fields"][1],
+            serde_json::json!({
+                "path": "body.request.get",
+                "position": 1,
+                "number": 3,
+                "wire_type": "I32",
+                "value": 0x0403_0201
+            })
+        );
+        assert_eq!(json["unknown_fields"][2]["path"], "body.request");
+        assert_eq!(json["unknown_fields"][2]["number"], 42);
+
+        // A request of a type unknown to this library, only field 42 of the Request is set
+        let raw = [
+            0x0a, 0x07, 0x0a, 0x03, 0x67, 0x65, 0x74, 0x10, 0x01, 0x12, 0x07, 0x0a, 0x05, 0xd2,
+            0x02, 0x02, 0x08, 0x01,
+        ];
+        let msg = try_decode_msg_lenient(&raw).unwrap();
+        let json = serde_json::to_value(&msg).unwrap();
+        assert_eq!(json["Body"], serde_json::json!({ "Request": {} }));
+        assert_eq!(json["unknown_fields"][0]["number"], 42);
+
+        // Without the unknown fields the Msg cannot be serialised
+        assert!(serde_json::to_value(&*msg).is_err());
+        assert!(serde_json::to_value(try_decode_msg(&raw).unwrap()).is_err());
+    }
+
+    #[test]
+    fn record_roundtrip() {
+        let record = RecordBuilder::new()
+            .with_version("1.3".into())
+            .with_to_id("proto::to".into())
+            .with_from_id("proto::from".into())
+            .with_no_session_context_payload_bytes(future_msg())
+            .build()
+            .unwrap();
+        let mut raw = record.to_vec().unwrap();
+        // Unknown varint field 30 in the Record
+        raw.extend_from_slice(&[0xf0, 0x01, 0x05]);
+
+        let record = try_decode_record_lenient(&raw).unwrap();
+        assert_eq!(record.to_vec().unwrap(), raw);
+        let field = record.unknown_fields().iter().next().unwrap();
+        assert_eq!((field.path.as_str(), field.position), ("", 4));
+        assert_eq!(field.value, UnknownValue::Varint(5));
+
+        // The unknown fields of the payload are shown as well
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["unknown_fields"][0]["number"], 30);
+        assert_eq!(json["payload"]["unknown_fields"][0]["number"], 10);
+
+        let record = try_decode_record(&raw).unwrap();
+        assert_ne!(record.to_vec().unwrap(), raw);
+    }
+
+    #[test]
+    fn known_messages_unchanged() {
+        let body = GetBuilder::new()
+            .with_params(vec!["Device.".into()])
+            .build()
+            .unwrap();
+        let msg = MsgBuilder::new()
+            .with_msg_id("get".into())
+            .with_body(body)
+            .build()
+            .unwrap();
+        let raw = msg.to_vec().unwrap();
+
+        let decoded = try_decode_msg_lenient(&raw).unwrap();
+        assert!(decoded.unknown_fields().is_empty());
+        assert_eq!(*decoded, msg);
+        assert_eq!(
+            serde_json::to_string(&decoded).unwrap(),
+            serde_json::to_string(&msg).unwrap()
+        );
+    }
+}